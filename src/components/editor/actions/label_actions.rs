@@ -6,9 +6,26 @@ use crate::components::note_explorer::NoteExplorer;
 use crate::components::visualizer::Visualizer;
 use crate::notebook;
 
+/// Most label suggestions shown below the label input.
+const LABEL_SUGGESTION_LIMIT: usize = 6;
+
 // Handle label input changed
-pub fn handle_label_input_changed(state: &mut EditorState, text: String) {
+pub fn handle_label_input_changed(
+    state: &mut EditorState,
+    note_explorer: &NoteExplorer,
+    text: String,
+) {
     if !state.show_about_info() {
+        // Labels the selected note already has are not worth suggesting, unless the input
+        // applies to the marked notes.
+        let exclude = if note_explorer.marked_notes.is_empty() {
+            state.selected_note_labels()
+        } else {
+            &[]
+        };
+        let suggestions =
+            notebook::suggest_labels(&note_explorer.notes, &text, exclude, LABEL_SUGGESTION_LIMIT);
+        state.set_label_suggestions(suggestions);
         state.set_new_label_text(text);
     }
 }
@@ -22,7 +39,7 @@ pub fn handle_add_label(
     if !state.show_about_info()
         && let Some(selected_path) = state.selected_note_path().cloned()
     {
-        let label = state.new_label_text().trim().to_string();
+        let label = notebook::normalize_label(state.new_label_text());
         let mut selected_labels = state.selected_note_labels().to_vec();
 
         if !label.is_empty() && !selected_labels.contains(&label) {
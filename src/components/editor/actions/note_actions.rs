@@ -1,6 +1,7 @@
 use iced::task::Task; // Use Task instead of Command
 use iced::widget::text_editor::Content;
 use native_dialog::{DialogBuilder, MessageLevel};
+use time::Date;
 
 // Use root-level imports that avoid circular references
 use crate::components::editor::Message;
@@ -11,7 +12,9 @@ use crate::components::note_explorer;
 use crate::components::note_explorer::NoteExplorer;
 use crate::components::visualizer;
 use crate::components::visualizer::Visualizer;
-use crate::notebook::{self, NoteMetadata, NotebookError};
+use crate::notebook::{
+    self, CreatedNote, MovedNote, NoteMetadata, NotebookError, SearchMatchRange,
+};
 
 fn report_metadata_load_issue(title: &str, detail: &str) {
     eprintln!("{}: {}", title, detail);
@@ -55,6 +58,9 @@ fn apply_selected_note_change(
     note_path: &str,
     hide_visualizer: bool,
 ) {
+    if state.selected_note_path().map(String::as_str) != Some(note_path) {
+        state.set_selected_note_backlinks(Vec::new());
+    }
     state.set_selected_note_path(Some(note_path.to_string()));
     state.clear_new_label_text();
     state.hide_move_note_dialog();
@@ -67,9 +73,35 @@ fn apply_selected_note_change(
     sync_selected_note_labels(state, note_explorer, Some(note_path));
 }
 
+// Load notes linking to the given note through wiki links
+pub fn load_backlinks_command(
+    state: &EditorState,
+    note_explorer: &NoteExplorer,
+    note_path: &str,
+) -> Task<Message> {
+    if state.notebook_path().is_empty() {
+        return Task::none();
+    }
+
+    let notebook_path = state.notebook_path().to_string();
+    let note_paths = note_explorer
+        .notes
+        .iter()
+        .map(|note| note.rel_path.clone())
+        .collect::<Vec<String>>();
+    let target_path = note_path.to_string();
+    let loaded_path = target_path.clone();
+
+    Task::perform(
+        async move { notebook::load_backlinks(notebook_path, note_paths, target_path).await },
+        move |backlinks| Message::BacklinksLoaded(loaded_path.clone(), backlinks),
+    )
+}
+
 fn clear_selected_note_change(state: &mut EditorState) {
     state.set_selected_note_path(None);
     state.set_selected_note_labels(Vec::new());
+    state.set_selected_note_backlinks(Vec::new());
     state.hide_move_note_dialog();
     state.clear_new_label_text();
 }
@@ -95,6 +127,7 @@ fn handle_note_selection_internal(
                 note_path.clone(),
             ))
             .map(Message::NoteExplorerMsg),
+        load_backlinks_command(state, note_explorer, &note_path),
     ];
 
     if !state.show_visualizer() && !state.notebook_path().is_empty() {
@@ -106,10 +139,26 @@ fn handle_note_selection_internal(
         let notebook_path = state.notebook_path().to_string();
         let selected_note_path = note_path;
 
-        commands.push(Task::perform(
-            async move { note_coordinator::load_note_payload(notebook_path, selected_note_path).await },
-            |payload| Message::LoadedNoteContent(payload.note_path, payload.content, payload.images),
-        ));
+        commands.push(
+            Task::future(note_coordinator::load_note_payload(
+                notebook_path,
+                selected_note_path,
+            ))
+            // The disk state becomes the base that later saves are checked against.
+            .then(|payload| {
+                Task::batch(vec![
+                    Task::done(Message::NoteDiskStateLoaded(
+                        payload.note_path.clone(),
+                        payload.disk_state.clone(),
+                    )),
+                    Task::done(Message::LoadedNoteContent(
+                        payload.note_path,
+                        payload.disk_state.content,
+                        payload.images,
+                    )),
+                ])
+            }),
+        );
     }
 
     Task::batch(commands)
@@ -154,7 +203,7 @@ pub fn handle_note_explorer_message(
                 );
 
                 if let Some(load_warning) = load_warning {
-                    report_metadata_load_issue("Notebook Metadata Recovered", &load_warning);
+                    report_metadata_load_issue("Notebook Metadata Updated", &load_warning);
                 }
             }
             Err(load_error) => {
@@ -170,6 +219,7 @@ pub fn handle_note_explorer_message(
 
         // Update the visualizer with the new notes data
         visualizer.sync_notes(&note_explorer.notes);
+        visualizer.sync_label_registry(&note_explorer.label_registry);
 
         if let Some(selected_path) = state.selected_note_path().cloned() {
             if !note_explorer
@@ -185,6 +235,7 @@ pub fn handle_note_explorer_message(
                 *markdown_text = String::new();
             } else {
                 sync_selected_note_labels(state, note_explorer, Some(selected_path.as_str()));
+                editor_command = load_backlinks_command(state, note_explorer, &selected_path);
             }
         } else if !note_explorer.notes.is_empty() {
             let first_note_path = note_explorer.notes[0].rel_path.clone();
@@ -290,13 +341,20 @@ pub fn handle_create_note(
             eprintln!("New note name cannot be empty.");
             Task::none()
         } else {
+            let template = state.new_note_template().map(str::to_string);
             state.hide_new_note_dialog();
             let notebook_path = state.notebook_path().to_string();
             let mut notes = current_notes;
 
             Task::perform(
                 async move {
-                    notebook::create_new_note(&notebook_path, &new_note_rel_path, &mut notes).await
+                    notebook::create_note_from_template(
+                        &notebook_path,
+                        &new_note_rel_path,
+                        template.as_deref(),
+                        &mut notes,
+                    )
+                    .await
                 },
                 Message::NoteCreated,
             )
@@ -306,15 +364,79 @@ pub fn handle_create_note(
     }
 }
 
+// List the templates the new note dialog offers
+pub fn load_new_note_templates_command(state: &EditorState) -> Task<Message> {
+    if state.notebook_path().is_empty() {
+        return Task::none();
+    }
+
+    let notebook_path = state.notebook_path().to_string();
+    Task::perform(
+        notebook::list_templates(notebook_path),
+        Message::NewNoteTemplatesLoaded,
+    )
+}
+
+/// Date of the selected note when it is a daily note.
+pub fn selected_journal_date(state: &EditorState) -> Option<Date> {
+    state
+        .selected_note_path()
+        .and_then(|path| notebook::journal_note_date(state.journal_path_pattern(), path))
+}
+
+// Open the daily note for a date, creating it when it is missing
+pub fn open_journal_note_command(
+    state: &EditorState,
+    current_notes: Vec<NoteMetadata>,
+    date: Date,
+) -> Task<Message> {
+    if state.notebook_path().is_empty() {
+        return Task::none();
+    }
+
+    let notebook_path = state.notebook_path().to_string();
+    let path_pattern = state.journal_path_pattern().to_string();
+    let template = state.journal_template().map(str::to_string);
+    let mut notes = current_notes;
+    Task::perform(
+        async move {
+            notebook::open_journal_note(
+                &notebook_path,
+                &path_pattern,
+                template.as_deref(),
+                date,
+                &mut notes,
+            )
+            .await
+        },
+        Message::JournalNoteOpened,
+    )
+}
+
 // Handle note created
 pub fn handle_note_created(
-    result: Result<NoteMetadata, NotebookError>,
+    result: Result<CreatedNote, NotebookError>,
+    state: &mut EditorState,
     note_explorer: &mut NoteExplorer,
 ) -> Task<Message> {
     match result {
-        Ok(new_note_metadata) => {
+        Ok(CreatedNote {
+            metadata: new_note_metadata,
+            cursor,
+        }) => {
             #[cfg(debug_assertions)]
             eprintln!("Note created successfully: {}", new_note_metadata.rel_path);
+            // An empty match range places the cursor once the note is loaded.
+            if let Some((line, column)) = cursor {
+                state.set_pending_search_match(
+                    new_note_metadata.rel_path.clone(),
+                    line,
+                    SearchMatchRange {
+                        start: column,
+                        end: column,
+                    },
+                );
+            }
             let reload_command = note_explorer
                 .update(note_explorer::Message::LoadNotes)
                 .map(Message::NoteExplorerMsg);
@@ -359,10 +481,7 @@ pub fn handle_delete_note(state: &mut EditorState) -> Task<Message> {
                     DialogBuilder::message()
                         .set_level(MessageLevel::Warning)
                         .set_title("Confirm Deletion")
-                        .set_text(format!(
-                            "Are you sure you want to delete the note '{}'?",
-                            note_path_clone
-                        ))
+                        .set_text(format!("Move the note '{}' to the trash?", note_path_clone))
                         .confirm()
                         .show()
                         .unwrap_or(false)
@@ -512,19 +631,19 @@ pub fn handle_confirm_move_note(
 
 // Handle note moved
 pub fn handle_note_moved(
-    result: Result<String, NotebookError>,
+    result: Result<MovedNote, NotebookError>,
     old_path: String,
     _state: &mut EditorState,
     undo_manager: &mut UndoManager,
     note_explorer: &mut NoteExplorer,
 ) -> Task<Message> {
     match result {
-        Ok(new_rel_path) => {
+        Ok(moved) => {
             #[cfg(debug_assertions)]
-            eprintln!("Item moved/renamed successfully to: {}", new_rel_path);
+            eprintln!("Item moved/renamed successfully to: {}", moved.rel_path);
 
             // If we're moving a note that had an undo history, update the key
-            undo_manager.handle_path_change(&old_path, &new_rel_path);
+            undo_manager.handle_path_change(&old_path, &moved.rel_path);
 
             note_explorer
                 .update(note_explorer::Message::LoadNotes)
@@ -2,6 +2,7 @@ use iced::widget::text_editor::{Action, Cursor as EditorCursor, Edit, Position a
 use std::collections::{HashMap, HashSet};
 
 use super::HTML_BR_SENTINEL;
+use super::preview_links::rewrite_wiki_links_for_preview;
 
 #[cfg(test)]
 const HTML_BR_SENTINEL_CHAR: char = '\u{E000}';
@@ -405,7 +406,7 @@ pub(super) fn build_markdown_preview_content(
     images: &HashMap<String, String>,
 ) -> String {
     let _ = images;
-    normalize_html_line_break_tags(markdown)
+    rewrite_wiki_links_for_preview(&normalize_html_line_break_tags(markdown))
 }
 
 pub(super) fn normalize_html_line_break_tags(markdown: &str) -> String {
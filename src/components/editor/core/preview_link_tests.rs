@@ -0,0 +1,122 @@
+use super::preview::build_markdown_preview_content;
+use super::preview_links::{
+    PreviewLinkTarget, classify_preview_link, heading_line_for_anchor,
+    rewrite_wiki_links_for_preview,
+};
+use std::collections::HashMap;
+
+const NOTE_PATHS: [&str; 4] = [
+    "inbox",
+    "projects/alpha",
+    "projects/beta",
+    "archive/old notes",
+];
+
+#[test]
+fn rewrite_wiki_links_for_preview_builds_note_links() {
+    let markdown = "See [[projects/alpha|Alpha]] and [[old notes#Summary]].\n`[[skip]]`";
+
+    let rewritten = rewrite_wiki_links_for_preview(markdown);
+
+    assert_eq!(
+        rewritten,
+        "See [Alpha](cognate-note:projects/alpha) and \
+         [old notes#Summary](cognate-note:old%20notes#Summary).\n`[[skip]]`"
+    );
+}
+
+#[test]
+fn build_markdown_preview_content_renders_wiki_links_as_links() {
+    let rendered = build_markdown_preview_content("[[inbox]]", &HashMap::new());
+
+    assert_eq!(rendered, "[inbox](cognate-note:inbox)");
+}
+
+#[test]
+fn classify_preview_link_resolves_wiki_and_relative_targets() {
+    assert_eq!(
+        classify_preview_link(
+            "cognate-note:old%20notes#Summary",
+            Some("inbox"),
+            &NOTE_PATHS
+        ),
+        PreviewLinkTarget::Note {
+            rel_path: "archive/old notes".to_string(),
+            anchor: Some("Summary".to_string()),
+        }
+    );
+    assert_eq!(
+        classify_preview_link("../beta/note.md", Some("projects/alpha"), &NOTE_PATHS),
+        PreviewLinkTarget::Note {
+            rel_path: "projects/beta".to_string(),
+            anchor: None,
+        }
+    );
+    assert_eq!(
+        classify_preview_link(
+            "/archive/old%20notes#Intro",
+            Some("projects/alpha"),
+            &NOTE_PATHS
+        ),
+        PreviewLinkTarget::Note {
+            rel_path: "archive/old notes".to_string(),
+            anchor: Some("Intro".to_string()),
+        }
+    );
+}
+
+#[test]
+fn classify_preview_link_separates_anchors_external_and_broken_links() {
+    assert_eq!(
+        classify_preview_link("#Next%20Steps", Some("inbox"), &NOTE_PATHS),
+        PreviewLinkTarget::Anchor("Next Steps".to_string())
+    );
+    assert_eq!(
+        classify_preview_link("https://example.com/a?b=c", Some("inbox"), &NOTE_PATHS),
+        PreviewLinkTarget::External("https://example.com/a?b=c".to_string())
+    );
+    assert_eq!(
+        classify_preview_link("mailto:someone@example.com", None, &NOTE_PATHS),
+        PreviewLinkTarget::External("mailto:someone@example.com".to_string())
+    );
+    assert_eq!(
+        classify_preview_link("HTTP://example.com", None, &NOTE_PATHS),
+        PreviewLinkTarget::External("HTTP://example.com".to_string())
+    );
+    for unsafe_uri in [
+        "file:///etc/passwd",
+        "javascript:alert(1)",
+        "ssh://host",
+        "smb://share/file",
+    ] {
+        assert!(
+            matches!(
+                classify_preview_link(unsafe_uri, Some("inbox"), &NOTE_PATHS),
+                PreviewLinkTarget::Broken(_)
+            ),
+            "{} should not be opened",
+            unsafe_uri
+        );
+    }
+    assert!(matches!(
+        classify_preview_link("cognate-note:missing", Some("inbox"), &NOTE_PATHS),
+        PreviewLinkTarget::Broken(_)
+    ));
+    assert!(matches!(
+        classify_preview_link("../../../escape", Some("inbox"), &NOTE_PATHS),
+        PreviewLinkTarget::Broken(_)
+    ));
+    assert!(matches!(
+        classify_preview_link("gamma", Some("projects/alpha"), &NOTE_PATHS),
+        PreviewLinkTarget::Broken(_)
+    ));
+}
+
+#[test]
+fn heading_line_for_anchor_matches_slugged_headings_outside_code() {
+    let markdown = "# Title\n```\n## Next Steps\n```\ntext\n## Next Steps ##\n";
+
+    assert_eq!(heading_line_for_anchor(markdown, "next-steps"), Some(5));
+    assert_eq!(heading_line_for_anchor(markdown, "Title"), Some(0));
+    assert_eq!(heading_line_for_anchor(markdown, "missing"), None);
+}
@@ -0,0 +1,201 @@
+use crate::notebook;
+
+use super::clipboard::percent_decode;
+
+/// URI scheme used for `[[wiki]]` links once they are rewritten for the preview.
+pub(super) const NOTE_LINK_SCHEME: &str = "cognate-note:";
+/// Schemes handed to the link opener; any other scheme could start an arbitrary handler.
+const OPENABLE_LINK_SCHEMES: [&str; 3] = ["http", "https", "mailto"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum PreviewLinkTarget {
+    Note {
+        rel_path: String,
+        anchor: Option<String>,
+    },
+    Anchor(String),
+    External(String),
+    Broken(String),
+}
+
+/// Rewrites `[[target|alias]]` references into regular Markdown links the preview can render.
+pub(super) fn rewrite_wiki_links_for_preview(markdown: &str) -> String {
+    let links = notebook::parse_wiki_links(markdown);
+    if links.is_empty() {
+        return markdown.to_string();
+    }
+
+    let mut rewritten = String::with_capacity(markdown.len());
+    let mut cursor = 0usize;
+
+    for link in links {
+        rewritten.push_str(&markdown[cursor..link.range.start]);
+        let label = link.alias.as_deref().unwrap_or(&link.target);
+        rewritten.push('[');
+        rewritten.push_str(&escape_link_label(label));
+        rewritten.push_str("](");
+        rewritten.push_str(NOTE_LINK_SCHEME);
+        rewritten.push_str(&encode_link_destination(&link.target));
+        rewritten.push(')');
+        cursor = link.range.end;
+    }
+
+    rewritten.push_str(&markdown[cursor..]);
+    rewritten
+}
+
+/// Decides what a clicked preview link points at.
+///
+/// Relative paths resolve against the directory of the current note, absolute paths against
+/// the notebook root, and `[[wiki]]` targets use the same matching as the backlink index.
+pub(super) fn classify_preview_link(
+    uri: &str,
+    current_note_path: Option<&str>,
+    note_paths: &[&str],
+) -> PreviewLinkTarget {
+    let uri = uri.trim();
+    if uri.is_empty() {
+        return PreviewLinkTarget::Broken("Link has no target.".to_string());
+    }
+
+    if let Some(encoded_target) = uri.strip_prefix(NOTE_LINK_SCHEME) {
+        let target = percent_decode(encoded_target).unwrap_or_else(|| encoded_target.to_string());
+        let (path, anchor) = split_anchor(&target);
+        if path.trim().is_empty() {
+            return match anchor {
+                Some(anchor) => PreviewLinkTarget::Anchor(anchor),
+                None => PreviewLinkTarget::Broken("Link has no target.".to_string()),
+            };
+        }
+
+        return match notebook::resolve_link_target(path, note_paths.iter().copied()) {
+            Some(rel_path) => PreviewLinkTarget::Note { rel_path, anchor },
+            None => PreviewLinkTarget::Broken(format!("No note matches [[{}]].", path.trim())),
+        };
+    }
+
+    if let Some(anchor) = uri.strip_prefix('#') {
+        let anchor = percent_decode(anchor).unwrap_or_else(|| anchor.to_string());
+        return PreviewLinkTarget::Anchor(anchor);
+    }
+
+    if notebook::is_external_link_destination(uri) {
+        let scheme = uri.split_once(':').unwrap_or_default().0;
+        return if OPENABLE_LINK_SCHEMES
+            .iter()
+            .any(|openable| scheme.eq_ignore_ascii_case(openable))
+        {
+            PreviewLinkTarget::External(uri.to_string())
+        } else {
+            PreviewLinkTarget::Broken(format!(
+                "Links with the '{}:' scheme are not opened.",
+                scheme
+            ))
+        };
+    }
+
+    let decoded = percent_decode(uri).unwrap_or_else(|| uri.to_string());
+    let (path, anchor) = split_anchor(&decoded);
+    let path = path.split('?').next().unwrap_or_default();
+
+    let Some(rel_path) =
+        notebook::resolve_relative_link_path(path, current_note_path.unwrap_or_default())
+    else {
+        return PreviewLinkTarget::Broken(format!("Link '{}' points outside the notebook.", uri));
+    };
+
+    let normalized = notebook::normalize_link_target(&rel_path);
+    if normalized.is_empty() {
+        return match anchor {
+            Some(anchor) => PreviewLinkTarget::Anchor(anchor),
+            None => PreviewLinkTarget::Broken(format!("Link '{}' does not point to a note.", uri)),
+        };
+    }
+
+    if note_paths.contains(&normalized.as_str()) {
+        return PreviewLinkTarget::Note {
+            rel_path: normalized,
+            anchor,
+        };
+    }
+
+    PreviewLinkTarget::Broken(format!("No note found at '{}'.", normalized))
+}
+
+/// Returns the zero-based line of the Markdown heading whose slug matches `anchor`.
+pub(super) fn heading_line_for_anchor(markdown: &str, anchor: &str) -> Option<usize> {
+    let wanted = heading_slug(anchor);
+    if wanted.is_empty() {
+        return None;
+    }
+
+    let mut in_fenced_block = false;
+    for (line_index, line) in markdown.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fenced_block = !in_fenced_block;
+            continue;
+        }
+        if in_fenced_block {
+            continue;
+        }
+
+        let level = trimmed.chars().take_while(|ch| *ch == '#').count();
+        if level == 0 || level > 6 {
+            continue;
+        }
+
+        let heading_text = &trimmed[level..];
+        if !heading_text.is_empty() && !heading_text.starts_with([' ', '\t']) {
+            continue;
+        }
+
+        if heading_slug(heading_text.trim().trim_end_matches('#')) == wanted {
+            return Some(line_index);
+        }
+    }
+
+    None
+}
+
+fn heading_slug(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+    for ch in text.trim().chars() {
+        if ch.is_alphanumeric() {
+            slug.extend(ch.to_lowercase());
+        } else if (ch == ' ' || ch == '-' || ch == '_') && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_matches('-').to_string()
+}
+
+fn split_anchor(target: &str) -> (&str, Option<String>) {
+    match target.split_once('#') {
+        Some((path, anchor)) if !anchor.trim().is_empty() => {
+            (path, Some(anchor.trim().to_string()))
+        }
+        Some((path, _)) => (path, None),
+        None => (target, None),
+    }
+}
+
+fn escape_link_label(label: &str) -> String {
+    label.replace('[', "\\[").replace(']', "\\]")
+}
+
+fn encode_link_destination(target: &str) -> String {
+    let mut encoded = String::with_capacity(target.len());
+    for ch in target.trim().chars() {
+        match ch {
+            ' ' => encoded.push_str("%20"),
+            '%' => encoded.push_str("%25"),
+            '(' => encoded.push_str("%28"),
+            ')' => encoded.push_str("%29"),
+            '<' => encoded.push_str("%3C"),
+            '>' => encoded.push_str("%3E"),
+            other => encoded.push(other),
+        }
+    }
+    encoded
+}
@@ -19,10 +19,17 @@ mod image_tag_tests;
 mod message;
 #[path = "metadata_debounce.rs"]
 mod metadata_debounce;
+#[path = "notebook_watch.rs"]
+mod notebook_watch;
 #[path = "core/persistence.rs"]
 mod persistence;
 #[path = "core/preview.rs"]
 mod preview;
+#[cfg(test)]
+#[path = "core/preview_link_tests.rs"]
+mod preview_link_tests;
+#[path = "core/preview_links.rs"]
+mod preview_links;
 #[path = "reducer.rs"]
 mod reducer;
 #[path = "selection_handlers.rs"]
@@ -47,6 +54,7 @@ use self::preview::{
     build_markdown_preview_content, cursor_preview_character_index, cursor_preview_character_range,
     preview_rendered_char_count,
 };
+use self::preview_links::{PreviewLinkTarget, classify_preview_link, heading_line_for_anchor};
 
 // Import required types and modules
 use crate::components::editor::actions::note_actions;
@@ -124,12 +132,29 @@ impl Editor {
         editor_instance.state.set_config_path(flags.config_path);
         editor_instance.state.set_ui_scale(flags.scale);
         editor_instance.state.set_app_version(flags.version);
+        editor_instance.state.set_link_opener(flags.link_opener);
+        editor_instance
+            .state
+            .set_trash_retention_days(flags.trash_retention_days);
+        editor_instance
+            .state
+            .set_journal_settings(flags.journal_path_pattern, flags.journal_template);
 
         let initial_command = if !editor_instance.state.notebook_path().is_empty() {
-            editor_instance
+            let load_notes_command = editor_instance
                 .note_explorer
                 .update(note_explorer::Message::LoadNotes)
-                .map(Message::NoteExplorerMsg)
+                .map(Message::NoteExplorerMsg);
+            let preload_search_index_command = Task::future(notebook::preload_search_index(
+                editor_instance.state.notebook_path().to_string(),
+            ))
+            .discard();
+            // Loading the trash also purges entries past their retention period.
+            Task::batch(vec![
+                load_notes_command,
+                editor_instance.load_trash_task(),
+                preload_search_index_command,
+            ])
         } else {
             Task::none()
         };
@@ -169,6 +194,19 @@ impl Editor {
         }
     }
 
+    /// Replaces the whole note text as one undoable edit.
+    fn replace_markdown_with_undo(&mut self, note_path: &str, new_markdown: String) {
+        self.undo_manager.add_to_history(
+            note_path,
+            self.markdown_text.clone(),
+            self.content.cursor(),
+        );
+        self.content = iced::widget::text_editor::Content::with_text(&new_markdown);
+        self.markdown_text = new_markdown;
+        self.undo_manager.reset_edit_debounce(note_path);
+        self.prune_embedded_images_for_current_markdown();
+    }
+
     fn handle_confirm_delete_embedded_images(&mut self, confirmed: bool) -> Task<Message> {
         if !self.embedded_image_workflow.has_pending_deletion() {
             self.embedded_image_workflow.clear_pending_deletion();
@@ -257,6 +295,13 @@ impl Editor {
         )
     }
 
+    /// Re-runs the saved searches behind the explorer's smart folders.
+    fn refresh_smart_folders_task(&mut self) -> Task<Message> {
+        self.note_explorer
+            .update(note_explorer::Message::RefreshSmartFolders)
+            .map(Message::NoteExplorerMsg)
+    }
+
     fn touch_selected_note_last_updated_and_schedule_save_task(&mut self) -> Task<Message> {
         if self.touch_selected_note_last_updated() {
             self.schedule_debounced_metadata_save_task()
@@ -279,24 +324,133 @@ impl Editor {
         )
     }
 
+    fn load_trash_task(&self) -> Task<Message> {
+        if self.state.notebook_path().trim().is_empty() {
+            return Task::none();
+        }
+
+        Task::perform(
+            notebook::load_trash(
+                self.state.notebook_path().to_string(),
+                self.state.trash_retention_days(),
+            ),
+            Message::TrashLoaded,
+        )
+    }
+
     fn with_preview_scroll_task(&self, task: Task<Message>) -> Task<Message> {
         Task::batch(vec![task, self.scroll_preview_to_cursor_task()])
     }
 
     fn scroll_preview_to_cursor_task(&self) -> Task<Message> {
-        if self.state.selected_note_path().is_none()
-            || self.state.show_visualizer()
-            || self.state.show_move_note_input()
-            || self.state.show_new_note_input()
-            || self.state.show_embedded_image_delete_confirmation()
-            || self.state.show_about_info()
-        {
+        self.scroll_preview_to_editor_cursor_task(self.content.cursor())
+    }
+
+    /// Moves the editor cursor to the heading matching `anchor` and scrolls the preview there.
+    fn scroll_preview_to_anchor_task(&mut self, anchor: &str) -> Task<Message> {
+        let Some(line) = heading_line_for_anchor(&self.markdown_text, anchor) else {
+            self.state
+                .set_status_message(format!("Heading '#{}' was not found in this note.", anchor));
+            return Task::none();
+        };
+
+        let cursor = iced::widget::text_editor::Cursor {
+            position: iced::widget::text_editor::Position { line, column: 0 },
+            selection: None,
+        };
+        self.content.move_to(cursor);
+        self.scroll_preview_to_editor_cursor_task(cursor)
+    }
+
+    /// Selects a search match in the editor and scrolls the preview there.
+    ///
+    /// The match may predate later edits, so it is clamped to the current text.
+    fn select_search_match_task(
+        &mut self,
+        line_number: usize,
+        range: notebook::SearchMatchRange,
+    ) -> Task<Message> {
+        let line = line_number
+            .saturating_sub(1)
+            .min(self.content.line_count().saturating_sub(1));
+        let line_text = self
+            .content
+            .line(line)
+            .map_or_else(String::new, |line| line.text.into_owned());
+        let column = |offset: usize| {
+            if line_text.is_char_boundary(offset) {
+                offset
+            } else {
+                0
+            }
+        };
+
+        let (start, end) = (column(range.start), column(range.end));
+        let cursor = iced::widget::text_editor::Cursor {
+            position: iced::widget::text_editor::Position { line, column: end },
+            selection: (start < end).then_some(iced::widget::text_editor::Position {
+                line,
+                column: start,
+            }),
+        };
+        self.content.move_to(cursor);
+        self.scroll_preview_to_editor_cursor_task(cursor)
+    }
+
+    /// Moves to the next or previous search match in the open note, wrapping around.
+    fn step_search_match_task(&mut self, forward: bool) -> Task<Message> {
+        let Some(note_path) = self.state.selected_note_path() else {
+            return Task::none();
+        };
+        if self.content_note_path.as_ref() != Some(note_path) {
+            return Task::none();
+        }
+        let Some(result) = self.state.search_result_for(note_path) else {
+            return Task::none();
+        };
+
+        let cursor = self.content.cursor();
+        let from = cursor.selection.map_or(cursor.position, |selection| {
+            if (selection.line, selection.column) < (cursor.position.line, cursor.position.column) {
+                selection
+            } else {
+                cursor.position
+            }
+        });
+        let from = (from.line + 1, from.column);
+
+        let locations: Vec<(usize, notebook::SearchMatchRange)> =
+            result.match_locations().collect();
+        let target = if forward {
+            locations
+                .iter()
+                .find(|(line_number, range)| (*line_number, range.start) > from)
+                .or(locations.first())
+        } else {
+            locations
+                .iter()
+                .rev()
+                .find(|(line_number, range)| (*line_number, range.start) < from)
+                .or(locations.last())
+        };
+
+        match target.copied() {
+            Some((line_number, range)) => self.select_search_match_task(line_number, range),
+            None => Task::none(),
+        }
+    }
+
+    fn scroll_preview_to_editor_cursor_task(
+        &self,
+        cursor: iced::widget::text_editor::Cursor,
+    ) -> Task<Message> {
+        if self.state.selected_note_path().is_none() || self.state.blocks_note_editing() {
             return Task::none();
         }
 
         let Some(cursor_char_index) = cursor_preview_character_index(
             &self.markdown_text,
-            self.content.cursor(),
+            cursor,
             self.embedded_image_workflow.images(),
         ) else {
             return Task::none();
@@ -323,6 +477,7 @@ impl Editor {
         )
     }
 
+    /// Marks the selected note as updated and reads its front matter from the editor text.
     fn touch_selected_note_last_updated(&mut self) -> bool {
         if let Some(selected_path) = self.state.selected_note_path().cloned()
             && let Some(note) = self
@@ -332,6 +487,14 @@ impl Editor {
                 .find(|note| note.rel_path == selected_path)
         {
             note.last_updated = Some(notebook::current_timestamp_rfc3339());
+            let previous_labels = note.labels.clone();
+            if notebook::apply_front_matter(note, &self.markdown_text)
+                && note.labels != previous_labels
+            {
+                let labels = note.labels.clone();
+                self.state.set_selected_note_labels(labels);
+                self.visualizer.sync_notes(&self.note_explorer.notes);
+            }
             return true;
         }
 
@@ -373,7 +536,7 @@ impl Editor {
     }
 
     // Keep subscription method as is
-    pub fn subscription(_state: &Self) -> Subscription<Message> {
+    pub fn subscription(state: &Self) -> Subscription<Message> {
         let keyboard_subscription =
             iced::event::listen_with(|event, _status, _shell| match event {
                 Event::Keyboard(iced::keyboard::Event::KeyPressed { key, modifiers, .. }) => {
@@ -394,6 +557,14 @@ impl Editor {
                         }
                     }
 
+                    // F3 and Shift+F3 step through search matches in the open note
+                    if key == Key::Named(iced::keyboard::key::Named::F3) {
+                        if modifiers.shift() {
+                            return Some(Message::PreviousSearchMatch);
+                        }
+                        return Some(Message::NextSearchMatch);
+                    }
+
                     // Handle Tab key press (no modifiers)
                     if key == Key::Named(iced::keyboard::key::Named::Tab) && modifiers.is_empty() {
                         return Some(Message::HandleTabKey);
@@ -407,7 +578,14 @@ impl Editor {
         let close_request_subscription =
             window::close_requests().map(Message::WindowCloseRequested);
 
-        Subscription::batch(vec![keyboard_subscription, close_request_subscription])
+        let mut subscriptions = vec![keyboard_subscription, close_request_subscription];
+        if !state.state.notebook_path().trim().is_empty() {
+            subscriptions.push(notebook_watch::notebook_watch_subscription(
+                state.state.notebook_path().to_string(),
+            ));
+        }
+
+        Subscription::batch(subscriptions)
     }
 
     #[cfg(test)]
@@ -419,16 +597,99 @@ impl Editor {
             .and_then(|note| note.last_updated.clone())
     }
 
+    #[cfg(test)]
+    pub(crate) fn debug_note_metadata(&self, rel_path: &str) -> Option<notebook::NoteMetadata> {
+        self.note_explorer
+            .notes
+            .iter()
+            .find(|note| note.rel_path == rel_path)
+            .cloned()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn debug_selected_note_labels(&self) -> Vec<String> {
+        self.state.selected_note_labels().to_vec()
+    }
+
     #[cfg(test)]
     pub(crate) fn debug_selected_note_path(&self) -> Option<String> {
         self.state.selected_note_path().cloned()
     }
 
+    #[cfg(test)]
+    pub(crate) fn debug_selected_note_backlinks(&self) -> Vec<notebook::NoteBacklink> {
+        self.state.selected_note_backlinks().to_vec()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn debug_status_message(&self) -> Option<String> {
+        self.state.status_message().map(str::to_string)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn debug_trash_state(&self) -> (bool, Vec<notebook::TrashEntry>, Option<String>) {
+        (
+            self.state.show_trash(),
+            self.state.trash_entries().to_vec(),
+            self.state.trash_restore_entry_id().map(str::to_string),
+        )
+    }
+
+    #[cfg(test)]
+    pub(crate) fn debug_fsck_state(&self) -> (bool, bool, Vec<notebook::FsckFinding>) {
+        (
+            self.state.show_fsck(),
+            self.state.is_fsck_in_progress(),
+            self.state.fsck_findings().to_vec(),
+        )
+    }
+
+    #[cfg(test)]
+    pub(crate) fn debug_replace_state(&self) -> (bool, bool, Option<notebook::ReplacePreview>) {
+        (
+            self.state.show_replace(),
+            self.state.is_replace_in_progress(),
+            self.state.replace_preview().cloned(),
+        )
+    }
+
+    #[cfg(test)]
+    pub(crate) fn debug_label_edit_state(
+        &self,
+    ) -> (bool, bool, Option<notebook::LabelEditPreview>) {
+        (
+            self.state.show_label_editor(),
+            self.state.is_label_edit_in_progress(),
+            self.state.label_edit_preview().cloned(),
+        )
+    }
+
+    #[cfg(test)]
+    pub(crate) fn debug_label_suggestions(&self) -> Vec<String> {
+        self.state.label_suggestions().to_vec()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn debug_label_style(&self) -> (String, String, String, notebook::LabelRegistry) {
+        let (color, description, icon) = self.state.label_style();
+        (
+            color.to_string(),
+            description.to_string(),
+            icon.to_string(),
+            self.note_explorer.label_registry.clone(),
+        )
+    }
+
     #[cfg(test)]
     pub(crate) fn debug_markdown_text(&self) -> String {
         self.markdown_text.clone()
     }
 
+    #[cfg(test)]
+    pub(crate) fn debug_cursor(&self) -> iced::widget::text_editor::Cursor {
+        self.content.cursor()
+    }
+
     #[cfg(test)]
     pub(crate) fn debug_metadata_state(&self) -> (u64, bool, bool) {
         (
@@ -447,6 +708,26 @@ impl Editor {
         )
     }
 
+    #[cfg(test)]
+    pub(crate) fn debug_search_mode(&self) -> notebook::SearchMode {
+        self.state.search_mode()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn debug_search_scope(&self) -> Option<String> {
+        self.state.search_scope().map(str::to_string)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn debug_new_note_template(&self) -> Option<String> {
+        self.state.new_note_template().map(str::to_string)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn debug_search_error(&self) -> Option<String> {
+        self.state.search_error().map(str::to_string)
+    }
+
     #[cfg(test)]
     pub(crate) fn debug_shutdown_in_progress(&self) -> bool {
         self.shutdown_in_progress
@@ -455,14 +736,31 @@ impl Editor {
     #[cfg(test)]
     pub(crate) fn debug_shutdown_payload(
         &self,
-    ) -> (String, Option<String>, String, Vec<notebook::NoteMetadata>) {
+    ) -> (
+        String,
+        Option<String>,
+        String,
+        Option<notebook::NoteDiskState>,
+        Vec<notebook::NoteMetadata>,
+    ) {
         (
             self.state.notebook_path().to_string(),
             self.content_note_path.clone(),
             self.markdown_text.clone(),
+            self.content_note_path
+                .as_deref()
+                .and_then(|note_path| self.state.note_disk_state(note_path))
+                .cloned(),
             self.note_explorer.notes.clone(),
         )
     }
+
+    #[cfg(test)]
+    pub(crate) fn debug_note_conflict(
+        &self,
+    ) -> Option<&crate::components::editor::state::editor_state::NoteConflict> {
+        self.state.note_conflict()
+    }
 }
 
 // Keep Default impl for Editor
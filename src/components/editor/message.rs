@@ -4,7 +4,10 @@ use std::collections::HashMap;
 
 use crate::components::note_explorer;
 use crate::components::visualizer;
-use crate::notebook::{self, NoteMetadata, NotebookError};
+use crate::notebook::{
+    self, CreatedNote, FsckFinding, MovedNote, NoteDiskState, NoteMetadata, NoteSaveOutcome,
+    NoteVersion, NotebookChanges, NotebookError, ReplacePreview, SearchMatchRange, TrashEntry,
+};
 
 #[derive(Debug, Clone)]
 pub enum Message {
@@ -26,12 +29,51 @@ pub enum Message {
     AddLabel,
     RemoveLabel(String),
     MetadataSaved(Result<(), NotebookError>),
+    ToggleLabelEditor,
+    LabelEditLabelChanged(String),
+    LabelEditTargetChanged(String),
+    PreviewLabelEdit(notebook::LabelEditKind),
+    ApplyLabelEdit,
+    LabelEditApplied(Result<Vec<String>, NotebookError>),
+    LabelStyleColorChanged(String),
+    LabelStyleDescriptionChanged(String),
+    LabelStyleIconChanged(String),
+    SaveLabelStyle,
+    LabelStyleSaved(Result<notebook::LabelRegistry, NotebookError>),
+    /// Adds the label input to, or removes it from, every note marked in the explorer.
+    EditMarkedNotesLabel(notebook::BulkLabelAction),
+    /// The action, the normalized label and the saved changes of a marked-notes label edit.
+    MarkedNotesLabelEdited(
+        notebook::BulkLabelAction,
+        String,
+        Vec<notebook::LabelEditNote>,
+        Result<Vec<String>, NotebookError>,
+    ),
+
+    // Links
+    BacklinksLoaded(String, Vec<notebook::NoteBacklink>),
+    MarkdownLinkClicked(String),
+    ExternalLinkOpened(Result<(), String>),
 
     // Search
     SearchQueryChanged(String),
     RunSearch,
-    SearchCompleted(u64, Vec<notebook::NoteSearchResult>),
+    SearchModeChanged(notebook::SearchMode),
+    SearchCompleted(
+        u64,
+        Result<Vec<notebook::NoteSearchResult>, notebook::NotebookError>,
+    ),
     ClearSearch,
+    OpenSearchMatch(String, usize, SearchMatchRange),
+    NextSearchMatch,
+    PreviousSearchMatch,
+    SearchInFolder(String),
+    ClearSearchScope,
+    SavedSearchNameChanged(String),
+    SaveSearch,
+    OpenSavedSearch(String),
+    DeleteSavedSearch(String),
+    SavedSearchesChanged(Result<(), NotebookError>),
 
     // Content management
     NoteContentSaved(Result<(), NotebookError>),
@@ -48,8 +90,16 @@ pub enum Message {
     NewNote,
     NewNoteInputChanged(String),
     CreateNote,
-    NoteCreated(Result<NoteMetadata, NotebookError>),
+    NoteCreated(Result<CreatedNote, NotebookError>),
     CancelNewNote,
+    NewNoteTemplatesLoaded(Result<Vec<String>, NotebookError>),
+    NewNoteTemplateSelected(Option<String>),
+    SetDefaultTemplate,
+    DefaultTemplateSaved(Result<(), NotebookError>),
+    OpenTodayJournalNote,
+    PreviousJournalDay,
+    NextJournalDay,
+    JournalNoteOpened(Result<CreatedNote, NotebookError>),
     DeleteNote,
     ConfirmDeleteNote(bool),
     ConfirmDeleteEmbeddedImages(bool),
@@ -58,7 +108,54 @@ pub enum Message {
     MoveNoteInputChanged(String),
     ConfirmMoveNote,
     CancelMoveNote,
-    NoteMoved(Result<String, NotebookError>, String),
+    NoteMoved(Result<MovedNote, NotebookError>, String),
+
+    // Trash
+    ToggleTrash,
+    TrashLoaded(Result<Vec<TrashEntry>, NotebookError>),
+    RestoreTrashEntry(String),
+    TrashRestorePathChanged(String),
+    ConfirmTrashRestore,
+    CancelTrashRestore,
+    TrashEntryRestored(Result<NoteMetadata, NotebookError>),
+    PurgeTrashEntry(String),
+    ConfirmPurgeTrashEntry(String, bool),
+    TrashEntryPurged(Result<(), NotebookError>),
+
+    // History
+    ToggleHistory,
+    NoteHistoryLoaded(String, Result<Vec<NoteVersion>, NotebookError>),
+    SelectHistoryVersion(String),
+    HistoryVersionLoaded(String, String, Result<String, NotebookError>),
+    RestoreHistoryVersion,
+
+    // Filesystem watcher
+    NotebookChangedOnDisk(NotebookChanges),
+    OpenNoteReloadedFromDisk(String, String, NoteDiskState),
+
+    // Save conflicts
+    NoteDiskStateLoaded(String, NoteDiskState),
+    NoteContentSaveFinished(String, Result<NoteSaveOutcome, NotebookError>),
+    KeepMineInConflict,
+    KeepTheirsInConflict,
+    MergeConflict,
+
+    // Notebook check
+    ToggleFsck,
+    FsckCompleted(Result<Vec<FsckFinding>, NotebookError>),
+    RepairFsckFinding(FsckFinding),
+    ConfirmRepairFsckFinding(FsckFinding, bool),
+    FsckFindingRepaired(Result<Vec<FsckFinding>, NotebookError>),
+
+    // Find and replace
+    ToggleReplace,
+    ReplaceFindChanged(String),
+    ReplaceWithChanged(String),
+    PreviewReplace,
+    ReplacePreviewLoaded(Result<ReplacePreview, NotebookError>),
+    ToggleReplaceHit(usize),
+    ApplyReplace,
+    ReplaceApplied(Result<Vec<String>, NotebookError>),
 
     // Folder operations
     InitiateFolderRename(String),
@@ -67,6 +164,6 @@ pub enum Message {
     AboutButtonClicked,
     IncreaseScale,
     DecreaseScale,
-    MarkdownLinkClicked(String),
+    DismissStatus,
     ScaleSaved(Result<(), String>),
 }
@@ -6,12 +6,15 @@
 use std::collections::HashMap;
 use std::path::Path;
 
-use crate::notebook::{self, NoteMetadata, NotebookError};
+use iced::task::Task;
+
+use crate::components::editor::Message;
+use crate::notebook::{self, NoteDiskState, NoteMetadata, NoteSaveOutcome, NotebookError};
 
 #[derive(Debug, Clone)]
 pub struct LoadedNotePayload {
     pub note_path: String,
-    pub content: String,
+    pub disk_state: NoteDiskState,
     pub images: HashMap<String, String>,
 }
 
@@ -20,26 +23,40 @@ pub async fn load_note_payload(
     selected_note_path: String,
 ) -> LoadedNotePayload {
     let note_dir_path = Path::new(&notebook_path).join(&selected_note_path);
-    let full_note_path = note_dir_path.join("note.md");
-    let loaded_content = match std::fs::read_to_string(full_note_path) {
-        Ok(content) => content,
-        Err(_err) => {
-            #[cfg(debug_assertions)]
-            eprintln!("Failed to read note file for editor: {}", _err);
-            String::new()
-        }
-    };
+    let disk_state = notebook::load_note_content_sync(&notebook_path, &selected_note_path);
 
     // Legacy cleanup: embedded image state is now inferred from markdown.
     let _ = std::fs::remove_file(note_dir_path.join("embedded_images.json"));
 
     LoadedNotePayload {
         note_path: selected_note_path,
-        content: loaded_content,
+        disk_state,
         images: HashMap::new(),
     }
 }
 
+/// Saves note content against the on-disk state the editor loaded for the note.
+///
+/// The save is refused with a conflict when another program changed the file since then.
+pub fn save_note_content_task(
+    notebook_path: &str,
+    note_path: String,
+    content: String,
+    base: Option<NoteDiskState>,
+) -> Task<Message> {
+    let notebook_path = notebook_path.to_string();
+
+    Task::perform(
+        {
+            let note_path = note_path.clone();
+            async move {
+                notebook::save_note_content_checked(notebook_path, note_path, content, base).await
+            }
+        },
+        move |result| Message::NoteContentSaveFinished(note_path.clone(), result),
+    )
+}
+
 pub fn save_metadata_snapshot(
     notebook_path: &str,
     notes: &[NoteMetadata],
@@ -51,6 +68,7 @@ pub fn flush_for_shutdown(
     notebook_path: &str,
     content_note_path: Option<String>,
     markdown_text: &str,
+    content_note_base: Option<NoteDiskState>,
     notes: &[NoteMetadata],
 ) -> Result<(), NotebookError> {
     if notebook_path.trim().is_empty() {
@@ -58,7 +76,21 @@ pub fn flush_for_shutdown(
     }
 
     if let Some(note_path) = content_note_path {
-        notebook::save_note_content_sync(notebook_path, &note_path, markdown_text)?;
+        let outcome = notebook::save_note_content_checked_sync(
+            notebook_path,
+            &note_path,
+            markdown_text,
+            content_note_base.as_ref(),
+        )?;
+        if let NoteSaveOutcome::Conflict(_) = outcome {
+            return Err(NotebookError::validation(
+                "save note content",
+                format!(
+                    "'{}' was changed by another program. Resolve the conflict before closing.",
+                    note_path
+                ),
+            ));
+        }
     }
 
     save_metadata_snapshot(notebook_path, notes)
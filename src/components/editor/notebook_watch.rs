@@ -0,0 +1,40 @@
+use iced::Subscription;
+use iced::futures::channel::mpsc;
+use iced::futures::{SinkExt, Stream, StreamExt};
+
+use super::Message;
+use crate::notebook;
+
+/// Subscription that reports external changes to the open notebook.
+pub(super) fn notebook_watch_subscription(notebook_path: String) -> Subscription<Message> {
+    Subscription::run_with(notebook_path, |notebook_path| {
+        watch_notebook_stream(notebook_path.clone())
+    })
+}
+
+fn watch_notebook_stream(notebook_path: String) -> impl Stream<Item = Message> {
+    iced::stream::channel(16, async move |mut output: mpsc::Sender<Message>| {
+        let (change_sender, mut change_receiver) = mpsc::unbounded();
+        // Dropping the watcher stops it, so it lives as long as this stream.
+        let _watcher = match notebook::watch_notebook(&notebook_path, move |changes| {
+            let _ = change_sender.unbounded_send(changes);
+        }) {
+            Ok(watcher) => watcher,
+            Err(_err) => {
+                #[cfg(debug_assertions)]
+                eprintln!("Notebook watcher unavailable: {}", _err);
+                return;
+            }
+        };
+
+        while let Some(changes) = change_receiver.next().await {
+            if output
+                .send(Message::NotebookChangedOnDisk(changes))
+                .await
+                .is_err()
+            {
+                break;
+            }
+        }
+    })
+}
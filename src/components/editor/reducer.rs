@@ -5,12 +5,19 @@ pub(super) enum MessageDomain {
     Text,
     Selection,
     Label,
+    Links,
     Search,
     DebouncedMetadata,
     Shutdown,
     SaveFeedback,
     Visualizer,
     NoteLifecycle,
+    Trash,
+    History,
+    Watcher,
+    Conflict,
+    Fsck,
+    Replace,
     Ui,
 }
 
@@ -26,14 +33,42 @@ fn message_domain(message: &Message) -> MessageDomain {
 
         Message::NoteExplorerMsg(_) | Message::NoteSelected(_) => MessageDomain::Selection,
 
-        Message::NewLabelInputChanged(_) | Message::AddLabel | Message::RemoveLabel(_) => {
-            MessageDomain::Label
-        }
+        Message::NewLabelInputChanged(_)
+        | Message::AddLabel
+        | Message::RemoveLabel(_)
+        | Message::ToggleLabelEditor
+        | Message::LabelEditLabelChanged(_)
+        | Message::LabelEditTargetChanged(_)
+        | Message::PreviewLabelEdit(_)
+        | Message::ApplyLabelEdit
+        | Message::LabelEditApplied(_)
+        | Message::LabelStyleColorChanged(_)
+        | Message::LabelStyleDescriptionChanged(_)
+        | Message::LabelStyleIconChanged(_)
+        | Message::SaveLabelStyle
+        | Message::LabelStyleSaved(_)
+        | Message::EditMarkedNotesLabel(_)
+        | Message::MarkedNotesLabelEdited(..) => MessageDomain::Label,
+
+        Message::BacklinksLoaded(_, _)
+        | Message::MarkdownLinkClicked(_)
+        | Message::ExternalLinkOpened(_) => MessageDomain::Links,
 
         Message::SearchQueryChanged(_)
         | Message::RunSearch
+        | Message::SearchModeChanged(_)
         | Message::SearchCompleted(_, _)
-        | Message::ClearSearch => MessageDomain::Search,
+        | Message::ClearSearch
+        | Message::OpenSearchMatch(_, _, _)
+        | Message::NextSearchMatch
+        | Message::PreviousSearchMatch
+        | Message::SearchInFolder(_)
+        | Message::ClearSearchScope
+        | Message::SavedSearchNameChanged(_)
+        | Message::SaveSearch
+        | Message::OpenSavedSearch(_)
+        | Message::DeleteSavedSearch(_)
+        | Message::SavedSearchesChanged(_) => MessageDomain::Search,
 
         Message::DebouncedMetadataSaveElapsed(_)
         | Message::DebouncedMetadataSaveCompleted(_, _) => MessageDomain::DebouncedMetadata,
@@ -53,6 +88,14 @@ fn message_domain(message: &Message) -> MessageDomain {
         | Message::CreateNote
         | Message::CancelNewNote
         | Message::NoteCreated(_)
+        | Message::NewNoteTemplatesLoaded(_)
+        | Message::NewNoteTemplateSelected(_)
+        | Message::SetDefaultTemplate
+        | Message::DefaultTemplateSaved(_)
+        | Message::OpenTodayJournalNote
+        | Message::PreviousJournalDay
+        | Message::NextJournalDay
+        | Message::JournalNoteOpened(_)
         | Message::DeleteNote
         | Message::ConfirmDeleteNote(_)
         | Message::ConfirmDeleteEmbeddedImages(_)
@@ -63,11 +106,53 @@ fn message_domain(message: &Message) -> MessageDomain {
         | Message::CancelMoveNote
         | Message::NoteMoved(_, _) => MessageDomain::NoteLifecycle,
 
+        Message::ToggleTrash
+        | Message::TrashLoaded(_)
+        | Message::RestoreTrashEntry(_)
+        | Message::TrashRestorePathChanged(_)
+        | Message::ConfirmTrashRestore
+        | Message::CancelTrashRestore
+        | Message::TrashEntryRestored(_)
+        | Message::PurgeTrashEntry(_)
+        | Message::ConfirmPurgeTrashEntry(_, _)
+        | Message::TrashEntryPurged(_) => MessageDomain::Trash,
+
+        Message::ToggleHistory
+        | Message::NoteHistoryLoaded(_, _)
+        | Message::SelectHistoryVersion(_)
+        | Message::HistoryVersionLoaded(_, _, _)
+        | Message::RestoreHistoryVersion => MessageDomain::History,
+
+        Message::NotebookChangedOnDisk(_) | Message::OpenNoteReloadedFromDisk(_, _, _) => {
+            MessageDomain::Watcher
+        }
+
+        Message::NoteDiskStateLoaded(_, _)
+        | Message::NoteContentSaveFinished(_, _)
+        | Message::KeepMineInConflict
+        | Message::KeepTheirsInConflict
+        | Message::MergeConflict => MessageDomain::Conflict,
+
+        Message::ToggleFsck
+        | Message::FsckCompleted(_)
+        | Message::RepairFsckFinding(_)
+        | Message::ConfirmRepairFsckFinding(_, _)
+        | Message::FsckFindingRepaired(_) => MessageDomain::Fsck,
+
+        Message::ToggleReplace
+        | Message::ReplaceFindChanged(_)
+        | Message::ReplaceWithChanged(_)
+        | Message::PreviewReplace
+        | Message::ReplacePreviewLoaded(_)
+        | Message::ToggleReplaceHit(_)
+        | Message::ApplyReplace
+        | Message::ReplaceApplied(_) => MessageDomain::Replace,
+
         Message::InitiateFolderRename(_)
         | Message::AboutButtonClicked
         | Message::IncreaseScale
         | Message::DecreaseScale
-        | Message::MarkdownLinkClicked(_) => MessageDomain::Ui,
+        | Message::DismissStatus => MessageDomain::Ui,
     }
 }
 
@@ -76,6 +161,7 @@ pub(super) fn route_message(state: &mut Editor, message: Message) -> Task<Messag
         MessageDomain::Text => Editor::handle_text_messages(state, message),
         MessageDomain::Selection => Editor::handle_selection_messages(state, message),
         MessageDomain::Label => Editor::handle_label_messages(state, message),
+        MessageDomain::Links => Editor::handle_link_messages(state, message),
         MessageDomain::Search => Editor::handle_search_messages(state, message),
         MessageDomain::DebouncedMetadata => {
             Editor::handle_debounced_metadata_messages(state, message)
@@ -84,6 +170,12 @@ pub(super) fn route_message(state: &mut Editor, message: Message) -> Task<Messag
         MessageDomain::SaveFeedback => Editor::handle_save_feedback_messages(message),
         MessageDomain::Visualizer => Editor::handle_visualizer_messages(state, message),
         MessageDomain::NoteLifecycle => Editor::handle_note_lifecycle_messages(state, message),
+        MessageDomain::Trash => Editor::handle_trash_messages(state, message),
+        MessageDomain::History => Editor::handle_history_messages(state, message),
+        MessageDomain::Watcher => Editor::handle_watcher_messages(state, message),
+        MessageDomain::Conflict => Editor::handle_conflict_messages(state, message),
+        MessageDomain::Fsck => Editor::handle_fsck_messages(state, message),
+        MessageDomain::Replace => Editor::handle_replace_messages(state, message),
         MessageDomain::Ui => Editor::handle_ui_messages(state, message),
     }
 }
@@ -1,7 +1,11 @@
 use std::collections::HashSet;
 use std::path::Path;
 
-use crate::notebook::NoteSearchResult;
+use crate::notebook::{
+    DEFAULT_JOURNAL_PATH_PATTERN, FsckFinding, LabelEditPreview, LabelInfo, LineDiff, MergeResult,
+    NoteBacklink, NoteDiskState, NoteSearchResult, NoteVersion, ReplacePreview, SearchMatchRange,
+    SearchMode, TrashEntry,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum UiMode {
@@ -11,6 +15,24 @@ enum UiMode {
     MoveNoteDialog,
     EmbeddedImageDeleteDialog,
     About,
+    Trash,
+    History,
+    NoteConflict,
+    Fsck,
+    Replace,
+    LabelEditor,
+}
+
+/// The open note's file was changed by another program while the editor had its own edits.
+#[derive(Debug, Clone)]
+pub struct NoteConflict {
+    pub note_path: String,
+    /// Current on-disk state written by the other program.
+    pub theirs: NoteDiskState,
+    /// Line diff from the on-disk content to the editor buffer.
+    pub diff: Vec<LineDiff>,
+    /// Three-way merge of the base, the on-disk content and the editor buffer.
+    pub merge: MergeResult,
 }
 
 #[derive(Debug)]
@@ -20,22 +42,67 @@ pub struct EditorState {
     config_path: String,
     ui_scale: f32,
     app_version: String,
+    link_opener: String,
+    trash_retention_days: u32,
+    /// Path pattern and optional template of daily notes, from the config file.
+    journal_path_pattern: String,
+    journal_template: Option<String>,
 
     // Note selection and metadata
     selected_note_path: Option<String>,
     selected_note_labels: Vec<String>,
+    selected_note_backlinks: Vec<NoteBacklink>,
+    pending_preview_anchor: Option<(String, String)>,
+    pending_search_match: Option<(String, usize, SearchMatchRange)>,
 
     // Text input states
     new_label_text: String,
+    label_suggestions: Vec<String>,
     search_query: String,
     search_results: Vec<NoteSearchResult>,
+    search_error: Option<String>,
+    search_mode: SearchMode,
+    /// Folder the search box is limited to; `None` searches the whole notebook.
+    search_scope: Option<String>,
+    saved_search_name: String,
 
     // UI mode and dialog-specific state
     ui_mode: UiMode,
     new_note_path_input: String,
+    /// Templates in the notebook's `.templates` directory, offered by the new-note dialog.
+    new_note_templates: Vec<String>,
+    /// Template the next note is created from; `None` creates an empty note.
+    new_note_template: Option<String>,
     move_note_current_path: Option<String>,
     move_note_new_path_input: String,
     pending_embedded_image_delete_count: usize,
+    trash_entries: Vec<TrashEntry>,
+    trash_restore_entry_id: Option<String>,
+    trash_restore_path_input: String,
+    note_history: Vec<NoteVersion>,
+    selected_history_version: Option<String>,
+    history_version_content: Option<String>,
+    history_diff: Vec<LineDiff>,
+    note_conflict: Option<NoteConflict>,
+    fsck_findings: Vec<FsckFinding>,
+    fsck_in_progress: bool,
+    replace_find: String,
+    replace_with: String,
+    replace_preview: Option<ReplacePreview>,
+    replace_in_progress: bool,
+    label_edit_label: String,
+    label_edit_target: String,
+    label_edit_preview: Option<LabelEditPreview>,
+    label_edit_in_progress: bool,
+    label_style_color: String,
+    label_style_description: String,
+    label_style_icon: String,
+
+    // On-disk state the open note was loaded or last saved with
+    note_disk_state: Option<(String, NoteDiskState)>,
+
+    // Inline status shown above the editor (broken links, opener failures)
+    status_message: Option<String>,
 
     // Flag indicating if we're loading a new note
     loading_note: bool,
@@ -48,16 +115,53 @@ impl EditorState {
             config_path: String::new(),
             ui_scale: 1.0,
             app_version: String::new(),
+            link_opener: String::new(),
+            trash_retention_days: 0,
+            journal_path_pattern: DEFAULT_JOURNAL_PATH_PATTERN.to_string(),
+            journal_template: None,
             selected_note_path: None,
             selected_note_labels: Vec::new(),
+            selected_note_backlinks: Vec::new(),
+            pending_preview_anchor: None,
+            pending_search_match: None,
             new_label_text: String::new(),
+            label_suggestions: Vec::new(),
             search_query: String::new(),
             search_results: Vec::new(),
+            search_error: None,
+            search_mode: SearchMode::default(),
+            search_scope: None,
+            saved_search_name: String::new(),
             ui_mode: UiMode::Editor,
             new_note_path_input: String::new(),
+            new_note_templates: Vec::new(),
+            new_note_template: None,
             move_note_current_path: None,
             move_note_new_path_input: String::new(),
             pending_embedded_image_delete_count: 0,
+            trash_entries: Vec::new(),
+            trash_restore_entry_id: None,
+            trash_restore_path_input: String::new(),
+            note_history: Vec::new(),
+            selected_history_version: None,
+            history_version_content: None,
+            history_diff: Vec::new(),
+            note_conflict: None,
+            fsck_findings: Vec::new(),
+            fsck_in_progress: false,
+            replace_find: String::new(),
+            replace_with: String::new(),
+            replace_preview: None,
+            replace_in_progress: false,
+            label_edit_label: String::new(),
+            label_edit_target: String::new(),
+            label_edit_preview: None,
+            label_edit_in_progress: false,
+            label_style_color: String::new(),
+            label_style_description: String::new(),
+            label_style_icon: String::new(),
+            note_disk_state: None,
+            status_message: None,
             loading_note: false,
         }
     }
@@ -75,6 +179,22 @@ impl EditorState {
         &self.config_path
     }
 
+    pub fn link_opener(&self) -> &str {
+        &self.link_opener
+    }
+
+    pub fn trash_retention_days(&self) -> u32 {
+        self.trash_retention_days
+    }
+
+    pub fn journal_path_pattern(&self) -> &str {
+        &self.journal_path_pattern
+    }
+
+    pub fn journal_template(&self) -> Option<&str> {
+        self.journal_template.as_deref()
+    }
+
     pub fn ui_scale(&self) -> f32 {
         self.ui_scale
     }
@@ -87,10 +207,22 @@ impl EditorState {
         &self.selected_note_labels
     }
 
+    pub fn selected_note_backlinks(&self) -> &[NoteBacklink] {
+        &self.selected_note_backlinks
+    }
+
+    pub fn status_message(&self) -> Option<&str> {
+        self.status_message.as_deref()
+    }
+
     pub fn new_label_text(&self) -> &str {
         &self.new_label_text
     }
 
+    pub fn label_suggestions(&self) -> &[String] {
+        &self.label_suggestions
+    }
+
     pub fn search_query(&self) -> &str {
         &self.search_query
     }
@@ -99,6 +231,29 @@ impl EditorState {
         &self.search_results
     }
 
+    /// Search result for `note_path`, if the note is among the current results.
+    pub fn search_result_for(&self, note_path: &str) -> Option<&NoteSearchResult> {
+        self.search_results
+            .iter()
+            .find(|result| result.rel_path == note_path)
+    }
+
+    pub fn search_error(&self) -> Option<&str> {
+        self.search_error.as_deref()
+    }
+
+    pub fn search_mode(&self) -> SearchMode {
+        self.search_mode
+    }
+
+    pub fn search_scope(&self) -> Option<&str> {
+        self.search_scope.as_deref()
+    }
+
+    pub fn saved_search_name(&self) -> &str {
+        &self.saved_search_name
+    }
+
     pub fn show_visualizer(&self) -> bool {
         self.ui_mode == UiMode::Visualizer
     }
@@ -111,6 +266,14 @@ impl EditorState {
         &self.new_note_path_input
     }
 
+    pub fn new_note_templates(&self) -> &[String] {
+        &self.new_note_templates
+    }
+
+    pub fn new_note_template(&self) -> Option<&str> {
+        self.new_note_template.as_deref()
+    }
+
     pub fn show_move_note_input(&self) -> bool {
         self.ui_mode == UiMode::MoveNoteDialog
     }
@@ -127,6 +290,118 @@ impl EditorState {
         self.ui_mode == UiMode::About
     }
 
+    pub fn show_trash(&self) -> bool {
+        self.ui_mode == UiMode::Trash
+    }
+
+    pub fn show_history(&self) -> bool {
+        self.ui_mode == UiMode::History
+    }
+
+    pub fn note_history(&self) -> &[NoteVersion] {
+        &self.note_history
+    }
+
+    pub fn selected_history_version(&self) -> Option<&str> {
+        self.selected_history_version.as_deref()
+    }
+
+    pub fn history_version_content(&self) -> Option<&str> {
+        self.history_version_content.as_deref()
+    }
+
+    pub fn history_diff(&self) -> &[LineDiff] {
+        &self.history_diff
+    }
+
+    pub fn show_note_conflict(&self) -> bool {
+        self.ui_mode == UiMode::NoteConflict
+    }
+
+    pub fn note_conflict(&self) -> Option<&NoteConflict> {
+        self.note_conflict.as_ref()
+    }
+
+    pub fn show_fsck(&self) -> bool {
+        self.ui_mode == UiMode::Fsck
+    }
+
+    pub fn fsck_findings(&self) -> &[FsckFinding] {
+        &self.fsck_findings
+    }
+
+    pub fn is_fsck_in_progress(&self) -> bool {
+        self.fsck_in_progress
+    }
+
+    pub fn show_replace(&self) -> bool {
+        self.ui_mode == UiMode::Replace
+    }
+
+    pub fn replace_find(&self) -> &str {
+        &self.replace_find
+    }
+
+    pub fn replace_with(&self) -> &str {
+        &self.replace_with
+    }
+
+    pub fn replace_preview(&self) -> Option<&ReplacePreview> {
+        self.replace_preview.as_ref()
+    }
+
+    pub fn is_replace_in_progress(&self) -> bool {
+        self.replace_in_progress
+    }
+
+    pub fn show_label_editor(&self) -> bool {
+        self.ui_mode == UiMode::LabelEditor
+    }
+
+    pub fn label_edit_label(&self) -> &str {
+        &self.label_edit_label
+    }
+
+    pub fn label_edit_target(&self) -> &str {
+        &self.label_edit_target
+    }
+
+    pub fn label_edit_preview(&self) -> Option<&LabelEditPreview> {
+        self.label_edit_preview.as_ref()
+    }
+
+    pub fn is_label_edit_in_progress(&self) -> bool {
+        self.label_edit_in_progress
+    }
+
+    /// Color, description and icon entered for the label being edited.
+    pub fn label_style(&self) -> (&str, &str, &str) {
+        (
+            &self.label_style_color,
+            &self.label_style_description,
+            &self.label_style_icon,
+        )
+    }
+
+    pub fn note_disk_state(&self, note_path: &str) -> Option<&NoteDiskState> {
+        self.note_disk_state
+            .as_ref()
+            .filter(|(path, _)| path == note_path)
+            .map(|(_, disk_state)| disk_state)
+    }
+
+    pub fn trash_entries(&self) -> &[TrashEntry] {
+        &self.trash_entries
+    }
+
+    pub fn trash_restore_entry_id(&self) -> Option<&str> {
+        self.trash_restore_entry_id.as_deref()
+    }
+
+    pub fn trash_restore_path_input(&self) -> &str {
+        &self.trash_restore_path_input
+    }
+
     pub fn show_embedded_image_delete_confirmation(&self) -> bool {
         self.ui_mode == UiMode::EmbeddedImageDeleteDialog
     }
@@ -147,6 +422,25 @@ impl EditorState {
                 | UiMode::MoveNoteDialog
                 | UiMode::EmbeddedImageDeleteDialog
                 | UiMode::About
+                | UiMode::NoteConflict
+        )
+    }
+
+    /// Whether a view or dialog covers the note editor, so text edits must not reach the note.
+    pub fn blocks_note_editing(&self) -> bool {
+        matches!(
+            self.ui_mode,
+            UiMode::Visualizer
+                | UiMode::NewNoteDialog
+                | UiMode::MoveNoteDialog
+                | UiMode::EmbeddedImageDeleteDialog
+                | UiMode::About
+                | UiMode::Trash
+                | UiMode::History
+                | UiMode::NoteConflict
+                | UiMode::Fsck
+                | UiMode::Replace
+                | UiMode::LabelEditor
         )
     }
 
@@ -163,6 +457,23 @@ impl EditorState {
         self.config_path = path;
     }
 
+    pub fn set_link_opener(&mut self, opener: String) {
+        self.link_opener = opener;
+    }
+
+    pub fn set_trash_retention_days(&mut self, days: u32) {
+        self.trash_retention_days = days;
+    }
+
+    pub fn set_journal_settings(&mut self, path_pattern: String, template: Option<String>) {
+        self.journal_path_pattern = path_pattern;
+        self.journal_template = template;
+    }
+
+    pub fn set_trash_entries(&mut self, entries: Vec<TrashEntry>) {
+        self.trash_entries = entries;
+    }
+
     pub fn set_ui_scale(&mut self, scale: f32) {
         if scale.is_finite() && scale > 0.0 {
             self.ui_scale = scale;
@@ -177,12 +488,67 @@ impl EditorState {
         self.selected_note_labels = labels;
     }
 
+    pub fn set_selected_note_backlinks(&mut self, backlinks: Vec<NoteBacklink>) {
+        self.selected_note_backlinks = backlinks;
+    }
+
+    pub fn set_pending_preview_anchor(&mut self, note_path: String, anchor: String) {
+        self.pending_preview_anchor = Some((note_path, anchor));
+    }
+
+    pub fn clear_pending_preview_anchor(&mut self) {
+        self.pending_preview_anchor = None;
+    }
+
+    /// Returns the anchor queued for `note_path`, dropping any anchor queued for another note.
+    pub fn take_pending_preview_anchor(&mut self, note_path: &str) -> Option<String> {
+        match self.pending_preview_anchor.take() {
+            Some((pending_path, anchor)) if pending_path == note_path => Some(anchor),
+            _ => None,
+        }
+    }
+
+    pub fn set_pending_search_match(
+        &mut self,
+        note_path: String,
+        line_number: usize,
+        range: SearchMatchRange,
+    ) {
+        self.pending_search_match = Some((note_path, line_number, range));
+    }
+
+    /// Returns the search match queued for `note_path`, dropping any match queued for another note.
+    pub fn take_pending_search_match(
+        &mut self,
+        note_path: &str,
+    ) -> Option<(usize, SearchMatchRange)> {
+        match self.pending_search_match.take() {
+            Some((pending_path, line_number, range)) if pending_path == note_path => {
+                Some((line_number, range))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn set_status_message(&mut self, message: String) {
+        self.status_message = Some(message);
+    }
+
+    pub fn clear_status_message(&mut self) {
+        self.status_message = None;
+    }
+
     pub fn set_new_label_text(&mut self, text: String) {
         self.new_label_text = text;
     }
 
+    pub fn set_label_suggestions(&mut self, suggestions: Vec<String>) {
+        self.label_suggestions = suggestions;
+    }
+
     pub fn clear_new_label_text(&mut self) {
         self.new_label_text = String::new();
+        self.label_suggestions.clear();
     }
 
     pub fn set_search_query(&mut self, query: String) {
@@ -191,11 +557,33 @@ impl EditorState {
 
     pub fn set_search_results(&mut self, results: Vec<NoteSearchResult>) {
         self.search_results = results;
+        self.search_error = None;
+    }
+
+    pub fn set_search_mode(&mut self, mode: SearchMode) {
+        self.search_mode = mode;
+    }
+
+    pub fn set_search_scope(&mut self, scope: Option<String>) {
+        self.search_scope = scope;
+    }
+
+    pub fn set_saved_search_name(&mut self, name: String) {
+        self.saved_search_name = name;
+    }
+
+    /// Replaces the results with an error explaining why the query could not run.
+    pub fn set_search_error(&mut self, error: String) {
+        self.search_results.clear();
+        self.search_error = Some(error);
     }
 
     pub fn clear_search(&mut self) {
         self.search_query.clear();
         self.search_results.clear();
+        self.search_error = None;
+        self.search_scope = None;
+        self.saved_search_name.clear();
     }
 
     pub fn set_loading_note(&mut self, loading: bool) {
@@ -219,6 +607,201 @@ impl EditorState {
         };
     }
 
+    pub fn toggle_trash(&mut self) {
+        self.ui_mode = if self.ui_mode == UiMode::Trash {
+            UiMode::Editor
+        } else {
+            UiMode::Trash
+        };
+        self.clear_trash_restore();
+    }
+
+    pub fn set_show_trash(&mut self, show: bool) {
+        if show {
+            self.ui_mode = UiMode::Trash;
+        } else if self.ui_mode == UiMode::Trash {
+            self.ui_mode = UiMode::Editor;
+        }
+        self.clear_trash_restore();
+    }
+
+    pub fn begin_trash_restore(&mut self, entry_id: String, suggested_path: String) {
+        self.trash_restore_entry_id = Some(entry_id);
+        self.trash_restore_path_input = suggested_path;
+    }
+
+    pub fn update_trash_restore_path(&mut self, path: String) {
+        if self.trash_restore_entry_id.is_some() {
+            self.trash_restore_path_input = path;
+        }
+    }
+
+    pub fn clear_trash_restore(&mut self) {
+        self.trash_restore_entry_id = None;
+        self.trash_restore_path_input.clear();
+    }
+
+    pub fn toggle_history(&mut self) {
+        self.ui_mode = if self.ui_mode == UiMode::History {
+            UiMode::Editor
+        } else {
+            UiMode::History
+        };
+        self.clear_note_history();
+    }
+
+    pub fn set_show_history(&mut self, show: bool) {
+        if show {
+            self.ui_mode = UiMode::History;
+        } else if self.ui_mode == UiMode::History {
+            self.ui_mode = UiMode::Editor;
+        }
+        self.clear_note_history();
+    }
+
+    pub fn set_note_history(&mut self, versions: Vec<NoteVersion>) {
+        self.note_history = versions;
+    }
+
+    pub fn select_history_version(&mut self, version_id: String) {
+        self.selected_history_version = Some(version_id);
+        self.history_version_content = None;
+        self.history_diff.clear();
+    }
+
+    pub fn set_history_version_content(&mut self, content: String, diff: Vec<LineDiff>) {
+        self.history_version_content = Some(content);
+        self.history_diff = diff;
+    }
+
+    pub fn clear_note_history(&mut self) {
+        self.note_history.clear();
+        self.selected_history_version = None;
+        self.history_version_content = None;
+        self.history_diff.clear();
+    }
+
+    pub fn toggle_fsck(&mut self) {
+        self.ui_mode = if self.ui_mode == UiMode::Fsck {
+            UiMode::Editor
+        } else {
+            UiMode::Fsck
+        };
+        self.fsck_findings.clear();
+        self.fsck_in_progress = self.ui_mode == UiMode::Fsck;
+    }
+
+    pub fn set_fsck_in_progress(&mut self, in_progress: bool) {
+        self.fsck_in_progress = in_progress;
+    }
+
+    pub fn set_fsck_findings(&mut self, findings: Vec<FsckFinding>) {
+        self.fsck_findings = findings;
+        self.fsck_in_progress = false;
+    }
+
+    pub fn toggle_replace(&mut self) {
+        self.ui_mode = if self.ui_mode == UiMode::Replace {
+            UiMode::Editor
+        } else {
+            UiMode::Replace
+        };
+        self.replace_preview = None;
+        self.replace_in_progress = false;
+    }
+
+    /// Changing the searched text makes the current preview meaningless, so it is dropped.
+    pub fn set_replace_find(&mut self, find: String) {
+        self.replace_find = find;
+        self.replace_preview = None;
+    }
+
+    pub fn set_replace_with(&mut self, replacement: String) {
+        if let Some(preview) = &mut self.replace_preview {
+            preview.replacement = replacement.clone();
+        }
+        self.replace_with = replacement;
+    }
+
+    pub fn set_replace_preview(&mut self, preview: Option<ReplacePreview>) {
+        self.replace_preview = preview;
+        self.replace_in_progress = false;
+    }
+
+    pub fn set_replace_in_progress(&mut self, in_progress: bool) {
+        self.replace_in_progress = in_progress;
+    }
+
+    pub fn toggle_replace_hit(&mut self, hit_id: usize) {
+        if let Some(preview) = &mut self.replace_preview {
+            preview.toggle_hit(hit_id);
+        }
+    }
+
+    pub fn toggle_label_editor(&mut self) {
+        self.ui_mode = if self.ui_mode == UiMode::LabelEditor {
+            UiMode::Editor
+        } else {
+            UiMode::LabelEditor
+        };
+        self.label_edit_preview = None;
+        self.label_edit_in_progress = false;
+    }
+
+    /// Editing either label makes the current preview meaningless, so it is dropped.
+    pub fn set_label_edit_label(&mut self, label: String) {
+        self.label_edit_label = label;
+        self.label_edit_preview = None;
+    }
+
+    pub fn set_label_edit_target(&mut self, target: String) {
+        self.label_edit_target = target;
+        self.label_edit_preview = None;
+    }
+
+    pub fn set_label_edit_preview(&mut self, preview: Option<LabelEditPreview>) {
+        self.label_edit_preview = preview;
+        self.label_edit_in_progress = false;
+    }
+
+    pub fn set_label_edit_in_progress(&mut self, in_progress: bool) {
+        self.label_edit_in_progress = in_progress;
+    }
+
+    pub fn set_label_style(&mut self, info: &LabelInfo) {
+        self.label_style_color = info.color.clone().unwrap_or_default();
+        self.label_style_description = info.description.clone().unwrap_or_default();
+        self.label_style_icon = info.icon.clone().unwrap_or_default();
+    }
+
+    pub fn set_label_style_color(&mut self, color: String) {
+        self.label_style_color = color;
+    }
+
+    pub fn set_label_style_description(&mut self, description: String) {
+        self.label_style_description = description;
+    }
+
+    pub fn set_label_style_icon(&mut self, icon: String) {
+        self.label_style_icon = icon;
+    }
+
+    pub fn set_note_disk_state(&mut self, note_path: String, disk_state: NoteDiskState) {
+        self.note_disk_state = Some((note_path, disk_state));
+    }
+
+    pub fn open_note_conflict(&mut self, conflict: NoteConflict) {
+        self.ui_mode = UiMode::NoteConflict;
+        self.note_conflict = Some(conflict);
+    }
+
+    pub fn take_note_conflict(&mut self) -> Option<NoteConflict> {
+        if self.ui_mode == UiMode::NoteConflict {
+            self.ui_mode = UiMode::Editor;
+        }
+        self.note_conflict.take()
+    }
+
     pub fn show_new_note_dialog(&mut self) {
         if !self.notebook_path.is_empty() {
             self.ui_mode = UiMode::NewNoteDialog;
@@ -239,6 +822,20 @@ impl EditorState {
         }
     }
 
+    /// Replaces the template list, dropping a selection that no longer exists.
+    pub fn set_new_note_templates(&mut self, templates: Vec<String>) {
+        if let Some(selected) = &self.new_note_template
+            && !templates.contains(selected)
+        {
+            self.new_note_template = None;
+        }
+        self.new_note_templates = templates;
+    }
+
+    pub fn select_new_note_template(&mut self, template: Option<String>) {
+        self.new_note_template = template;
+    }
+
     pub fn show_move_note_dialog(&mut self, current_path: String) {
         self.ui_mode = UiMode::MoveNoteDialog;
         self.move_note_current_path = Some(current_path.clone());
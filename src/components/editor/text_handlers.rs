@@ -134,6 +134,8 @@ impl Editor {
                     return Task::none();
                 }
                 state.content_note_path = Some(note_path.clone());
+                let pending_anchor = state.state.take_pending_preview_anchor(&note_path);
+                let pending_search_match = state.state.take_pending_search_match(&note_path);
                 state.embedded_image_workflow.set_loaded_images(images);
                 let previous_markdown = state.markdown_text.clone();
                 let task = content_handler::handle_loaded_note_content(
@@ -148,20 +150,23 @@ impl Editor {
                     state.prune_embedded_images_for_current_markdown();
                     state.sync_markdown_preview();
                 }
-                state.with_preview_scroll_task(task)
+                match (pending_anchor, pending_search_match) {
+                    (Some(anchor), _) => {
+                        Task::batch(vec![task, state.scroll_preview_to_anchor_task(&anchor)])
+                    }
+                    (None, Some((line_number, range))) => Task::batch(vec![
+                        task,
+                        state.select_search_match_task(line_number, range),
+                    ]),
+                    (None, None) => state.with_preview_scroll_task(task),
+                }
             }
             _ => unreachable!("text handler received non-text message"),
         }
     }
 
     fn handle_paste_from_clipboard_shortcut(state: &mut Self) -> Task<Message> {
-        if state.state.selected_note_path().is_none()
-            || state.state.show_visualizer()
-            || state.state.show_move_note_input()
-            || state.state.show_new_note_input()
-            || state.state.show_embedded_image_delete_confirmation()
-            || state.state.show_about_info()
-        {
+        if state.state.selected_note_path().is_none() || state.state.blocks_note_editing() {
             return Task::none();
         }
 
@@ -208,14 +213,12 @@ impl Editor {
                     state.touch_selected_note_last_updated_and_schedule_save_task();
                 state.sync_markdown_preview();
 
-                let notebook_path = state.state.notebook_path().to_string();
-                let note_path = selected_note_path;
-                let content_text = state.markdown_text.clone();
-                let save_content_task = Task::perform(
-                    async move {
-                        notebook::save_note_content(notebook_path, note_path, content_text).await
-                    },
-                    Message::NoteContentSaved,
+                let base = state.state.note_disk_state(&selected_note_path).cloned();
+                let save_content_task = note_coordinator::save_note_content_task(
+                    state.state.notebook_path(),
+                    selected_note_path,
+                    state.markdown_text.clone(),
+                    base,
                 );
 
                 Task::batch(vec![
@@ -255,13 +258,7 @@ impl Editor {
     }
 
     fn handle_paste_action(state: &mut Self, fallback_action: Action) -> Task<Message> {
-        if state.state.selected_note_path().is_none()
-            || state.state.show_visualizer()
-            || state.state.show_move_note_input()
-            || state.state.show_new_note_input()
-            || state.state.show_embedded_image_delete_confirmation()
-            || state.state.show_about_info()
-        {
+        if state.state.selected_note_path().is_none() || state.state.blocks_note_editing() {
             let task = content_handler::handle_editor_action(
                 &mut state.content,
                 &mut state.markdown_text,
@@ -324,12 +321,12 @@ impl Editor {
         let metadata_save_task = state.touch_selected_note_last_updated_and_schedule_save_task();
         state.sync_markdown_preview();
 
-        let notebook_path = state.state.notebook_path().to_string();
-        let note_path = selected_note_path;
-        let content_text = state.markdown_text.clone();
-        let save_content_task = Task::perform(
-            async move { notebook::save_note_content(notebook_path, note_path, content_text).await },
-            Message::NoteContentSaved,
+        let base = state.state.note_disk_state(&selected_note_path).cloned();
+        let save_content_task = note_coordinator::save_note_content_task(
+            state.state.notebook_path(),
+            selected_note_path,
+            state.markdown_text.clone(),
+            base,
         );
 
         Task::batch(vec![
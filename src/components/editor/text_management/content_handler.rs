@@ -2,9 +2,9 @@ use iced::task::Task;
 use iced::widget::text_editor::{Action, Content, Edit}; // Use Task instead of Command
 
 use crate::components::editor::Message;
+use crate::components::editor::note_coordinator;
 use crate::components::editor::state::editor_state::EditorState;
 use crate::components::editor::text_management::undo_manager::UndoManager;
-use crate::notebook;
 
 fn should_debounce_undo_for_edit(edit: &Edit) -> bool {
     matches!(
@@ -26,13 +26,7 @@ pub fn handle_tab_key(
     notebook_path: &str,
     state: &EditorState,
 ) -> Task<Message> {
-    if selected_note_path.is_some()
-        && !state.show_visualizer()
-        && !state.show_move_note_input()
-        && !state.show_new_note_input()
-        && !state.show_embedded_image_delete_confirmation()
-        && !state.show_about_info()
-    {
+    if selected_note_path.is_some() && !state.blocks_note_editing() {
         #[cfg(debug_assertions)]
         eprintln!("Editor: Handling HandleTabKey message by inserting 4 spaces.");
 
@@ -43,7 +37,6 @@ pub fn handle_tab_key(
 
         *markdown_text = content.text();
         if let Some(selected_path) = selected_note_path {
-            let notebook_path = notebook_path.to_string();
             let note_path = selected_path.clone();
             let content_text = markdown_text.clone();
             #[cfg(debug_assertions)]
@@ -51,9 +44,12 @@ pub fn handle_tab_key(
                 "Editor: Handling Tab: Saving content for note: {}",
                 note_path
             );
-            return Task::perform(
-                async move { notebook::save_note_content(notebook_path, note_path, content_text).await },
-                Message::NoteContentSaved,
+            let base = state.note_disk_state(&note_path).cloned();
+            return note_coordinator::save_note_content_task(
+                notebook_path,
+                note_path,
+                content_text,
+                base,
             );
         }
     }
@@ -62,13 +58,7 @@ pub fn handle_tab_key(
 
 // Handler for select all action
 pub fn handle_select_all(content: &mut Content, state: &EditorState) -> Task<Message> {
-    if state.selected_note_path().is_some()
-        && !state.show_visualizer()
-        && !state.show_move_note_input()
-        && !state.show_new_note_input()
-        && !state.show_embedded_image_delete_confirmation()
-        && !state.show_about_info()
-    {
+    if state.selected_note_path().is_some() && !state.blocks_note_editing() {
         #[cfg(debug_assertions)]
         eprintln!("Editor: Handling SelectAll message.");
 
@@ -90,11 +80,7 @@ pub fn handle_editor_action(
     state: &EditorState,
 ) -> Task<Message> {
     if let Some(selected_path) = selected_note_path
-        && !state.show_visualizer()
-        && !state.show_move_note_input()
-        && !state.show_new_note_input()
-        && !state.show_embedded_image_delete_confirmation()
-        && !state.show_about_info()
+        && !state.blocks_note_editing()
     {
         #[cfg(debug_assertions)]
         eprintln!("Editor: Performing EditorAction: {:?}", action);
@@ -113,7 +99,6 @@ pub fn handle_editor_action(
             content.perform(action);
             *markdown_text = content.text();
 
-            let notebook_path_clone = notebook_path.to_string();
             let note_path_clone = selected_path.clone();
             let content_text = markdown_text.clone();
             #[cfg(debug_assertions)]
@@ -121,12 +106,12 @@ pub fn handle_editor_action(
                 "Editor: Performing EditorAction: Saving content for note: {}",
                 note_path_clone
             );
-            return Task::perform(
-                async move {
-                    notebook::save_note_content(notebook_path_clone, note_path_clone, content_text)
-                        .await
-                },
-                Message::NoteContentSaved,
+            let base = state.note_disk_state(&note_path_clone).cloned();
+            return note_coordinator::save_note_content_task(
+                notebook_path,
+                note_path_clone,
+                content_text,
+                base,
             );
         }
 
@@ -145,12 +130,7 @@ pub fn handle_loaded_note_content(
     loaded_note_path: String,
     new_content: String,
 ) -> Task<Message> {
-    if !state.show_visualizer()
-        && !state.show_move_note_input()
-        && !state.show_new_note_input()
-        && !state.show_embedded_image_delete_confirmation()
-        && !state.show_about_info()
-    {
+    if !state.blocks_note_editing() {
         if state.selected_note_path() != Some(&loaded_note_path) {
             #[cfg(debug_assertions)]
             eprintln!(
@@ -6,8 +6,8 @@ use std::collections::HashMap; // Use Task instead of Command
 use std::time::{Duration, Instant};
 
 use crate::components::editor::Message;
+use crate::components::editor::note_coordinator;
 use crate::components::editor::state::editor_state::EditorState;
-use crate::notebook;
 
 pub struct UndoManager {
     undo_histories: HashMap<String, Vec<UndoSnapshot>>, // Store previous states for undo per note
@@ -342,11 +342,8 @@ pub fn handle_undo(
     state: &EditorState,
 ) -> Task<Message> {
     if let Some(note_path) = selected_note_path {
-        if !state.show_visualizer()
-            && !state.show_move_note_input()
-            && !state.show_new_note_input()
-            && !state.show_about_info()
-        {
+        // Undo history stays reachable behind the embedded image delete confirmation.
+        if !state.blocks_note_editing() || state.show_embedded_image_delete_confirmation() {
             if let Some(previous_snapshot) = undo_manager.get_previous_snapshot(note_path) {
                 #[cfg(debug_assertions)]
                 eprintln!(
@@ -369,19 +366,11 @@ pub fn handle_undo(
                 undo_manager.reset_edit_debounce(note_path);
 
                 // Save the content after undo
-                let notebook_path_clone = notebook_path.to_string();
-                let note_path_clone = note_path.clone();
-
-                return Task::perform(
-                    async move {
-                        notebook::save_note_content(
-                            notebook_path_clone,
-                            note_path_clone,
-                            previous_snapshot.content,
-                        )
-                        .await
-                    },
-                    Message::NoteContentSaved,
+                return note_coordinator::save_note_content_task(
+                    notebook_path,
+                    note_path.clone(),
+                    previous_snapshot.content,
+                    state.note_disk_state(note_path).cloned(),
                 );
             } else {
                 #[cfg(debug_assertions)]
@@ -408,11 +397,8 @@ pub fn handle_redo(
     state: &EditorState,
 ) -> Task<Message> {
     if let Some(note_path) = selected_note_path {
-        if !state.show_visualizer()
-            && !state.show_move_note_input()
-            && !state.show_new_note_input()
-            && !state.show_about_info()
-        {
+        // Undo history stays reachable behind the embedded image delete confirmation.
+        if !state.blocks_note_editing() || state.show_embedded_image_delete_confirmation() {
             if let Some(next_snapshot) = undo_manager.get_next_redo_snapshot(note_path) {
                 #[cfg(debug_assertions)]
                 eprintln!(
@@ -427,19 +413,11 @@ pub fn handle_redo(
                 *markdown_text = next_snapshot.content.clone();
                 undo_manager.reset_edit_debounce(note_path);
 
-                let notebook_path_clone = notebook_path.to_string();
-                let note_path_clone = note_path.clone();
-
-                return Task::perform(
-                    async move {
-                        notebook::save_note_content(
-                            notebook_path_clone,
-                            note_path_clone,
-                            next_snapshot.content,
-                        )
-                        .await
-                    },
-                    Message::NoteContentSaved,
+                return note_coordinator::save_note_content_task(
+                    notebook_path,
+                    note_path.clone(),
+                    next_snapshot.content,
+                    state.note_disk_state(note_path).cloned(),
                 );
             } else {
                 #[cfg(debug_assertions)]
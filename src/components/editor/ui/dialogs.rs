@@ -1,7 +1,23 @@
-use iced::widget::{Column, Container, Row, Text, TextInput as IcedTextInput, button};
+use std::fmt;
+
+use iced::widget::{Column, Container, Row, Text, TextInput as IcedTextInput, button, pick_list};
 use iced::{Element, Length};
 
 use crate::components::editor::Message;
+use crate::components::editor::state::editor_state::EditorState;
+
+/// Entry of the new note template picker; `None` is the empty note.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TemplateChoice(Option<String>);
+
+impl fmt::Display for TemplateChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Some(name) => f.write_str(name),
+            None => f.write_str("Empty note"),
+        }
+    }
+}
 
 // About dialog
 pub fn about_dialog<'a>(app_version: &str) -> Element<'a, Message> {
@@ -20,17 +36,44 @@ pub fn about_dialog<'a>(app_version: &str) -> Element<'a, Message> {
 }
 
 // New note dialog
-pub fn new_note_dialog<'a>(new_note_path_input: &str) -> Element<'a, Message> {
+pub fn new_note_dialog<'a>(state: &EditorState) -> Element<'a, Message> {
+    let choices: Vec<TemplateChoice> = std::iter::once(TemplateChoice(None))
+        .chain(
+            state
+                .new_note_templates()
+                .iter()
+                .map(|name| TemplateChoice(Some(name.clone()))),
+        )
+        .collect();
+    let selected = TemplateChoice(state.new_note_template().map(str::to_string));
+
     Column::new()
         .push(Text::new(
             "Enter new note name/relative path (e.g., folder/note_name):",
         ))
         .push(
-            IcedTextInput::new("Note name...", new_note_path_input)
+            IcedTextInput::new("Note name...", state.new_note_path_input())
                 .on_input(Message::NewNoteInputChanged)
                 .on_submit(Message::CreateNote)
                 .width(Length::Fixed(300.0)),
         )
+        .push(
+            Row::new()
+                .push(Text::new("Template:"))
+                .push(
+                    pick_list(choices, Some(selected), |choice: TemplateChoice| {
+                        Message::NewNoteTemplateSelected(choice.0)
+                    })
+                    .width(Length::Fixed(200.0)),
+                )
+                .push(
+                    button("Make Default")
+                        .padding(5)
+                        .on_press(Message::SetDefaultTemplate),
+                )
+                .spacing(10)
+                .align_y(iced::Alignment::Center),
+        )
         .push(
             Row::new()
                 .push(button("Create").padding(5).on_press(Message::CreateNote))
@@ -1,25 +1,95 @@
-use iced::Length;
-use iced::widget::{Button, Row, Text, button, text_input};
+use iced::widget::{Button, Container, Row, Text, button, container, text_input, tooltip};
+use iced::{Color, Element, Length};
 
 use crate::components::editor::Message;
+use crate::components::note_explorer;
+use crate::notebook::{self, BulkLabelAction, LabelRegistry};
+
+/// Button for one of the note's labels, styled from its registry entry; pressing removes it.
+fn label_chip<'a>(label: &str, registry: &LabelRegistry) -> Element<'a, Message> {
+    let info = notebook::label_info(registry, label);
+    let text = match info.and_then(|info| info.icon.as_deref()) {
+        Some(icon) => format!("{} {}", icon, label),
+        None => label.to_string(),
+    };
+    let mut chip = button(Text::new(text)).on_press(Message::RemoveLabel(label.to_string()));
+    if let Some([red, green, blue]) = notebook::label_color(registry, label) {
+        let background = Color::from_rgb8(red, green, blue);
+        let luminance = 0.299 * background.r + 0.587 * background.g + 0.114 * background.b;
+        let text_color = if luminance > 0.6 {
+            Color::BLACK
+        } else {
+            Color::WHITE
+        };
+        chip = chip.style(move |theme, status| button::Style {
+            background: Some(background.into()),
+            text_color,
+            ..button::primary(theme, status)
+        });
+    }
+
+    match info.and_then(|info| info.description.clone()) {
+        Some(description) => tooltip(
+            chip,
+            Container::new(Text::new(description).size(14))
+                .padding(5)
+                .style(container::rounded_box),
+            tooltip::Position::Top,
+        )
+        .into(),
+        None => chip.into(),
+    }
+}
 
 // Create the labels section
 pub fn create_labels_section<'a>(
     selected_note_path: Option<&String>,
     selected_labels: &[String],
     new_label_text: &str,
+    label_registry: &LabelRegistry,
+    label_suggestions: &[String],
+    marked_note_count: usize,
 ) -> Row<'a, Message> {
-    let mut labels_row = Row::new().spacing(10).padding(5).width(Length::Fill);
+    let mut labels_row = Row::new()
+        .spacing(10)
+        .padding(5)
+        .align_y(iced::Alignment::Center)
+        .width(Length::Fill);
 
-    if selected_note_path.is_some() {
+    if marked_note_count > 0 {
+        labels_row = labels_row
+            .push(Text::new(format!("{} marked note(s): ", marked_note_count)))
+            .push(
+                text_input("Label", new_label_text)
+                    .on_input(Message::NewLabelInputChanged)
+                    .on_submit(Message::EditMarkedNotesLabel(BulkLabelAction::Add))
+                    .width(Length::Fixed(150.0)),
+            )
+            .push(
+                Button::new(Text::new("Add to Marked"))
+                    .padding(5)
+                    .on_press(Message::EditMarkedNotesLabel(BulkLabelAction::Add)),
+            )
+            .push(
+                Button::new(Text::new("Remove from Marked"))
+                    .padding(5)
+                    .on_press(Message::EditMarkedNotesLabel(BulkLabelAction::Remove)),
+            )
+            .push(
+                Button::new(Text::new("Clear Marks"))
+                    .padding(5)
+                    .style(button::secondary)
+                    .on_press(Message::NoteExplorerMsg(
+                        note_explorer::Message::ClearNoteMarks,
+                    )),
+            );
+    } else if selected_note_path.is_some() {
         labels_row = labels_row.push(Text::new("Labels: "));
         if selected_labels.is_empty() {
             labels_row = labels_row.push(Text::new("No labels"));
         } else {
             for label in selected_labels {
-                labels_row = labels_row.push(
-                    button(Text::new(label.clone())).on_press(Message::RemoveLabel(label.clone())),
-                );
+                labels_row = labels_row.push(label_chip(label, label_registry));
             }
         }
 
@@ -37,6 +107,17 @@ pub fn create_labels_section<'a>(
             );
     } else {
         labels_row = labels_row.push(Text::new("Select a note to manage labels."));
+        return labels_row;
+    }
+
+    // Picking a suggestion completes the input; submitting it applies the label.
+    for suggestion in label_suggestions {
+        labels_row = labels_row.push(
+            Button::new(Text::new(suggestion.clone()).size(14))
+                .padding(3)
+                .style(button::text)
+                .on_press(Message::NewLabelInputChanged(suggestion.clone())),
+        );
     }
 
     labels_row
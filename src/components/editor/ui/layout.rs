@@ -7,9 +7,16 @@ use crate::components::editor::state::editor_state::EditorState;
 use crate::components::note_explorer;
 use crate::components::visualizer;
 
+mod backlinks;
+mod conflict;
+mod fsck;
+mod history;
+mod label_editor;
 mod preview;
+mod replace;
 mod search_results;
 mod top_bar;
+mod trash;
 mod workspace;
 
 pub const MARKDOWN_PREVIEW_SCROLLABLE_ID: &str = "cognate_markdown_preview_scrollable";
@@ -0,0 +1,33 @@
+use iced::widget::{Column, Container, Text, button};
+use iced::{Element, Length};
+
+use crate::components::editor::Message;
+use crate::notebook::NoteBacklink;
+
+pub(super) fn render_backlinks_panel(backlinks: &[NoteBacklink]) -> Element<'static, Message> {
+    let mut backlinks_column = Column::new()
+        .spacing(4)
+        .push(Text::new(format!("Linked from: {}", backlinks.len())).size(14));
+
+    if backlinks.is_empty() {
+        backlinks_column = backlinks_column.push(Text::new("No notes link to this note.").size(12));
+    } else {
+        for backlink in backlinks {
+            backlinks_column = backlinks_column.push(
+                button(Text::new(backlink.rel_path.clone()).size(13))
+                    .on_press(Message::NoteSelected(backlink.rel_path.clone()))
+                    .style(button::text)
+                    .padding(2),
+            );
+            if !backlink.context.is_empty() {
+                backlinks_column =
+                    backlinks_column.push(Text::new(backlink.context.clone()).size(12));
+            }
+        }
+    }
+
+    Container::new(backlinks_column)
+        .padding(6)
+        .width(Length::Fill)
+        .into()
+}
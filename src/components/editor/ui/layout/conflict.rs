@@ -0,0 +1,67 @@
+use iced::widget::{Column, Row, Text, button, scrollable};
+use iced::{Element, Length};
+
+use crate::components::editor::Message;
+use crate::components::editor::state::editor_state::EditorState;
+
+use super::history::render_diff_line;
+
+pub(super) fn render_conflict_view(state: &EditorState) -> Element<'_, Message> {
+    let Some(conflict) = state.note_conflict() else {
+        return Column::new().into();
+    };
+
+    let merge_label = match conflict.merge.conflict_count {
+        0 => "Merge".to_string(),
+        1 => "Merge (1 conflict)".to_string(),
+        count => format!("Merge ({} conflicts)", count),
+    };
+
+    let actions = Row::new()
+        .spacing(8)
+        .align_y(iced::Alignment::Center)
+        .push(
+            Text::new("- only on disk, + only in the editor")
+                .size(13)
+                .width(Length::Fill),
+        )
+        .push(
+            button(Text::new("Keep Mine").size(13))
+                .on_press(Message::KeepMineInConflict)
+                .padding(4),
+        )
+        .push(
+            button(Text::new("Keep Theirs").size(13))
+                .on_press(Message::KeepTheirsInConflict)
+                .padding(4),
+        )
+        .push(
+            button(Text::new(merge_label).size(13))
+                .on_press(Message::MergeConflict)
+                .padding(4),
+        );
+
+    let diff_lines = conflict
+        .diff
+        .iter()
+        .fold(Column::new().spacing(1), |column, line| {
+            column.push(render_diff_line(line))
+        });
+
+    Column::new()
+        .spacing(6)
+        .padding(10)
+        .push(
+            Text::new(format!(
+                "'{}' was changed by another program while it was open here. \
+                 Your latest edits were not saved.",
+                conflict.note_path
+            ))
+            .size(14),
+        )
+        .push(actions)
+        .push(scrollable(diff_lines).height(Length::Fill))
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+}
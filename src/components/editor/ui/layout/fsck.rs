@@ -0,0 +1,95 @@
+use iced::widget::{Column, Container, Row, Text, button, scrollable};
+use iced::{Element, Length};
+
+use crate::components::editor::Message;
+use crate::components::editor::state::editor_state::EditorState;
+use crate::notebook::{FsckFinding, FsckFindingKind};
+
+fn finding_labels(kind: FsckFindingKind) -> (&'static str, &'static str) {
+    match kind {
+        FsckFindingKind::DanglingEntry => ("Missing note", "Remove from Metadata"),
+        FsckFindingKind::UntrackedNote => ("Untracked note", "Add to Notebook"),
+        FsckFindingKind::StaleTempFile => ("Leftover temporary file", "Delete"),
+        FsckFindingKind::UnreferencedImage => ("Unused image", "Delete"),
+    }
+}
+
+fn render_finding(finding: &FsckFinding) -> Element<'_, Message> {
+    let (kind_label, repair_label) = finding_labels(finding.kind);
+
+    let details = Column::new()
+        .spacing(2)
+        .width(Length::Fill)
+        .push(Text::new(format!("{}: {}", kind_label, finding.path)).size(15))
+        .push(Text::new(finding.detail.clone()).size(12));
+
+    let mut repair_button = button(Text::new(repair_label).size(13))
+        .on_press(Message::RepairFsckFinding(finding.clone()))
+        .padding(4);
+    if matches!(
+        finding.kind,
+        FsckFindingKind::StaleTempFile | FsckFindingKind::UnreferencedImage
+    ) {
+        repair_button = repair_button.style(button::danger);
+    }
+
+    Container::new(
+        Row::new()
+            .spacing(8)
+            .align_y(iced::Alignment::Center)
+            .push(details)
+            .push(repair_button),
+    )
+    .padding(6)
+    .width(Length::Fill)
+    .into()
+}
+
+pub(super) fn render_fsck_view(state: &EditorState) -> Element<'_, Message> {
+    let summary = if state.is_fsck_in_progress() {
+        "Checking notebook...".to_string()
+    } else {
+        format!("Problems found: {}", state.fsck_findings().len())
+    };
+
+    let mut fsck_column = Column::new()
+        .spacing(8)
+        .padding(10)
+        .width(Length::Fill)
+        .push(Text::new(summary).size(18))
+        .push(Text::new("Each repair asks for confirmation before anything is changed.").size(12));
+
+    if let Some(status_message) = state.status_message() {
+        fsck_column = fsck_column.push(
+            Row::new()
+                .spacing(8)
+                .align_y(iced::Alignment::Center)
+                .push(
+                    Text::new(status_message.to_string())
+                        .size(14)
+                        .width(Length::Fill)
+                        .style(|_: &_| iced::widget::text::Style {
+                            color: Some(iced::Color::from_rgb(0.7, 0.2, 0.2)),
+                        }),
+                )
+                .push(
+                    button(Text::new("Dismiss").size(13))
+                        .on_press(Message::DismissStatus)
+                        .padding(4),
+                ),
+        );
+    }
+
+    if !state.is_fsck_in_progress() && state.fsck_findings().is_empty() {
+        fsck_column = fsck_column.push(Text::new("No problems found.").size(14));
+    } else {
+        for finding in state.fsck_findings() {
+            fsck_column = fsck_column.push(render_finding(finding));
+        }
+    }
+
+    Container::new(scrollable(fsck_column))
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+}
@@ -0,0 +1,114 @@
+use iced::widget::{Column, Container, Row, Text, button, scrollable};
+use iced::{Element, Length};
+
+use crate::components::editor::Message;
+use crate::components::editor::state::editor_state::EditorState;
+use crate::notebook::{LineDiff, LineDiffKind};
+
+pub(super) fn render_diff_line(line: &LineDiff) -> Element<'_, Message> {
+    let (prefix, color) = match line.kind {
+        LineDiffKind::Unchanged => ("  ", None),
+        LineDiffKind::Removed => ("- ", Some(iced::Color::from_rgb(0.75, 0.25, 0.25))),
+        LineDiffKind::Added => ("+ ", Some(iced::Color::from_rgb(0.2, 0.6, 0.3))),
+    };
+
+    Text::new(format!("{}{}", prefix, line.text))
+        .size(13)
+        .font(iced::Font::MONOSPACE)
+        .style(move |_: &_| iced::widget::text::Style { color })
+        .into()
+}
+
+pub(super) fn render_history_view(state: &EditorState) -> Element<'_, Message> {
+    let mut versions_column = Column::new()
+        .spacing(4)
+        .push(Text::new(format!("Versions: {}", state.note_history().len())).size(16));
+
+    if state.note_history().is_empty() {
+        versions_column = versions_column.push(
+            Text::new("No earlier versions yet. Versions are kept as the note is edited.").size(13),
+        );
+    } else {
+        for version in state.note_history() {
+            let is_selected = state.selected_history_version() == Some(version.id.as_str());
+            versions_column = versions_column.push(
+                button(Text::new(version.saved_at.clone()).size(13))
+                    .on_press(Message::SelectHistoryVersion(version.id.clone()))
+                    .style(if is_selected {
+                        button::primary
+                    } else {
+                        button::text
+                    })
+                    .padding(3),
+            );
+        }
+    }
+
+    let mut diff_column = Column::new().spacing(6).width(Length::Fill);
+
+    if let Some(status_message) = state.status_message() {
+        diff_column = diff_column.push(
+            Row::new()
+                .spacing(8)
+                .align_y(iced::Alignment::Center)
+                .push(
+                    Text::new(status_message.to_string())
+                        .size(14)
+                        .width(Length::Fill)
+                        .style(|_: &_| iced::widget::text::Style {
+                            color: Some(iced::Color::from_rgb(0.7, 0.2, 0.2)),
+                        }),
+                )
+                .push(
+                    button(Text::new("Dismiss").size(13))
+                        .on_press(Message::DismissStatus)
+                        .padding(4),
+                ),
+        );
+    }
+
+    if state.history_version_content().is_some() {
+        diff_column = diff_column.push(
+            Row::new()
+                .spacing(8)
+                .align_y(iced::Alignment::Center)
+                .push(
+                    Text::new("- only in this version, + only in the current text")
+                        .size(13)
+                        .width(Length::Fill),
+                )
+                .push(
+                    button(Text::new("Restore This Version").size(13))
+                        .on_press(Message::RestoreHistoryVersion)
+                        .padding(4),
+                ),
+        );
+
+        let diff_lines = state
+            .history_diff()
+            .iter()
+            .fold(Column::new().spacing(1), |column, line| {
+                column.push(render_diff_line(line))
+            });
+        diff_column = diff_column.push(scrollable(diff_lines).height(Length::Fill));
+    } else if state.selected_history_version().is_some() {
+        diff_column = diff_column.push(Text::new("Loading version...").size(13));
+    }
+
+    Row::new()
+        .spacing(10)
+        .padding(10)
+        .push(
+            Container::new(scrollable(versions_column))
+                .width(Length::FillPortion(1))
+                .height(Length::Fill),
+        )
+        .push(
+            Container::new(diff_column)
+                .width(Length::FillPortion(3))
+                .height(Length::Fill),
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+}
@@ -0,0 +1,193 @@
+use iced::widget::{Column, Container, Row, Text, TextInput, button, scrollable};
+use iced::{Element, Length};
+
+use crate::components::editor::Message;
+use crate::components::editor::state::editor_state::EditorState;
+use crate::notebook::{self, LabelEditKind, LabelEditNote, LabelEditPreview};
+
+fn render_labels_line<'a>(
+    prefix: &str,
+    labels: &[String],
+    color: iced::Color,
+) -> Element<'a, Message> {
+    let labels = if labels.is_empty() {
+        "(no labels)".to_string()
+    } else {
+        labels.join(", ")
+    };
+    Text::new(format!("{}{}", prefix, labels))
+        .size(13)
+        .font(iced::Font::MONOSPACE)
+        .style(move |_: &_| iced::widget::text::Style { color: Some(color) })
+        .into()
+}
+
+fn render_note_change(note: &LabelEditNote) -> Element<'_, Message> {
+    Container::new(
+        Column::new()
+            .spacing(4)
+            .push(Text::new(note.rel_path.clone()).size(15))
+            .push(render_labels_line(
+                "- ",
+                &note.labels,
+                iced::Color::from_rgb(0.75, 0.25, 0.25),
+            ))
+            .push(render_labels_line(
+                "+ ",
+                &note.new_labels,
+                iced::Color::from_rgb(0.2, 0.6, 0.3),
+            )),
+    )
+    .padding(6)
+    .width(Length::Fill)
+    .into()
+}
+
+fn preview_summary(preview: &LabelEditPreview) -> String {
+    match preview.kind {
+        LabelEditKind::Rename => format!(
+            "Renaming '{}' to '{}' changes {} note(s).",
+            preview.label,
+            preview.target,
+            preview.notes.len()
+        ),
+        LabelEditKind::Merge => format!(
+            "Merging '{}' into '{}' changes {} note(s).",
+            preview.label,
+            preview.target,
+            preview.notes.len()
+        ),
+        LabelEditKind::Delete => format!(
+            "Deleting '{}' changes {} note(s).",
+            preview.label,
+            preview.notes.len()
+        ),
+    }
+}
+
+pub(super) fn render_label_editor_view(state: &EditorState) -> Element<'_, Message> {
+    let in_progress = state.is_label_edit_in_progress();
+    let preview_button = |label: &'static str, kind: LabelEditKind| {
+        button(Text::new(label).size(13))
+            .on_press_maybe((!in_progress).then_some(Message::PreviewLabelEdit(kind)))
+            .padding(5)
+    };
+    let has_changes = state
+        .label_edit_preview()
+        .is_some_and(|preview| !preview.notes.is_empty());
+
+    let inputs = Row::new()
+        .spacing(8)
+        .align_y(iced::Alignment::Center)
+        .push(
+            TextInput::new("Label...", state.label_edit_label())
+                .on_input(Message::LabelEditLabelChanged)
+                .padding(5)
+                .width(Length::FillPortion(1)),
+        )
+        .push(
+            TextInput::new("New label...", state.label_edit_target())
+                .on_input(Message::LabelEditTargetChanged)
+                .on_submit(Message::PreviewLabelEdit(LabelEditKind::Rename))
+                .padding(5)
+                .width(Length::FillPortion(1)),
+        )
+        .push(preview_button("Rename", LabelEditKind::Rename))
+        .push(preview_button("Merge", LabelEditKind::Merge))
+        .push(preview_button("Delete", LabelEditKind::Delete))
+        .push(
+            button(Text::new("Apply").size(13))
+                .on_press_maybe((!in_progress && has_changes).then_some(Message::ApplyLabelEdit))
+                .style(button::danger)
+                .padding(5),
+        );
+
+    let (color, description, icon) = state.label_style();
+    let swatch_color = notebook::parse_label_color(color)
+        .map(|[red, green, blue]| iced::Color::from_rgb8(red, green, blue));
+    let style_inputs = Row::new()
+        .spacing(8)
+        .align_y(iced::Alignment::Center)
+        .push(
+            Text::new("\u{25CF}")
+                .size(16)
+                .style(move |_: &_| iced::widget::text::Style {
+                    color: swatch_color,
+                }),
+        )
+        .push(
+            TextInput::new("#rrggbb", color)
+                .on_input(Message::LabelStyleColorChanged)
+                .padding(5)
+                .width(Length::Fixed(100.0)),
+        )
+        .push(
+            TextInput::new("Icon", icon)
+                .on_input(Message::LabelStyleIconChanged)
+                .padding(5)
+                .width(Length::Fixed(60.0)),
+        )
+        .push(
+            TextInput::new("Description...", description)
+                .on_input(Message::LabelStyleDescriptionChanged)
+                .on_submit(Message::SaveLabelStyle)
+                .padding(5)
+                .width(Length::Fill),
+        )
+        .push(
+            button(Text::new("Save Style").size(13))
+                .on_press_maybe((!in_progress).then_some(Message::SaveLabelStyle))
+                .padding(5),
+        );
+
+    let summary = match state.label_edit_preview() {
+        _ if in_progress => "Working...".to_string(),
+        Some(preview) => preview_summary(preview),
+        None => "Changes apply to the label and the labels nested below it in every note. \
+                 Preview the changes before applying them."
+            .to_string(),
+    };
+
+    let mut editor_column = Column::new()
+        .spacing(8)
+        .padding(10)
+        .width(Length::Fill)
+        .push(inputs)
+        .push(style_inputs)
+        .push(Text::new(summary).size(14));
+
+    if let Some(status_message) = state.status_message() {
+        editor_column = editor_column.push(
+            Row::new()
+                .spacing(8)
+                .align_y(iced::Alignment::Center)
+                .push(
+                    Text::new(status_message.to_string())
+                        .size(14)
+                        .width(Length::Fill)
+                        .style(|_: &_| iced::widget::text::Style {
+                            color: Some(iced::Color::from_rgb(0.7, 0.2, 0.2)),
+                        }),
+                )
+                .push(
+                    button(Text::new("Dismiss").size(13))
+                        .on_press(Message::DismissStatus)
+                        .padding(4),
+                ),
+        );
+    }
+
+    if let Some(preview) = state.label_edit_preview() {
+        if preview.notes.is_empty() {
+            editor_column = editor_column.push(Text::new("No labels would change.").size(14));
+        }
+        for note in &preview.notes {
+            editor_column = editor_column.push(render_note_change(note));
+        }
+    }
+
+    Container::new(scrollable(editor_column))
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+}
@@ -0,0 +1,165 @@
+use iced::widget::{Column, Container, Row, Text, TextInput, button, checkbox, scrollable};
+use iced::{Element, Length};
+
+use crate::components::editor::Message;
+use crate::components::editor::state::editor_state::EditorState;
+use crate::notebook::{NoteReplacePreview, ReplacePreview};
+
+fn render_change_line<'a>(prefix: &str, text: &str, color: iced::Color) -> Element<'a, Message> {
+    Text::new(format!("{}{}", prefix, text))
+        .size(13)
+        .font(iced::Font::MONOSPACE)
+        .style(move |_: &_| iced::widget::text::Style { color: Some(color) })
+        .into()
+}
+
+fn render_note_preview<'a>(
+    note: &'a NoteReplacePreview,
+    replacement: &str,
+) -> Element<'a, Message> {
+    let mut title = note.rel_path.clone();
+    if note.is_open_note {
+        title.push_str(" (open)");
+    }
+    let mut note_column = Column::new().spacing(4).push(Text::new(title).size(15));
+
+    for line in &note.lines {
+        let hit_toggles = line.hits.iter().fold(
+            Row::new()
+                .spacing(10)
+                .push(Text::new(format!("Line {}", line.line_number)).size(13)),
+            |row, hit| {
+                let hit_id = hit.id;
+                let column_number = line.text[..hit.start].chars().count() + 1;
+                row.push(
+                    checkbox(hit.selected)
+                        .label(format!("Col {}", column_number))
+                        .text_size(13)
+                        .on_toggle(move |_| Message::ToggleReplaceHit(hit_id)),
+                )
+            },
+        );
+
+        note_column = note_column
+            .push(hit_toggles)
+            .push(render_change_line(
+                "- ",
+                &line.text,
+                iced::Color::from_rgb(0.75, 0.25, 0.25),
+            ))
+            .push(render_change_line(
+                "+ ",
+                &line.replaced_text(replacement),
+                iced::Color::from_rgb(0.2, 0.6, 0.3),
+            ));
+    }
+
+    Container::new(note_column)
+        .padding(6)
+        .width(Length::Fill)
+        .into()
+}
+
+fn selected_hit_count(preview: &ReplacePreview) -> (usize, usize) {
+    let hits = preview
+        .notes
+        .iter()
+        .flat_map(|note| &note.lines)
+        .flat_map(|line| &line.hits);
+    let total = hits.clone().count();
+    (hits.filter(|hit| hit.selected).count(), total)
+}
+
+pub(super) fn render_replace_view(state: &EditorState) -> Element<'_, Message> {
+    let in_progress = state.is_replace_in_progress();
+    let selected_hits = state
+        .replace_preview()
+        .map_or(0, |preview| selected_hit_count(preview).0);
+
+    let inputs = Row::new()
+        .spacing(8)
+        .align_y(iced::Alignment::Center)
+        .push(
+            TextInput::new("Find...", state.replace_find())
+                .on_input(Message::ReplaceFindChanged)
+                .on_submit(Message::PreviewReplace)
+                .padding(5)
+                .width(Length::FillPortion(1)),
+        )
+        .push(
+            TextInput::new("Replace with...", state.replace_with())
+                .on_input(Message::ReplaceWithChanged)
+                .on_submit(Message::PreviewReplace)
+                .padding(5)
+                .width(Length::FillPortion(1)),
+        )
+        .push(
+            button(Text::new("Preview").size(13))
+                .on_press_maybe((!in_progress).then_some(Message::PreviewReplace))
+                .padding(5),
+        )
+        .push(
+            button(Text::new("Replace Selected").size(13))
+                .on_press_maybe(
+                    (!in_progress && selected_hits > 0).then_some(Message::ApplyReplace),
+                )
+                .style(button::danger)
+                .padding(5),
+        );
+
+    let summary = match state.replace_preview() {
+        _ if in_progress => "Working...".to_string(),
+        Some(preview) => {
+            let (selected, total) = selected_hit_count(preview);
+            format!(
+                "{} of {} occurrence(s) selected in {} note(s).",
+                selected,
+                total,
+                preview.notes.len()
+            )
+        }
+        None => "Matching is case-sensitive. Preview the changes before replacing.".to_string(),
+    };
+
+    let mut replace_column = Column::new()
+        .spacing(8)
+        .padding(10)
+        .width(Length::Fill)
+        .push(inputs)
+        .push(Text::new(summary).size(14));
+
+    if let Some(status_message) = state.status_message() {
+        replace_column = replace_column.push(
+            Row::new()
+                .spacing(8)
+                .align_y(iced::Alignment::Center)
+                .push(
+                    Text::new(status_message.to_string())
+                        .size(14)
+                        .width(Length::Fill)
+                        .style(|_: &_| iced::widget::text::Style {
+                            color: Some(iced::Color::from_rgb(0.7, 0.2, 0.2)),
+                        }),
+                )
+                .push(
+                    button(Text::new("Dismiss").size(13))
+                        .on_press(Message::DismissStatus)
+                        .padding(4),
+                ),
+        );
+    }
+
+    if let Some(preview) = state.replace_preview() {
+        if preview.notes.is_empty() {
+            replace_column = replace_column.push(Text::new("No matches found.").size(14));
+        }
+        for note in &preview.notes {
+            replace_column = replace_column.push(render_note_preview(note, &preview.replacement));
+        }
+    }
+
+    Container::new(scrollable(replace_column))
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+}
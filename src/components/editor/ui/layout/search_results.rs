@@ -1,33 +1,141 @@
-use iced::widget::{Column, Container, Text, button};
-use iced::{Element, Length};
+use iced::widget::text::Span;
+use iced::widget::{Column, Container, Row, Text, TextInput, button, rich_text, span};
+use iced::{Element, Font, Length};
 
 use crate::components::editor::Message;
-use crate::notebook::NoteSearchResult;
-
-pub(super) fn render_search_results(
-    search_query: &str,
-    results: &[NoteSearchResult],
-) -> Element<'static, Message> {
-    let mut results_column = Column::new().spacing(4).push(
-        Text::new(format!(
-            "Search results for '{}': {}",
-            search_query,
-            results.len()
+use crate::components::editor::state::editor_state::EditorState;
+use crate::notebook::{SearchLineMatch, SearchMatchRange};
+
+/// Splits the line's excerpt into plain and highlighted spans.
+fn highlighted_spans(line_match: &SearchLineMatch) -> Vec<Span<'static, (), Font>> {
+    let mut spans = vec![span(format!("{}: ", line_match.line_number))];
+    let mut cursor = 0;
+    for highlight in &line_match.highlights {
+        if highlight.start > cursor {
+            spans.push(span(line_match.text[cursor..highlight.start].to_string()));
+        }
+        spans.push(
+            span(line_match.text[highlight.start..highlight.end].to_string())
+                .color(iced::Color::BLACK)
+                .background(iced::Color::from_rgb(1.0, 0.85, 0.3)),
+        );
+        cursor = highlight.end;
+    }
+    if cursor < line_match.text.len() {
+        spans.push(span(line_match.text[cursor..].to_string()));
+    }
+    spans
+}
+
+fn render_line_match(rel_path: &str, line_match: &SearchLineMatch) -> Element<'static, Message> {
+    let first_column = line_match
+        .columns
+        .first()
+        .copied()
+        .unwrap_or(SearchMatchRange { start: 0, end: 0 });
+
+    button(rich_text(highlighted_spans(line_match)).size(12))
+        .on_press(Message::OpenSearchMatch(
+            rel_path.to_string(),
+            line_match.line_number,
+            first_column,
         ))
-        .size(14),
-    );
+        .style(button::text)
+        .padding(0)
+        .into()
+}
+
+pub(super) fn render_search_results(state: &EditorState) -> Element<'_, Message> {
+    let results = state.search_results();
+    let selected_note_path = state.selected_note_path().map(String::as_str);
+    let scope_text = state
+        .search_scope()
+        .map_or_else(String::new, |scope| format!(" in '{}/'", scope));
+    let mut results_column = Column::new()
+        .spacing(4)
+        .push(
+            Text::new(format!(
+                "Search results for '{}'{}: {}",
+                state.search_query(),
+                scope_text,
+                results.len()
+            ))
+            .size(14),
+        )
+        .push(
+            Row::new()
+                .spacing(6)
+                .align_y(iced::Alignment::Center)
+                .push(
+                    TextInput::new("Saved search name", state.saved_search_name())
+                        .on_input(Message::SavedSearchNameChanged)
+                        .on_submit(Message::SaveSearch)
+                        .size(12)
+                        .padding(3),
+                )
+                .push(
+                    button(Text::new("Save Search").size(12))
+                        .on_press_maybe(
+                            (!state.saved_search_name().trim().is_empty())
+                                .then_some(Message::SaveSearch),
+                        )
+                        .padding(3),
+                ),
+        );
+
+    let selected_match_count = results
+        .iter()
+        .find(|result| Some(result.rel_path.as_str()) == selected_note_path)
+        .map_or(0, |result| result.match_locations().count());
+    if selected_match_count > 0 {
+        results_column = results_column.push(
+            Row::new()
+                .spacing(6)
+                .align_y(iced::Alignment::Center)
+                .push(
+                    Text::new(format!("{} match(es) in this note", selected_match_count)).size(12),
+                )
+                .push(
+                    button(Text::new("Previous").size(12))
+                        .on_press(Message::PreviousSearchMatch)
+                        .padding(3),
+                )
+                .push(
+                    button(Text::new("Next").size(12))
+                        .on_press(Message::NextSearchMatch)
+                        .padding(3),
+                ),
+        );
+    }
 
     if results.is_empty() {
         results_column = results_column.push(Text::new("No matches found.").size(13));
     } else {
         let max_results_to_render = 8;
+        let max_lines_per_result = 5;
         for result in results.iter().take(max_results_to_render) {
             results_column = results_column.push(
                 button(Text::new(result.rel_path.clone()).size(14))
                     .on_press(Message::NoteSelected(result.rel_path.clone()))
                     .padding(3),
             );
-            results_column = results_column.push(Text::new(result.snippet.clone()).size(12));
+            if result.line_matches.is_empty() {
+                results_column = results_column.push(Text::new(result.snippet.clone()).size(12));
+            } else {
+                for line_match in result.line_matches.iter().take(max_lines_per_result) {
+                    results_column =
+                        results_column.push(render_line_match(&result.rel_path, line_match));
+                }
+                if result.line_matches.len() > max_lines_per_result {
+                    results_column = results_column.push(
+                        Text::new(format!(
+                            "... and {} more lines",
+                            result.line_matches.len() - max_lines_per_result
+                        ))
+                        .size(12),
+                    );
+                }
+            }
         }
 
         if results.len() > max_results_to_render {
@@ -7,6 +7,7 @@ use crate::components::editor::Message;
 use crate::components::editor::state::editor_state::EditorState;
 use crate::components::note_explorer;
 use crate::components::visualizer;
+use crate::notebook::{SearchMode, journal_note_date};
 
 pub(super) fn build_top_bar<'a>(
     state: &'a EditorState,
@@ -60,12 +61,123 @@ pub(super) fn build_top_bar<'a>(
             );
         }
 
+        if state.show_history() {
+            top_bar = top_bar.push(
+                button("Close History")
+                    .padding(5)
+                    .on_press(Message::ToggleHistory),
+            );
+        }
+
+        if !is_dialog_open
+            && !state.show_visualizer()
+            && !state.show_history()
+            && !state.show_fsck()
+            && !state.show_replace()
+            && !state.show_label_editor()
+        {
+            let trash_button_text = if state.show_trash() {
+                "Close Trash"
+            } else {
+                "Trash"
+            };
+            top_bar = top_bar.push(
+                button(trash_button_text)
+                    .padding(5)
+                    .on_press(Message::ToggleTrash),
+            );
+        }
+
+        if !is_dialog_open
+            && !state.show_visualizer()
+            && !state.show_history()
+            && !state.show_trash()
+            && !state.show_replace()
+            && !state.show_label_editor()
+        {
+            let fsck_button_text = if state.show_fsck() {
+                "Close Check"
+            } else {
+                "Check Notebook"
+            };
+            top_bar = top_bar.push(
+                button(fsck_button_text)
+                    .padding(5)
+                    .on_press(Message::ToggleFsck),
+            );
+        }
+
+        if !is_dialog_open
+            && !state.show_visualizer()
+            && !state.show_history()
+            && !state.show_trash()
+            && !state.show_fsck()
+            && !state.show_label_editor()
+        {
+            let replace_button_text = if state.show_replace() {
+                "Close Replace"
+            } else {
+                "Find & Replace"
+            };
+            top_bar = top_bar.push(
+                button(replace_button_text)
+                    .padding(5)
+                    .on_press(Message::ToggleReplace),
+            );
+        }
+
+        if !is_dialog_open
+            && !state.show_visualizer()
+            && !state.show_history()
+            && !state.show_trash()
+            && !state.show_fsck()
+            && !state.show_replace()
+        {
+            let label_editor_button_text = if state.show_label_editor() {
+                "Close Labels"
+            } else {
+                "Manage Labels"
+            };
+            top_bar = top_bar.push(
+                button(label_editor_button_text)
+                    .padding(5)
+                    .on_press(Message::ToggleLabelEditor),
+            );
+        }
+
         if !state.show_visualizer()
             && !state.show_new_note_input()
             && !state.show_move_note_input()
             && !state.show_about_info()
+            && !state.show_trash()
+            && !state.show_history()
+            && !state.show_note_conflict()
+            && !state.show_fsck()
+            && !state.show_replace()
+            && !state.show_label_editor()
         {
             top_bar = top_bar.push(button("New Note").padding(5).on_press(Message::NewNote));
+            top_bar = top_bar.push(
+                button("Today")
+                    .padding(5)
+                    .on_press(Message::OpenTodayJournalNote),
+            );
+            let is_journal_note = state.selected_note_path().is_some_and(|path| {
+                journal_note_date(state.journal_path_pattern(), path).is_some()
+            });
+            if is_journal_note {
+                top_bar = top_bar
+                    .push(
+                        button("Previous Day")
+                            .padding(5)
+                            .on_press(Message::PreviousJournalDay),
+                    )
+                    .push(
+                        button("Next Day")
+                            .padding(5)
+                            .on_press(Message::NextJournalDay),
+                    );
+            }
             if state.selected_note_path().is_some() {
                 top_bar = top_bar.push(
                     button("Delete Note")
@@ -73,6 +185,11 @@ pub(super) fn build_top_bar<'a>(
                         .on_press(Message::DeleteNote),
                 );
                 top_bar = top_bar.push(button("Move Note").padding(5).on_press(Message::MoveNote));
+                top_bar = top_bar.push(
+                    button("History")
+                        .padding(5)
+                        .on_press(Message::ToggleHistory),
+                );
             }
 
             top_bar = top_bar.push(
@@ -82,9 +199,52 @@ pub(super) fn build_top_bar<'a>(
                     .padding(5)
                     .width(Length::Fixed(240.0)),
             );
+            for mode in SearchMode::ALL {
+                let label = match mode {
+                    SearchMode::Text => "Text",
+                    SearchMode::Fuzzy => "Fuzzy",
+                    SearchMode::Regex => "Regex",
+                };
+                top_bar = top_bar.push(button(label).padding(5).on_press_maybe(
+                    (state.search_mode() != mode).then_some(Message::SearchModeChanged(mode)),
+                ));
+            }
+            if let Some(scope) = state.search_scope() {
+                top_bar = top_bar.push(Text::new(format!("In '{}/'", scope))).push(
+                    button("All Folders")
+                        .padding(5)
+                        .on_press(Message::ClearSearchScope),
+                );
+            }
             top_bar = top_bar.push(button("Clear").padding(5).on_press(Message::ClearSearch));
+            if let Some(search_error) = state.search_error() {
+                top_bar =
+                    top_bar.push(Text::new(search_error.to_string()).size(13).style(|_: &_| {
+                        iced::widget::text::Style {
+                            color: Some(iced::Color::from_rgb(0.7, 0.2, 0.2)),
+                        }
+                    }));
+            }
         } else if state.show_new_note_input() {
             top_bar = top_bar.push(Text::new("Creating New Note..."));
+        } else if state.show_trash() {
+            top_bar = top_bar.push(Text::new("Viewing Trash"));
+        } else if state.show_fsck() {
+            top_bar = top_bar.push(Text::new("Checking Notebook"));
+        } else if state.show_replace() {
+            top_bar = top_bar.push(Text::new("Finding and Replacing"));
+        } else if state.show_label_editor() {
+            top_bar = top_bar.push(Text::new("Managing Labels"));
+        } else if state.show_history() {
+            top_bar = top_bar.push(Text::new(format!(
+                "History of '{}'",
+                state.selected_note_path().map_or("", String::as_str)
+            )));
+        } else if let Some(conflict) = state.note_conflict() {
+            top_bar = top_bar.push(Text::new(format!(
+                "Resolving Changes to '{}'",
+                conflict.note_path
+            )));
         } else if state.show_move_note_input() {
             let mut all_folders_in_notes: HashSet<String> = HashSet::new();
             for note in &note_explorer_component.notes {
@@ -0,0 +1,115 @@
+use iced::widget::{Column, Container, Row, Text, TextInput as IcedTextInput, button, scrollable};
+use iced::{Element, Length};
+
+use crate::components::editor::Message;
+use crate::components::editor::state::editor_state::EditorState;
+use crate::notebook::TrashEntry;
+
+fn render_trash_entry<'a>(state: &'a EditorState, entry: &'a TrashEntry) -> Element<'a, Message> {
+    let mut details = Column::new()
+        .spacing(2)
+        .width(Length::Fill)
+        .push(Text::new(entry.rel_path.clone()).size(15))
+        .push(Text::new(format!("Deleted: {}", entry.deleted_at)).size(12));
+
+    if !entry.labels.is_empty() {
+        details = details.push(Text::new(format!("Labels: {}", entry.labels.join(", "))).size(12));
+    }
+
+    let row = Row::new()
+        .spacing(8)
+        .align_y(iced::Alignment::Center)
+        .push(details)
+        .push(
+            button(Text::new("Restore").size(13))
+                .on_press(Message::RestoreTrashEntry(entry.id.clone()))
+                .padding(4),
+        )
+        .push(
+            button(Text::new("Delete Permanently").size(13))
+                .on_press(Message::PurgeTrashEntry(entry.id.clone()))
+                .style(button::danger)
+                .padding(4),
+        );
+
+    let mut entry_column = Column::new().spacing(6).push(row);
+
+    if state.trash_restore_entry_id() == Some(entry.id.as_str()) {
+        entry_column = entry_column.push(
+            Row::new()
+                .spacing(8)
+                .align_y(iced::Alignment::Center)
+                .push(Text::new("Restore as:").size(13))
+                .push(
+                    IcedTextInput::new("Restore path", state.trash_restore_path_input())
+                        .on_input(Message::TrashRestorePathChanged)
+                        .on_submit(Message::ConfirmTrashRestore)
+                        .padding(4)
+                        .width(Length::Fill),
+                )
+                .push(
+                    button(Text::new("Restore").size(13))
+                        .on_press(Message::ConfirmTrashRestore)
+                        .padding(4),
+                )
+                .push(
+                    button(Text::new("Cancel").size(13))
+                        .on_press(Message::CancelTrashRestore)
+                        .padding(4),
+                ),
+        );
+    }
+
+    Container::new(entry_column)
+        .padding(6)
+        .width(Length::Fill)
+        .into()
+}
+
+pub(super) fn render_trash_view(state: &EditorState) -> Element<'_, Message> {
+    let retention_text = match state.trash_retention_days() {
+        0 => "Deleted notes are kept until they are removed permanently.".to_string(),
+        days => format!("Deleted notes are removed permanently after {} days.", days),
+    };
+
+    let mut trash_column = Column::new()
+        .spacing(8)
+        .padding(10)
+        .width(Length::Fill)
+        .push(Text::new(format!("Trash: {}", state.trash_entries().len())).size(18))
+        .push(Text::new(retention_text).size(12));
+
+    if let Some(status_message) = state.status_message() {
+        trash_column = trash_column.push(
+            Row::new()
+                .spacing(8)
+                .align_y(iced::Alignment::Center)
+                .push(
+                    Text::new(status_message.to_string())
+                        .size(14)
+                        .width(Length::Fill)
+                        .style(|_: &_| iced::widget::text::Style {
+                            color: Some(iced::Color::from_rgb(0.7, 0.2, 0.2)),
+                        }),
+                )
+                .push(
+                    button(Text::new("Dismiss").size(13))
+                        .on_press(Message::DismissStatus)
+                        .padding(4),
+                ),
+        );
+    }
+
+    if state.trash_entries().is_empty() {
+        trash_column = trash_column.push(Text::new("The trash is empty.").size(14));
+    } else {
+        for entry in state.trash_entries() {
+            trash_column = trash_column.push(render_trash_entry(state, entry));
+        }
+    }
+
+    Container::new(scrollable(trash_column))
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+}
@@ -9,8 +9,15 @@ use crate::components::editor::ui::input_fields;
 use crate::components::note_explorer;
 use crate::components::visualizer;
 
+use super::backlinks;
+use super::conflict;
+use super::fsck;
+use super::history;
+use super::label_editor;
 use super::preview;
+use super::replace;
 use super::search_results;
+use super::trash;
 
 pub(super) fn build_main_content<'a>(
     state: &'a EditorState,
@@ -32,8 +39,32 @@ pub(super) fn build_main_content<'a>(
             .into();
     }
 
+    if state.show_note_conflict() {
+        return conflict::render_conflict_view(state);
+    }
+
+    if state.show_history() {
+        return history::render_history_view(state);
+    }
+
+    if state.show_trash() {
+        return trash::render_trash_view(state);
+    }
+
+    if state.show_fsck() {
+        return fsck::render_fsck_view(state);
+    }
+
+    if state.show_replace() {
+        return replace::render_replace_view(state);
+    }
+
+    if state.show_label_editor() {
+        return label_editor::render_label_editor_view(state);
+    }
+
     if state.show_new_note_input() {
-        return dialogs::new_note_dialog(state.new_note_path_input());
+        return dialogs::new_note_dialog(state);
     }
 
     if state.show_move_note_input() {
@@ -92,11 +123,8 @@ fn build_editor_workspace<'a>(
 ) -> Element<'a, Message> {
     let mut explorer_column = Column::new().spacing(8).width(Length::Fill);
 
-    if !state.search_query().trim().is_empty() {
-        explorer_column = explorer_column.push(search_results::render_search_results(
-            state.search_query(),
-            state.search_results(),
-        ));
+    if !state.search_query().trim().is_empty() && state.search_error().is_none() {
+        explorer_column = explorer_column.push(search_results::render_search_results(state));
     }
 
     explorer_column = explorer_column.push(
@@ -110,6 +138,9 @@ fn build_editor_workspace<'a>(
                 note_explorer::Message::InitiateFolderRename(path) => {
                     Message::InitiateFolderRename(path)
                 }
+                note_explorer::Message::SearchInFolder(path) => Message::SearchInFolder(path),
+                note_explorer::Message::OpenSavedSearch(name) => Message::OpenSavedSearch(name),
+                note_explorer::Message::DeleteSavedSearch(name) => Message::DeleteSavedSearch(name),
                 other_msg => Message::NoteExplorerMsg(other_msg),
             }),
     );
@@ -141,37 +172,81 @@ fn build_editor_workspace<'a>(
             });
     }
 
-    let selected_note_last_updated = state
-        .selected_note_path()
-        .and_then(|selected_path| {
-            note_explorer_component
-                .notes
-                .iter()
-                .find(|note| &note.rel_path == selected_path)
-        })
-        .and_then(|note| note.last_updated.as_deref());
+    let selected_note = state.selected_note_path().and_then(|selected_path| {
+        note_explorer_component
+            .notes
+            .iter()
+            .find(|note| &note.rel_path == selected_path)
+    });
 
     let selected_note_info = state.selected_note_path().map(|_| {
-        let updated_text = selected_note_last_updated.map_or_else(
-            || "Last updated: unknown".to_string(),
-            |value| format!("Last updated: {}", value),
-        );
+        let updated_text = selected_note
+            .and_then(|note| note.last_updated.as_deref())
+            .map_or_else(
+                || "Last updated: unknown".to_string(),
+                |value| format!("Last updated: {}", value),
+            );
 
-        Row::new().push(
-            Container::new(Text::new(updated_text).size(14))
-                .width(Length::Fill)
-                .align_x(iced::Alignment::End),
-        )
+        // Title, aliases and custom properties come from the note's front matter.
+        let mut front_matter_parts = Vec::new();
+        if let Some(note) = selected_note {
+            if let Some(title) = &note.title {
+                front_matter_parts.push(title.clone());
+            }
+            if !note.aliases.is_empty() {
+                front_matter_parts.push(format!("Aliases: {}", note.aliases.join(", ")));
+            }
+            front_matter_parts.extend(
+                note.properties
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key, value)),
+            );
+        }
+
+        Row::new()
+            .push(Text::new(front_matter_parts.join(" · ")).size(14))
+            .push(
+                Container::new(Text::new(updated_text).size(14))
+                    .width(Length::Fill)
+                    .align_x(iced::Alignment::End),
+            )
     });
 
     let mut editor_column = Column::new().spacing(5).width(Length::Fill);
 
+    if let Some(status_message) = state.status_message() {
+        editor_column = editor_column.push(
+            Row::new()
+                .spacing(8)
+                .align_y(iced::Alignment::Center)
+                .push(
+                    Text::new(status_message.to_string())
+                        .size(14)
+                        .width(Length::Fill)
+                        .style(|_: &_| iced::widget::text::Style {
+                            color: Some(iced::Color::from_rgb(0.7, 0.2, 0.2)),
+                        }),
+                )
+                .push(
+                    iced::widget::button(Text::new("Dismiss").size(13))
+                        .on_press(Message::DismissStatus)
+                        .padding(4),
+                ),
+        );
+    }
+
     if let Some(note_info_row) = selected_note_info {
         editor_column = editor_column.push(note_info_row);
     }
 
     editor_column = editor_column.push(editor_widget).width(Length::Fill);
 
+    if state.selected_note_path().is_some() {
+        editor_column = editor_column.push(backlinks::render_backlinks_panel(
+            state.selected_note_backlinks(),
+        ));
+    }
+
     let editor_with_padding = Row::new()
         .push(editor_column)
         .push(Container::new(Text::new("").width(Length::Fixed(20.0))))
@@ -205,6 +280,9 @@ fn build_editor_workspace<'a>(
         state.selected_note_path(),
         state.selected_note_labels(),
         state.new_label_text(),
+        &note_explorer_component.label_registry,
+        state.label_suggestions(),
+        note_explorer_component.marked_notes.len(),
     );
 
     let bottom_bar: Element<'_, Message> = Container::new(labels_row)
@@ -0,0 +1,112 @@
+use super::*;
+use crate::components::editor::state::editor_state::NoteConflict;
+use crate::notebook::{NoteDiskState, NoteSaveOutcome};
+
+/// Shows the conflict view for the open note against the on-disk state another program wrote.
+pub(super) fn open_note_conflict(state: &mut Editor, note_path: String, theirs: NoteDiskState) {
+    let base = state
+        .state
+        .note_disk_state(&note_path)
+        .map(|disk_state| disk_state.content.clone())
+        .unwrap_or_default();
+    let diff = notebook::diff_lines(&theirs.content, &state.markdown_text);
+    let merge = notebook::merge_three_way(&base, &state.markdown_text, &theirs.content);
+
+    state.state.open_note_conflict(NoteConflict {
+        note_path,
+        theirs,
+        diff,
+        merge,
+    });
+}
+
+/// Closes the conflict view and returns the conflict if it still belongs to the open note.
+fn take_open_note_conflict(state: &mut Editor) -> Option<NoteConflict> {
+    let conflict = state.state.take_note_conflict()?;
+    (state.state.selected_note_path() == Some(&conflict.note_path)).then_some(conflict)
+}
+
+/// Replaces the editor buffer with a resolved text and saves it over the on-disk version.
+fn apply_resolution(state: &mut Editor, conflict: NoteConflict, resolved: String) -> Task<Message> {
+    let note_path = conflict.note_path;
+    // The resolution was made against this disk version, so it is the new base for saving.
+    let base = conflict.theirs;
+    state
+        .state
+        .set_note_disk_state(note_path.clone(), base.clone());
+
+    let mut tasks = Vec::new();
+    if resolved != state.markdown_text {
+        state.replace_markdown_with_undo(&note_path, resolved);
+        tasks.push(state.touch_selected_note_last_updated_and_schedule_save_task());
+        state.sync_markdown_preview();
+        tasks.push(state.scroll_preview_to_cursor_task());
+    }
+
+    tasks.push(note_coordinator::save_note_content_task(
+        state.state.notebook_path(),
+        note_path,
+        state.markdown_text.clone(),
+        Some(base),
+    ));
+    Task::batch(tasks)
+}
+
+pub(super) fn handle(state: &mut Editor, message: Message) -> Task<Message> {
+    match message {
+        Message::NoteDiskStateLoaded(note_path, disk_state) => {
+            if state.state.selected_note_path() == Some(&note_path) {
+                state.state.set_note_disk_state(note_path, disk_state);
+            }
+            Task::none()
+        }
+        Message::NoteContentSaveFinished(note_path, result) => match result {
+            Ok(NoteSaveOutcome::Saved(disk_state)) => {
+                if state.state.selected_note_path() == Some(&note_path) {
+                    state.state.set_note_disk_state(note_path, disk_state);
+                }
+                Task::none()
+            }
+            Ok(NoteSaveOutcome::Conflict(theirs)) => {
+                // Saves already in flight report the same conflict; the first one opens the view.
+                if state.state.selected_note_path() == Some(&note_path)
+                    && !state.state.show_note_conflict()
+                {
+                    open_note_conflict(state, note_path, theirs);
+                }
+                Task::none()
+            }
+            Err(error) => {
+                Editor::handle_save_feedback_messages(Message::NoteContentSaved(Err(error)))
+            }
+        },
+        Message::KeepMineInConflict => {
+            let Some(conflict) = take_open_note_conflict(state) else {
+                return Task::none();
+            };
+            let mine = state.markdown_text.clone();
+            apply_resolution(state, conflict, mine)
+        }
+        Message::KeepTheirsInConflict => {
+            let Some(conflict) = take_open_note_conflict(state) else {
+                return Task::none();
+            };
+            let theirs = conflict.theirs.content.clone();
+            apply_resolution(state, conflict, theirs)
+        }
+        Message::MergeConflict => {
+            let Some(conflict) = take_open_note_conflict(state) else {
+                return Task::none();
+            };
+            if conflict.merge.conflict_count > 0 {
+                state.state.set_status_message(format!(
+                    "Merged '{}' with {} conflicting section(s) marked between <<<<<<< and >>>>>>>.",
+                    conflict.note_path, conflict.merge.conflict_count
+                ));
+            }
+            let merged = conflict.merge.text.clone();
+            apply_resolution(state, conflict, merged)
+        }
+        _ => unreachable!("conflict handler received invalid message"),
+    }
+}
@@ -0,0 +1,106 @@
+use native_dialog::{DialogBuilder, MessageLevel};
+
+use super::*;
+use crate::notebook::{FsckFinding, FsckFindingKind};
+
+fn check_notebook_task(state: &Editor) -> Task<Message> {
+    let notebook_path = state.state.notebook_path().to_string();
+    let notes = state.note_explorer.notes.clone();
+
+    Task::perform(
+        async move { notebook::check_notebook(&notebook_path, &notes).await },
+        Message::FsckCompleted,
+    )
+}
+
+fn repair_confirmation_text(finding: &FsckFinding) -> String {
+    match finding.kind {
+        FsckFindingKind::DanglingEntry => format!(
+            "Remove '{}' from metadata.json? Its labels will be lost.",
+            finding.path
+        ),
+        FsckFindingKind::UntrackedNote => {
+            format!("Add '{}' to the notebook as a note?", finding.path)
+        }
+        FsckFindingKind::StaleTempFile => format!(
+            "Delete the leftover temporary file '{}'? This cannot be undone.",
+            finding.path
+        ),
+        FsckFindingKind::UnreferencedImage => format!(
+            "Delete the unused image '{}'? This cannot be undone.",
+            finding.path
+        ),
+    }
+}
+
+pub(super) fn handle(state: &mut Editor, message: Message) -> Task<Message> {
+    match message {
+        Message::ToggleFsck => {
+            state.state.toggle_fsck();
+            if state.state.show_fsck() {
+                check_notebook_task(state)
+            } else {
+                Task::none()
+            }
+        }
+        Message::FsckCompleted(result) => {
+            if !state.state.show_fsck() {
+                return Task::none();
+            }
+            match result {
+                Ok(findings) => state.state.set_fsck_findings(findings),
+                Err(error) => {
+                    state.state.set_fsck_in_progress(false);
+                    state.state.set_status_message(error.ui_message());
+                }
+            }
+            Task::none()
+        }
+        Message::RepairFsckFinding(finding) => Task::perform(
+            async move {
+                let confirmed = DialogBuilder::message()
+                    .set_level(MessageLevel::Warning)
+                    .set_title("Confirm Notebook Repair")
+                    .set_text(repair_confirmation_text(&finding))
+                    .confirm()
+                    .show()
+                    .unwrap_or(false);
+                (finding, confirmed)
+            },
+            |(finding, confirmed)| Message::ConfirmRepairFsckFinding(finding, confirmed),
+        ),
+        Message::ConfirmRepairFsckFinding(finding, confirmed) => {
+            if !confirmed {
+                return Task::none();
+            }
+
+            let notebook_path = state.state.notebook_path().to_string();
+            let mut notes = state.note_explorer.notes.clone();
+            state.state.set_fsck_in_progress(true);
+            Task::perform(
+                async move {
+                    notebook::repair_fsck_finding(&notebook_path, &finding, &mut notes).await?;
+                    notebook::check_notebook(&notebook_path, &notes).await
+                },
+                Message::FsckFindingRepaired,
+            )
+        }
+        Message::FsckFindingRepaired(result) => match result {
+            Ok(findings) => {
+                state.state.clear_status_message();
+                state.state.set_fsck_findings(findings);
+                // Repairs may have added or dropped notes in metadata.json.
+                state
+                    .note_explorer
+                    .update(note_explorer::Message::LoadNotes)
+                    .map(Message::NoteExplorerMsg)
+            }
+            Err(error) => {
+                state.state.set_fsck_in_progress(false);
+                state.state.set_status_message(error.ui_message());
+                Task::none()
+            }
+        },
+        _ => unreachable!("fsck handler received invalid message"),
+    }
+}
@@ -0,0 +1,124 @@
+use super::*;
+
+fn load_history_version_task(
+    state: &Editor,
+    note_path: String,
+    version_id: String,
+) -> Task<Message> {
+    let notebook_path = state.state.notebook_path().to_string();
+
+    Task::perform(
+        {
+            let note_path = note_path.clone();
+            let version_id = version_id.clone();
+            async move { notebook::load_note_version(notebook_path, note_path, version_id).await }
+        },
+        move |result| Message::HistoryVersionLoaded(note_path.clone(), version_id.clone(), result),
+    )
+}
+
+pub(super) fn handle(state: &mut Editor, message: Message) -> Task<Message> {
+    match message {
+        Message::ToggleHistory => {
+            let Some(note_path) = state.state.selected_note_path().cloned() else {
+                return Task::none();
+            };
+
+            state.state.toggle_history();
+            if !state.state.show_history() {
+                return Task::none();
+            }
+
+            let notebook_path = state.state.notebook_path().to_string();
+            Task::perform(
+                {
+                    let note_path = note_path.clone();
+                    async move { notebook::load_note_history(notebook_path, note_path).await }
+                },
+                move |result| Message::NoteHistoryLoaded(note_path.clone(), result),
+            )
+        }
+        Message::NoteHistoryLoaded(note_path, result) => {
+            if !state.state.show_history() || state.state.selected_note_path() != Some(&note_path) {
+                return Task::none();
+            }
+
+            match result {
+                Ok(versions) => {
+                    let newest_version_id = versions.first().map(|version| version.id.clone());
+                    state.state.set_note_history(versions);
+                    match newest_version_id {
+                        Some(version_id) => {
+                            state.state.select_history_version(version_id.clone());
+                            load_history_version_task(state, note_path, version_id)
+                        }
+                        None => Task::none(),
+                    }
+                }
+                Err(error) => {
+                    state.state.set_status_message(error.ui_message());
+                    Task::none()
+                }
+            }
+        }
+        Message::SelectHistoryVersion(version_id) => {
+            let Some(note_path) = state.state.selected_note_path().cloned() else {
+                return Task::none();
+            };
+
+            state.state.select_history_version(version_id.clone());
+            load_history_version_task(state, note_path, version_id)
+        }
+        Message::HistoryVersionLoaded(note_path, version_id, result) => {
+            if state.state.selected_note_path() != Some(&note_path)
+                || state.state.selected_history_version() != Some(version_id.as_str())
+            {
+                return Task::none();
+            }
+
+            match result {
+                Ok(content) => {
+                    let diff = notebook::diff_lines(&content, &state.markdown_text);
+                    state.state.set_history_version_content(content, diff);
+                }
+                Err(error) => state.state.set_status_message(error.ui_message()),
+            }
+            Task::none()
+        }
+        Message::RestoreHistoryVersion => {
+            let Some(note_path) = state.state.selected_note_path().cloned() else {
+                return Task::none();
+            };
+            let Some(restored_content) = state.state.history_version_content().map(str::to_string)
+            else {
+                return Task::none();
+            };
+
+            state.state.set_show_history(false);
+            if restored_content == state.markdown_text {
+                return Task::none();
+            }
+
+            // Restoring is a regular edit so it can be undone like any other change.
+            state.replace_markdown_with_undo(&note_path, restored_content);
+            let metadata_save_task =
+                state.touch_selected_note_last_updated_and_schedule_save_task();
+            state.sync_markdown_preview();
+
+            let base = state.state.note_disk_state(&note_path).cloned();
+            let save_content_task = note_coordinator::save_note_content_task(
+                state.state.notebook_path(),
+                note_path,
+                state.markdown_text.clone(),
+                base,
+            );
+
+            Task::batch(vec![
+                save_content_task,
+                metadata_save_task,
+                state.scroll_preview_to_cursor_task(),
+            ])
+        }
+        _ => unreachable!("history handler received invalid message"),
+    }
+}
@@ -1,23 +1,332 @@
 use super::*;
 use crate::components::editor::actions::label_actions;
+use crate::notebook::{BulkLabelAction, LabelEditKind, LabelEditNote, LabelEditPreview, LabelInfo};
+
+/// Writes the selected note's labels into the front matter of its open text.
+fn write_labels_to_front_matter(state: &mut Editor) -> Task<Message> {
+    let Some(note_path) = state.state.selected_note_path().cloned() else {
+        return Task::none();
+    };
+    if state.content_note_path.as_ref() != Some(&note_path) {
+        return Task::none();
+    }
+
+    let updated =
+        notebook::set_front_matter_labels(&state.markdown_text, state.state.selected_note_labels());
+    if updated == state.markdown_text {
+        return Task::none();
+    }
+    state.replace_markdown_with_undo(&note_path, updated);
+    let metadata_save_task = state.touch_selected_note_last_updated_and_schedule_save_task();
+    state.sync_markdown_preview();
+
+    let base = state.state.note_disk_state(&note_path).cloned();
+    let save_content_task = note_coordinator::save_note_content_task(
+        state.state.notebook_path(),
+        note_path,
+        state.markdown_text.clone(),
+        base,
+    );
+    Task::batch(vec![save_content_task, metadata_save_task])
+}
+
+fn label_edit_summary(preview: &LabelEditPreview, changed_notes: usize) -> String {
+    match preview.kind {
+        LabelEditKind::Rename => format!(
+            "Renamed '{}' to '{}' in {} note(s).",
+            preview.label, preview.target, changed_notes
+        ),
+        LabelEditKind::Merge => format!(
+            "Merged '{}' into '{}' in {} note(s).",
+            preview.label, preview.target, changed_notes
+        ),
+        LabelEditKind::Delete => format!(
+            "Deleted '{}' from {} note(s).",
+            preview.label, changed_notes
+        ),
+    }
+}
+
+/// Puts the saved labels of the open note into its buffer as one undoable edit.
+///
+/// The batch save already rewrote the front matter on disk, and the watcher takes that for our
+/// own write, so a buffer left with the old labels would write them back on the next edit.
+/// Like the batch save, only a buffer whose front matter lists labels is rewritten.
+fn apply_label_changes_to_open_note(
+    state: &mut Editor,
+    changes: &[LabelEditNote],
+) -> Task<Message> {
+    let Some(note_path) = state.content_note_path.clone() else {
+        return Task::none();
+    };
+    let Some(change) = changes.iter().find(|change| change.rel_path == note_path) else {
+        return Task::none();
+    };
+    if !notebook::front_matter_lists_labels(&state.markdown_text) {
+        return Task::none();
+    }
+
+    let updated = notebook::set_front_matter_labels(&state.markdown_text, &change.new_labels);
+    if updated == state.markdown_text {
+        return Task::none();
+    }
+    state.replace_markdown_with_undo(&note_path, updated);
+    state.sync_markdown_preview();
+
+    // The file may already hold this text; saving it refreshes the editor's view of the disk
+    // state either way.
+    let base = state.state.note_disk_state(&note_path).cloned();
+    note_coordinator::save_note_content_task(
+        state.state.notebook_path(),
+        note_path,
+        state.markdown_text.clone(),
+        base,
+    )
+}
+
+/// Puts the saved labels into the explorer's notes and the open note, then rebuilds the graph.
+fn apply_label_changes_to_notes(state: &mut Editor, changes: &[LabelEditNote]) -> Task<Message> {
+    let timestamp = notebook::current_timestamp_rfc3339();
+    for change in changes {
+        if let Some(note) = state
+            .note_explorer
+            .notes
+            .iter_mut()
+            .find(|note| note.rel_path == change.rel_path)
+        {
+            note.labels = change.new_labels.clone();
+            note.last_updated = Some(timestamp.clone());
+        }
+        if state.state.selected_note_path() == Some(&change.rel_path) {
+            state
+                .state
+                .set_selected_note_labels(change.new_labels.clone());
+        }
+    }
+    state.visualizer.sync_notes(&state.note_explorer.notes);
+    let save_open_note_task = apply_label_changes_to_open_note(state, changes);
+    Task::batch(vec![
+        save_open_note_task,
+        state.refresh_smart_folders_task(),
+    ])
+}
 
 pub(super) fn handle(state: &mut Editor, message: Message) -> Task<Message> {
-    match message {
+    let previous_labels = state.state.selected_note_labels().to_vec();
+    // Batch edits update the open buffer themselves, only where its front matter lists labels.
+    let is_batch_edit = matches!(
+        message,
+        Message::LabelEditApplied(_) | Message::MarkedNotesLabelEdited(..)
+    );
+    let task = match message {
         Message::NewLabelInputChanged(text) => {
-            label_actions::handle_label_input_changed(&mut state.state, text);
+            label_actions::handle_label_input_changed(&mut state.state, &state.note_explorer, text);
             Task::none()
         }
-        Message::AddLabel => label_actions::handle_add_label(
-            &mut state.state,
-            &mut state.note_explorer,
-            &mut state.visualizer,
-        ),
-        Message::RemoveLabel(label) => label_actions::handle_remove_label(
-            &mut state.state,
-            &mut state.note_explorer,
-            &mut state.visualizer,
-            label,
-        ),
+        Message::AddLabel => {
+            let save_task = label_actions::handle_add_label(
+                &mut state.state,
+                &mut state.note_explorer,
+                &mut state.visualizer,
+            );
+            Task::batch(vec![save_task, state.refresh_smart_folders_task()])
+        }
+        Message::RemoveLabel(label) => {
+            let save_task = label_actions::handle_remove_label(
+                &mut state.state,
+                &mut state.note_explorer,
+                &mut state.visualizer,
+                label,
+            );
+            Task::batch(vec![save_task, state.refresh_smart_folders_task()])
+        }
+        Message::ToggleLabelEditor => {
+            state.state.toggle_label_editor();
+            Task::none()
+        }
+        Message::LabelEditLabelChanged(label) => {
+            let info = notebook::label_info(&state.note_explorer.label_registry, &label)
+                .cloned()
+                .unwrap_or_default();
+            state.state.set_label_style(&info);
+            state.state.set_label_edit_label(label);
+            Task::none()
+        }
+        Message::LabelEditTargetChanged(target) => {
+            state.state.set_label_edit_target(target);
+            Task::none()
+        }
+        Message::PreviewLabelEdit(kind) => {
+            if !state.state.show_label_editor() || state.state.is_label_edit_in_progress() {
+                return Task::none();
+            }
+            match notebook::preview_label_edit(
+                &state.note_explorer.notes,
+                kind,
+                state.state.label_edit_label(),
+                state.state.label_edit_target(),
+            ) {
+                Ok(preview) => state.state.set_label_edit_preview(Some(preview)),
+                Err(error) => {
+                    state.state.set_label_edit_preview(None);
+                    state.state.set_status_message(error.ui_message());
+                }
+            }
+            Task::none()
+        }
+        Message::ApplyLabelEdit => {
+            let Some(preview) = state.state.label_edit_preview().cloned() else {
+                return Task::none();
+            };
+            if state.state.is_label_edit_in_progress() {
+                return Task::none();
+            }
+
+            let notebook_path = state.state.notebook_path().to_string();
+            let mut notes = state.note_explorer.notes.clone();
+            state.state.set_label_edit_in_progress(true);
+            Task::perform(
+                async move { notebook::apply_label_edit(&notebook_path, &preview, &mut notes).await },
+                Message::LabelEditApplied,
+            )
+        }
+        Message::LabelEditApplied(result) => {
+            let Some(preview) = state.state.label_edit_preview().cloned() else {
+                state.state.set_label_edit_in_progress(false);
+                return Task::none();
+            };
+            state.state.set_label_edit_preview(None);
+
+            match result {
+                Ok(changed_paths) => {
+                    state
+                        .state
+                        .set_status_message(label_edit_summary(&preview, changed_paths.len()));
+                    notebook::apply_label_edit_to_registry(
+                        &mut state.note_explorer.label_registry,
+                        &preview,
+                    );
+                    state
+                        .visualizer
+                        .sync_label_registry(&state.note_explorer.label_registry);
+                    apply_label_changes_to_notes(state, &preview.notes)
+                }
+                Err(error) => {
+                    state.state.set_status_message(error.ui_message());
+                    Task::none()
+                }
+            }
+        }
+        Message::LabelStyleColorChanged(color) => {
+            state.state.set_label_style_color(color);
+            Task::none()
+        }
+        Message::LabelStyleDescriptionChanged(description) => {
+            state.state.set_label_style_description(description);
+            Task::none()
+        }
+        Message::LabelStyleIconChanged(icon) => {
+            state.state.set_label_style_icon(icon);
+            Task::none()
+        }
+        Message::SaveLabelStyle => {
+            if !state.state.show_label_editor() || state.state.is_label_edit_in_progress() {
+                return Task::none();
+            }
+            let (color, description, icon) = state.state.label_style();
+            let info = LabelInfo {
+                color: Some(color.to_string()),
+                description: Some(description.to_string()),
+                icon: Some(icon.to_string()),
+            };
+            let label = state.state.label_edit_label().to_string();
+            let notebook_path = state.state.notebook_path().to_string();
+            let notes = state.note_explorer.notes.clone();
+            let mut registry = state.note_explorer.label_registry.clone();
+            Task::perform(
+                async move {
+                    notebook::save_label_info(&notebook_path, &notes, &mut registry, &label, info)
+                        .await
+                        .map(|()| registry)
+                },
+                Message::LabelStyleSaved,
+            )
+        }
+        Message::LabelStyleSaved(result) => {
+            match result {
+                Ok(registry) => {
+                    state.note_explorer.label_registry = registry;
+                    state
+                        .visualizer
+                        .sync_label_registry(&state.note_explorer.label_registry);
+                    state.state.set_status_message(format!(
+                        "Saved the style of '{}'.",
+                        notebook::normalize_label(state.state.label_edit_label())
+                    ));
+                }
+                Err(error) => state.state.set_status_message(error.ui_message()),
+            }
+            Task::none()
+        }
+        Message::EditMarkedNotesLabel(action) => {
+            let marked: Vec<String> = state.note_explorer.marked_notes.iter().cloned().collect();
+            let label = notebook::normalize_label(state.state.new_label_text());
+            let changes = match notebook::preview_bulk_label_edit(
+                &state.note_explorer.notes,
+                &marked,
+                action,
+                &label,
+            ) {
+                Ok(changes) => changes,
+                Err(error) => {
+                    state.state.set_status_message(error.ui_message());
+                    return Task::none();
+                }
+            };
+            if changes.is_empty() {
+                state.state.set_status_message(match action {
+                    BulkLabelAction::Add => format!("Every marked note already has '{}'.", label),
+                    BulkLabelAction::Remove => format!("No marked note has '{}'.", label),
+                });
+                return Task::none();
+            }
+
+            let notebook_path = state.state.notebook_path().to_string();
+            let mut notes = state.note_explorer.notes.clone();
+            Task::perform(
+                async move {
+                    let result =
+                        notebook::apply_bulk_label_edit(&notebook_path, &changes, &mut notes).await;
+                    (changes, result)
+                },
+                move |(changes, result)| {
+                    Message::MarkedNotesLabelEdited(action, label.clone(), changes, result)
+                },
+            )
+        }
+        Message::MarkedNotesLabelEdited(action, label, changes, result) => match result {
+            Ok(changed_paths) => {
+                state.state.set_status_message(match action {
+                    BulkLabelAction::Add => {
+                        format!("Added '{}' to {} note(s).", label, changed_paths.len())
+                    }
+                    BulkLabelAction::Remove => {
+                        format!("Removed '{}' from {} note(s).", label, changed_paths.len())
+                    }
+                });
+                state.state.clear_new_label_text();
+                apply_label_changes_to_notes(state, &changes)
+            }
+            Err(error) => {
+                state.state.set_status_message(error.ui_message());
+                Task::none()
+            }
+        },
         _ => unreachable!("label handler received invalid message"),
+    };
+
+    if !is_batch_edit && state.state.selected_note_labels() != previous_labels.as_slice() {
+        return Task::batch(vec![task, write_labels_to_front_matter(state)]);
     }
+    task
 }
@@ -0,0 +1,91 @@
+use super::*;
+
+pub(super) fn handle(state: &mut Editor, message: Message) -> Task<Message> {
+    match message {
+        Message::BacklinksLoaded(note_path, backlinks) => {
+            if state.state.selected_note_path() == Some(&note_path) {
+                state.state.set_selected_note_backlinks(backlinks);
+            }
+            Task::none()
+        }
+        Message::MarkdownLinkClicked(uri) => {
+            let note_paths: Vec<&str> = state
+                .note_explorer
+                .notes
+                .iter()
+                .map(|note| note.rel_path.as_str())
+                .collect();
+            let target = classify_preview_link(
+                &uri,
+                state.state.selected_note_path().map(String::as_str),
+                &note_paths,
+            );
+
+            match target {
+                PreviewLinkTarget::Note { rel_path, anchor } => {
+                    state.state.clear_status_message();
+                    if state.state.selected_note_path() == Some(&rel_path) {
+                        return match anchor {
+                            Some(anchor) => state.scroll_preview_to_anchor_task(&anchor),
+                            None => Task::none(),
+                        };
+                    }
+
+                    match anchor {
+                        Some(anchor) => state
+                            .state
+                            .set_pending_preview_anchor(rel_path.clone(), anchor),
+                        None => state.state.clear_pending_preview_anchor(),
+                    }
+                    Task::perform(async move { rel_path }, Message::NoteSelected)
+                }
+                PreviewLinkTarget::Anchor(anchor) => {
+                    state.state.clear_status_message();
+                    state.scroll_preview_to_anchor_task(&anchor)
+                }
+                PreviewLinkTarget::External(url) => {
+                    state.state.clear_status_message();
+                    let opener = state.state.link_opener().to_string();
+                    Task::perform(
+                        async move { open_external_link(&opener, &url) },
+                        Message::ExternalLinkOpened,
+                    )
+                }
+                PreviewLinkTarget::Broken(reason) => {
+                    state.state.set_status_message(reason);
+                    Task::none()
+                }
+            }
+        }
+        Message::ExternalLinkOpened(result) => {
+            if let Err(err) = result {
+                state.state.set_status_message(err);
+            }
+            Task::none()
+        }
+        _ => unreachable!("links handler received invalid message"),
+    }
+}
+
+fn open_external_link(opener: &str, url: &str) -> Result<(), String> {
+    let mut parts = opener.split_whitespace();
+    let Some(program) = parts.next() else {
+        return Err(format!(
+            "No link opener is configured to open '{}'. Set `link_opener` in the config file.",
+            url
+        ));
+    };
+
+    let mut child = std::process::Command::new(program)
+        .args(parts)
+        .arg(url)
+        .spawn()
+        .map_err(|err| format!("Failed to open '{}' with '{}': {}", url, opener, err))?;
+
+    // Openers like a browser may keep running; wait off the UI thread so the exited process
+    // is reaped instead of left as a zombie.
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
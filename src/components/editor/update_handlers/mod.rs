@@ -1,17 +1,28 @@
 use super::*;
 
+mod conflict;
+mod fsck;
+mod history;
 mod label;
+mod links;
 mod note_lifecycle;
 mod persistence;
+mod replace;
 mod search;
+mod trash;
 mod ui;
 mod visualizer;
+mod watcher;
 
 impl Editor {
     pub(super) fn handle_label_messages(state: &mut Self, message: Message) -> Task<Message> {
         label::handle(state, message)
     }
 
+    pub(super) fn handle_link_messages(state: &mut Self, message: Message) -> Task<Message> {
+        links::handle(state, message)
+    }
+
     pub(super) fn handle_search_messages(state: &mut Self, message: Message) -> Task<Message> {
         search::handle(state, message)
     }
@@ -42,6 +53,30 @@ impl Editor {
         note_lifecycle::handle(state, message)
     }
 
+    pub(super) fn handle_history_messages(state: &mut Self, message: Message) -> Task<Message> {
+        history::handle(state, message)
+    }
+
+    pub(super) fn handle_conflict_messages(state: &mut Self, message: Message) -> Task<Message> {
+        conflict::handle(state, message)
+    }
+
+    pub(super) fn handle_fsck_messages(state: &mut Self, message: Message) -> Task<Message> {
+        fsck::handle(state, message)
+    }
+
+    pub(super) fn handle_replace_messages(state: &mut Self, message: Message) -> Task<Message> {
+        replace::handle(state, message)
+    }
+
+    pub(super) fn handle_watcher_messages(state: &mut Self, message: Message) -> Task<Message> {
+        watcher::handle(state, message)
+    }
+
+    pub(super) fn handle_trash_messages(state: &mut Self, message: Message) -> Task<Message> {
+        trash::handle(state, message)
+    }
+
     pub(super) fn handle_ui_messages(state: &mut Self, message: Message) -> Task<Message> {
         ui::handle(state, message)
     }
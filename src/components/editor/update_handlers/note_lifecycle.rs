@@ -1,11 +1,48 @@
 use super::*;
+use crate::notebook::RewrittenLinksNote;
+
+/// Puts the links a move rewrote into the open note's buffer as one undoable edit.
+///
+/// The move already wrote the file; a buffer that has moved on since it was last saved is left
+/// alone so its next save reports the conflict instead of dropping either change.
+fn apply_rewritten_links_to_open_note(
+    state: &mut Editor,
+    rewritten_notes: &[RewrittenLinksNote],
+) -> Task<Message> {
+    let Some(note_path) = state.content_note_path.clone() else {
+        return Task::none();
+    };
+    let Some(rewritten) = rewritten_notes
+        .iter()
+        .find(|note| note.rel_path == note_path)
+    else {
+        return Task::none();
+    };
+    if state.markdown_text != rewritten.previous_content {
+        return Task::none();
+    }
+
+    state.replace_markdown_with_undo(&note_path, rewritten.content.clone());
+
+    // The file already holds this text; saving it refreshes the editor's view of the disk state.
+    let base = state.state.note_disk_state(&note_path).cloned();
+    note_coordinator::save_note_content_task(
+        state.state.notebook_path(),
+        note_path,
+        state.markdown_text.clone(),
+        base,
+    )
+}
 
 pub(super) fn handle(state: &mut Editor, message: Message) -> Task<Message> {
     let previous_markdown = state.markdown_text.clone();
     let task = match message {
         Message::NewNote => {
             state.state.show_new_note_dialog();
-            Task::none()
+            state
+                .state
+                .select_new_note_template(state.note_explorer.settings.default_template.clone());
+            note_actions::load_new_note_templates_command(&state.state)
         }
         Message::NewNoteInputChanged(text) => {
             state.state.update_new_note_path(text);
@@ -19,8 +56,77 @@ pub(super) fn handle(state: &mut Editor, message: Message) -> Task<Message> {
             Task::none()
         }
         Message::NoteCreated(result) => {
-            note_actions::handle_note_created(result, &mut state.note_explorer)
+            note_actions::handle_note_created(result, &mut state.state, &mut state.note_explorer)
+        }
+        Message::NewNoteTemplatesLoaded(result) => {
+            match result {
+                Ok(templates) => state.state.set_new_note_templates(templates),
+                Err(error) => state.state.set_status_message(error.ui_message()),
+            }
+            Task::none()
+        }
+        Message::NewNoteTemplateSelected(template) => {
+            state.state.select_new_note_template(template);
+            Task::none()
+        }
+        Message::SetDefaultTemplate => {
+            let notebook_path = state.state.notebook_path().to_string();
+            let notes = state.note_explorer.notes.clone();
+            let template = state.state.new_note_template().map(str::to_string);
+            Task::perform(
+                async move { notebook::set_default_template(&notebook_path, &notes, template).await },
+                Message::DefaultTemplateSaved,
+            )
+        }
+        Message::DefaultTemplateSaved(result) => match result {
+            // Reloading picks up the stored settings.
+            Ok(()) => state
+                .note_explorer
+                .update(note_explorer::Message::LoadNotes)
+                .map(Message::NoteExplorerMsg),
+            Err(error) => {
+                state.state.set_status_message(error.ui_message());
+                Task::none()
+            }
+        },
+        Message::OpenTodayJournalNote => note_actions::open_journal_note_command(
+            &state.state,
+            state.note_explorer.notes.clone(),
+            notebook::today_local_date(),
+        ),
+        Message::PreviousJournalDay | Message::NextJournalDay => {
+            let forward = matches!(message, Message::NextJournalDay);
+            let current = note_actions::selected_journal_date(&state.state)
+                .unwrap_or_else(notebook::today_local_date);
+            // Only "Today" creates daily notes; stepping moves between the ones that exist.
+            match notebook::adjacent_journal_note_path(
+                state.state.journal_path_pattern(),
+                &state.note_explorer.notes,
+                current,
+                forward,
+            ) {
+                Some(rel_path) => Task::perform(async move { rel_path }, Message::NoteSelected),
+                None => {
+                    state.state.set_status_message(if forward {
+                        "There is no later daily note.".to_string()
+                    } else {
+                        "There is no earlier daily note.".to_string()
+                    });
+                    Task::none()
+                }
+            }
         }
+        Message::JournalNoteOpened(result) => match result {
+            Ok(note) => note_actions::handle_note_created(
+                Ok(note),
+                &mut state.state,
+                &mut state.note_explorer,
+            ),
+            Err(error) => {
+                state.state.set_status_message(error.ui_message());
+                Task::none()
+            }
+        },
         Message::DeleteNote => note_actions::handle_delete_note(&mut state.state),
         Message::ConfirmDeleteNote(confirmed) => note_actions::handle_confirm_delete_note(
             confirmed,
@@ -60,13 +166,20 @@ pub(super) fn handle(state: &mut Editor, message: Message) -> Task<Message> {
                 &state.note_explorer.notes,
             )
         }
-        Message::NoteMoved(result, old_path) => note_actions::handle_note_moved(
-            result,
-            old_path,
-            &mut state.state,
-            &mut state.undo_manager,
-            &mut state.note_explorer,
-        ),
+        Message::NoteMoved(result, old_path) => {
+            let open_note_task = match &result {
+                Ok(moved) => apply_rewritten_links_to_open_note(state, &moved.rewritten_notes),
+                Err(_) => Task::none(),
+            };
+            let moved_task = note_actions::handle_note_moved(
+                result,
+                old_path,
+                &mut state.state,
+                &mut state.undo_manager,
+                &mut state.note_explorer,
+            );
+            Task::batch(vec![open_note_task, moved_task])
+        }
         _ => unreachable!("note-lifecycle handler received invalid message"),
     };
 
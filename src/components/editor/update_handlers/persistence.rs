@@ -33,6 +33,12 @@ pub(super) fn handle_debounced_metadata(state: &mut Editor, message: Message) ->
                 #[cfg(debug_assertions)]
                 eprintln!("Debounced metadata saved successfully.");
             }
+            // Edits reach disk before their debounced metadata save, so smart folders can catch up.
+            let refresh_task = if result.is_ok() {
+                state.refresh_smart_folders_task()
+            } else {
+                Task::none()
+            };
 
             let should_save_latest = state.metadata_save_reschedule_after_in_flight
                 || saved_generation < state.metadata_save_generation;
@@ -40,10 +46,13 @@ pub(super) fn handle_debounced_metadata(state: &mut Editor, message: Message) ->
 
             if should_save_latest {
                 state.metadata_save_in_flight = true;
-                return state.persist_metadata_snapshot_task(state.metadata_save_generation);
+                return Task::batch(vec![
+                    refresh_task,
+                    state.persist_metadata_snapshot_task(state.metadata_save_generation),
+                ]);
             }
 
-            Task::none()
+            refresh_task
         }
         _ => unreachable!("debounced-metadata handler received invalid message"),
     }
@@ -61,6 +70,10 @@ pub(super) fn handle_shutdown(state: &mut Editor, message: Message) -> Task<Mess
             let notebook_path = state.state.notebook_path().to_string();
             let content_note_path = state.content_note_path.clone();
             let markdown_text = state.markdown_text.clone();
+            let content_note_base = content_note_path
+                .as_deref()
+                .and_then(|note_path| state.state.note_disk_state(note_path))
+                .cloned();
             let notes = state.note_explorer.notes.clone();
 
             Task::perform(
@@ -69,6 +82,7 @@ pub(super) fn handle_shutdown(state: &mut Editor, message: Message) -> Task<Mess
                         &notebook_path,
                         content_note_path,
                         &markdown_text,
+                        content_note_base,
                         &notes,
                     );
                     (window_id, result)
@@ -82,6 +96,7 @@ pub(super) fn handle_shutdown(state: &mut Editor, message: Message) -> Task<Mess
             match result {
                 Ok(()) => {
                     notebook::clear_search_index_for_notebook(state.state.notebook_path());
+                    notebook::clear_link_index_for_notebook(state.state.notebook_path());
                     window::close(window_id)
                 }
                 Err(error) => {
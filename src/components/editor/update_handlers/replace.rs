@@ -0,0 +1,185 @@
+use super::*;
+use crate::notebook::ReplacePreview;
+
+fn preview_replace_task(state: &Editor) -> Task<Message> {
+    let notebook_path = state.state.notebook_path().to_string();
+    let notes = state.note_explorer.notes.clone();
+    let find = state.state.replace_find().to_string();
+    let replacement = state.state.replace_with().to_string();
+    let open_note = state.state.selected_note_path().map(|note_path| {
+        (
+            note_path.clone(),
+            state.markdown_text.clone(),
+            state.state.note_disk_state(note_path).cloned(),
+        )
+    });
+
+    Task::perform(
+        async move {
+            let open_note = open_note.as_ref().map(|(note_path, buffer, disk_base)| {
+                (note_path.as_str(), buffer.as_str(), disk_base.as_ref())
+            });
+            notebook::preview_replace(&notebook_path, &notes, &find, &replacement, open_note).await
+        },
+        Message::ReplacePreviewLoaded,
+    )
+}
+
+/// Whether the preview still describes the open note; it was taken from the editor buffer.
+fn preview_matches_open_note(state: &Editor, preview: &ReplacePreview) -> bool {
+    let selected_note_path = state.state.selected_note_path();
+    preview.notes.iter().all(|note| {
+        if selected_note_path == Some(&note.rel_path) {
+            note.original_content == state.markdown_text
+        } else {
+            !note.is_open_note
+        }
+    })
+}
+
+fn selected_hit_count(preview: &ReplacePreview, changed_paths: &[String]) -> usize {
+    preview
+        .notes
+        .iter()
+        .filter(|note| changed_paths.contains(&note.rel_path))
+        .flat_map(|note| &note.lines)
+        .flat_map(|line| &line.hits)
+        .filter(|hit| hit.selected)
+        .count()
+}
+
+/// Puts the replaced text into the open note's buffer as one undoable edit.
+fn apply_replace_to_open_note(state: &mut Editor, preview: &ReplacePreview) -> Task<Message> {
+    let Some(note_path) = state.state.selected_note_path().cloned() else {
+        return Task::none();
+    };
+    let Some(note_preview) = preview.notes.iter().find(|note| note.rel_path == note_path) else {
+        return Task::none();
+    };
+
+    let replaced = note_preview.replaced_content(&preview.replacement);
+    if replaced == state.markdown_text {
+        return Task::none();
+    }
+    state.replace_markdown_with_undo(&note_path, replaced);
+    state.sync_markdown_preview();
+
+    // The file already holds this text; saving it refreshes the editor's view of the disk state.
+    let base = state.state.note_disk_state(&note_path).cloned();
+    note_coordinator::save_note_content_task(
+        state.state.notebook_path(),
+        note_path,
+        state.markdown_text.clone(),
+        base,
+    )
+}
+
+pub(super) fn handle(state: &mut Editor, message: Message) -> Task<Message> {
+    match message {
+        Message::ToggleReplace => {
+            state.state.toggle_replace();
+            Task::none()
+        }
+        Message::ReplaceFindChanged(find) => {
+            state.state.set_replace_find(find);
+            Task::none()
+        }
+        Message::ReplaceWithChanged(replacement) => {
+            state.state.set_replace_with(replacement);
+            Task::none()
+        }
+        Message::PreviewReplace => {
+            if !state.state.show_replace() || state.state.is_replace_in_progress() {
+                return Task::none();
+            }
+            state.state.set_replace_in_progress(true);
+            preview_replace_task(state)
+        }
+        Message::ReplacePreviewLoaded(result) => {
+            if !state.state.show_replace() {
+                return Task::none();
+            }
+            match result {
+                // A preview for text that has since been edited in the find box is dropped.
+                Ok(preview) if preview.find == state.state.replace_find() => {
+                    let replacement = state.state.replace_with().to_string();
+                    state.state.set_replace_preview(Some(preview));
+                    state.state.set_replace_with(replacement);
+                }
+                Ok(_) => state.state.set_replace_in_progress(false),
+                Err(error) => {
+                    state.state.set_replace_in_progress(false);
+                    state.state.set_status_message(error.ui_message());
+                }
+            }
+            Task::none()
+        }
+        Message::ToggleReplaceHit(hit_id) => {
+            state.state.toggle_replace_hit(hit_id);
+            Task::none()
+        }
+        Message::ApplyReplace => {
+            let Some(preview) = state.state.replace_preview().cloned() else {
+                return Task::none();
+            };
+            if state.state.is_replace_in_progress() {
+                return Task::none();
+            }
+            if !preview_matches_open_note(state, &preview) {
+                state.state.set_replace_preview(None);
+                state.state.set_status_message(
+                    "The open note changed since the preview. Preview the replacement again."
+                        .to_string(),
+                );
+                return Task::none();
+            }
+
+            let notebook_path = state.state.notebook_path().to_string();
+            let mut notes = state.note_explorer.notes.clone();
+            state.state.set_replace_in_progress(true);
+            Task::perform(
+                async move { notebook::apply_replace(&notebook_path, &preview, &mut notes).await },
+                Message::ReplaceApplied,
+            )
+        }
+        Message::ReplaceApplied(result) => {
+            let Some(preview) = state.state.replace_preview().cloned() else {
+                state.state.set_replace_in_progress(false);
+                return Task::none();
+            };
+            state.state.set_replace_preview(None);
+
+            match result {
+                Ok(changed_paths) => {
+                    state.state.set_status_message(format!(
+                        "Replaced {} occurrence(s) in {} note(s).",
+                        selected_hit_count(&preview, &changed_paths),
+                        changed_paths.len()
+                    ));
+
+                    let mut tasks = Vec::new();
+                    if state
+                        .state
+                        .selected_note_path()
+                        .is_some_and(|note_path| changed_paths.contains(note_path))
+                    {
+                        tasks.push(apply_replace_to_open_note(state, &preview));
+                    }
+                    // Replaced notes have a new last update time in metadata.json.
+                    tasks.push(
+                        state
+                            .note_explorer
+                            .update(note_explorer::Message::LoadNotes)
+                            .map(Message::NoteExplorerMsg),
+                    );
+                    Task::batch(tasks)
+                }
+                Err(error) => {
+                    state.state.set_status_message(error.ui_message());
+                    Task::none()
+                }
+            }
+        }
+        _ => unreachable!("replace handler received invalid message"),
+    }
+}
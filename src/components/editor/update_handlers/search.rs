@@ -23,11 +23,18 @@ pub(super) fn handle(state: &mut Editor, message: Message) -> Task<Message> {
 
             spawn_search_task(state, query, generation)
         }
-        Message::SearchCompleted(generation, results) => {
+        Message::SearchModeChanged(mode) => {
+            state.state.set_search_mode(mode);
+            restart_search(state)
+        }
+        Message::SearchCompleted(generation, result) => {
             if generation == state.search_generation
                 && !state.state.search_query().trim().is_empty()
             {
-                state.state.set_search_results(results);
+                match result {
+                    Ok(results) => state.state.set_search_results(results),
+                    Err(error) => state.state.set_search_error(error.ui_message()),
+                }
             }
             Task::none()
         }
@@ -36,20 +43,126 @@ pub(super) fn handle(state: &mut Editor, message: Message) -> Task<Message> {
             state.state.clear_search();
             Task::none()
         }
+        Message::OpenSearchMatch(note_path, line_number, range) => {
+            if state.state.selected_note_path() == Some(&note_path)
+                && state.content_note_path.as_ref() == Some(&note_path)
+            {
+                return state.select_search_match_task(line_number, range);
+            }
+
+            state.state.clear_pending_preview_anchor();
+            state
+                .state
+                .set_pending_search_match(note_path.clone(), line_number, range);
+            Task::perform(async move { note_path }, Message::NoteSelected)
+        }
+        Message::NextSearchMatch => state.step_search_match_task(true),
+        Message::PreviousSearchMatch => state.step_search_match_task(false),
+        Message::SearchInFolder(folder_path) => {
+            state.state.set_search_scope(Some(folder_path));
+            restart_search(state)
+        }
+        Message::ClearSearchScope => {
+            state.state.set_search_scope(None);
+            restart_search(state)
+        }
+        Message::SavedSearchNameChanged(name) => {
+            state.state.set_saved_search_name(name);
+            Task::none()
+        }
+        Message::SaveSearch => {
+            let search = notebook::SavedSearch {
+                name: state.state.saved_search_name().to_string(),
+                query: state.state.search_query().to_string(),
+                mode: state.state.search_mode(),
+                scope: state.state.search_scope().map(str::to_string),
+            };
+            let notebook_path = state.state.notebook_path().to_string();
+            let notes = state.note_explorer.notes.clone();
+            let mut saved_searches = state.note_explorer.saved_searches.clone();
+            Task::perform(
+                async move {
+                    notebook::save_search(&notebook_path, &notes, &mut saved_searches, search).await
+                },
+                Message::SavedSearchesChanged,
+            )
+        }
+        Message::OpenSavedSearch(name) => {
+            let Some(saved) = state
+                .note_explorer
+                .saved_searches
+                .iter()
+                .find(|saved| saved.name == name)
+                .cloned()
+            else {
+                return Task::none();
+            };
+
+            state.state.set_search_query(saved.query);
+            state.state.set_search_mode(saved.mode);
+            state.state.set_search_scope(saved.scope);
+            state.state.set_saved_search_name(saved.name);
+            restart_search(state)
+        }
+        Message::DeleteSavedSearch(name) => {
+            let notebook_path = state.state.notebook_path().to_string();
+            let notes = state.note_explorer.notes.clone();
+            let mut saved_searches = state.note_explorer.saved_searches.clone();
+            Task::perform(
+                async move {
+                    notebook::delete_saved_search(
+                        &notebook_path,
+                        &notes,
+                        &mut saved_searches,
+                        &name,
+                    )
+                    .await
+                },
+                Message::SavedSearchesChanged,
+            )
+        }
+        Message::SavedSearchesChanged(result) => match result {
+            Ok(()) => {
+                state.state.set_saved_search_name(String::new());
+                // Reloading picks up the stored searches and re-evaluates the smart folders.
+                state
+                    .note_explorer
+                    .update(note_explorer::Message::LoadNotes)
+                    .map(Message::NoteExplorerMsg)
+            }
+            Err(error) => {
+                state.state.set_status_message(error.ui_message());
+                Task::none()
+            }
+        },
         _ => unreachable!("search handler received invalid message"),
     }
 }
 
+/// Runs the current query again, for example after its mode or scope changed.
+fn restart_search(state: &mut Editor) -> Task<Message> {
+    let generation = state.next_search_generation();
+    let query = state.state.search_query().trim().to_string();
+    if query.is_empty() || state.state.notebook_path().is_empty() {
+        return Task::none();
+    }
+
+    spawn_search_task(state, query, generation)
+}
+
 fn spawn_search_task(state: &Editor, query: String, generation: u64) -> Task<Message> {
     let notebook_path = state.state.notebook_path().to_string();
+    let mode = state.state.search_mode();
+    let scope = state.state.search_scope();
     let notes = state
         .note_explorer
         .notes
         .iter()
+        .filter(|note| notebook::is_in_search_scope(&note.rel_path, scope))
         .map(notebook::SearchNote::from)
         .collect::<Vec<notebook::SearchNote>>();
     Task::perform(
-        async move { notebook::search_notes_with_snapshot(notebook_path, notes, query).await },
-        move |results| Message::SearchCompleted(generation, results),
+        async move { notebook::search_notes_with_snapshot(notebook_path, notes, query, mode).await },
+        move |result| Message::SearchCompleted(generation, result),
     )
 }
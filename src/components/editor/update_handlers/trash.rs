@@ -0,0 +1,157 @@
+use native_dialog::{DialogBuilder, MessageLevel};
+
+use super::*;
+
+fn restore_trash_entry_task(
+    state: &Editor,
+    entry_id: String,
+    target_path: String,
+) -> Task<Message> {
+    let notebook_path = state.state.notebook_path().to_string();
+    let mut notes = state.note_explorer.notes.clone();
+
+    Task::perform(
+        async move {
+            notebook::restore_trashed_note(&notebook_path, &entry_id, &target_path, &mut notes)
+                .await
+        },
+        Message::TrashEntryRestored,
+    )
+}
+
+pub(super) fn handle(state: &mut Editor, message: Message) -> Task<Message> {
+    match message {
+        Message::ToggleTrash => {
+            state.state.toggle_trash();
+            if state.state.show_trash() {
+                state.load_trash_task()
+            } else {
+                Task::none()
+            }
+        }
+        Message::TrashLoaded(result) => {
+            match result {
+                Ok(entries) => state.state.set_trash_entries(entries),
+                Err(error) => state.state.set_status_message(error.ui_message()),
+            }
+            Task::none()
+        }
+        Message::RestoreTrashEntry(entry_id) => {
+            let Some(original_path) = state
+                .state
+                .trash_entries()
+                .iter()
+                .find(|entry| entry.id == entry_id)
+                .map(|entry| entry.rel_path.clone())
+            else {
+                return Task::none();
+            };
+
+            let suggested_path = notebook::available_restore_path(
+                state.state.notebook_path(),
+                &original_path,
+                &state.note_explorer.notes,
+            );
+            if suggested_path != original_path {
+                // The original path is in use again; let the user pick where the note goes.
+                state.state.begin_trash_restore(entry_id, suggested_path);
+                return Task::none();
+            }
+
+            restore_trash_entry_task(state, entry_id, original_path)
+        }
+        Message::TrashRestorePathChanged(path) => {
+            state.state.update_trash_restore_path(path);
+            Task::none()
+        }
+        Message::ConfirmTrashRestore => {
+            let Some(entry_id) = state.state.trash_restore_entry_id().map(str::to_string) else {
+                return Task::none();
+            };
+            let target_path = state.state.trash_restore_path_input().trim().to_string();
+            if target_path.is_empty() {
+                state
+                    .state
+                    .set_status_message("Restore path cannot be empty.".to_string());
+                return Task::none();
+            }
+
+            restore_trash_entry_task(state, entry_id, target_path)
+        }
+        Message::CancelTrashRestore => {
+            state.state.clear_trash_restore();
+            Task::none()
+        }
+        Message::TrashEntryRestored(result) => match result {
+            Ok(restored_note) => {
+                state.state.clear_trash_restore();
+                state.state.clear_status_message();
+                state.state.set_show_trash(false);
+
+                let reload_command = state
+                    .note_explorer
+                    .update(note_explorer::Message::LoadNotes)
+                    .map(Message::NoteExplorerMsg);
+                let select_command =
+                    Task::perform(async { restored_note.rel_path }, Message::NoteSelected);
+
+                Task::batch(vec![
+                    reload_command,
+                    select_command,
+                    state.load_trash_task(),
+                ])
+            }
+            Err(error) => {
+                state.state.set_status_message(error.ui_message());
+                Task::none()
+            }
+        },
+        Message::PurgeTrashEntry(entry_id) => {
+            let Some(original_path) = state
+                .state
+                .trash_entries()
+                .iter()
+                .find(|entry| entry.id == entry_id)
+                .map(|entry| entry.rel_path.clone())
+            else {
+                return Task::none();
+            };
+
+            Task::perform(
+                async move {
+                    let confirmed = DialogBuilder::message()
+                        .set_level(MessageLevel::Warning)
+                        .set_title("Confirm Permanent Deletion")
+                        .set_text(format!(
+                            "Permanently delete '{}' from the trash? This cannot be undone.",
+                            original_path
+                        ))
+                        .confirm()
+                        .show()
+                        .unwrap_or(false);
+                    (entry_id, confirmed)
+                },
+                |(entry_id, confirmed)| Message::ConfirmPurgeTrashEntry(entry_id, confirmed),
+            )
+        }
+        Message::ConfirmPurgeTrashEntry(entry_id, confirmed) => {
+            if !confirmed {
+                return Task::none();
+            }
+
+            state.state.clear_trash_restore();
+            let notebook_path = state.state.notebook_path().to_string();
+            Task::perform(
+                async move { notebook::purge_trashed_note(&notebook_path, &entry_id).await },
+                Message::TrashEntryPurged,
+            )
+        }
+        Message::TrashEntryPurged(result) => {
+            if let Err(error) = result {
+                state.state.set_status_message(error.ui_message());
+            }
+            state.load_trash_task()
+        }
+        _ => unreachable!("trash handler received invalid message"),
+    }
+}
@@ -20,9 +20,8 @@ pub(super) fn handle(state: &mut Editor, message: Message) -> Task<Message> {
             state.state.set_ui_scale(new_scale);
             state.persist_scale_task()
         }
-        Message::MarkdownLinkClicked(_uri) => {
-            #[cfg(debug_assertions)]
-            eprintln!("Markdown link clicked: {}", _uri);
+        Message::DismissStatus => {
+            state.state.clear_status_message();
             Task::none()
         }
         _ => unreachable!("ui handler received invalid message"),
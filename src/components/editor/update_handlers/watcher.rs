@@ -0,0 +1,138 @@
+use std::path::Path;
+
+use super::*;
+
+/// Whether a changed path adds, removes or renames notes rather than only editing one.
+fn is_structural_change(
+    notebook_path: &str,
+    notes: &[notebook::NoteMetadata],
+    rel_path: &str,
+) -> bool {
+    let note_dir = Path::new(notebook_path).join(rel_path);
+    let is_known_note = notes.iter().any(|note| note.rel_path == rel_path);
+    if is_known_note != note_dir.join("note.md").exists() {
+        return true;
+    }
+
+    let prefix = format!("{rel_path}/");
+    !note_dir.exists() && notes.iter().any(|note| note.rel_path.starts_with(&prefix))
+}
+
+pub(super) fn handle(state: &mut Editor, message: Message) -> Task<Message> {
+    match message {
+        Message::NotebookChangedOnDisk(changes) => {
+            let notebook_path = state.state.notebook_path().to_string();
+            let mut tasks = Vec::new();
+
+            let structure_changed = changes.metadata_changed
+                || changes.note_paths.iter().any(|rel_path| {
+                    is_structural_change(&notebook_path, &state.note_explorer.notes, rel_path)
+                });
+            if structure_changed {
+                // Reloading the explorer also refreshes the visualizer, the selection and the smart folders.
+                tasks.push(
+                    state
+                        .note_explorer
+                        .update(note_explorer::Message::LoadNotes)
+                        .map(Message::NoteExplorerMsg),
+                );
+            } else {
+                // Front matter edited in another program updates the notes' metadata; the open
+                // note keeps the front matter of its unsaved edits.
+                let selected_path = state.state.selected_note_path().cloned();
+                let keep_selected = selected_path.as_ref().is_some_and(|selected_path| {
+                    !notebook::is_note_content_persisted(
+                        &notebook_path,
+                        selected_path,
+                        &state.markdown_text,
+                    )
+                });
+                let changed_notes = state.note_explorer.notes.iter_mut().filter(|note| {
+                    changes.note_paths.contains(&note.rel_path)
+                        && !(keep_selected && selected_path.as_ref() == Some(&note.rel_path))
+                });
+                if notebook::sync_front_matter_from_disk(&notebook_path, changed_notes) {
+                    if let Some(note) = selected_path.and_then(|selected_path| {
+                        state
+                            .note_explorer
+                            .notes
+                            .iter()
+                            .find(|note| note.rel_path == selected_path)
+                    }) {
+                        state.state.set_selected_note_labels(note.labels.clone());
+                    }
+                    state.visualizer.sync_notes(&state.note_explorer.notes);
+                    tasks.push(state.schedule_debounced_metadata_save_task());
+                }
+                tasks.push(state.refresh_smart_folders_task());
+            }
+
+            if let Some(selected_path) = state.state.selected_note_path().cloned()
+                && changes.note_paths.contains(&selected_path)
+                && Path::new(&notebook_path)
+                    .join(&selected_path)
+                    .join("note.md")
+                    .exists()
+            {
+                if notebook::is_note_content_persisted(
+                    &notebook_path,
+                    &selected_path,
+                    &state.markdown_text,
+                ) {
+                    let previous_markdown = state.markdown_text.clone();
+                    tasks.push(Task::perform(
+                        note_coordinator::load_note_payload(notebook_path.clone(), selected_path),
+                        move |payload| {
+                            Message::OpenNoteReloadedFromDisk(
+                                payload.note_path,
+                                previous_markdown.clone(),
+                                payload.disk_state,
+                            )
+                        },
+                    ));
+                } else {
+                    state.state.set_status_message(format!(
+                        "'{}' changed on disk while it had unsaved edits; your edits were kept.",
+                        selected_path
+                    ));
+                }
+            }
+
+            if !state.state.search_query().trim().is_empty() {
+                tasks.push(Editor::handle_search_messages(state, Message::RunSearch));
+            }
+
+            Task::batch(tasks)
+        }
+        Message::OpenNoteReloadedFromDisk(note_path, previous_markdown, disk_state) => {
+            if state.state.selected_note_path() != Some(&note_path) {
+                return Task::none();
+            }
+            // The note was edited while it was being read, so both sides have changes.
+            if state.markdown_text != previous_markdown {
+                if state.markdown_text != disk_state.content && !state.state.show_note_conflict() {
+                    super::conflict::open_note_conflict(state, note_path, disk_state);
+                }
+                return Task::none();
+            }
+
+            state
+                .state
+                .set_note_disk_state(note_path.clone(), disk_state.clone());
+            if state.markdown_text == disk_state.content {
+                return Task::none();
+            }
+
+            state.replace_markdown_with_undo(&note_path, disk_state.content);
+            state.sync_markdown_preview();
+
+            let backlinks_task = note_actions::load_backlinks_command(
+                &state.state,
+                &state.note_explorer,
+                &note_path,
+            );
+            Task::batch(vec![backlinks_task, state.scroll_preview_to_cursor_task()])
+        }
+        _ => unreachable!("watcher handler received invalid message"),
+    }
+}
@@ -1,12 +1,17 @@
-use iced::widget::{Button, Column, Container, Row, Scrollable, Text};
+use iced::widget::{
+    Button, Column, Container, Row, Scrollable, Text, checkbox, container, tooltip,
+};
 use iced::{Element, Length, task::Task};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::Path;
 
 // Import the correct styling types - button directly
 use iced::widget::button;
 
-use crate::notebook::{self, NoteMetadata, NotebookError};
+use crate::notebook::{
+    self, LabelRegistry, LabelTreeNode, NoteMetadata, NotebookError, NotebookSettings, SavedSearch,
+    SavedSearchMatches,
+};
 
 #[derive(Debug, Clone)]
 pub enum Message {
@@ -17,6 +22,25 @@ pub enum Message {
     InitiateFolderRename(String),
     // Removed: ExpandToNote(String),
     CollapseAllAndExpandToNote(String),
+    /// Limits the search box to a folder; handled by the editor.
+    SearchInFolder(String),
+    /// Re-runs the saved searches after notes changed.
+    RefreshSmartFolders,
+    SmartFoldersEvaluated(u64, Vec<SavedSearchMatches>),
+    ToggleSmartFolder(String),
+    /// Loads a saved search into the search box; handled by the editor.
+    OpenSavedSearch(String),
+    /// Deletes a saved search; handled by the editor.
+    DeleteSavedSearch(String),
+    ToggleLabelTree,
+    /// Expands or collapses a label in the label tree.
+    ToggleLabel(String),
+    /// Shows only notes with the label or a label nested below it; the active label clears it.
+    FilterByLabel(String),
+    ClearLabelFilter,
+    /// Marks or unmarks a note for bulk label edits.
+    ToggleNoteMark(String),
+    ClearNoteMarks,
 }
 
 #[derive(Debug, Clone)]
@@ -39,6 +63,21 @@ pub struct NoteExplorer {
     pub notes: Vec<NoteMetadata>,
     pub notebook_path: String,
     pub expanded_folders: HashMap<String, bool>,
+    pub saved_searches: Vec<SavedSearch>,
+    /// Latest matches of each saved search, shown as smart folders.
+    pub smart_folders: Vec<SavedSearchMatches>,
+    pub settings: NotebookSettings,
+    /// Colors, descriptions and icons of labels, keyed by lowercase label.
+    pub label_registry: LabelRegistry,
+    /// Label the explorer is filtered to; `None` shows every note.
+    pub label_filter: Option<String>,
+    /// Paths of the notes marked for bulk label edits.
+    pub marked_notes: BTreeSet<String>,
+    expanded_smart_folders: HashSet<String>,
+    smart_folder_generation: u64,
+    show_label_tree: bool,
+    /// Lowercase paths of the expanded labels in the label tree.
+    expanded_labels: HashSet<String>,
 }
 
 impl NoteExplorer {
@@ -47,9 +86,59 @@ impl NoteExplorer {
             notes: Vec::new(),
             notebook_path,
             expanded_folders: HashMap::new(),
+            saved_searches: Vec::new(),
+            smart_folders: Vec::new(),
+            settings: NotebookSettings::default(),
+            label_registry: LabelRegistry::new(),
+            label_filter: None,
+            marked_notes: BTreeSet::new(),
+            expanded_smart_folders: HashSet::new(),
+            smart_folder_generation: 0,
+            show_label_tree: false,
+            expanded_labels: HashSet::new(),
         }
     }
 
+    /// Notes shown in the tree: all of them, or those matching the label filter.
+    fn visible_notes(&self) -> Vec<NoteMetadata> {
+        let Some(filter) = &self.label_filter else {
+            return self.notes.clone();
+        };
+        let filter = filter.to_lowercase();
+        self.notes
+            .iter()
+            .filter(|note| {
+                note.labels
+                    .iter()
+                    .any(|label| notebook::label_matches(label, &filter))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Re-evaluates every saved search against the current notes; older evaluations still in
+    /// flight are ignored when they finish.
+    fn refresh_smart_folders(&mut self) -> Task<Message> {
+        self.smart_folder_generation += 1;
+        if self.saved_searches.is_empty() || self.notebook_path.is_empty() {
+            self.smart_folders.clear();
+            return Task::none();
+        }
+
+        let generation = self.smart_folder_generation;
+        let notebook_path = self.notebook_path.clone();
+        let notes = self
+            .notes
+            .iter()
+            .map(notebook::SearchNote::from)
+            .collect::<Vec<notebook::SearchNote>>();
+        let saved_searches = self.saved_searches.clone();
+        Task::perform(
+            notebook::evaluate_saved_searches(notebook_path, notes, saved_searches),
+            move |matches| Message::SmartFoldersEvaluated(generation, matches),
+        )
+    }
+
     pub fn update(&mut self, message: Message) -> Task<Message> {
         match message {
             Message::LoadNotes => {
@@ -102,8 +191,22 @@ impl NoteExplorer {
                     new_expanded_folders.insert("".to_string(), is_root_expanded);
 
                     self.expanded_folders = new_expanded_folders;
+                    self.settings = load_result.settings;
+                    self.label_registry = load_result.label_registry;
 
-                    Task::none()
+                    if self.label_filter.is_some() && self.visible_notes().is_empty() {
+                        self.label_filter = None;
+                    }
+                    let notes = &self.notes;
+                    self.marked_notes
+                        .retain(|path| notes.iter().any(|note| &note.rel_path == path));
+
+                    self.saved_searches = load_result.saved_searches;
+                    let saved_searches = &self.saved_searches;
+                    self.expanded_smart_folders
+                        .retain(|name| saved_searches.iter().any(|saved| &saved.name == name));
+
+                    self.refresh_smart_folders()
                 }
                 Err(_load_error) => {
                     #[cfg(debug_assertions)]
@@ -130,6 +233,59 @@ impl NoteExplorer {
                 Task::none()
             }
             Message::InitiateFolderRename(_folder_path) => Task::none(),
+            Message::SearchInFolder(_folder_path) => Task::none(),
+            Message::RefreshSmartFolders => self.refresh_smart_folders(),
+            Message::SmartFoldersEvaluated(generation, matches) => {
+                if generation == self.smart_folder_generation {
+                    self.smart_folders = matches;
+                }
+                Task::none()
+            }
+            Message::ToggleSmartFolder(name) => {
+                if !self.expanded_smart_folders.remove(&name) {
+                    self.expanded_smart_folders.insert(name);
+                }
+                Task::none()
+            }
+            Message::OpenSavedSearch(_name) | Message::DeleteSavedSearch(_name) => Task::none(),
+            Message::ToggleLabelTree => {
+                self.show_label_tree = !self.show_label_tree;
+                Task::none()
+            }
+            Message::ToggleLabel(label) => {
+                let label = label.to_lowercase();
+                if !self.expanded_labels.remove(&label) {
+                    self.expanded_labels.insert(label);
+                }
+                Task::none()
+            }
+            Message::FilterByLabel(label) => {
+                let label = notebook::normalize_label(&label);
+                let is_active = self
+                    .label_filter
+                    .as_ref()
+                    .is_some_and(|filter| filter.eq_ignore_ascii_case(&label));
+                self.label_filter = if is_active || label.is_empty() {
+                    None
+                } else {
+                    Some(label)
+                };
+                Task::none()
+            }
+            Message::ClearLabelFilter => {
+                self.label_filter = None;
+                Task::none()
+            }
+            Message::ToggleNoteMark(note_path) => {
+                if !self.marked_notes.remove(&note_path) {
+                    self.marked_notes.insert(note_path);
+                }
+                Task::none()
+            }
+            Message::ClearNoteMarks => {
+                self.marked_notes.clear();
+                Task::none()
+            }
             Message::CollapseAllAndExpandToNote(note_path) => {
                 #[cfg(debug_assertions)]
                 eprintln!(
@@ -275,6 +431,7 @@ impl NoteExplorer {
     fn render_owned_nodes(
         nodes: &[NodeOwned],
         selected_note_path: Option<&str>,
+        marked_notes: &BTreeSet<String>,
         indent_level: usize,
     ) -> Column<'static, Message> {
         let mut column = Column::new().spacing(3);
@@ -307,6 +464,13 @@ impl NoteExplorer {
                     let mut folder_row = Row::new().push(folder_button);
 
                     if !folder_path.is_empty() {
+                        folder_row = folder_row.push(
+                            Button::new(Text::new("Search").size(14))
+                                .on_press(Message::SearchInFolder(folder_path.clone()))
+                                .style(button::secondary)
+                                .padding(3)
+                                .width(Length::Shrink),
+                        );
                         folder_row = folder_row.push(
                             Button::new(Text::new("Move").size(14))
                                 .on_press(Message::InitiateFolderRename(folder_path.clone()))
@@ -327,6 +491,7 @@ impl NoteExplorer {
                         column = column.push(Self::render_owned_nodes(
                             children,
                             selected_note_path,
+                            marked_notes,
                             indent_level + 1,
                         ));
                     }
@@ -346,10 +511,23 @@ impl NoteExplorer {
 
                     let note_button_text = format!("{}o {}", indent_space, name);
 
+                    let mark_path = note_path.clone();
                     column = column.push(
-                        Button::new(Text::new(note_button_text).size(16))
-                            .on_press(Message::NoteSelected(note_path.clone()))
-                            .style(button_style),
+                        Row::new()
+                            .push(
+                                Button::new(Text::new(note_button_text).size(16))
+                                    .on_press(Message::NoteSelected(note_path.clone()))
+                                    .style(button_style)
+                                    .width(Length::Fill),
+                            )
+                            .push(
+                                checkbox(marked_notes.contains(note_path))
+                                    .on_toggle(move |_| Message::ToggleNoteMark(mark_path.clone()))
+                                    .size(14),
+                            )
+                            .spacing(5)
+                            .align_y(iced::Alignment::Center)
+                            .width(Length::Fill),
                     );
                 }
                 NodeOwned::Placeholder => {
@@ -361,16 +539,235 @@ impl NoteExplorer {
         column
     }
 
+    fn render_smart_folders(&self, selected_note_path: Option<&str>) -> Column<'static, Message> {
+        let mut column = Column::new().spacing(3);
+        column = column.push(Text::new("Saved Searches").size(14));
+
+        for saved in &self.saved_searches {
+            let is_expanded = self.expanded_smart_folders.contains(&saved.name);
+            let matches = self
+                .smart_folders
+                .iter()
+                .find(|matches| matches.name == saved.name)
+                .map(|matches| &matches.note_paths);
+
+            let folder_indicator = if is_expanded { 'v' } else { '>' };
+            let folder_label = match matches {
+                Some(Ok(note_paths)) => format!("{} ({})", saved.name, note_paths.len()),
+                _ => saved.name.clone(),
+            };
+            let folder_content_row = Row::new()
+                .push(Text::new(format!("{} *", folder_indicator)))
+                .push(Text::new(folder_label).size(16))
+                .spacing(3)
+                .align_y(iced::Alignment::Center);
+
+            column = column.push(
+                Row::new()
+                    .push(
+                        Button::new(folder_content_row)
+                            .on_press(Message::ToggleSmartFolder(saved.name.clone()))
+                            .style(button::text)
+                            .width(Length::Fill),
+                    )
+                    .push(
+                        Button::new(Text::new("Search").size(14))
+                            .on_press(Message::OpenSavedSearch(saved.name.clone()))
+                            .style(button::secondary)
+                            .padding(3)
+                            .width(Length::Shrink),
+                    )
+                    .push(
+                        Button::new(Text::new("Delete").size(14))
+                            .on_press(Message::DeleteSavedSearch(saved.name.clone()))
+                            .style(button::secondary)
+                            .padding(3)
+                            .width(Length::Shrink),
+                    )
+                    .spacing(5)
+                    .align_y(iced::Alignment::Center)
+                    .width(Length::Fill),
+            );
+
+            if !is_expanded {
+                continue;
+            }
+            match matches {
+                None => column = column.push(Text::new("    Searching...").size(14)),
+                Some(Err(error)) => {
+                    column = column.push(Text::new(format!("    {}", error.ui_message())).size(14))
+                }
+                Some(Ok(note_paths)) if note_paths.is_empty() => {
+                    column = column.push(Text::new("    No matching notes.").size(14))
+                }
+                Some(Ok(note_paths)) => {
+                    for note_path in note_paths {
+                        let is_selected = selected_note_path == Some(note_path.as_str());
+                        column = column.push(
+                            Button::new(Text::new(format!("  o {}", note_path)).size(16))
+                                .on_press(Message::NoteSelected(note_path.clone()))
+                                .style(if is_selected {
+                                    button::primary
+                                } else {
+                                    button::text
+                                }),
+                        );
+                    }
+                }
+            }
+        }
+        column
+    }
+
+    fn render_label_nodes(
+        &self,
+        nodes: &[LabelTreeNode],
+        indent_level: usize,
+    ) -> Column<'static, Message> {
+        let mut column = Column::new().spacing(3);
+        let indent_space = "  ".repeat(indent_level);
+
+        for node in nodes {
+            let is_expanded = self.expanded_labels.contains(&node.path.to_lowercase());
+            let is_active = self
+                .label_filter
+                .as_ref()
+                .is_some_and(|filter| filter.eq_ignore_ascii_case(&node.path));
+
+            let info = notebook::label_info(&self.label_registry, &node.path);
+            let icon = info.and_then(|info| info.icon.as_deref()).unwrap_or("#");
+            let indicator = if node.children.is_empty() {
+                Button::new(Text::new(format!("{} {}", indent_space, icon))).style(button::text)
+            } else {
+                Button::new(Text::new(format!(
+                    "{} {}",
+                    indent_space,
+                    if is_expanded { 'v' } else { '>' }
+                )))
+                .on_press(Message::ToggleLabel(node.path.clone()))
+                .style(button::text)
+            };
+
+            let mut name = Text::new(if node.children.is_empty() {
+                format!("{} ({})", node.name, node.note_count)
+            } else {
+                format!("{} {} ({})", icon, node.name, node.note_count)
+            })
+            .size(16);
+            if !is_active
+                && let Some([red, green, blue]) =
+                    notebook::label_color(&self.label_registry, &node.path)
+            {
+                name = name.color(iced::Color::from_rgb8(red, green, blue));
+            }
+            let label_button: Element<'static, Message> = Button::new(name)
+                .on_press(Message::FilterByLabel(node.path.clone()))
+                .style(if is_active {
+                    button::primary
+                } else {
+                    button::text
+                })
+                .width(Length::Fill)
+                .into();
+            let label_button = match info.and_then(|info| info.description.clone()) {
+                Some(description) => tooltip(
+                    label_button,
+                    Container::new(Text::new(description).size(14))
+                        .padding(5)
+                        .style(container::rounded_box),
+                    tooltip::Position::Bottom,
+                )
+                .into(),
+                None => label_button,
+            };
+
+            column = column.push(
+                Row::new()
+                    .push(indicator)
+                    .push(label_button)
+                    .spacing(3)
+                    .align_y(iced::Alignment::Center)
+                    .width(Length::Fill),
+            );
+
+            if is_expanded {
+                column = column.push(self.render_label_nodes(&node.children, indent_level + 1));
+            }
+        }
+        column
+    }
+
+    fn render_label_tree(&self) -> Column<'static, Message> {
+        let mut column = Column::new().spacing(3);
+        let indicator = if self.show_label_tree { 'v' } else { '>' };
+        let mut header = Row::new()
+            .push(
+                Button::new(Text::new(format!("{} Labels", indicator)).size(14))
+                    .on_press(Message::ToggleLabelTree)
+                    .style(button::text)
+                    .width(Length::Fill),
+            )
+            .spacing(5)
+            .align_y(iced::Alignment::Center)
+            .width(Length::Fill);
+        if let Some(filter) = &self.label_filter {
+            header = header.push(
+                Button::new(Text::new(format!("Clear {}", filter)).size(14))
+                    .on_press(Message::ClearLabelFilter)
+                    .style(button::secondary)
+                    .padding(3)
+                    .width(Length::Shrink),
+            );
+        }
+        column = column.push(header);
+
+        if self.show_label_tree {
+            column =
+                column.push(self.render_label_nodes(&notebook::build_label_tree(&self.notes), 0));
+        }
+        column
+    }
+
     pub fn view(&self, selected_note_path: Option<&String>) -> Element<'_, Message> {
         let mut column = Column::new().spacing(5).width(Length::Fill);
 
+        if self.notes.iter().any(|note| !note.labels.is_empty()) || self.label_filter.is_some() {
+            column = column.push(self.render_label_tree());
+        }
+
+        if !self.saved_searches.is_empty() {
+            column = column
+                .push(self.render_smart_folders(selected_note_path.map(|path| path.as_str())));
+        }
+
         if self.notebook_path.is_empty() || self.notes.is_empty() {
             column = column.push(Text::new("No notes found."));
+        } else if self.label_filter.is_some() {
+            // Filtered notes are few; show them all without folding folders away.
+            let notes = self.visible_notes();
+            let expanded_folders: HashMap<String, bool> = self
+                .expanded_folders
+                .keys()
+                .map(|folder_path| (folder_path.clone(), true))
+                .collect();
+            let root_tree = NoteExplorer::build_owned_tree(&notes, &expanded_folders);
+            let tree_view = Self::render_owned_nodes(
+                &root_tree,
+                selected_note_path.map(|path| path.as_str()),
+                &self.marked_notes,
+                0,
+            );
+            column = column.push(if notes.is_empty() {
+                Column::new().push(Text::new("No notes with this label."))
+            } else {
+                tree_view
+            });
         } else {
             let root_tree = NoteExplorer::build_owned_tree(&self.notes, &self.expanded_folders);
             let tree_view = Self::render_owned_nodes(
                 &root_tree,
                 selected_note_path.map(|path| path.as_str()),
+                &self.marked_notes,
                 0,
             );
             column = column.push(tree_view);
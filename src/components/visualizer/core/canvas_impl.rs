@@ -3,6 +3,8 @@ use iced::{Color, Font, Pixels, Point, Rectangle, Theme, mouse};
 use std::cmp::Ordering;
 use std::time::Instant;
 
+use crate::notebook;
+
 use super::math::{
     color_from_seed, ease_in_out_cubic, finalize_center_transition, hash_to_unit_f32, lerp,
     lerp_3d, lerp_angle, rotate_3d, rotated_point_for_note_path, truncate_label, wrap_angle,
@@ -413,7 +415,12 @@ impl canvas::Program<Message> for GraphProgram {
                 .first()
                 .map(String::as_str)
                 .unwrap_or("unlabeled");
-            let mut node_color = color_from_seed(color_anchor, 0.63, 0.86);
+            let mut node_color = node
+                .labels
+                .iter()
+                .find_map(|label| notebook::label_color(&self.label_registry, label))
+                .map(|[red, green, blue]| Color::from_rgb8(red, green, blue))
+                .unwrap_or_else(|| color_from_seed(color_anchor, 0.63, 0.86));
 
             if node.labels.is_empty() {
                 node_color = Color::from_rgb(0.68, 0.73, 0.82);
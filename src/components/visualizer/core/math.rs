@@ -4,14 +4,22 @@ use std::collections::hash_map::DefaultHasher;
 use std::f32::consts::PI;
 use std::hash::{Hash, Hasher};
 
+use crate::notebook;
+
 use super::{GraphCanvasState, GraphNode, MAX_LABEL_LENGTH};
 
+/// Normalizes a note's labels and adds the labels they are nested below, so notes under the
+/// same parent label are linked.
 pub(super) fn normalize_labels(raw_labels: &[String]) -> Vec<String> {
     let mut labels: Vec<String> = raw_labels
         .iter()
-        .map(|label| label.trim())
+        .map(|label| notebook::normalize_label(label))
         .filter(|label| !label.is_empty())
-        .map(ToString::to_string)
+        .flat_map(|label| {
+            notebook::label_with_ancestors(&label)
+                .map(ToString::to_string)
+                .collect::<Vec<String>>()
+        })
         .collect();
     labels.sort();
     labels.dedup();
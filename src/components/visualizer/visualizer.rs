@@ -1,4 +1,4 @@
-use crate::notebook::NoteMetadata;
+use crate::notebook::{LabelRegistry, NoteMetadata};
 use iced::widget::{Column, Container, Text, canvas, container};
 use iced::{Color, Element, Length, Point, Theme, task::Task};
 #[cfg(test)]
@@ -63,6 +63,7 @@ struct GraphProgram {
     nodes: Vec<GraphNode>,
     edges: Vec<GraphEdge>,
     max_shared_labels_per_edge: usize,
+    label_registry: LabelRegistry,
     selected_note_path: Option<String>,
     focus_yaw: f32,
     focus_pitch: f32,
@@ -103,6 +104,8 @@ struct GraphCanvasState {
 pub struct Visualizer {
     note_count: usize,
     graph_cache: GraphCache,
+    /// Label colors shown instead of the hash-derived node colors.
+    label_registry: LabelRegistry,
     focus_target_note: Option<String>,
     focus_yaw: f32,
     focus_pitch: f32,
@@ -115,6 +118,7 @@ impl Visualizer {
         Self {
             note_count: 0,
             graph_cache: GraphCache::default(),
+            label_registry: LabelRegistry::new(),
             focus_target_note: None,
             focus_yaw: DEFAULT_CAMERA_YAW,
             focus_pitch: DEFAULT_CAMERA_PITCH,
@@ -145,6 +149,10 @@ impl Visualizer {
         }
     }
 
+    pub fn sync_label_registry(&mut self, registry: &LabelRegistry) {
+        self.label_registry = registry.clone();
+    }
+
     pub fn view(&self) -> Element<'_, Message, Theme> {
         let mut content = Column::new()
             .spacing(12)
@@ -193,6 +201,7 @@ impl Visualizer {
             nodes: self.graph_cache.nodes.clone(),
             edges: self.graph_cache.edges.clone(),
             max_shared_labels_per_edge: self.graph_cache.max_shared_labels_per_edge,
+            label_registry: self.label_registry.clone(),
             selected_note_path: self.focus_target_note.clone(),
             focus_yaw: self.focus_yaw,
             focus_pitch: self.focus_pitch,
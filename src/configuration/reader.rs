@@ -1,4 +1,5 @@
 use crate::json::reader::read_json_file;
+use crate::notebook::{DEFAULT_JOURNAL_PATH_PATTERN, journal_note_path, today_local_date};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::env;
@@ -15,6 +16,10 @@ pub struct Configuration {
     pub scale: f32,
     pub config_path: String,
     pub version: String,
+    pub link_opener: String,
+    pub trash_retention_days: u32,
+    pub journal_path_pattern: String,
+    pub journal_template: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -24,8 +29,18 @@ struct RawConfiguration {
     notebook_path: Option<String>,
     #[serde(default)]
     scale: Option<f32>,
+    #[serde(default)]
+    link_opener: Option<String>,
+    #[serde(default)]
+    trash_retention_days: Option<u32>,
+    #[serde(default)]
+    journal_path_pattern: Option<String>,
+    #[serde(default)]
+    journal_template: Option<String>,
 }
 
+const DEFAULT_TRASH_RETENTION_DAYS: u32 = 30;
+
 #[cfg(test)]
 const FAIL_CONFIG_ATOMIC_RENAME_MARKER: &str = ".cognate_fail_config_atomic_rename";
 
@@ -36,6 +51,16 @@ fn config_directory(config_path: &Path) -> &Path {
         .unwrap_or_else(|| Path::new("."))
 }
 
+fn default_link_opener() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(target_os = "windows") {
+        "explorer"
+    } else {
+        "xdg-open"
+    }
+}
+
 fn invalid_config(message: impl Into<String>) -> Box<dyn std::error::Error> {
     Box::new(std::io::Error::new(ErrorKind::InvalidData, message.into()))
 }
@@ -114,12 +139,41 @@ pub fn read_configuration(file_path: &str) -> Result<Configuration, Box<dyn std:
         }
     };
 
+    let link_opener = raw
+        .link_opener
+        .map(|opener| opener.trim().to_string())
+        .filter(|opener| !opener.is_empty())
+        .unwrap_or_else(|| default_link_opener().to_string());
+
+    let journal_path_pattern = raw
+        .journal_path_pattern
+        .map(|pattern| pattern.trim().to_string())
+        .filter(|pattern| !pattern.is_empty())
+        .unwrap_or_else(|| DEFAULT_JOURNAL_PATH_PATTERN.to_string());
+    if let Err(error) = journal_note_path(&journal_path_pattern, today_local_date()) {
+        return Err(invalid_config(format!(
+            "Journal path pattern in config.json is invalid: {}",
+            error
+        )));
+    }
+
+    let journal_template = raw
+        .journal_template
+        .map(|template| template.trim().to_string())
+        .filter(|template| !template.is_empty());
+
     Ok(Configuration {
         theme: raw.theme,
         notebook_path: raw.notebook_path.unwrap_or_default(),
         scale,
         config_path: file_path.to_string(),
         version,
+        link_opener,
+        trash_retention_days: raw
+            .trash_retention_days
+            .unwrap_or(DEFAULT_TRASH_RETENTION_DAYS),
+        journal_path_pattern,
+        journal_template,
     })
 }
 
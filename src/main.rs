@@ -19,6 +19,9 @@ use std::env;
 use std::process::exit;
 
 pub fn main() -> iced::Result {
+    // Must run before any thread is spawned.
+    notebook::init_local_utc_offset();
+
     let config_path_env_var = "COGNATE_CONFIG_PATH";
     let default_config_path = "./config.json";
 
@@ -1,7 +1,11 @@
 //! Notebook domain layer.
 //!
 //! This module defines note metadata and re-exports notebook operations for
-//! create/delete/move/search and metadata/content persistence.
+//! create/delete/move/search, nested labels, saved searches, the trash, note history, filesystem
+//! watching, integrity checks, and metadata/content persistence.
+
+use std::collections::BTreeMap;
+use std::time::SystemTime;
 
 use serde::{Deserialize, Serialize};
 
@@ -10,17 +14,43 @@ const STAGED_DELETE_CLEANUP_GRACE_NANOS: u128 = 5 * 60 * 1_000_000_000;
 
 #[path = "notebook/error.rs"]
 mod error;
+#[path = "notebook/front_matter.rs"]
+mod front_matter;
+#[path = "notebook/fsck.rs"]
+mod fsck;
+#[path = "notebook/fuzzy.rs"]
+mod fuzzy;
+#[path = "notebook/history.rs"]
+mod history;
+#[path = "notebook/journal.rs"]
+mod journal;
+#[path = "notebook/labels.rs"]
+mod labels;
+#[path = "notebook/links.rs"]
+mod links;
 #[path = "notebook/operations.rs"]
 mod operations;
+#[path = "notebook/query.rs"]
+mod query;
 #[path = "notebook/relative_path.rs"]
 mod relative_path;
+#[path = "notebook/replace.rs"]
+mod replace;
+#[path = "notebook/saved_searches.rs"]
+mod saved_searches;
 #[path = "notebook/search.rs"]
 mod search;
 #[path = "notebook/storage.rs"]
 mod storage;
+#[path = "notebook/templates.rs"]
+mod templates;
+#[path = "notebook/trash.rs"]
+mod trash;
+#[path = "notebook/watcher.rs"]
+mod watcher;
 
 /// Metadata persisted for a single note directory.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct NoteMetadata {
     /// Note directory path relative to the notebook root.
     pub rel_path: String,
@@ -30,13 +60,205 @@ pub struct NoteMetadata {
     /// Last update timestamp in RFC3339 format.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub last_updated: Option<String>,
+    /// `title` from the note's front matter.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// `aliases` from the note's front matter.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// Other front matter keys, with lists joined by commas.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub properties: BTreeMap<String, String>,
 }
 
 /// Root metadata object stored in `metadata.json`.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct NotebookMetadata {
+    /// Version of the metadata layout; files without one are version 0.
+    pub schema_version: u32,
     /// All known notes in the notebook.
     pub notes: Vec<NoteMetadata>,
+    /// Named queries shown as smart folders in the note explorer.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub saved_searches: Vec<SavedSearch>,
+    /// Notebook-wide preferences.
+    #[serde(default, skip_serializing_if = "NotebookSettings::is_default")]
+    pub settings: NotebookSettings,
+    /// Colors, descriptions and icons of labels.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub label_registry: LabelRegistry,
+}
+
+/// How a label is shown, stored in the label registry.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LabelInfo {
+    /// `#rrggbb` color of the label's chips and graph nodes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// What the label is for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Short symbol shown before the label.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+}
+
+/// Label registry keyed by the lowercase normalized label.
+pub type LabelRegistry = BTreeMap<String, LabelInfo>;
+
+/// Notebook-wide preferences stored in `metadata.json`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotebookSettings {
+    /// Template preselected in the new-note dialog, by name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_template: Option<String>,
+}
+
+impl NotebookSettings {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Note created by [`create_note_from_template`] or opened by [`open_journal_note`].
+#[derive(Debug, Clone)]
+pub struct CreatedNote {
+    pub metadata: NoteMetadata,
+    /// 1-based line and byte column where the template placed the cursor.
+    pub cursor: Option<(usize, usize)>,
+}
+
+/// Item moved by [`move_note`] and the notes whose links were rewritten to follow it.
+#[derive(Debug, Clone)]
+pub struct MovedNote {
+    /// New path of the moved note or folder.
+    pub rel_path: String,
+    /// Rewritten notes, at their paths after the move.
+    pub rewritten_notes: Vec<RewrittenLinksNote>,
+}
+
+/// Note whose links a move rewrote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RewrittenLinksNote {
+    pub rel_path: String,
+    /// File content before the rewrite.
+    pub previous_content: String,
+    pub content: String,
+}
+
+/// Label in the label tree, with the labels nested below it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabelTreeNode {
+    /// Last level of the label.
+    pub name: String,
+    /// Full label, levels joined by `/`.
+    pub path: String,
+    /// Number of notes with this label or a label nested below it.
+    pub note_count: usize,
+    /// Labels one level below, sorted by name.
+    pub children: Vec<LabelTreeNode>,
+}
+
+/// Notebook-wide change to a label and the labels nested below it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LabelEditKind {
+    /// Gives the label a name no note uses yet.
+    Rename,
+    /// Moves the label's notes onto another label.
+    Merge,
+    /// Removes the label from every note.
+    Delete,
+}
+
+/// Change a bulk label edit makes to each marked note.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulkLabelAction {
+    Add,
+    Remove,
+}
+
+/// Note whose labels a label edit changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabelEditNote {
+    /// Note path relative to the notebook root.
+    pub rel_path: String,
+    /// Labels the preview was made from.
+    pub labels: Vec<String>,
+    /// Labels after the edit.
+    pub new_labels: Vec<String>,
+}
+
+/// Every change a notebook-wide label edit would make.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabelEditPreview {
+    pub kind: LabelEditKind,
+    /// Normalized label being edited.
+    pub label: String,
+    /// Normalized label it is renamed or merged into; empty for a delete.
+    pub target: String,
+    /// Affected notes, sorted by path.
+    pub notes: Vec<LabelEditNote>,
+}
+
+/// Named search stored in `metadata.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedSearch {
+    /// Name shown in the note explorer; unique within the notebook.
+    pub name: String,
+    /// Search box text.
+    pub query: String,
+    /// How `query` is interpreted.
+    #[serde(default)]
+    pub mode: SearchMode,
+    /// Folder the search is limited to, without a trailing slash.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+/// Notes currently matching a saved search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedSearchMatches {
+    /// Name of the saved search.
+    pub name: String,
+    /// Matching note paths in path order, or why the search could not run.
+    pub note_paths: Result<Vec<String>, NotebookError>,
+}
+
+/// How the search box interprets its query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMode {
+    /// Words, phrases, filters and boolean operators matched against note text.
+    #[default]
+    Text,
+    /// Typo-tolerant matching of note paths, labels and headings.
+    Fuzzy,
+    /// Regular expression applied to each line of note content.
+    Regex,
+}
+
+impl SearchMode {
+    pub const ALL: [SearchMode; 3] = [SearchMode::Text, SearchMode::Fuzzy, SearchMode::Regex];
+}
+
+/// Byte range `start..end` of matched text within a line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchMatchRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Line of note content that matched a search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchLineMatch {
+    /// 1-based line number within `note.md`.
+    pub line_number: usize,
+    /// Line text, shortened for display around the first match.
+    pub text: String,
+    /// Columns of every match in the full line, in order.
+    pub columns: Vec<SearchMatchRange>,
+    /// Matched parts of `text`, for highlighting.
+    pub highlights: Vec<SearchMatchRange>,
 }
 
 /// Search result surface returned to the editor search UI.
@@ -46,13 +268,250 @@ pub struct NoteSearchResult {
     pub rel_path: String,
     /// User-facing snippet that explains the match.
     pub snippet: String,
+    /// Every matching content line; empty when only the path, labels or headings matched.
+    pub line_matches: Vec<SearchLineMatch>,
+}
+
+impl NoteSearchResult {
+    /// Every match location in the note as a line number and column range, in order.
+    ///
+    /// A line that matched without any matched text, such as an empty regex match, yields a
+    /// location at the start of the line.
+    pub fn match_locations(&self) -> impl Iterator<Item = (usize, SearchMatchRange)> + '_ {
+        self.line_matches.iter().flat_map(|line_match| {
+            let columns = if line_match.columns.is_empty() {
+                &[SearchMatchRange { start: 0, end: 0 }][..]
+            } else {
+                line_match.columns.as_slice()
+            };
+            columns
+                .iter()
+                .map(move |range| (line_match.line_number, *range))
+        })
+    }
+}
+
+/// Note that links to another note through a `[[wiki]]` reference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteBacklink {
+    /// Path of the note containing the link.
+    pub rel_path: String,
+    /// Line of the linking note that contains the reference.
+    pub context: String,
+}
+
+/// Note kept in `.cognate_trash` after deletion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashEntry {
+    /// Directory name of the entry inside the trash.
+    pub id: String,
+    /// Note path before deletion.
+    pub rel_path: String,
+    /// Labels the note had when it was deleted.
+    pub labels: Vec<String>,
+    /// Last update timestamp the note had when it was deleted.
+    pub last_updated: Option<String>,
+    /// Deletion timestamp in RFC3339 format.
+    pub deleted_at: String,
+}
+
+/// Snapshot of a note's content kept in the note's history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteVersion {
+    /// Content hash identifying the snapshot blob.
+    pub id: String,
+    /// Timestamp in RFC3339 format of when this content was last saved.
+    pub saved_at: String,
+}
+
+/// How a line differs between two versions of a note.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineDiffKind {
+    Unchanged,
+    Added,
+    Removed,
+}
+
+/// One line of a line diff between two versions of a note.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineDiff {
+    /// Whether the line was kept, added or removed.
+    pub kind: LineDiffKind,
+    /// Line text without its trailing newline.
+    pub text: String,
+}
+
+/// Result of merging two edited versions of a note with their common base.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeResult {
+    /// Merged text; overlapping edits are wrapped in conflict markers.
+    pub text: String,
+    /// Number of overlapping edits that could not be merged automatically.
+    pub conflict_count: usize,
+}
+
+/// A note's `note.md` content as read from or written to disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteDiskState {
+    /// File content.
+    pub content: String,
+    /// File modification time, when the filesystem reports one.
+    pub modified_time: Option<SystemTime>,
+}
+
+/// Outcome of a save that must not overwrite changes made outside Cognate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NoteSaveOutcome {
+    /// The content was written; holds the new on-disk state.
+    Saved(NoteDiskState),
+    /// The file changed on disk since it was loaded; holds the current on-disk state.
+    Conflict(NoteDiskState),
+}
+
+/// Kind of inconsistency found by a notebook check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FsckFindingKind {
+    /// Metadata entry whose note directory or `note.md` is gone.
+    DanglingEntry,
+    /// Note directory with a `note.md` that is missing from metadata.
+    UntrackedNote,
+    /// Temp file or staged delete left behind by an interrupted operation.
+    StaleTempFile,
+    /// File in a note's `images/` directory that the note does not reference.
+    UnreferencedImage,
+}
+
+/// One inconsistency found by a notebook check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FsckFinding {
+    /// What is wrong and which repair applies.
+    pub kind: FsckFindingKind,
+    /// Affected path relative to the notebook root.
+    pub path: String,
+    /// User-facing explanation of the finding.
+    pub detail: String,
+}
+
+/// One occurrence of the searched text in a find and replace preview.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplaceHit {
+    /// Identifier of the hit, unique within its preview.
+    pub id: usize,
+    /// Byte offset where the hit starts in its line.
+    pub start: usize,
+    /// Byte offset just past the hit in its line.
+    pub end: usize,
+    /// Whether the hit will be replaced.
+    pub selected: bool,
+}
+
+/// Line of a note with at least one hit in a find and replace preview.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplaceLinePreview {
+    /// 1-based line number within `note.md`.
+    pub line_number: usize,
+    /// Line text without its line ending.
+    pub text: String,
+    /// Hits in the line, in order.
+    pub hits: Vec<ReplaceHit>,
+}
+
+/// Note affected by a find and replace preview.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteReplacePreview {
+    /// Note path relative to the notebook root.
+    pub rel_path: String,
+    /// Content the hits were found in.
+    pub original_content: String,
+    /// Whether `original_content` is the editor buffer of the open note rather than its file.
+    pub is_open_note: bool,
+    /// For the open note, the file state its editor buffer was last loaded from or saved as.
+    pub disk_base: Option<NoteDiskState>,
+    /// Lines with hits, in order.
+    pub lines: Vec<ReplaceLinePreview>,
+}
+
+/// Every change a notebook-wide find and replace would make.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplacePreview {
+    /// Text that was searched for.
+    pub find: String,
+    /// Text each selected hit is replaced with.
+    pub replacement: String,
+    /// Notes with hits, sorted by path.
+    pub notes: Vec<NoteReplacePreview>,
+}
+
+impl ReplacePreview {
+    /// Selects or deselects the hit with the given identifier.
+    pub fn toggle_hit(&mut self, hit_id: usize) {
+        if let Some(hit) = self
+            .notes
+            .iter_mut()
+            .flat_map(|note| note.lines.iter_mut())
+            .flat_map(|line| line.hits.iter_mut())
+            .find(|hit| hit.id == hit_id)
+        {
+            hit.selected = !hit.selected;
+        }
+    }
+}
+
+/// External changes reported by the notebook watcher.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NotebookChanges {
+    /// Whether `metadata.json` was changed by another program.
+    pub metadata_changed: bool,
+    /// Note directories whose `note.md` was added, modified or removed, or folders that were
+    /// renamed or removed.
+    pub note_paths: Vec<String>,
+}
+
+impl NotebookChanges {
+    pub fn is_empty(&self) -> bool {
+        !self.metadata_changed && self.note_paths.is_empty()
+    }
 }
 
 pub use error::{NotebookError, NotebookErrorKind};
-pub use operations::{create_new_note, delete_note, move_note};
+pub use front_matter::{
+    apply_front_matter, front_matter_lists_labels, set_front_matter_labels,
+    sync_front_matter_from_disk,
+};
+pub use fsck::{check_notebook, repair_fsck_finding};
+pub use history::{diff_lines, load_note_history, load_note_version, merge_three_way};
+pub use journal::{
+    DEFAULT_JOURNAL_PATH_PATTERN, adjacent_journal_note_path, journal_note_date, journal_note_path,
+    open_journal_note, today_local_date,
+};
+pub use labels::{
+    apply_bulk_label_edit, apply_label_edit, apply_label_edit_to_registry, build_label_tree,
+    label_color, label_info, label_matches, label_with_ancestors, normalize_label,
+    parse_label_color, preview_bulk_label_edit, preview_label_edit, save_label_info,
+    suggest_labels,
+};
+pub use links::{
+    clear_link_index_for_notebook, is_external_link_destination, load_backlinks,
+    normalize_link_target, parse_wiki_links, resolve_link_target, resolve_relative_link_path,
+};
+#[cfg(test)]
+pub use operations::create_new_note;
+pub use operations::{delete_note, move_note};
 pub use relative_path::NotebookRelativePath;
-pub use search::{SearchNote, clear_search_index_for_notebook, search_notes_with_snapshot};
+pub use replace::{apply_replace, preview_replace};
+pub use saved_searches::{delete_saved_search, evaluate_saved_searches, save_search};
+pub use search::{
+    SearchNote, clear_search_index_for_notebook, is_in_search_scope, preload_search_index,
+    search_notes_with_snapshot,
+};
+#[cfg(test)]
+pub(crate) use storage::{METADATA_SCHEMA_VERSION, to_local_datetime};
 pub use storage::{
-    MetadataLoadResult, current_timestamp_rfc3339, load_notes_metadata, save_metadata,
-    save_note_content, save_note_content_sync,
+    MetadataLoadResult, current_timestamp_rfc3339, init_local_utc_offset, load_note_content_sync,
+    load_notes_metadata, save_metadata, save_note_content_checked, save_note_content_checked_sync,
 };
+#[cfg(test)]
+pub use storage::{save_note_content, save_note_content_sync};
+pub use templates::{create_note_from_template, list_templates, set_default_template};
+pub use trash::{available_restore_path, load_trash, purge_trashed_note, restore_trashed_note};
+pub use watcher::{is_note_content_persisted, watch_notebook};
@@ -0,0 +1,445 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+use super::NoteMetadata;
+use super::search::note_file_modified_time;
+
+const FRONT_MATTER_FENCE: &str = "---";
+/// Keys read into their own `NoteMetadata` fields; every other key is a custom property.
+const LABELS_KEY: &str = "labels";
+/// Other tools keep labels under `tags`; it is read when a note has no `labels` key.
+const TAGS_KEY: &str = "tags";
+const TITLE_KEY: &str = "title";
+const ALIASES_KEY: &str = "aliases";
+
+/// Byte ranges of a leading front matter block in `content`.
+struct FrontMatterRange {
+    /// The block without its fences.
+    block: Range<usize>,
+    /// Where the text after the closing fence starts.
+    body_start: usize,
+}
+
+fn front_matter_range(content: &str) -> Option<FrontMatterRange> {
+    let first_line_end = content.find('\n')?;
+    if content[..first_line_end].trim_end() != FRONT_MATTER_FENCE {
+        return None;
+    }
+
+    let block_start = first_line_end + 1;
+    let mut line_start = block_start;
+    for line in content[block_start..].split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        if trimmed == FRONT_MATTER_FENCE || trimmed == "..." {
+            return Some(FrontMatterRange {
+                block: block_start..line_start,
+                body_start: line_start + line.len(),
+            });
+        }
+        line_start += line.len();
+    }
+    None
+}
+
+/// Splits a leading `---` fenced YAML block from `content`.
+///
+/// Returns the block without its fences and the text after it; the block ends at a line
+/// holding `---` or `...`. Content without a complete block is returned as the body.
+pub(super) fn split_front_matter(content: &str) -> (Option<&str>, &str) {
+    match front_matter_range(content) {
+        Some(range) => (Some(&content[range.block]), &content[range.body_start..]),
+        None => (None, content),
+    }
+}
+
+/// A top-level `key: value` of a front matter block and the indented or `- item` lines
+/// under it.
+struct FrontMatterEntry<'a> {
+    key: &'a str,
+    value: &'a str,
+    items: Vec<&'a str>,
+    /// Bytes of the block taken by the entry, including its nested lines.
+    span: Range<usize>,
+}
+
+fn front_matter_entries(front_matter: &str) -> Vec<FrontMatterEntry<'_>> {
+    let mut entries: Vec<FrontMatterEntry<'_>> = Vec::new();
+    let mut line_start = 0;
+    for line in front_matter.split_inclusive('\n') {
+        let line_end = line_start + line.len();
+        let content = line.trim_end();
+        let is_nested = content.starts_with([' ', '\t', '-']);
+
+        if is_nested {
+            if let Some(entry) = entries.last_mut() {
+                if let Some(item) = content.trim_start().strip_prefix('-') {
+                    entry.items.push(item);
+                }
+                entry.span.end = line_end;
+            }
+        } else if !content.is_empty()
+            && !content.starts_with('#')
+            && let Some((key, value)) = content.split_once(':')
+        {
+            entries.push(FrontMatterEntry {
+                key: key.trim().trim_matches(['"', '\'']),
+                value: value.trim(),
+                items: Vec::new(),
+                span: line_start..line_end,
+            });
+        }
+        line_start = line_end;
+    }
+    entries
+}
+
+/// Removes the quotes around a YAML scalar; single-quoted scalars write `'` as `''`.
+fn unquote(value: &str) -> Cow<'_, str> {
+    let value = value.trim();
+    if let Some(inner) = value
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+    {
+        return Cow::Borrowed(inner);
+    }
+    if let Some(inner) = value
+        .strip_prefix('\'')
+        .and_then(|rest| rest.strip_suffix('\''))
+    {
+        return Cow::Owned(inner.replace("''", "'"));
+    }
+    Cow::Borrowed(value)
+}
+
+/// Splits a flow list body at commas outside quotes.
+fn split_flow_items(value: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut quote = None;
+    let mut item_start = 0;
+    for (index, ch) in value.char_indices() {
+        match (quote, ch) {
+            (None, '"' | '\'') => quote = Some(ch),
+            (Some(open), _) if ch == open => quote = None,
+            (None, ',') => {
+                items.push(&value[item_start..index]);
+                item_start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(&value[item_start..]);
+    items
+}
+
+fn entry_list(entry: &FrontMatterEntry<'_>) -> Vec<String> {
+    let items: Vec<&str> = if entry.value.is_empty() {
+        entry.items.clone()
+    } else {
+        let value = entry
+            .value
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+            .unwrap_or(entry.value);
+        split_flow_items(value)
+    };
+    items
+        .into_iter()
+        .map(|item| unquote(item).to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// Reads an entry as one string; nested list items are joined with commas.
+fn entry_text(entry: &FrontMatterEntry<'_>) -> String {
+    if entry.value.is_empty() {
+        entry_list(entry).join(", ")
+    } else {
+        unquote(entry.value).to_string()
+    }
+}
+
+/// Reads `key` from a front matter block as a list of strings.
+///
+/// Accepts a flow list (`key: [a, b]`), a comma separated scalar (`key: a, b`) and a block
+/// list of `- item` lines. Returns `None` when the key is missing.
+pub(super) fn front_matter_list(front_matter: &str, key: &str) -> Option<Vec<String>> {
+    front_matter_entries(front_matter)
+        .iter()
+        .find(|entry| entry.key == key)
+        .map(entry_list)
+}
+
+fn labels_entry<'a, 'b>(entries: &'b [FrontMatterEntry<'a>]) -> Option<&'b FrontMatterEntry<'a>> {
+    entries
+        .iter()
+        .find(|entry| entry.key == LABELS_KEY)
+        .or_else(|| entries.iter().find(|entry| entry.key == TAGS_KEY))
+}
+
+/// Whether the front matter of `content` lists labels, so they override the stored ones.
+pub fn front_matter_lists_labels(content: &str) -> bool {
+    split_front_matter(content)
+        .0
+        .is_some_and(|front_matter| labels_entry(&front_matter_entries(front_matter)).is_some())
+}
+
+/// The `NoteMetadata` fields a note's front matter sets.
+#[derive(Clone, Default)]
+struct FrontMatterFields {
+    /// `None` when the front matter does not list labels, so the stored ones are kept.
+    labels: Option<Vec<String>>,
+    title: Option<String>,
+    aliases: Vec<String>,
+    properties: BTreeMap<String, String>,
+}
+
+fn read_front_matter_fields(content: &str) -> FrontMatterFields {
+    let entries = split_front_matter(content)
+        .0
+        .map(front_matter_entries)
+        .unwrap_or_default();
+    let labels_key = labels_entry(&entries).map(|entry| entry.key);
+
+    let mut fields = FrontMatterFields::default();
+    for entry in &entries {
+        if Some(entry.key) == labels_key {
+            let mut unique = Vec::new();
+            for label in entry_list(entry) {
+                if !unique.contains(&label) {
+                    unique.push(label);
+                }
+            }
+            fields.labels = Some(unique);
+        } else if entry.key == TITLE_KEY {
+            fields.title = Some(entry_text(entry)).filter(|title| !title.is_empty());
+        } else if entry.key == ALIASES_KEY {
+            fields.aliases = entry_list(entry);
+        } else {
+            fields
+                .properties
+                .insert(entry.key.to_string(), entry_text(entry));
+        }
+    }
+    fields
+}
+
+fn apply_front_matter_fields(note: &mut NoteMetadata, fields: &FrontMatterFields) -> bool {
+    let mut changed = false;
+    if let Some(labels) = &fields.labels
+        && note.labels != *labels
+    {
+        note.labels = labels.clone();
+        changed = true;
+    }
+    if note.title != fields.title {
+        note.title = fields.title.clone();
+        changed = true;
+    }
+    if note.aliases != fields.aliases {
+        note.aliases = fields.aliases.clone();
+        changed = true;
+    }
+    if note.properties != fields.properties {
+        note.properties = fields.properties.clone();
+        changed = true;
+    }
+    changed
+}
+
+/// Updates `note` from the front matter of its Markdown `content`.
+///
+/// Labels are replaced only when the front matter lists them; the title, aliases and custom
+/// properties always follow it. Returns whether anything changed.
+pub fn apply_front_matter(note: &mut NoteMetadata, content: &str) -> bool {
+    apply_front_matter_fields(note, &read_front_matter_fields(content))
+}
+
+/// Front matter last read from each note file, keyed by full path, with the file's
+/// modification time at that read.
+fn synced_front_matter() -> &'static Mutex<HashMap<PathBuf, (SystemTime, FrontMatterFields)>> {
+    static SYNCED_FRONT_MATTER: OnceLock<Mutex<HashMap<PathBuf, (SystemTime, FrontMatterFields)>>> =
+        OnceLock::new();
+    SYNCED_FRONT_MATTER.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Re-reads the front matter of `notes` from their `note.md` files.
+///
+/// Files whose modification time has not changed since they were last read are not read
+/// again; the front matter read then is applied instead. Notes whose file cannot be read are
+/// left as they are. Returns whether any note changed.
+pub fn sync_front_matter_from_disk<'a>(
+    notebook_path: &str,
+    notes: impl IntoIterator<Item = &'a mut NoteMetadata>,
+) -> bool {
+    let mut synced = match synced_front_matter().lock() {
+        Ok(synced) => synced,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+
+    let mut changed = false;
+    for note in notes {
+        let note_file = Path::new(notebook_path)
+            .join(&note.rel_path)
+            .join("note.md");
+        let modified_time = note_file_modified_time(&note_file);
+        if let Some(modified_time) = modified_time
+            && let Some((synced_time, fields)) = synced.get(&note_file)
+            && *synced_time == modified_time
+        {
+            changed |= apply_front_matter_fields(note, fields);
+            continue;
+        }
+
+        let Ok(content) = std::fs::read_to_string(&note_file) else {
+            continue;
+        };
+        let fields = read_front_matter_fields(&content);
+        changed |= apply_front_matter_fields(note, &fields);
+        match modified_time {
+            Some(modified_time) => synced.insert(note_file, (modified_time, fields)),
+            None => synced.remove(&note_file),
+        };
+    }
+    changed
+}
+
+fn quote_list_item(item: &str) -> String {
+    let is_plain = !item.is_empty()
+        && item.trim() == item
+        && !item.contains([',', '[', ']', '{', '}', ':', '#', '"', '\'', '\n'])
+        && !item.starts_with(['-', '&', '*', '!', '|', '>', '%', '@', '`']);
+    if is_plain {
+        item.to_string()
+    } else {
+        format!("'{}'", item.replace('\'', "''"))
+    }
+}
+
+/// Writes `labels` into the front matter of `content`.
+///
+/// Replaces the existing `labels` (or `tags`) entry, adds one when the note has none and
+/// starts a front matter block when the note has no block yet. Removing every label drops the
+/// entry, and a block left empty is dropped with it.
+pub fn set_front_matter_labels(content: &str, labels: &[String]) -> String {
+    let labels_line = |key: &str| {
+        let items: Vec<String> = labels.iter().map(|label| quote_list_item(label)).collect();
+        format!("{}: [{}]\n", key, items.join(", "))
+    };
+
+    let Some(range) = front_matter_range(content) else {
+        if labels.is_empty() {
+            return content.to_string();
+        }
+        return format!(
+            "{fence}\n{}{fence}\n{}",
+            labels_line(LABELS_KEY),
+            content,
+            fence = FRONT_MATTER_FENCE
+        );
+    };
+
+    let block = &content[range.block.clone()];
+    let entries = front_matter_entries(block);
+    let mut new_block = block.to_string();
+    match labels_entry(&entries) {
+        Some(entry) if labels.is_empty() => new_block.replace_range(entry.span.clone(), ""),
+        Some(entry) => new_block.replace_range(entry.span.clone(), &labels_line(entry.key)),
+        None if labels.is_empty() => {}
+        None => {
+            if !new_block.is_empty() && !new_block.ends_with('\n') {
+                new_block.push('\n');
+            }
+            new_block.push_str(&labels_line(LABELS_KEY));
+        }
+    }
+
+    if new_block.trim().is_empty() {
+        return content[range.body_start..].to_string();
+    }
+    format!(
+        "{}{}{}",
+        &content[..range.block.start],
+        new_block,
+        &content[range.block.end..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_front_matter_requires_both_fences() {
+        assert_eq!(
+            split_front_matter("---\nlabels: [a]\n---\n# Body\n"),
+            (Some("labels: [a]\n"), "# Body\n")
+        );
+        assert_eq!(
+            split_front_matter("---\r\ntitle: x\r\n...\r\nBody"),
+            (Some("title: x\r\n"), "Body")
+        );
+        assert_eq!(split_front_matter("---\nno end"), (None, "---\nno end"));
+        assert_eq!(
+            split_front_matter("# Title\n---\n"),
+            (None, "# Title\n---\n")
+        );
+    }
+
+    #[test]
+    fn front_matter_list_reads_flow_scalar_and_block_lists() {
+        assert_eq!(
+            front_matter_list("labels: [work, \"on hold\"]\n", "labels"),
+            Some(vec!["work".to_string(), "on hold".to_string()])
+        );
+        assert_eq!(
+            front_matter_list("title: x\nlabels: a, b\n", "labels"),
+            Some(vec!["a".to_string(), "b".to_string()])
+        );
+        assert_eq!(
+            front_matter_list("labels:\n  - a\n  - 'b'\ntitle: x\n", "labels"),
+            Some(vec!["a".to_string(), "b".to_string()])
+        );
+        assert_eq!(
+            front_matter_list("labels: [\"a, b\", c]\n", "labels"),
+            Some(vec!["a, b".to_string(), "c".to_string()])
+        );
+        assert_eq!(
+            front_matter_list("labels: []\n", "labels"),
+            Some(Vec::new())
+        );
+        assert_eq!(front_matter_list("title: x\n", "labels"), None);
+    }
+
+    #[test]
+    fn set_front_matter_labels_rewrites_only_the_labels_entry() {
+        let labels = vec!["work".to_string(), "on hold".to_string()];
+        assert_eq!(
+            set_front_matter_labels("# Body\n", &labels),
+            "---\nlabels: [work, on hold]\n---\n# Body\n"
+        );
+        assert_eq!(
+            set_front_matter_labels(
+                "---\ntitle: Plan\ntags:\n  - old\n  - older\nstatus: draft\n---\nBody",
+                &labels
+            ),
+            "---\ntitle: Plan\ntags: [work, on hold]\nstatus: draft\n---\nBody"
+        );
+        assert_eq!(
+            set_front_matter_labels("---\ntitle: Plan\n---\nBody", &["a: b".to_string()]),
+            "---\ntitle: Plan\nlabels: ['a: b']\n---\nBody"
+        );
+        assert_eq!(
+            set_front_matter_labels("---\ntitle: Plan\nlabels: [x]\n---\nBody", &[]),
+            "---\ntitle: Plan\n---\nBody"
+        );
+        assert_eq!(
+            set_front_matter_labels("---\nlabels: [x]\n---\nBody", &[]),
+            "Body"
+        );
+        assert_eq!(set_front_matter_labels("Body", &[]), "Body");
+    }
+}
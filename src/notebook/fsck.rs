@@ -0,0 +1,442 @@
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::links::{cache_remove_note_links, cache_upsert_note_links, decode_link_path};
+use super::search::{
+    cache_remove_search_index_entries, cache_upsert_search_index_note_content,
+    note_file_modified_time,
+};
+use super::storage::{format_system_time_rfc3339, save_metadata};
+use super::{
+    FsckFinding, FsckFindingKind, NoteMetadata, NotebookError, NotebookRelativePath,
+    STAGED_DELETE_CLEANUP_GRACE_NANOS,
+};
+
+const NOTE_FILE_NAME: &str = "note.md";
+const IMAGE_DIR_NAME: &str = "images";
+const TEMP_FILE_MARKER: &str = ".cognate_tmp_";
+const TRANSACTION_STAGING_PREFIX: &str = ".cognate_txn_";
+/// Hidden directories Cognate writes into; other hidden directories are left alone.
+const COGNATE_HIDDEN_DIR_PREFIX: &str = ".cognate_";
+
+fn to_rel_path_string(notebook_path: &Path, path: &Path) -> String {
+    path.strip_prefix(notebook_path)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Whether `file_name` is an atomic-write temp file or, at the notebook root, a staged delete.
+fn is_temp_entry_name(file_name: &str, at_root: bool) -> bool {
+    file_name.starts_with('.')
+        && (file_name.contains(TEMP_FILE_MARKER)
+            || (at_root && file_name.starts_with(TRANSACTION_STAGING_PREFIX)))
+}
+
+/// Temp names end in a nanosecond timestamp; entries younger than the grace period may still be
+/// in use by a write that is in progress.
+fn is_stale_temp_entry(file_name: &str) -> bool {
+    let Some(timestamp_nanos) = file_name
+        .rsplit('_')
+        .next()
+        .and_then(|timestamp| timestamp.parse::<u128>().ok())
+    else {
+        return false;
+    };
+    let now_nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+
+    now_nanos.saturating_sub(timestamp_nanos) >= STAGED_DELETE_CLEANUP_GRACE_NANOS
+}
+
+/// File names under `images/` that the note's Markdown images point at.
+///
+/// Destinations are read the way the preview renders them: angle brackets, escapes and
+/// reference definitions are resolved by the Markdown parser, then percent-encoding is decoded.
+fn referenced_image_names(markdown: &str) -> HashSet<String> {
+    let options = pulldown_cmark::Options::ENABLE_YAML_STYLE_METADATA_BLOCKS
+        | pulldown_cmark::Options::ENABLE_PLUSES_DELIMITED_METADATA_BLOCKS
+        | pulldown_cmark::Options::ENABLE_TABLES
+        | pulldown_cmark::Options::ENABLE_STRIKETHROUGH
+        | pulldown_cmark::Options::ENABLE_TASKLISTS;
+    let image_dir_prefix = format!("{}/", IMAGE_DIR_NAME);
+
+    let mut names = HashSet::new();
+    for event in pulldown_cmark::Parser::new_ext(markdown, options) {
+        if let pulldown_cmark::Event::Start(pulldown_cmark::Tag::Image { dest_url, .. }) = event {
+            let image_ref = decode_link_path(dest_url.trim()).replace('\\', "/");
+            if let Some(file_name) = image_ref.strip_prefix(&image_dir_prefix) {
+                names.insert(file_name.to_string());
+            }
+        }
+    }
+    names
+}
+
+#[derive(Default)]
+struct NotebookScan {
+    /// Whether to collect stale temp files, which also scans Cognate's hidden directories.
+    include_temp_entries: bool,
+    note_dirs: Vec<PathBuf>,
+    stale_temp_entries: Vec<PathBuf>,
+}
+
+fn scan_directory(notebook_path: &Path, dir: &Path, inside_hidden: bool, scan: &mut NotebookScan) {
+    let read_dir = match fs::read_dir(dir) {
+        Ok(read_dir) => read_dir,
+        Err(_e) => {
+            #[cfg(debug_assertions)]
+            eprintln!(
+                "Warning: Failed to scan '{}' during notebook check: {}",
+                dir.display(),
+                _e
+            );
+            return;
+        }
+    };
+
+    if !inside_hidden && dir != notebook_path && dir.join(NOTE_FILE_NAME).is_file() {
+        scan.note_dirs.push(dir.to_path_buf());
+    }
+
+    for entry_result in read_dir {
+        let Ok(entry) = entry_result else {
+            continue;
+        };
+        // Symlinks are never followed so a link cannot lead the scan outside the notebook.
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let file_name = entry.file_name();
+        let file_name = file_name.to_string_lossy();
+        let path = entry.path();
+
+        if is_temp_entry_name(&file_name, dir == notebook_path) {
+            if scan.include_temp_entries && is_stale_temp_entry(&file_name) {
+                scan.stale_temp_entries.push(path);
+            }
+            continue;
+        }
+
+        if !file_type.is_dir() {
+            continue;
+        }
+        if file_name.starts_with('.') {
+            // Trash and history directories only hold Cognate's own files; scan them for temp files.
+            if scan.include_temp_entries && file_name.starts_with(COGNATE_HIDDEN_DIR_PREFIX) {
+                scan_directory(notebook_path, &path, true, scan);
+            }
+            continue;
+        }
+
+        scan_directory(notebook_path, &path, inside_hidden, scan);
+    }
+}
+
+/// Metadata for a note directory that is not yet listed, dated by its `note.md` modification time.
+fn untracked_note_metadata(note_dir: &Path, rel_path: String) -> NoteMetadata {
+    NoteMetadata {
+        rel_path,
+        labels: Vec::new(),
+        last_updated: note_file_modified_time(&note_dir.join(NOTE_FILE_NAME))
+            .and_then(format_system_time_rfc3339),
+        ..NoteMetadata::default()
+    }
+}
+
+/// Finds note directories with a `note.md` that `notes` does not list, sorted by path.
+///
+/// Hidden directories, which include the trash, note history and staged deletes, are skipped.
+pub(super) fn find_untracked_notes(
+    notebook_path: &Path,
+    notes: &[NoteMetadata],
+) -> Vec<NoteMetadata> {
+    let mut scan = NotebookScan::default();
+    scan_directory(notebook_path, notebook_path, false, &mut scan);
+
+    let known_paths: HashSet<&str> = notes.iter().map(|note| note.rel_path.as_str()).collect();
+    let mut untracked: Vec<NoteMetadata> = scan
+        .note_dirs
+        .iter()
+        .map(|note_dir| (note_dir, to_rel_path_string(notebook_path, note_dir)))
+        .filter(|(_, rel_path)| !known_paths.contains(rel_path.as_str()))
+        .map(|(note_dir, rel_path)| untracked_note_metadata(note_dir, rel_path))
+        .collect();
+    untracked.sort_by(|left, right| left.rel_path.cmp(&right.rel_path));
+    untracked
+}
+
+fn unreferenced_images(note_dir: &Path) -> Vec<PathBuf> {
+    let image_dir = note_dir.join(IMAGE_DIR_NAME);
+    // A note that happens to be named `images` is not an image directory.
+    if image_dir.join(NOTE_FILE_NAME).exists() {
+        return Vec::new();
+    }
+    let Ok(read_dir) = fs::read_dir(&image_dir) else {
+        return Vec::new();
+    };
+    // Without the note text nothing can be proven unreferenced.
+    let Ok(markdown) = fs::read_to_string(note_dir.join(NOTE_FILE_NAME)) else {
+        return Vec::new();
+    };
+
+    let referenced = referenced_image_names(&markdown);
+
+    let mut images = Vec::new();
+    for entry in read_dir.flatten() {
+        let file_name = entry.file_name();
+        let file_name = file_name.to_string_lossy();
+        if file_name.starts_with('.') || !entry.file_type().is_ok_and(|kind| kind.is_file()) {
+            continue;
+        }
+        if !referenced.contains(file_name.as_ref()) {
+            images.push(entry.path());
+        }
+    }
+    images
+}
+
+/// Checks a notebook for inconsistencies between `metadata.json` and the note directories.
+///
+/// Reports metadata entries without a `note.md`, note directories missing from metadata,
+/// temp files left behind by interrupted writes, and embedded images no note references.
+/// Nothing is changed; see [`repair_fsck_finding`].
+pub async fn check_notebook(
+    notebook_path: &str,
+    notes: &[NoteMetadata],
+) -> Result<Vec<FsckFinding>, NotebookError> {
+    let root = Path::new(notebook_path);
+    if !root.is_dir() {
+        return Err(NotebookError::validation(
+            "check notebook",
+            format!("Notebook directory '{}' does not exist.", notebook_path),
+        ));
+    }
+
+    let mut scan = NotebookScan {
+        include_temp_entries: true,
+        ..NotebookScan::default()
+    };
+    scan_directory(root, root, false, &mut scan);
+
+    let mut findings = Vec::new();
+    let known_paths: HashSet<&str> = notes.iter().map(|note| note.rel_path.as_str()).collect();
+
+    for note in notes {
+        let note_dir = root.join(&note.rel_path);
+        if note_dir.join(NOTE_FILE_NAME).is_file() {
+            continue;
+        }
+        let detail = if note_dir.is_dir() {
+            format!(
+                "Listed in metadata.json, but its {} is missing.",
+                NOTE_FILE_NAME
+            )
+        } else {
+            "Listed in metadata.json, but its directory is missing.".to_string()
+        };
+        findings.push(FsckFinding {
+            kind: FsckFindingKind::DanglingEntry,
+            path: note.rel_path.clone(),
+            detail,
+        });
+    }
+
+    for note_dir in &scan.note_dirs {
+        let rel_path = to_rel_path_string(root, note_dir);
+        if !known_paths.contains(rel_path.as_str()) {
+            findings.push(FsckFinding {
+                kind: FsckFindingKind::UntrackedNote,
+                path: rel_path,
+                detail: format!(
+                    "Contains a {} but is not listed in metadata.json.",
+                    NOTE_FILE_NAME
+                ),
+            });
+        }
+    }
+
+    for temp_path in &scan.stale_temp_entries {
+        let detail = if temp_path.is_dir() {
+            "Leftover directory from an interrupted delete."
+        } else {
+            "Leftover temporary file from an interrupted save."
+        };
+        findings.push(FsckFinding {
+            kind: FsckFindingKind::StaleTempFile,
+            path: to_rel_path_string(root, temp_path),
+            detail: detail.to_string(),
+        });
+    }
+
+    for note_dir in &scan.note_dirs {
+        let note_rel_path = to_rel_path_string(root, note_dir);
+        for image_path in unreferenced_images(note_dir) {
+            findings.push(FsckFinding {
+                kind: FsckFindingKind::UnreferencedImage,
+                path: to_rel_path_string(root, &image_path),
+                detail: format!("Not referenced by '{}'.", note_rel_path),
+            });
+        }
+    }
+
+    findings.sort_by(|left, right| {
+        (left.kind, left.path.as_str()).cmp(&(right.kind, right.path.as_str()))
+    });
+    Ok(findings)
+}
+
+fn finding_no_longer_applies(finding: &FsckFinding) -> NotebookError {
+    NotebookError::validation(
+        "repair notebook",
+        format!(
+            "'{}' has changed since the notebook was checked. Check the notebook again.",
+            finding.path
+        ),
+    )
+}
+
+fn remove_entry(path: &Path, finding: &FsckFinding) -> Result<(), NotebookError> {
+    let result = if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    };
+
+    match result {
+        Ok(()) => Ok(()),
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(()),
+        Err(error) => Err(NotebookError::storage(
+            "repair notebook",
+            format!("Failed to remove '{}': {}", finding.path, error),
+        )),
+    }
+}
+
+/// Applies the repair for one finding reported by [`check_notebook`].
+///
+/// Untracked notes are added to metadata, dangling entries are dropped from it, and stale temp
+/// files and unreferenced images are deleted. The finding is re-checked first, so a repair never
+/// acts on something that changed after the check.
+pub async fn repair_fsck_finding(
+    notebook_path: &str,
+    finding: &FsckFinding,
+    notes: &mut Vec<NoteMetadata>,
+) -> Result<(), NotebookError> {
+    let rel_path = NotebookRelativePath::parse("repair path", &finding.path)?;
+    let root = Path::new(notebook_path);
+    let full_path = rel_path.join_under(root);
+
+    match finding.kind {
+        FsckFindingKind::DanglingEntry => {
+            if full_path.join(NOTE_FILE_NAME).is_file() {
+                return Err(finding_no_longer_applies(finding));
+            }
+
+            let previous_notes = notes.clone();
+            notes.retain(|note| note.rel_path != rel_path.as_str());
+            if notes.len() == previous_notes.len() {
+                return Ok(());
+            }
+            if let Err(error) = save_metadata(notebook_path, notes) {
+                *notes = previous_notes;
+                return Err(error);
+            }
+
+            cache_remove_note_links(notebook_path, rel_path.as_str());
+            cache_remove_search_index_entries(notebook_path, rel_path.as_str());
+            Ok(())
+        }
+        FsckFindingKind::UntrackedNote => {
+            let note_file_path = full_path.join(NOTE_FILE_NAME);
+            if notes.iter().any(|note| note.rel_path == rel_path.as_str())
+                || !note_file_path.is_file()
+            {
+                return Err(finding_no_longer_applies(finding));
+            }
+
+            let previous_notes = notes.clone();
+            notes.push(untracked_note_metadata(
+                &full_path,
+                rel_path.as_str().to_string(),
+            ));
+            if let Err(error) = save_metadata(notebook_path, notes) {
+                *notes = previous_notes;
+                return Err(error);
+            }
+
+            let content = fs::read_to_string(&note_file_path).unwrap_or_default();
+            let modified_time = note_file_modified_time(&note_file_path);
+            cache_upsert_note_links(notebook_path, rel_path.as_str(), &content, modified_time);
+            cache_upsert_search_index_note_content(
+                notebook_path,
+                rel_path.as_str(),
+                &content,
+                modified_time,
+            );
+            Ok(())
+        }
+        FsckFindingKind::StaleTempFile => {
+            let file_name = full_path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let at_root = full_path.parent() == Some(root);
+            if !is_temp_entry_name(&file_name, at_root) || !is_stale_temp_entry(&file_name) {
+                return Err(NotebookError::validation(
+                    "repair notebook",
+                    format!("'{}' is not a stale temporary file.", finding.path),
+                ));
+            }
+
+            remove_entry(&full_path, finding)
+        }
+        FsckFindingKind::UnreferencedImage => {
+            let Some(note_dir) = full_path
+                .parent()
+                .filter(|image_dir| image_dir.file_name() == Some(OsStr::new(IMAGE_DIR_NAME)))
+                .and_then(Path::parent)
+            else {
+                return Err(NotebookError::validation(
+                    "repair notebook",
+                    format!("'{}' is not an embedded image.", finding.path),
+                ));
+            };
+            if !full_path.is_file() || !unreferenced_images(note_dir).contains(&full_path) {
+                return Err(finding_no_longer_applies(finding));
+            }
+
+            remove_entry(&full_path, finding)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn temp_entries_are_recognized_by_name_and_age() {
+        assert!(is_temp_entry_name(".note.md.cognate_tmp_12_34", false));
+        assert!(is_temp_entry_name(".cognate_txn_delete_alpha_34", true));
+        assert!(!is_temp_entry_name(".cognate_txn_delete_alpha_34", false));
+        assert!(!is_temp_entry_name("note.md", false));
+
+        assert!(is_stale_temp_entry(".note.md.cognate_tmp_12_0"));
+        let now_nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos())
+            .unwrap_or_default();
+        assert!(!is_stale_temp_entry(&format!(
+            ".note.md.cognate_tmp_12_{}",
+            now_nanos
+        )));
+        assert!(!is_stale_temp_entry(".note.md.cognate_tmp_unknown"));
+    }
+}
@@ -0,0 +1,152 @@
+/// Below this length a query word must match as a subsequence; typos are not tolerated.
+const MIN_TYPO_WORD_LENGTH: usize = 4;
+/// Longer words tolerate a second typo.
+const TWO_TYPO_WORD_LENGTH: usize = 8;
+/// A typo match is never worth more than this, so subsequence matches rank first.
+const TYPO_MATCH_WEIGHT: f64 = 0.6;
+/// Each skipped character between the first and last matched one lowers the score this much.
+const SUBSEQUENCE_GAP_PENALTY: f64 = 0.05;
+
+fn is_word_start(text: &[char], position: usize) -> bool {
+    position == 0 || !text[position - 1].is_alphanumeric()
+}
+
+/// Scores `pattern` as a subsequence of `text`, both lowercase, between 0 and 1.
+///
+/// Matched characters earn more when they continue the previous match or start a word, so
+/// `mtng` scores well against `meetings` and best against `Meeting Notes` style initials.
+fn subsequence_score(pattern: &[char], text: &[char]) -> Option<f64> {
+    let first = *pattern.first()?;
+    let mut best: Option<f64> = None;
+
+    for start in (0..text.len()).filter(|&position| text[position] == first) {
+        let mut points = if is_word_start(text, start) { 2 } else { 1 };
+        let mut previous = start;
+        let mut matched_all = true;
+
+        for &ch in &pattern[1..] {
+            let Some(offset) = text[previous + 1..].iter().position(|&other| other == ch) else {
+                matched_all = false;
+                break;
+            };
+            let position = previous + 1 + offset;
+            points += 1;
+            if position == previous + 1 {
+                points += 1;
+            }
+            if is_word_start(text, position) {
+                points += 1;
+            }
+            previous = position;
+        }
+        if !matched_all {
+            // Later starts only leave less text to match in.
+            break;
+        }
+
+        let gaps = (previous + 1 - start - pattern.len()) as f64;
+        let score =
+            f64::from(points) / (3 * pattern.len()) as f64 / (1.0 + SUBSEQUENCE_GAP_PENALTY * gaps);
+        best = Some(best.map_or(score, |best| best.max(score)));
+    }
+
+    best
+}
+
+/// Edit distance counting insertions, deletions, substitutions and adjacent transpositions.
+fn optimal_string_alignment_distance(left: &[char], right: &[char]) -> usize {
+    let width = right.len() + 1;
+    let mut rows = vec![0usize; (left.len() + 1) * width];
+    for (column, cell) in rows.iter_mut().take(width).enumerate() {
+        *cell = column;
+    }
+    for row in 1..=left.len() {
+        rows[row * width] = row;
+        for column in 1..=right.len() {
+            let cost = usize::from(left[row - 1] != right[column - 1]);
+            let mut distance = (rows[(row - 1) * width + column] + 1)
+                .min(rows[row * width + column - 1] + 1)
+                .min(rows[(row - 1) * width + column - 1] + cost);
+            if row > 1
+                && column > 1
+                && left[row - 1] == right[column - 2]
+                && left[row - 2] == right[column - 1]
+            {
+                distance = distance.min(rows[(row - 2) * width + column - 2] + 1);
+            }
+            rows[row * width + column] = distance;
+        }
+    }
+    rows[left.len() * width + right.len()]
+}
+
+/// Scores `word` against a single token it may be a misspelling of, or of the start of.
+fn typo_score(word: &[char], token: &[char]) -> Option<f64> {
+    let allowed_typos = match word.len() {
+        length if length < MIN_TYPO_WORD_LENGTH => return None,
+        length if length < TWO_TYPO_WORD_LENGTH => 1,
+        _ => 2,
+    };
+
+    let whole = optimal_string_alignment_distance(word, token);
+    let prefix = optimal_string_alignment_distance(word, &token[..word.len().min(token.len())]);
+    let distance = whole.min(prefix);
+    (distance <= allowed_typos)
+        .then(|| TYPO_MATCH_WEIGHT * (1.0 - distance as f64 / word.len() as f64))
+}
+
+/// How well a lowercase query word matches `text`, between 0 and 1, or `None` if it does not.
+pub(super) fn fuzzy_word_score(word: &str, text: &str) -> Option<f64> {
+    let word: Vec<char> = word.chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    let subsequence = subsequence_score(&word, &text);
+    let typo = text
+        .split(|ch| !ch.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .filter_map(|token| typo_score(&word, token))
+        .reduce(f64::max);
+
+    match (subsequence, typo) {
+        (Some(subsequence), Some(typo)) => Some(subsequence.max(typo)),
+        (score, None) | (None, score) => score,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_word_score_matches_abbreviations_and_typos() {
+        assert!(fuzzy_word_score("mtng", "meetings/notes-2026").is_some());
+        assert!(fuzzy_word_score("nts", "meetings/notes-2026").is_some());
+        assert!(fuzzy_word_score("meetnigs", "meetings/notes-2026").is_some());
+        assert!(fuzzy_word_score("recipe", "meetings/notes-2026").is_none());
+        assert!(fuzzy_word_score("xyz", "meetings").is_none());
+
+        let exact = fuzzy_word_score("notes", "meetings/notes").expect("exact match");
+        let scattered = fuzzy_word_score("notes", "north/east/s").expect("subsequence match");
+        let typo = fuzzy_word_score("ntoes", "meetings/notes").expect("typo match");
+        assert!(exact > scattered, "{exact} <= {scattered}");
+        assert!(exact > typo, "{exact} <= {typo}");
+        assert!(exact <= 1.0);
+    }
+
+    #[test]
+    fn optimal_string_alignment_distance_counts_transpositions_once() {
+        let chars = |text: &str| text.chars().collect::<Vec<_>>();
+        assert_eq!(
+            optimal_string_alignment_distance(&chars("notes"), &chars("ntoes")),
+            1
+        );
+        assert_eq!(
+            optimal_string_alignment_distance(&chars("kitten"), &chars("sitting")),
+            3
+        );
+        assert_eq!(
+            optimal_string_alignment_distance(&chars(""), &chars("abc")),
+            3
+        );
+    }
+}
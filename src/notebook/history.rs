@@ -0,0 +1,374 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+use super::storage::{
+    current_timestamp_rfc3339, format_system_time_rfc3339, write_text_file_atomically,
+};
+use super::{
+    LineDiff, LineDiffKind, MergeResult, NoteVersion, NotebookError, NotebookRelativePath,
+};
+
+const HISTORY_DIR_NAME: &str = ".cognate_history";
+const HISTORY_INDEX_FILE_NAME: &str = "versions.json";
+const SNAPSHOT_INTERVAL_SECONDS: i64 = 10 * 60;
+const MAX_VERSIONS_PER_NOTE: usize = 100;
+// Above this many cells the diff skips the LCS table and reports a block replacement.
+const MAX_DIFF_TABLE_CELLS: usize = 4_000_000;
+
+/// Version list stored next to the snapshot blobs of one note.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct HistoryIndex {
+    #[serde(default)]
+    versions: Vec<StoredVersion>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredVersion {
+    id: String,
+    saved_at: String,
+    captured_at: String,
+}
+
+fn history_dir(note_dir: &Path) -> PathBuf {
+    note_dir.join(HISTORY_DIR_NAME)
+}
+
+fn snapshot_blob_path(history_dir: &Path, version_id: &str) -> PathBuf {
+    history_dir.join(format!("{}.md", version_id))
+}
+
+/// 128-bit FNV-1a over the UTF-8 bytes of note content; unlike `DefaultHasher` it is stable
+/// across builds, so it can name files and be stored in caches.
+pub(super) fn content_hash(content: &str) -> u128 {
+    const FNV_OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const FNV_PRIME: u128 = 0x0000000001000000000000000000013b;
+
+    content.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u128::from(byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Content address of a snapshot: its content hash, hex encoded.
+fn snapshot_version_id(content: &str) -> String {
+    format!("{:032x}", content_hash(content))
+}
+
+fn is_valid_version_id(version_id: &str) -> bool {
+    version_id.len() == 32 && version_id.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+fn read_history_index(history_dir: &Path) -> Result<HistoryIndex, NotebookError> {
+    let index_path = history_dir.join(HISTORY_INDEX_FILE_NAME);
+    let contents = match fs::read_to_string(&index_path) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(HistoryIndex::default()),
+        Err(error) => {
+            return Err(NotebookError::storage(
+                "note history",
+                format!(
+                    "Failed to read history index '{}': {}",
+                    index_path.display(),
+                    error
+                ),
+            ));
+        }
+    };
+
+    serde_json::from_str(&contents).map_err(|error| {
+        NotebookError::storage(
+            "note history",
+            format!(
+                "Failed to parse history index '{}': {}",
+                index_path.display(),
+                error
+            ),
+        )
+    })
+}
+
+fn write_history_index(history_dir: &Path, index: &HistoryIndex) -> Result<(), NotebookError> {
+    let index_json = serde_json::to_string_pretty(index).map_err(|error| {
+        NotebookError::storage(
+            "note history",
+            format!("Failed to serialize history index: {}", error),
+        )
+    })?;
+    write_text_file_atomically(&history_dir.join(HISTORY_INDEX_FILE_NAME), &index_json)
+}
+
+fn is_within_snapshot_interval(captured_at: &str, now: OffsetDateTime) -> bool {
+    OffsetDateTime::parse(captured_at, &Rfc3339)
+        .is_ok_and(|captured_at| (now - captured_at).whole_seconds() < SNAPSHOT_INTERVAL_SECONDS)
+}
+
+/// Records the content a save is about to replace as a version of the note.
+///
+/// At most one snapshot is taken per snapshot interval, identical consecutive versions are
+/// skipped, and blobs are shared between versions with the same content.
+pub(super) fn record_note_snapshot(
+    note_dir: &Path,
+    previous_content: &str,
+    previous_modified_time: Option<SystemTime>,
+) -> Result<(), NotebookError> {
+    let history_dir = history_dir(note_dir);
+    let mut index = read_history_index(&history_dir)?;
+    let version_id = snapshot_version_id(previous_content);
+
+    if let Some(latest) = index.versions.last()
+        && (latest.id == version_id
+            || is_within_snapshot_interval(&latest.captured_at, OffsetDateTime::now_utc()))
+    {
+        return Ok(());
+    }
+
+    let blob_path = snapshot_blob_path(&history_dir, &version_id);
+    if !blob_path.exists() {
+        write_text_file_atomically(&blob_path, previous_content)?;
+    }
+
+    let captured_at = current_timestamp_rfc3339();
+    index.versions.push(StoredVersion {
+        id: version_id,
+        saved_at: previous_modified_time
+            .and_then(format_system_time_rfc3339)
+            .unwrap_or_else(|| captured_at.clone()),
+        captured_at,
+    });
+
+    let mut pruned_ids = Vec::new();
+    while index.versions.len() > MAX_VERSIONS_PER_NOTE {
+        pruned_ids.push(index.versions.remove(0).id);
+    }
+
+    write_history_index(&history_dir, &index)?;
+
+    for pruned_id in pruned_ids {
+        if index.versions.iter().all(|version| version.id != pruned_id) {
+            let _ = fs::remove_file(snapshot_blob_path(&history_dir, &pruned_id));
+        }
+    }
+
+    Ok(())
+}
+
+/// Lists the stored versions of a note, newest first.
+pub async fn load_note_history(
+    notebook_path: String,
+    rel_path: String,
+) -> Result<Vec<NoteVersion>, NotebookError> {
+    let rel_path = NotebookRelativePath::parse("note path", &rel_path)?;
+    let history_dir = history_dir(&rel_path.join_under(Path::new(&notebook_path)));
+    let index = read_history_index(&history_dir)?;
+
+    Ok(index
+        .versions
+        .into_iter()
+        .rev()
+        .map(|version| NoteVersion {
+            id: version.id,
+            saved_at: version.saved_at,
+        })
+        .collect())
+}
+
+/// Reads the content of one stored version of a note.
+pub async fn load_note_version(
+    notebook_path: String,
+    rel_path: String,
+    version_id: String,
+) -> Result<String, NotebookError> {
+    let rel_path = NotebookRelativePath::parse("note path", &rel_path)?;
+    if !is_valid_version_id(&version_id) {
+        return Err(NotebookError::validation(
+            "note history",
+            format!("Invalid version id '{}'.", version_id),
+        ));
+    }
+
+    let history_dir = history_dir(&rel_path.join_under(Path::new(&notebook_path)));
+    fs::read_to_string(snapshot_blob_path(&history_dir, &version_id)).map_err(|error| {
+        NotebookError::storage(
+            "note history",
+            format!(
+                "Failed to read version '{}' of '{}': {}",
+                version_id, rel_path, error
+            ),
+        )
+    })
+}
+
+/// Index pairs `(old, new)` of lines on a longest common subsequence of `old` and `new`.
+fn common_line_pairs(old_lines: &[&str], new_lines: &[&str]) -> Vec<(usize, usize)> {
+    let prefix_len = old_lines
+        .iter()
+        .zip(new_lines)
+        .take_while(|(left, right)| left == right)
+        .count();
+    let suffix_len = old_lines[prefix_len..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix_len..].iter().rev())
+        .take_while(|(left, right)| left == right)
+        .count();
+
+    let old_middle = &old_lines[prefix_len..old_lines.len() - suffix_len];
+    let new_middle = &new_lines[prefix_len..new_lines.len() - suffix_len];
+
+    let mut pairs: Vec<(usize, usize)> = (0..prefix_len).map(|index| (index, index)).collect();
+
+    if old_middle.len().saturating_mul(new_middle.len()) <= MAX_DIFF_TABLE_CELLS {
+        // lcs[i][j] is the longest common subsequence of old_middle[i..] and new_middle[j..].
+        let columns = new_middle.len() + 1;
+        let mut lcs = vec![0u32; (old_middle.len() + 1) * columns];
+        for i in (0..old_middle.len()).rev() {
+            for j in (0..new_middle.len()).rev() {
+                lcs[i * columns + j] = if old_middle[i] == new_middle[j] {
+                    lcs[(i + 1) * columns + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * columns + j].max(lcs[i * columns + j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0usize, 0usize);
+        while i < old_middle.len() && j < new_middle.len() {
+            if old_middle[i] == new_middle[j] {
+                pairs.push((prefix_len + i, prefix_len + j));
+                i += 1;
+                j += 1;
+            } else if lcs[(i + 1) * columns + j] >= lcs[i * columns + j + 1] {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+    }
+
+    let old_suffix_start = old_lines.len() - suffix_len;
+    let new_suffix_start = new_lines.len() - suffix_len;
+    pairs.extend(
+        (0..suffix_len).map(|offset| (old_suffix_start + offset, new_suffix_start + offset)),
+    );
+    pairs
+}
+
+/// Line diff from `old` to `new`: removed lines exist only in `old`, added lines only in `new`.
+pub fn diff_lines(old: &str, new: &str) -> Vec<LineDiff> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+
+    let line = |kind: LineDiffKind, text: &str| LineDiff {
+        kind,
+        text: text.to_string(),
+    };
+    let mut diff = Vec::with_capacity(old_lines.len().max(new_lines.len()));
+    let (mut old_index, mut new_index) = (0usize, 0usize);
+
+    let pairs = common_line_pairs(&old_lines, &new_lines);
+    for (old_match, new_match) in pairs
+        .into_iter()
+        .chain(std::iter::once((old_lines.len(), new_lines.len())))
+    {
+        diff.extend(
+            old_lines[old_index..old_match]
+                .iter()
+                .map(|text| line(LineDiffKind::Removed, text)),
+        );
+        diff.extend(
+            new_lines[new_index..new_match]
+                .iter()
+                .map(|text| line(LineDiffKind::Added, text)),
+        );
+        if old_match < old_lines.len() {
+            diff.push(line(LineDiffKind::Unchanged, old_lines[old_match]));
+        }
+        old_index = old_match + 1;
+        new_index = new_match + 1;
+    }
+
+    diff
+}
+
+/// For each line of `base`, the index of the line it is kept as in `other`, if any.
+fn base_line_matches(base_lines: &[&str], other_lines: &[&str]) -> Vec<Option<usize>> {
+    let mut matches = vec![None; base_lines.len()];
+    for (base_index, other_index) in common_line_pairs(base_lines, other_lines) {
+        matches[base_index] = Some(other_index);
+    }
+    matches
+}
+
+/// Three-way line merge of two edited versions of `base`.
+///
+/// Regions changed on only one side take that side's lines; regions both sides changed
+/// differently are kept as a conflict between `<<<<<<< mine` and `>>>>>>> theirs` markers.
+pub fn merge_three_way(base: &str, mine: &str, theirs: &str) -> MergeResult {
+    let base_lines: Vec<&str> = base.lines().collect();
+    let mine_lines: Vec<&str> = mine.lines().collect();
+    let theirs_lines: Vec<&str> = theirs.lines().collect();
+
+    let mine_matches = base_line_matches(&base_lines, &mine_lines);
+    let theirs_matches = base_line_matches(&base_lines, &theirs_lines);
+
+    let mut merged: Vec<&str> = Vec::new();
+    let mut conflict_count = 0;
+    let (mut base_index, mut mine_index, mut theirs_index) = (0usize, 0usize, 0usize);
+
+    loop {
+        // The next base line both sides kept closes the current changed region.
+        let stable = (base_index..base_lines.len()).find_map(|index| {
+            match (mine_matches[index], theirs_matches[index]) {
+                (Some(mine_match), Some(theirs_match)) => Some((index, mine_match, theirs_match)),
+                _ => None,
+            }
+        });
+        let (base_end, mine_end, theirs_end) =
+            stable.unwrap_or((base_lines.len(), mine_lines.len(), theirs_lines.len()));
+
+        let base_region = &base_lines[base_index..base_end];
+        let mine_region = &mine_lines[mine_index..mine_end];
+        let theirs_region = &theirs_lines[theirs_index..theirs_end];
+
+        if mine_region == base_region || mine_region == theirs_region {
+            merged.extend_from_slice(theirs_region);
+        } else if theirs_region == base_region {
+            merged.extend_from_slice(mine_region);
+        } else {
+            conflict_count += 1;
+            merged.push("<<<<<<< mine");
+            merged.extend_from_slice(mine_region);
+            merged.push("=======");
+            merged.extend_from_slice(theirs_region);
+            merged.push(">>>>>>> theirs");
+        }
+
+        let Some((base_end, mine_end, theirs_end)) = stable else {
+            break;
+        };
+        merged.push(base_lines[base_end]);
+        base_index = base_end + 1;
+        mine_index = mine_end + 1;
+        theirs_index = theirs_end + 1;
+    }
+
+    let mut text = merged.join("\n");
+    let ends_with_newline = if mine.ends_with('\n') != base.ends_with('\n') {
+        mine.ends_with('\n')
+    } else {
+        theirs.ends_with('\n')
+    };
+    if ends_with_newline && !text.is_empty() {
+        text.push('\n');
+    }
+
+    MergeResult {
+        text,
+        conflict_count,
+    }
+}
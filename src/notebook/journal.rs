@@ -0,0 +1,278 @@
+use time::{Date, Month};
+
+use super::operations::create_note_with_content;
+use super::storage::current_local_datetime;
+use super::templates::{create_note_with_template, format_date};
+use super::{CreatedNote, NoteMetadata, NotebookError, NotebookRelativePath};
+
+/// Where daily notes go unless the config file sets `journal_path_pattern`.
+pub const DEFAULT_JOURNAL_PATH_PATTERN: &str = "journal/{{year}}/{{month}}/{{day}}";
+/// Label every daily note is created with.
+const JOURNAL_LABEL: &str = "journal";
+
+/// Date parts a journal path pattern can hold, with the width of their value.
+const DATE_VARIABLES: [(&str, usize); 4] = [("year", 4), ("month", 2), ("day", 2), ("date", 10)];
+
+/// Today's date in the local offset in effect now.
+pub fn today_local_date() -> Date {
+    current_local_datetime().date()
+}
+
+/// Splits `pattern` into literal text and `{{variable}}` date parts.
+fn pattern_segments(pattern: &str) -> Result<Vec<(&str, Option<&str>)>, NotebookError> {
+    let invalid = |detail: &str| {
+        NotebookError::validation("journal path pattern", format!("'{}' {}", pattern, detail))
+    };
+
+    let mut segments = Vec::new();
+    let mut rest = pattern;
+    while let Some(start) = rest.find("{{") {
+        let Some(length) = rest[start + 2..].find("}}") else {
+            return Err(invalid("has an unclosed '{{'."));
+        };
+        let name = rest[start + 2..start + 2 + length].trim();
+        if !DATE_VARIABLES.iter().any(|(variable, _)| *variable == name) {
+            return Err(invalid(
+                "may only use {{year}}, {{month}}, {{day}} and {{date}}.",
+            ));
+        }
+        segments.push((&rest[..start], Some(name)));
+        rest = &rest[start + 4 + length..];
+    }
+    segments.push((rest, None));
+
+    let has = |name: &str| segments.iter().any(|(_, variable)| *variable == Some(name));
+    if !(has("date") || (has("year") && has("month") && has("day"))) {
+        return Err(invalid(
+            "must hold {{date}} or all of {{year}}, {{month}} and {{day}}.",
+        ));
+    }
+    Ok(segments)
+}
+
+fn parse_digits(digits: &str) -> Option<u16> {
+    if digits.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+/// Path of the daily note for `date` under `pattern`.
+pub fn journal_note_path(pattern: &str, date: Date) -> Result<String, NotebookError> {
+    let mut path = String::new();
+    for (literal, variable) in pattern_segments(pattern)? {
+        path.push_str(literal);
+        match variable {
+            Some("year") => path.push_str(&format!("{:04}", date.year())),
+            Some("month") => path.push_str(&format!("{:02}", u8::from(date.month()))),
+            Some("day") => path.push_str(&format!("{:02}", date.day())),
+            Some(_) => path.push_str(&format_date(date)),
+            None => {}
+        }
+    }
+    let path = NotebookRelativePath::parse("journal path pattern", &path)?;
+    Ok(path.as_str().to_string())
+}
+
+/// Date of the daily note at `rel_path`, or `None` when the path does not follow `pattern`.
+pub fn journal_note_date(pattern: &str, rel_path: &str) -> Option<Date> {
+    let segments = pattern_segments(pattern).ok()?;
+    let (mut year, mut month, mut day) = (None, None, None);
+    let mut rest = rel_path;
+    for (literal, variable) in segments {
+        rest = rest.strip_prefix(literal)?;
+        let Some(name) = variable else {
+            continue;
+        };
+        let width = DATE_VARIABLES
+            .iter()
+            .find(|(variable, _)| *variable == name)
+            .map(|(_, width)| *width)?;
+        let value = rest.get(..width)?;
+        rest = &rest[width..];
+
+        let parsed = match name {
+            "year" => (Some(parse_digits(value)?), None, None),
+            "month" => (None, Some(parse_digits(value)?), None),
+            "day" => (None, None, Some(parse_digits(value)?)),
+            _ => {
+                let mut parts = value.split('-');
+                let (Some(date_year), Some(date_month), Some(date_day), None) =
+                    (parts.next(), parts.next(), parts.next(), parts.next())
+                else {
+                    return None;
+                };
+                if date_year.len() != 4 || date_month.len() != 2 || date_day.len() != 2 {
+                    return None;
+                }
+                (
+                    Some(parse_digits(date_year)?),
+                    Some(parse_digits(date_month)?),
+                    Some(parse_digits(date_day)?),
+                )
+            }
+        };
+        // A part used twice must name the same date both times.
+        for (slot, value) in [
+            (&mut year, parsed.0),
+            (&mut month, parsed.1),
+            (&mut day, parsed.2),
+        ] {
+            if let Some(value) = value {
+                if slot.is_some_and(|existing| existing != value) {
+                    return None;
+                }
+                *slot = Some(value);
+            }
+        }
+    }
+    if !rest.is_empty() {
+        return None;
+    }
+
+    let month = Month::try_from(u8::try_from(month?).ok()?).ok()?;
+    Date::from_calendar_date(i32::from(year?), month, u8::try_from(day?).ok()?).ok()
+}
+
+/// Path of the existing daily note nearest to `date`, after it when `forward` and before it
+/// otherwise; days without a note are skipped rather than created.
+pub fn adjacent_journal_note_path(
+    pattern: &str,
+    notes: &[NoteMetadata],
+    date: Date,
+    forward: bool,
+) -> Option<String> {
+    let daily_notes = notes.iter().filter_map(|note| {
+        journal_note_date(pattern, &note.rel_path).map(|note_date| (note_date, &note.rel_path))
+    });
+    let adjacent = if forward {
+        daily_notes
+            .filter(|(note_date, _)| *note_date > date)
+            .min_by_key(|(note_date, _)| *note_date)
+    } else {
+        daily_notes
+            .filter(|(note_date, _)| *note_date < date)
+            .max_by_key(|(note_date, _)| *note_date)
+    };
+    adjacent.map(|(_, rel_path)| rel_path.clone())
+}
+
+/// Opens the daily note for `date`, creating it with the `journal` label when it is missing.
+///
+/// A new note is filled in from `template` when one is given, with `{{date}}` set to `date`.
+pub async fn open_journal_note(
+    notebook_path: &str,
+    pattern: &str,
+    template: Option<&str>,
+    date: Date,
+    notes: &mut Vec<NoteMetadata>,
+) -> Result<CreatedNote, NotebookError> {
+    let rel_path = journal_note_path(pattern, date)?;
+    if let Some(existing) = notes.iter().find(|note| note.rel_path == rel_path) {
+        return Ok(CreatedNote {
+            metadata: existing.clone(),
+            cursor: None,
+        });
+    }
+
+    let labels = vec![JOURNAL_LABEL.to_string()];
+    match template {
+        Some(template) => {
+            create_note_with_template(notebook_path, &rel_path, template, date, labels, notes)
+        }
+        None => {
+            let metadata = create_note_with_content(notebook_path, &rel_path, "", labels, notes)?;
+            Ok(CreatedNote {
+                metadata,
+                cursor: None,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: Month, day: u8) -> Date {
+        Date::from_calendar_date(year, month, day).expect("Expected a valid date")
+    }
+
+    #[test]
+    fn journal_paths_round_trip_through_their_pattern() {
+        let march_fourth = date(2026, Month::March, 4);
+        assert_eq!(
+            journal_note_path(DEFAULT_JOURNAL_PATH_PATTERN, march_fourth)
+                .expect("Default pattern should be valid"),
+            "journal/2026/03/04"
+        );
+        assert_eq!(
+            journal_note_date(DEFAULT_JOURNAL_PATH_PATTERN, "journal/2026/03/04"),
+            Some(march_fourth)
+        );
+        assert_eq!(
+            journal_note_path("days/{{year}}/{{date}}", march_fourth)
+                .expect("Pattern with a date should be valid"),
+            "days/2026/2026-03-04"
+        );
+        assert_eq!(
+            journal_note_date("days/{{year}}/{{date}}", "days/2026/2026-03-04"),
+            Some(march_fourth)
+        );
+
+        for rel_path in [
+            "journal/2026/02/30",
+            "journal/2026/3/04",
+            "journal/2026/03/04/extra",
+            "notes/2026/03/04",
+        ] {
+            assert_eq!(
+                journal_note_date(DEFAULT_JOURNAL_PATH_PATTERN, rel_path),
+                None
+            );
+        }
+        assert_eq!(
+            journal_note_date("days/{{year}}/{{date}}", "days/2025/2026-03-04"),
+            None
+        );
+
+        for pattern in [
+            "journal/{{year}}/{{month}}",
+            "journal/{{week}}/{{date}}",
+            "journal/{{date",
+            "../{{date}}",
+        ] {
+            assert!(journal_note_path(pattern, march_fourth).is_err());
+        }
+    }
+
+    #[test]
+    fn adjacent_journal_note_skips_days_without_a_note() {
+        let notes: Vec<NoteMetadata> = [
+            "journal/2026/03/01",
+            "journal/2026/03/04",
+            "journal/2026/03/09",
+            "notes/2026/03/05",
+        ]
+        .into_iter()
+        .map(|rel_path| NoteMetadata {
+            rel_path: rel_path.to_string(),
+            ..NoteMetadata::default()
+        })
+        .collect();
+        let adjacent = |day, forward| {
+            adjacent_journal_note_path(
+                DEFAULT_JOURNAL_PATH_PATTERN,
+                &notes,
+                date(2026, Month::March, day),
+                forward,
+            )
+        };
+
+        assert_eq!(adjacent(4, true).as_deref(), Some("journal/2026/03/09"));
+        assert_eq!(adjacent(4, false).as_deref(), Some("journal/2026/03/01"));
+        assert_eq!(adjacent(6, false).as_deref(), Some("journal/2026/03/04"));
+        assert_eq!(adjacent(9, true), None);
+        assert_eq!(adjacent(1, false), None);
+    }
+}
@@ -0,0 +1,646 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use super::front_matter::{front_matter_lists_labels, set_front_matter_labels};
+use super::fuzzy::fuzzy_word_score;
+use super::replace::{
+    RewrittenNote, cache_rewritten_notes, rollback_rewritten_notes, write_rewritten_notes,
+};
+use super::storage::{current_timestamp_rfc3339, save_metadata_with};
+use super::{
+    BulkLabelAction, LabelEditKind, LabelEditNote, LabelEditPreview, LabelInfo, LabelRegistry,
+    LabelTreeNode, NoteMetadata, NotebookError, NotebookMetadata, NotebookRelativePath,
+};
+
+const NOTE_FILE_NAME: &str = "note.md";
+const LABEL_EDIT_CONTEXT: &str = "edit label";
+const LABEL_INFO_CONTEXT: &str = "save label style";
+const BULK_LABEL_CONTEXT: &str = "label marked notes";
+/// Longest icon accepted, in characters; enough for an emoji with modifiers.
+const MAX_LABEL_ICON_CHARS: usize = 4;
+
+/// Separates the levels of a nested label such as `area/sub/topic`.
+pub const LABEL_SEPARATOR: char = '/';
+
+/// Trims a label and each of its levels, dropping empty levels.
+pub fn normalize_label(label: &str) -> String {
+    label
+        .split(LABEL_SEPARATOR)
+        .map(str::trim)
+        .filter(|level| !level.is_empty())
+        .collect::<Vec<&str>>()
+        .join(&LABEL_SEPARATOR.to_string())
+}
+
+/// Whether `label` is `parent` or nested below it, ignoring case.
+///
+/// `parent` is expected to be normalized and lowercase, as the search query parser stores it.
+pub fn label_matches(label: &str, parent: &str) -> bool {
+    let label = normalize_label(label).to_lowercase();
+    match label.strip_prefix(parent) {
+        Some(rest) => rest.is_empty() || rest.starts_with(LABEL_SEPARATOR),
+        None => false,
+    }
+}
+
+/// `label` and every label it is nested below, outermost first.
+pub fn label_with_ancestors(label: &str) -> impl Iterator<Item = &str> {
+    label
+        .match_indices(LABEL_SEPARATOR)
+        .map(|(index, _)| &label[..index])
+        .chain(std::iter::once(label))
+}
+
+#[derive(Default)]
+struct LabelTreeBuilder {
+    name: String,
+    path: String,
+    note_count: usize,
+    children: BTreeMap<String, LabelTreeBuilder>,
+}
+
+impl LabelTreeBuilder {
+    fn build(self) -> LabelTreeNode {
+        LabelTreeNode {
+            name: self.name,
+            path: self.path,
+            note_count: self.note_count,
+            children: self.children.into_values().map(Self::build).collect(),
+        }
+    }
+}
+
+/// Arranges the labels of `notes` by level, sorted by name.
+///
+/// Labels differing only in case share a node named after the first spelling seen. A node
+/// counts every note with its label or a label nested below it, each note once.
+pub fn build_label_tree(notes: &[NoteMetadata]) -> Vec<LabelTreeNode> {
+    let mut root = LabelTreeBuilder::default();
+    for note in notes {
+        let mut counted: HashSet<String> = HashSet::new();
+        for label in &note.labels {
+            let label = normalize_label(label);
+            let mut node = &mut root;
+            for path in label_with_ancestors(&label) {
+                let name = path.rsplit(LABEL_SEPARATOR).next().unwrap_or(path);
+                let key = path.to_lowercase();
+                node =
+                    node.children
+                        .entry(name.to_lowercase())
+                        .or_insert_with(|| LabelTreeBuilder {
+                            name: name.to_string(),
+                            path: path.to_string(),
+                            ..LabelTreeBuilder::default()
+                        });
+                if counted.insert(key) {
+                    node.note_count += 1;
+                }
+            }
+        }
+    }
+    root.build().children
+}
+
+/// `label`, which is `source` or nested below it, moved below `target`.
+fn move_label(label: &str, source: &str, target: &str) -> String {
+    let depth = source.split(LABEL_SEPARATOR).count();
+    let normalized = normalize_label(label);
+    std::iter::once(target)
+        .chain(normalized.split(LABEL_SEPARATOR).skip(depth))
+        .collect::<Vec<&str>>()
+        .join(&LABEL_SEPARATOR.to_string())
+}
+
+/// Lists the notes whose labels change when `label` and the labels nested below it are
+/// renamed, merged into `target` or deleted.
+///
+/// Labels match ignoring case, and labels of an affected note that end up differing only in
+/// case are kept once. A rename refuses a target that is already in use, since that is a
+/// merge; `target` is ignored for a delete.
+pub fn preview_label_edit(
+    notes: &[NoteMetadata],
+    kind: LabelEditKind,
+    label: &str,
+    target: &str,
+) -> Result<LabelEditPreview, NotebookError> {
+    let label = normalize_label(label);
+    if label.is_empty() {
+        return Err(NotebookError::validation(
+            LABEL_EDIT_CONTEXT,
+            "Enter the label to change.",
+        ));
+    }
+    let source = label.to_lowercase();
+    let target = match kind {
+        LabelEditKind::Delete => String::new(),
+        LabelEditKind::Rename | LabelEditKind::Merge => normalize_label(target),
+    };
+    let target_lower = target.to_lowercase();
+
+    if kind != LabelEditKind::Delete {
+        if target.is_empty() {
+            return Err(NotebookError::validation(
+                LABEL_EDIT_CONTEXT,
+                "Enter the new label.",
+            ));
+        }
+        if target_lower == source && kind == LabelEditKind::Merge {
+            return Err(NotebookError::validation(
+                LABEL_EDIT_CONTEXT,
+                "A label cannot be merged into itself.",
+            ));
+        }
+        if target_lower != source && label_matches(&target, &source) {
+            return Err(NotebookError::validation(
+                LABEL_EDIT_CONTEXT,
+                format!("'{}' cannot be moved below itself.", label),
+            ));
+        }
+        let target_in_use = notes
+            .iter()
+            .flat_map(|note| &note.labels)
+            .any(|existing| label_matches(existing, &target_lower));
+        if kind == LabelEditKind::Rename && target_lower != source && target_in_use {
+            return Err(NotebookError::validation(
+                LABEL_EDIT_CONTEXT,
+                format!("'{}' is already in use. Merge the labels instead.", target),
+            ));
+        }
+    }
+
+    let mut is_used = false;
+    let mut changed_notes = Vec::new();
+    for note in notes {
+        if !note
+            .labels
+            .iter()
+            .any(|existing| label_matches(existing, &source))
+        {
+            continue;
+        }
+        is_used = true;
+
+        let mut new_labels: Vec<String> = Vec::with_capacity(note.labels.len());
+        for existing in &note.labels {
+            let new_label = if label_matches(existing, &source) {
+                match kind {
+                    LabelEditKind::Delete => continue,
+                    LabelEditKind::Rename | LabelEditKind::Merge => {
+                        move_label(existing, &source, &target)
+                    }
+                }
+            } else {
+                existing.clone()
+            };
+            let new_key = new_label.to_lowercase();
+            if !new_labels.iter().any(|kept| kept.to_lowercase() == new_key) {
+                new_labels.push(new_label);
+            }
+        }
+        if new_labels != note.labels {
+            changed_notes.push(LabelEditNote {
+                rel_path: note.rel_path.clone(),
+                labels: note.labels.clone(),
+                new_labels,
+            });
+        }
+    }
+
+    if !is_used {
+        return Err(NotebookError::validation(
+            LABEL_EDIT_CONTEXT,
+            format!("No note has the label '{}'.", label),
+        ));
+    }
+    changed_notes.sort_by(|a, b| a.rel_path.cmp(&b.rel_path));
+
+    Ok(LabelEditPreview {
+        kind,
+        label,
+        target,
+        notes: changed_notes,
+    })
+}
+
+/// Applies `preview` to `notes` and saves them with one metadata write; returns the changed
+/// note paths.
+///
+/// Registry entries move with their labels. Notes whose front matter lists labels get the new
+/// labels written into it first. Nothing is written if a note's labels changed since the
+/// preview, and rewritten notes are restored if a write or the metadata update fails.
+pub async fn apply_label_edit(
+    notebook_path: &str,
+    preview: &LabelEditPreview,
+    notes: &mut Vec<NoteMetadata>,
+) -> Result<Vec<String>, NotebookError> {
+    save_label_changes(
+        notebook_path,
+        LABEL_EDIT_CONTEXT,
+        &preview.notes,
+        notes,
+        |metadata| apply_label_edit_to_registry(&mut metadata.label_registry, preview),
+    )
+}
+
+/// Writes `changes` into the front matter that lists labels and into `notes`, then saves the
+/// metadata after `update` changed the rest of it.
+fn save_label_changes(
+    notebook_path: &str,
+    context: &'static str,
+    changes: &[LabelEditNote],
+    notes: &mut Vec<NoteMetadata>,
+    update: impl FnOnce(&mut NotebookMetadata),
+) -> Result<Vec<String>, NotebookError> {
+    let mut planned = Vec::new();
+    for change in changes {
+        let is_unchanged = notes
+            .iter()
+            .any(|note| note.rel_path == change.rel_path && note.labels == change.labels);
+        if !is_unchanged {
+            return Err(NotebookError::validation(
+                context,
+                format!(
+                    "The labels of '{}' changed since the preview. Preview the change again.",
+                    change.rel_path
+                ),
+            ));
+        }
+
+        let rel_path = NotebookRelativePath::parse("label note path", &change.rel_path)?;
+        let note_file_path = rel_path
+            .join_under(Path::new(notebook_path))
+            .join(NOTE_FILE_NAME);
+        let previous_content = match fs::read_to_string(&note_file_path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(error) => {
+                return Err(NotebookError::storage(
+                    context,
+                    format!("Failed to read note '{}': {}", change.rel_path, error),
+                ));
+            }
+        };
+        if !front_matter_lists_labels(&previous_content) {
+            continue;
+        }
+        let content = set_front_matter_labels(&previous_content, &change.new_labels);
+        if content != previous_content {
+            planned.push(RewrittenNote {
+                rel_path: rel_path.as_str().to_string(),
+                note_file_path,
+                previous_content,
+                content,
+            });
+        }
+    }
+
+    let rewritten = write_rewritten_notes(context, planned)?;
+
+    let previous_notes = notes.clone();
+    let timestamp = current_timestamp_rfc3339();
+    for note in notes.iter_mut() {
+        if let Some(change) = changes
+            .iter()
+            .find(|change| change.rel_path == note.rel_path)
+        {
+            note.labels = change.new_labels.clone();
+            note.last_updated = Some(timestamp.clone());
+        }
+    }
+    if let Err(error) = save_metadata_with(notebook_path, notes, update) {
+        *notes = previous_notes;
+        return Err(rollback_rewritten_notes(context, error, &rewritten));
+    }
+    cache_rewritten_notes(notebook_path, &rewritten);
+
+    Ok(changes
+        .iter()
+        .map(|change| change.rel_path.clone())
+        .collect())
+}
+
+/// Lists the label change of each note in `rel_paths` that adding or removing `label` makes,
+/// sorted by path; notes that already have, or lack, the label are left out.
+///
+/// Labels are compared ignoring case, and a removal leaves the labels nested below `label`.
+pub fn preview_bulk_label_edit(
+    notes: &[NoteMetadata],
+    rel_paths: &[String],
+    action: BulkLabelAction,
+    label: &str,
+) -> Result<Vec<LabelEditNote>, NotebookError> {
+    let label = normalize_label(label);
+    let key = label.to_lowercase();
+    if label.is_empty() {
+        return Err(NotebookError::validation(
+            BULK_LABEL_CONTEXT,
+            "Enter the label to add or remove.",
+        ));
+    }
+    if rel_paths.is_empty() {
+        return Err(NotebookError::validation(
+            BULK_LABEL_CONTEXT,
+            "Mark the notes to label first.",
+        ));
+    }
+
+    let mut changes: Vec<LabelEditNote> = notes
+        .iter()
+        .filter(|note| rel_paths.contains(&note.rel_path))
+        .filter_map(|note| {
+            let has_label = note
+                .labels
+                .iter()
+                .any(|existing| normalize_label(existing).to_lowercase() == key);
+            let new_labels = match (action, has_label) {
+                (BulkLabelAction::Add, false) => {
+                    let mut labels = note.labels.clone();
+                    labels.push(label.clone());
+                    labels
+                }
+                (BulkLabelAction::Remove, true) => note
+                    .labels
+                    .iter()
+                    .filter(|existing| normalize_label(existing).to_lowercase() != key)
+                    .cloned()
+                    .collect(),
+                _ => return None,
+            };
+            Some(LabelEditNote {
+                rel_path: note.rel_path.clone(),
+                labels: note.labels.clone(),
+                new_labels,
+            })
+        })
+        .collect();
+    changes.sort_by(|left, right| left.rel_path.cmp(&right.rel_path));
+    Ok(changes)
+}
+
+/// Saves the changes of [`preview_bulk_label_edit`] like [`apply_label_edit`] does, leaving
+/// the label registry as it is.
+pub async fn apply_bulk_label_edit(
+    notebook_path: &str,
+    changes: &[LabelEditNote],
+    notes: &mut Vec<NoteMetadata>,
+) -> Result<Vec<String>, NotebookError> {
+    if changes.is_empty() {
+        return Ok(Vec::new());
+    }
+    save_label_changes(notebook_path, BULK_LABEL_CONTEXT, changes, notes, |_| {})
+}
+
+/// Existing labels that complete `input`, best first, leaving out `input` itself and the
+/// labels in `exclude`.
+///
+/// Labels where `input` starts the label or one of its levels come before fuzzy matches; each
+/// group is ranked by the number of notes using the label, counting notes with labels nested
+/// below it.
+pub fn suggest_labels(
+    notes: &[NoteMetadata],
+    input: &str,
+    exclude: &[String],
+    limit: usize,
+) -> Vec<String> {
+    let input = normalize_label(input).to_lowercase();
+    if input.is_empty() {
+        return Vec::new();
+    }
+
+    // Lowercase label -> (spelling first seen, number of notes).
+    let mut counts: HashMap<String, (String, usize)> = HashMap::new();
+    for note in notes {
+        let mut note_labels: HashSet<String> = HashSet::new();
+        for label in &note.labels {
+            let label = normalize_label(label);
+            for path in label_with_ancestors(&label) {
+                if note_labels.insert(path.to_lowercase()) {
+                    counts
+                        .entry(path.to_lowercase())
+                        .or_insert_with(|| (path.to_string(), 0))
+                        .1 += 1;
+                }
+            }
+        }
+    }
+
+    let excluded: HashSet<String> = exclude
+        .iter()
+        .map(|label| normalize_label(label).to_lowercase())
+        .chain(std::iter::once(input.clone()))
+        .collect();
+    let mut ranked: Vec<(bool, usize, f64, String)> = counts
+        .into_iter()
+        .filter(|(key, _)| !excluded.contains(key))
+        .filter_map(|(key, (label, count))| {
+            let is_prefix = key.starts_with(&input)
+                || key
+                    .match_indices(LABEL_SEPARATOR)
+                    .any(|(index, _)| key[index + 1..].starts_with(&input));
+            let score = if is_prefix {
+                1.0
+            } else {
+                fuzzy_word_score(&input, &key)?
+            };
+            Some((is_prefix, count, score, label))
+        })
+        .collect();
+    ranked.sort_by(|left, right| {
+        right
+            .0
+            .cmp(&left.0)
+            .then(right.1.cmp(&left.1))
+            .then(right.2.partial_cmp(&left.2).unwrap_or(Ordering::Equal))
+            .then_with(|| left.3.cmp(&right.3))
+    });
+    ranked
+        .into_iter()
+        .take(limit)
+        .map(|(_, _, _, label)| label)
+        .collect()
+}
+
+/// Moves the registry entries of the labels `preview` edits; a merge keeps the target's own
+/// entries and a delete drops them.
+pub fn apply_label_edit_to_registry(registry: &mut LabelRegistry, preview: &LabelEditPreview) {
+    let source = preview.label.to_lowercase();
+    let moved_keys: Vec<String> = registry
+        .keys()
+        .filter(|key| label_matches(key, &source))
+        .cloned()
+        .collect();
+    for key in moved_keys {
+        let Some(info) = registry.remove(&key) else {
+            continue;
+        };
+        if preview.kind != LabelEditKind::Delete {
+            registry
+                .entry(move_label(&key, &source, &preview.target).to_lowercase())
+                .or_insert(info);
+        }
+    }
+}
+
+/// Parses a `#rgb` or `#rrggbb` label color into its red, green and blue parts.
+pub fn parse_label_color(color: &str) -> Option<[u8; 3]> {
+    let hex = color.trim().strip_prefix('#')?;
+    if !hex.chars().all(|digit| digit.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |digits: &str| u8::from_str_radix(digits, 16).ok();
+    match hex.len() {
+        3 => {
+            let mut rgb = [0; 3];
+            for (part, digit) in rgb.iter_mut().zip(hex.chars()) {
+                *part = channel(&digit.to_string())? * 17;
+            }
+            Some(rgb)
+        }
+        6 => Some([
+            channel(&hex[0..2])?,
+            channel(&hex[2..4])?,
+            channel(&hex[4..6])?,
+        ]),
+        _ => None,
+    }
+}
+
+/// Registry entry of `label`, ignoring case.
+pub fn label_info<'a>(registry: &'a LabelRegistry, label: &str) -> Option<&'a LabelInfo> {
+    registry.get(&normalize_label(label).to_lowercase())
+}
+
+/// Color of `label`, or of the closest label it is nested below that has one.
+pub fn label_color(registry: &LabelRegistry, label: &str) -> Option<[u8; 3]> {
+    let label = normalize_label(label).to_lowercase();
+    label_with_ancestors(&label)
+        .collect::<Vec<&str>>()
+        .into_iter()
+        .rev()
+        .filter_map(|path| registry.get(path))
+        .find_map(|info| info.color.as_deref().and_then(parse_label_color))
+}
+
+/// Stores `info` as the registry entry of `label` in `metadata.json`; an entry with no color,
+/// description or icon removes it.
+///
+/// Colors must be `#rgb` or `#rrggbb` and are stored as lowercase `#rrggbb`.
+pub async fn save_label_info(
+    notebook_path: &str,
+    notes: &[NoteMetadata],
+    registry: &mut LabelRegistry,
+    label: &str,
+    info: LabelInfo,
+) -> Result<(), NotebookError> {
+    let key = normalize_label(label).to_lowercase();
+    if key.is_empty() {
+        return Err(NotebookError::validation(
+            LABEL_INFO_CONTEXT,
+            "Enter the label to style.",
+        ));
+    }
+    let trimmed = |value: Option<String>| {
+        value
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let color = match trimmed(info.color) {
+        Some(color) => {
+            let Some([red, green, blue]) = parse_label_color(&color) else {
+                return Err(NotebookError::validation(
+                    LABEL_INFO_CONTEXT,
+                    format!("'{}' is not a color like #3366ff.", color),
+                ));
+            };
+            Some(format!("#{:02x}{:02x}{:02x}", red, green, blue))
+        }
+        None => None,
+    };
+    let icon = trimmed(info.icon);
+    if icon
+        .as_ref()
+        .is_some_and(|icon| icon.chars().count() > MAX_LABEL_ICON_CHARS)
+    {
+        return Err(NotebookError::validation(
+            LABEL_INFO_CONTEXT,
+            format!(
+                "Label icons are at most {} characters.",
+                MAX_LABEL_ICON_CHARS
+            ),
+        ));
+    }
+    let info = LabelInfo {
+        color,
+        description: trimmed(info.description),
+        icon,
+    };
+
+    let previous_registry = registry.clone();
+    if info == LabelInfo::default() {
+        registry.remove(&key);
+    } else {
+        registry.insert(key, info);
+    }
+
+    if let Err(error) = save_metadata_with(notebook_path, notes, |metadata| {
+        metadata.label_registry = registry.clone();
+    }) {
+        *registry = previous_registry;
+        return Err(error);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_labels_normalize_and_match_their_parents() {
+        assert_eq!(normalize_label(" area / sub//topic/ "), "area/sub/topic");
+        assert_eq!(normalize_label(" / "), "");
+        assert_eq!(
+            label_with_ancestors("area/sub/topic").collect::<Vec<&str>>(),
+            vec!["area", "area/sub", "area/sub/topic"]
+        );
+
+        assert!(label_matches("Area/Sub", "area"));
+        assert!(label_matches("area / sub", "area/sub"));
+        assert!(!label_matches("areas/sub", "area"));
+        assert!(!label_matches("area", "area/sub"));
+    }
+
+    #[test]
+    fn label_colors_parse_and_fall_back_to_parent_labels() {
+        assert_eq!(parse_label_color("#3366FF"), Some([0x33, 0x66, 0xff]));
+        assert_eq!(parse_label_color(" #f0a "), Some([0xff, 0x00, 0xaa]));
+        for invalid in ["3366ff", "#12345", "#ggg", "#+1+2+3"] {
+            assert_eq!(parse_label_color(invalid), None, "{invalid}");
+        }
+
+        let registry = LabelRegistry::from([
+            (
+                "area".to_string(),
+                LabelInfo {
+                    color: Some("#102030".to_string()),
+                    ..LabelInfo::default()
+                },
+            ),
+            (
+                "area/sub".to_string(),
+                LabelInfo {
+                    icon: Some("*".to_string()),
+                    ..LabelInfo::default()
+                },
+            ),
+        ]);
+        assert_eq!(
+            label_color(&registry, "Area/Sub/Topic"),
+            Some([0x10, 0x20, 0x30])
+        );
+        assert_eq!(label_color(&registry, "other"), None);
+        assert!(label_info(&registry, " AREA / sub ").is_some());
+    }
+}
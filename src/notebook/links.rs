@@ -0,0 +1,717 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+use super::NoteBacklink;
+use super::search::note_file_modified_time;
+
+const WIKI_LINK_OPEN: &str = "[[";
+const WIKI_LINK_CLOSE: &str = "]]";
+const BACKLINK_CONTEXT_MAX_CHARS: usize = 120;
+
+/// A `[[target]]` or `[[target|alias]]` reference found in note Markdown.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WikiLink {
+    /// Raw link target as written between the brackets.
+    pub target: String,
+    /// Optional display alias after the `|` separator.
+    pub alias: Option<String>,
+    /// Zero-based line index of the link in the note body.
+    pub line: usize,
+    /// Byte range of the full `[[...]]` reference in the note body.
+    pub range: Range<usize>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct IndexedNoteLinks {
+    links: Vec<WikiLink>,
+    lines: HashMap<usize, String>,
+    modified_time: Option<SystemTime>,
+}
+
+#[derive(Debug, Default)]
+struct NotebookLinkIndex {
+    links_by_source: HashMap<String, IndexedNoteLinks>,
+}
+
+static LINK_INDEXES_BY_NOTEBOOK: OnceLock<Mutex<HashMap<String, NotebookLinkIndex>>> =
+    OnceLock::new();
+
+fn link_indexes() -> &'static Mutex<HashMap<String, NotebookLinkIndex>> {
+    LINK_INDEXES_BY_NOTEBOOK.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn with_link_indexes<R>(f: impl FnOnce(&mut HashMap<String, NotebookLinkIndex>) -> R) -> R {
+    let mut link_indexes = link_indexes()
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(&mut link_indexes)
+}
+
+pub fn clear_link_index_for_notebook(notebook_path: &str) {
+    with_link_indexes(|link_indexes| {
+        link_indexes.remove(notebook_path);
+    });
+}
+
+fn is_code_fence(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with("```") || trimmed.starts_with("~~~")
+}
+
+fn strip_inline_code(line: &str) -> String {
+    let mut stripped = String::with_capacity(line.len());
+    let mut in_code = false;
+    for ch in line.chars() {
+        if ch == '`' {
+            in_code = !in_code;
+        }
+        if ch == '`' || in_code {
+            // Keep byte offsets stable so link ranges map back onto the source line.
+            stripped.extend(std::iter::repeat_n(' ', ch.len_utf8()));
+        } else {
+            stripped.push(ch);
+        }
+    }
+    stripped
+}
+
+fn parse_wiki_link_body(body: &str, line: usize, range: Range<usize>) -> Option<WikiLink> {
+    let (target, alias) = match body.split_once('|') {
+        Some((target, alias)) => (target.trim(), Some(alias.trim())),
+        None => (body.trim(), None),
+    };
+
+    if target.is_empty() {
+        return None;
+    }
+
+    Some(WikiLink {
+        target: target.to_string(),
+        alias: alias.filter(|alias| !alias.is_empty()).map(str::to_string),
+        line,
+        range,
+    })
+}
+
+/// Extracts all wiki-style links from note Markdown, ignoring code spans and fenced blocks.
+pub fn parse_wiki_links(content: &str) -> Vec<WikiLink> {
+    let mut links = Vec::new();
+    let mut in_fenced_block = false;
+    let mut line_start = 0usize;
+
+    for (line_index, raw_line) in content.split_inclusive('\n').enumerate() {
+        let line = raw_line.trim_end_matches(['\n', '\r']);
+        let current_line_start = line_start;
+        line_start += raw_line.len();
+
+        if is_code_fence(line) {
+            in_fenced_block = !in_fenced_block;
+            continue;
+        }
+        if in_fenced_block {
+            continue;
+        }
+
+        let searchable = strip_inline_code(line);
+        let mut offset = 0usize;
+        while let Some(open_index) = searchable[offset..].find(WIKI_LINK_OPEN) {
+            let body_start = offset + open_index + WIKI_LINK_OPEN.len();
+            let Some(close_index) = searchable[body_start..].find(WIKI_LINK_CLOSE) else {
+                break;
+            };
+            let body_end = body_start + close_index;
+            let link_end = body_end + WIKI_LINK_CLOSE.len();
+            let range = current_line_start + offset + open_index..current_line_start + link_end;
+
+            if let Some(link) = parse_wiki_link_body(&line[body_start..body_end], line_index, range)
+            {
+                links.push(link);
+            }
+            offset = link_end;
+        }
+    }
+
+    links
+}
+
+/// Normalizes a written link target into the `rel_path` shape used by metadata.
+pub fn normalize_link_target(target: &str) -> String {
+    let without_anchor = target.split('#').next().unwrap_or_default();
+    let mut normalized = without_anchor.trim().trim_matches('/').to_string();
+    if let Some(stripped) = normalized.strip_suffix("/note.md") {
+        normalized = stripped.to_string();
+    } else if let Some(stripped) = normalized.strip_suffix(".md") {
+        normalized = stripped.to_string();
+    }
+    normalized
+}
+
+/// Resolves a link target against known note paths.
+///
+/// Exact `rel_path` matches win; otherwise a target matching the final path
+/// component of exactly one note resolves to that note.
+pub fn resolve_link_target<'a>(
+    target: &str,
+    note_paths: impl IntoIterator<Item = &'a str>,
+) -> Option<String> {
+    let normalized = normalize_link_target(target);
+    if normalized.is_empty() {
+        return None;
+    }
+
+    let mut suffix_match: Option<&str> = None;
+    let mut suffix_match_is_ambiguous = false;
+    let suffix = format!("/{normalized}");
+
+    for note_path in note_paths {
+        if note_path == normalized {
+            return Some(note_path.to_string());
+        }
+        if note_path.ends_with(&suffix) {
+            if suffix_match.is_some() {
+                suffix_match_is_ambiguous = true;
+            }
+            suffix_match = Some(note_path);
+        }
+    }
+
+    if suffix_match_is_ambiguous {
+        None
+    } else {
+        suffix_match.map(str::to_string)
+    }
+}
+
+/// Returns whether a Markdown link destination carries a URI scheme such as `https:`.
+pub fn is_external_link_destination(destination: &str) -> bool {
+    let Some((scheme, _)) = destination.split_once(':') else {
+        return false;
+    };
+
+    // A single letter is a Windows drive prefix rather than a scheme.
+    scheme.len() > 1
+        && scheme.starts_with(|ch: char| ch.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '+' | '-' | '.'))
+}
+
+/// Finds the byte ranges of inline `[text](dest)` / `![alt](dest)` destinations and of
+/// reference definitions, ignoring code spans and fenced blocks.
+fn parse_markdown_link_destinations(content: &str) -> Vec<Range<usize>> {
+    let mut destinations = Vec::new();
+    let mut in_fenced_block = false;
+    let mut line_start = 0usize;
+
+    for raw_line in content.split_inclusive('\n') {
+        let line = raw_line.trim_end_matches(['\n', '\r']);
+        let current_line_start = line_start;
+        line_start += raw_line.len();
+
+        if is_code_fence(line) {
+            in_fenced_block = !in_fenced_block;
+            continue;
+        }
+        if in_fenced_block {
+            continue;
+        }
+
+        let searchable = strip_inline_code(line);
+
+        let indent = searchable.len() - searchable.trim_start().len();
+        if indent <= 3
+            && searchable[indent..].starts_with('[')
+            && !searchable[indent..].starts_with("[^")
+            && let Some(label_end) = searchable[indent..].find("]:")
+            && let Some(range) = link_destination_range(&searchable, indent + label_end + 2, false)
+        {
+            destinations.push(current_line_start + range.start..current_line_start + range.end);
+            continue;
+        }
+
+        let mut offset = 0usize;
+        while let Some(open_index) = searchable[offset..].find("](") {
+            let destination_start = offset + open_index + 2;
+            match link_destination_range(&searchable, destination_start, true) {
+                Some(range) => {
+                    offset = range.end;
+                    destinations
+                        .push(current_line_start + range.start..current_line_start + range.end);
+                }
+                None => offset = destination_start,
+            }
+        }
+    }
+
+    destinations
+}
+
+fn link_destination_range(line: &str, start: usize, inline: bool) -> Option<Range<usize>> {
+    let leading_ws = line[start..].len() - line[start..].trim_start().len();
+    let start = start + leading_ws;
+    let rest = &line[start..];
+
+    if let Some(bracketed) = rest.strip_prefix('<') {
+        let end = bracketed.find('>')?;
+        return (end > 0).then(|| start + 1..start + 1 + end);
+    }
+
+    let mut depth = 0usize;
+    let mut end = rest.len();
+    for (index, ch) in rest.char_indices() {
+        match ch {
+            ' ' | '\t' => {
+                end = index;
+                break;
+            }
+            '(' => depth += 1,
+            ')' if depth == 0 && inline => {
+                end = index;
+                break;
+            }
+            ')' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    (end > 0).then(|| start..start + end)
+}
+
+fn map_moved_path(path: &str, moved_from: &str, moved_to: &str) -> String {
+    if path == moved_from {
+        return moved_to.to_string();
+    }
+    match path.strip_prefix(moved_from) {
+        Some(rest) if rest.starts_with('/') => format!("{moved_to}{rest}"),
+        _ => path.to_string(),
+    }
+}
+
+/// Resolves a link path against a note directory, returning `None` when it escapes the notebook.
+///
+/// Notes live in `<rel_path>/note.md`, so relative paths start from the note's own directory
+/// and a leading `/` starts from the notebook root.
+pub fn resolve_relative_link_path(path: &str, note_dir: &str) -> Option<String> {
+    let mut segments: Vec<&str> = Vec::new();
+    if !path.starts_with('/') {
+        segments.extend(note_dir.split('/').filter(|part| !part.is_empty()));
+    }
+
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop()?;
+            }
+            other => segments.push(other),
+        }
+    }
+
+    Some(segments.join("/"))
+}
+
+fn relative_notebook_path(from_dir: &str, to_path: &str) -> String {
+    let from: Vec<&str> = from_dir
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect();
+    let to: Vec<&str> = to_path.split('/').filter(|part| !part.is_empty()).collect();
+    let shared = from
+        .iter()
+        .zip(to.iter())
+        .take_while(|(left, right)| left == right)
+        .count();
+
+    let mut parts: Vec<&str> = std::iter::repeat_n("..", from.len() - shared).collect();
+    parts.extend(&to[shared..]);
+    if parts.is_empty() {
+        ".".to_string()
+    } else {
+        parts.join("/")
+    }
+}
+
+pub(super) fn decode_link_path(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0usize;
+    while index < bytes.len() {
+        let escaped = if bytes[index] == b'%' {
+            path.get(index + 1..index + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        } else {
+            None
+        };
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).unwrap_or_else(|_| path.to_string())
+}
+
+fn rewrite_markdown_destination(
+    destination: &str,
+    bracketed: bool,
+    source_from: &str,
+    source_to: &str,
+    moved_from: &str,
+    moved_to: &str,
+) -> Option<String> {
+    if destination.is_empty()
+        || destination.starts_with('#')
+        || is_external_link_destination(destination)
+    {
+        return None;
+    }
+
+    let suffix_start = destination.find(['#', '?']).unwrap_or(destination.len());
+    let (path, suffix) = destination.split_at(suffix_start);
+    let decoded = decode_link_path(path);
+    let is_absolute = decoded.starts_with('/');
+
+    let old_target = resolve_relative_link_path(&decoded, source_from)?;
+    let new_target = map_moved_path(&old_target, moved_from, moved_to);
+    if new_target == old_target && (is_absolute || source_from == source_to) {
+        return None;
+    }
+
+    let mut new_path = if is_absolute {
+        format!("/{new_target}")
+    } else {
+        relative_notebook_path(source_to, &new_target)
+    };
+    if decoded.ends_with('/') && !new_path.ends_with('/') {
+        new_path.push('/');
+    }
+    if new_path == decoded {
+        return None;
+    }
+
+    if !bracketed {
+        new_path = new_path.replace('%', "%25").replace(' ', "%20");
+    }
+    Some(format!("{new_path}{suffix}"))
+}
+
+/// Rewrites the links in one note after `moved_from` was moved to `moved_to`.
+///
+/// `source_from` is where the note lived before the move. Wiki links are re-resolved against
+/// the note paths before and after the move, and relative Markdown destinations (including
+/// embedded images) are recomputed from the note's new directory. Returns `None` when nothing
+/// changes.
+pub(super) fn rewrite_note_links_for_move(
+    content: &str,
+    source_from: &str,
+    moved_from: &str,
+    moved_to: &str,
+    old_note_paths: &[&str],
+    new_note_paths: &[&str],
+) -> Option<String> {
+    let source_to = map_moved_path(source_from, moved_from, moved_to);
+    let mut replacements: Vec<(Range<usize>, String)> = Vec::new();
+
+    for link in parse_wiki_links(content) {
+        let Some(old_target) = resolve_link_target(&link.target, old_note_paths.iter().copied())
+        else {
+            continue;
+        };
+        let new_target = map_moved_path(&old_target, moved_from, moved_to);
+        if resolve_link_target(&link.target, new_note_paths.iter().copied()).as_deref()
+            == Some(new_target.as_str())
+        {
+            continue;
+        }
+
+        let mut rewritten = format!("[[{new_target}");
+        if let Some((_, anchor)) = link.target.split_once('#') {
+            rewritten.push('#');
+            rewritten.push_str(anchor);
+        }
+        if let Some(alias) = &link.alias {
+            rewritten.push('|');
+            rewritten.push_str(alias);
+        }
+        rewritten.push_str("]]");
+        replacements.push((link.range, rewritten));
+    }
+
+    for range in parse_markdown_link_destinations(content) {
+        let bracketed = range.start > 0 && content.as_bytes()[range.start - 1] == b'<';
+        if let Some(rewritten) = rewrite_markdown_destination(
+            &content[range.clone()],
+            bracketed,
+            source_from,
+            &source_to,
+            moved_from,
+            moved_to,
+        ) {
+            replacements.push((range, rewritten));
+        }
+    }
+
+    if replacements.is_empty() {
+        return None;
+    }
+
+    replacements.sort_by_key(|(range, _)| range.start);
+    let mut rewritten = String::with_capacity(content.len());
+    let mut cursor = 0usize;
+    for (range, replacement) in replacements {
+        if range.start < cursor {
+            continue;
+        }
+        rewritten.push_str(&content[cursor..range.start]);
+        rewritten.push_str(&replacement);
+        cursor = range.end;
+    }
+    rewritten.push_str(&content[cursor..]);
+
+    Some(rewritten)
+}
+
+fn truncate_backlink_context(line: &str) -> String {
+    let trimmed = line.trim();
+    if trimmed.chars().count() <= BACKLINK_CONTEXT_MAX_CHARS {
+        trimmed.to_string()
+    } else {
+        let mut truncated: String = trimmed.chars().take(BACKLINK_CONTEXT_MAX_CHARS).collect();
+        truncated.push_str("...");
+        truncated
+    }
+}
+
+fn index_note_links(content: &str, modified_time: Option<SystemTime>) -> IndexedNoteLinks {
+    let links = parse_wiki_links(content);
+    let link_lines: HashSet<usize> = links.iter().map(|link| link.line).collect();
+    let lines = content
+        .lines()
+        .enumerate()
+        .filter(|(line_index, _)| link_lines.contains(line_index))
+        .map(|(line_index, line)| (line_index, truncate_backlink_context(line)))
+        .collect();
+
+    IndexedNoteLinks {
+        links,
+        lines,
+        modified_time,
+    }
+}
+
+fn read_note_links_for_index(notebook_path: &str, rel_path: &str) -> IndexedNoteLinks {
+    let note_file_path = Path::new(notebook_path).join(rel_path).join("note.md");
+    let content = fs::read_to_string(&note_file_path).unwrap_or_default();
+    index_note_links(&content, note_file_modified_time(&note_file_path))
+}
+
+pub(super) fn cache_upsert_note_links(
+    notebook_path: &str,
+    rel_path: &str,
+    content: &str,
+    modified_time: Option<SystemTime>,
+) {
+    let indexed = index_note_links(content, modified_time);
+    with_link_indexes(|link_indexes| {
+        link_indexes
+            .entry(notebook_path.to_string())
+            .or_default()
+            .links_by_source
+            .insert(rel_path.to_string(), indexed);
+    });
+}
+
+pub(super) fn cache_remove_note_links(notebook_path: &str, rel_path: &str) {
+    with_link_indexes(|link_indexes| {
+        let Some(index) = link_indexes.get_mut(notebook_path) else {
+            return;
+        };
+
+        let prefix = format!("{rel_path}/");
+        index
+            .links_by_source
+            .retain(|path, _| path != rel_path && !path.starts_with(&prefix));
+    });
+}
+
+pub(super) fn cache_rename_note_links(notebook_path: &str, from_rel_path: &str, to_rel_path: &str) {
+    with_link_indexes(|link_indexes| {
+        let Some(index) = link_indexes.get_mut(notebook_path) else {
+            return;
+        };
+
+        let from_prefix = format!("{from_rel_path}/");
+        let renamed_paths: Vec<(String, String)> = index
+            .links_by_source
+            .keys()
+            .filter_map(|path| {
+                if path == from_rel_path {
+                    Some((path.clone(), to_rel_path.to_string()))
+                } else {
+                    path.strip_prefix(&from_prefix)
+                        .map(|suffix| (path.clone(), format!("{to_rel_path}/{suffix}")))
+                }
+            })
+            .collect();
+
+        for (old_path, new_path) in renamed_paths {
+            if let Some(entry) = index.links_by_source.remove(&old_path) {
+                index.links_by_source.insert(new_path, entry);
+            }
+        }
+    });
+}
+
+/// Returns notes whose wiki links resolve to `target_rel_path`, sorted by source path.
+pub async fn load_backlinks(
+    notebook_path: String,
+    note_paths: Vec<String>,
+    target_rel_path: String,
+) -> Vec<NoteBacklink> {
+    let known_paths: HashSet<&str> = note_paths.iter().map(String::as_str).collect();
+    let cached_modified_times: HashMap<String, Option<SystemTime>> =
+        with_link_indexes(|link_indexes| {
+            let index = link_indexes.entry(notebook_path.clone()).or_default();
+            index
+                .links_by_source
+                .retain(|path, _| known_paths.contains(path.as_str()));
+            index
+                .links_by_source
+                .iter()
+                .map(|(path, indexed)| (path.clone(), indexed.modified_time))
+                .collect()
+        });
+
+    let stale_entries: Vec<(String, IndexedNoteLinks)> = note_paths
+        .iter()
+        .filter(|rel_path| {
+            let note_file_path = Path::new(&notebook_path).join(rel_path).join("note.md");
+            cached_modified_times
+                .get(rel_path.as_str())
+                .is_none_or(|cached| *cached != note_file_modified_time(&note_file_path))
+        })
+        .map(|rel_path| {
+            let indexed = read_note_links_for_index(&notebook_path, rel_path);
+            (rel_path.clone(), indexed)
+        })
+        .collect();
+
+    with_link_indexes(|link_indexes| {
+        let index = link_indexes.entry(notebook_path.clone()).or_default();
+        for (rel_path, indexed) in stale_entries {
+            index.links_by_source.insert(rel_path, indexed);
+        }
+
+        let mut backlinks = Vec::new();
+        for (source_path, indexed) in &index.links_by_source {
+            if source_path == &target_rel_path {
+                continue;
+            }
+
+            let linking_line = indexed.links.iter().find(|link| {
+                resolve_link_target(&link.target, known_paths.iter().copied()).as_deref()
+                    == Some(target_rel_path.as_str())
+            });
+
+            if let Some(link) = linking_line {
+                backlinks.push(NoteBacklink {
+                    rel_path: source_path.clone(),
+                    context: indexed.lines.get(&link.line).cloned().unwrap_or_default(),
+                });
+            }
+        }
+
+        backlinks.sort_by(|left, right| left.rel_path.cmp(&right.rel_path));
+        backlinks
+    })
+}
+
+#[cfg(test)]
+mod wiki_link_parser_tests {
+    use super::*;
+
+    #[test]
+    fn parse_wiki_links_reads_targets_aliases_and_ranges() {
+        let content = "See [[work/todo]] and [[ideas|my ideas]].\nNext [[x]]";
+        let links = parse_wiki_links(content);
+
+        let summary: Vec<(&str, Option<&str>, usize, &str)> = links
+            .iter()
+            .map(|link| {
+                (
+                    link.target.as_str(),
+                    link.alias.as_deref(),
+                    link.line,
+                    &content[link.range.clone()],
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("work/todo", None, 0, "[[work/todo]]"),
+                ("ideas", Some("my ideas"), 0, "[[ideas|my ideas]]"),
+                ("x", None, 1, "[[x]]"),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_wiki_links_ignores_code_spans_and_fenced_blocks() {
+        let content = "`[[inline]]` [[real]]\n```\n[[fenced]]\n```\n[[after]]";
+        let targets: Vec<String> = parse_wiki_links(content)
+            .into_iter()
+            .map(|link| link.target)
+            .collect();
+
+        assert_eq!(targets, vec!["real".to_string(), "after".to_string()]);
+    }
+
+    #[test]
+    fn resolve_link_target_prefers_exact_path_and_rejects_ambiguous_names() {
+        let paths = ["a/note", "b/note", "work/todo", "todo"];
+
+        assert_eq!(
+            resolve_link_target("work/todo", paths),
+            Some("work/todo".to_string())
+        );
+        assert_eq!(resolve_link_target("todo", paths), Some("todo".to_string()));
+        assert_eq!(resolve_link_target("note", paths), None);
+        assert_eq!(
+            resolve_link_target("/a/note/note.md", paths),
+            Some("a/note".to_string())
+        );
+    }
+
+    #[test]
+    fn rewrite_note_links_for_move_handles_reference_definitions_and_skips_urls() {
+        let content = "[ref]: ../old/note.md \"Title\"\n\
+                       [web](https://example.com/old) [spaced](<../old/my file.png>)\n\
+                       ```\n[code](../old)\n```";
+        let rewritten = rewrite_note_links_for_move(
+            content,
+            "home",
+            "old",
+            "new place",
+            &["home", "old"],
+            &["home", "new place"],
+        )
+        .expect("Expected links to be rewritten");
+
+        assert_eq!(
+            rewritten,
+            "[ref]: ../new%20place/note.md \"Title\"\n\
+             [web](https://example.com/old) [spaced](<../new place/my file.png>)\n\
+             ```\n[code](../old)\n```"
+        );
+    }
+}
@@ -3,15 +3,29 @@ use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use super::links::{
+    cache_remove_note_links, cache_rename_note_links, cache_upsert_note_links,
+    rewrite_note_links_for_move,
+};
+use super::replace::{
+    RewrittenNote, cache_rewritten_notes, rollback_rewritten_notes, write_rewritten_notes,
+};
 use super::search::{
     cache_remove_search_index_entries, cache_rename_search_index_entries,
     cache_upsert_search_index_note_content, note_file_modified_time,
 };
 use super::storage::{current_timestamp_rfc3339, save_metadata, write_text_file_atomically};
-use super::{NoteMetadata, NotebookError, NotebookErrorKind, NotebookRelativePath};
+use super::trash::{discard_trash_entry, prepare_trash_entry, trashed_note_dir};
+use super::watcher::remember_file_content;
+use super::{
+    MovedNote, NoteMetadata, NotebookError, NotebookErrorKind, NotebookRelativePath,
+    RewrittenLinksNote,
+};
 
 const FAIL_DELETE_ROLLBACK_MARKER: &str = ".cognate_fail_delete_rollback";
 const FAIL_MOVE_ROLLBACK_MARKER: &str = ".cognate_fail_move_rollback";
+#[cfg(test)]
+const FAIL_TRASH_MOVE_MARKER: &str = ".cognate_fail_trash_move";
 
 fn contextualize_error(
     error: NotebookError,
@@ -241,10 +255,128 @@ fn rollback_rename(
     })
 }
 
+fn move_staged_delete_into_trash(
+    staged_path: &Path,
+    entry_dir: &Path,
+    _notebook_root: &Path,
+) -> Result<(), NotebookError> {
+    #[cfg(test)]
+    if _notebook_root.join(FAIL_TRASH_MOVE_MARKER).exists() {
+        return Err(NotebookError::storage(
+            "delete note",
+            format!(
+                "simulated failure moving '{}' into the trash",
+                staged_path.display()
+            ),
+        ));
+    }
+
+    fs::rename(staged_path, trashed_note_dir(entry_dir)).map_err(|error| {
+        NotebookError::storage(
+            "delete note",
+            format!(
+                "Failed to move '{}' into the trash: {}",
+                staged_path.display(),
+                error
+            ),
+        )
+    })
+}
+
+/// Rewrites links in every note so they follow a move that already happened on disk.
+///
+/// `previous_notes` and `notes` must describe the same notes before and after
+/// `update_metadata_paths_for_move`. On failure, files rewritten so far are restored.
+fn rewrite_links_for_move(
+    notebook_root: &Path,
+    previous_notes: &[NoteMetadata],
+    notes: &[NoteMetadata],
+    moved_from: &str,
+    moved_to: &str,
+) -> Result<Vec<RewrittenNote>, NotebookError> {
+    let old_note_paths: Vec<&str> = previous_notes
+        .iter()
+        .map(|note| note.rel_path.as_str())
+        .collect();
+    let new_note_paths: Vec<&str> = notes.iter().map(|note| note.rel_path.as_str()).collect();
+    let mut planned: Vec<RewrittenNote> = Vec::new();
+
+    for (previous_note, note) in previous_notes.iter().zip(notes) {
+        let note_file_path = notebook_root.join(&note.rel_path).join("note.md");
+        let previous_content = match fs::read_to_string(&note_file_path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == ErrorKind::NotFound => continue,
+            Err(error) => {
+                return Err(NotebookError::storage(
+                    "rewrite links",
+                    format!(
+                        "Failed to read note '{}' while rewriting links: {}",
+                        note.rel_path, error
+                    ),
+                ));
+            }
+        };
+
+        let Some(content) = rewrite_note_links_for_move(
+            &previous_content,
+            &previous_note.rel_path,
+            moved_from,
+            moved_to,
+            &old_note_paths,
+            &new_note_paths,
+        ) else {
+            continue;
+        };
+
+        planned.push(RewrittenNote {
+            rel_path: note.rel_path.clone(),
+            note_file_path,
+            previous_content,
+            content,
+        });
+    }
+
+    write_rewritten_notes("rewrite links", planned)
+}
+
+fn rollback_failed_move(
+    error: NotebookError,
+    new_fs_path: &Path,
+    current_fs_path: &Path,
+    notebook_root: &Path,
+) -> NotebookError {
+    match rollback_rename(
+        new_fs_path,
+        current_fs_path,
+        notebook_root,
+        FAIL_MOVE_ROLLBACK_MARKER,
+    ) {
+        Ok(()) => error,
+        Err(rollback_error) => NotebookError::recovery(
+            "move note rollback",
+            format!(
+                "{} Rollback failed while restoring filesystem state: {}",
+                error, rollback_error
+            ),
+        ),
+    }
+}
+
 pub async fn create_new_note(
     notebook_path: &str,
     rel_path: &str,
     notes: &mut Vec<NoteMetadata>,
+) -> Result<NoteMetadata, NotebookError> {
+    create_note_with_content(notebook_path, rel_path, "", Vec::new(), notes)
+}
+
+/// Creates a note whose `note.md` starts out as `content`, with `labels` in its metadata.
+pub(super) fn create_note_with_content(
+    notebook_path: &str,
+    rel_path: &str,
+    content: &str,
+    labels: Vec<String>,
+    notes: &mut Vec<NoteMetadata>,
 ) -> Result<NoteMetadata, NotebookError> {
     let rel_path = NotebookRelativePath::parse("relative path", rel_path)?;
     #[cfg(debug_assertions)]
@@ -291,7 +423,7 @@ pub async fn create_new_note(
         ));
     }
 
-    if let Err(error) = write_text_file_atomically(&note_file_path, "") {
+    if let Err(error) = write_text_file_atomically(&note_file_path, content) {
         let _ = fs::remove_dir_all(&note_dir_path);
         return Err(contextualize_error(
             error,
@@ -302,8 +434,9 @@ pub async fn create_new_note(
 
     let new_note_metadata = NoteMetadata {
         rel_path: rel_path.as_str().to_string(),
-        labels: Vec::new(),
+        labels,
         last_updated: Some(current_timestamp_rfc3339()),
+        ..NoteMetadata::default()
     };
 
     let previous_notes = notes.clone();
@@ -336,11 +469,14 @@ pub async fn create_new_note(
 
     #[cfg(debug_assertions)]
     eprintln!("New note created successfully: {}", rel_path.as_str());
+    remember_file_content(&note_file_path, content);
+    let modified_time = note_file_modified_time(&note_file_path);
+    cache_upsert_note_links(notebook_path, rel_path.as_str(), content, modified_time);
     cache_upsert_search_index_note_content(
         notebook_path,
         rel_path.as_str(),
-        "",
-        note_file_modified_time(&note_file_path),
+        content,
+        modified_time,
     );
     Ok(new_note_metadata)
 }
@@ -399,6 +535,10 @@ pub async fn delete_note(
     }
 
     let previous_notes = notes.clone();
+    let trashed_metadata = notes
+        .iter()
+        .find(|note| note.rel_path == rel_path.as_str())
+        .cloned();
     let metadata_changed = remove_note_from_metadata(notes, &rel_path);
 
     if !metadata_changed {
@@ -410,6 +550,7 @@ pub async fn delete_note(
     }
 
     let mut staged_delete_path: Option<PathBuf> = None;
+    let mut trash_entry_dir: Option<PathBuf> = None;
 
     if note_dir_path.exists() {
         let transaction_path =
@@ -428,6 +569,32 @@ pub async fn delete_note(
             ));
         }
 
+        match prepare_trash_entry(full_notebook_path, &rel_path, trashed_metadata.as_ref()) {
+            Ok(entry_dir) => trash_entry_dir = Some(entry_dir),
+            Err(trash_error) => {
+                *notes = previous_notes;
+                if let Err(rollback_error) = rollback_rename(
+                    &transaction_path,
+                    &note_dir_path,
+                    full_notebook_path,
+                    FAIL_DELETE_ROLLBACK_MARKER,
+                ) {
+                    return Err(NotebookError::recovery(
+                        "delete note rollback",
+                        format!(
+                            "{} Rollback failed while restoring filesystem state: {}",
+                            trash_error, rollback_error
+                        ),
+                    ));
+                }
+                return Err(contextualize_error(
+                    trash_error,
+                    "delete note",
+                    "Failed to create trash entry",
+                ));
+            }
+        }
+
         staged_delete_path = Some(transaction_path);
         #[cfg(debug_assertions)]
         eprintln!(
@@ -450,6 +617,9 @@ pub async fn delete_note(
         &rel_path,
     ) {
         *notes = previous_notes;
+        if let Some(entry_dir) = &trash_entry_dir {
+            discard_trash_entry(entry_dir);
+        }
 
         if let Some(staged_path) = staged_delete_path
             && let Err(rollback_error) = rollback_rename(
@@ -471,19 +641,50 @@ pub async fn delete_note(
         return Err(metadata_error);
     }
 
+    // A staged delete left outside the trash would later be removed for good by the stale
+    // entry cleanup, so a failed move puts the note and its metadata back instead.
     if let Some(staged_path) = staged_delete_path
-        && let Err(_e) = fs::remove_dir_all(&staged_path)
+        && let Some(entry_dir) = &trash_entry_dir
+        && let Err(trash_error) =
+            move_staged_delete_into_trash(&staged_path, entry_dir, full_notebook_path)
     {
-        #[cfg(debug_assertions)]
-        eprintln!(
-            "Warning: Metadata commit succeeded, but failed to finalize staged deletion '{}': {}",
-            staged_path.display(),
-            _e
-        );
+        *notes = previous_notes;
+        discard_trash_entry(entry_dir);
+        let mut rollback_errors = Vec::new();
+        if let Err(rollback_error) = rollback_rename(
+            &staged_path,
+            &note_dir_path,
+            full_notebook_path,
+            FAIL_DELETE_ROLLBACK_MARKER,
+        ) {
+            rollback_errors.push(rollback_error.to_string());
+        }
+        if let Err(metadata_error) = persist_metadata_if_changed(
+            notebook_path,
+            notes,
+            metadata_changed,
+            "restoring a deleted item",
+            &rel_path,
+        ) {
+            rollback_errors.push(metadata_error.to_string());
+        }
+
+        if !rollback_errors.is_empty() {
+            return Err(NotebookError::recovery(
+                "delete note rollback",
+                format!(
+                    "{} Rollback failed while restoring filesystem state: {}",
+                    trash_error,
+                    rollback_errors.join("; ")
+                ),
+            ));
+        }
+        return Err(trash_error);
     }
 
     remove_empty_parent_directories(full_notebook_path, &note_dir_path);
     cache_remove_search_index_entries(notebook_path, rel_path.as_str());
+    cache_remove_note_links(notebook_path, rel_path.as_str());
 
     #[cfg(debug_assertions)]
     eprintln!("Deletion process completed for: {}", rel_path.as_str());
@@ -495,7 +696,7 @@ pub async fn move_note(
     current_rel_path: &str,
     new_rel_path: &str,
     notes: &mut Vec<NoteMetadata>,
-) -> Result<String, NotebookError> {
+) -> Result<MovedNote, NotebookError> {
     let current_rel_path = NotebookRelativePath::parse("current relative path", current_rel_path)?;
     let new_rel_path = NotebookRelativePath::parse("new relative path", new_rel_path)?;
     #[cfg(debug_assertions)]
@@ -644,6 +845,25 @@ pub async fn move_note(
         eprintln!("Updated metadata for notes within the moved/renamed folder.");
     }
 
+    let rewritten_links = match rewrite_links_for_move(
+        full_notebook_path,
+        &previous_notes,
+        notes,
+        current_rel_path.as_str(),
+        new_rel_path.as_str(),
+    ) {
+        Ok(rewritten_links) => rewritten_links,
+        Err(rewrite_error) => {
+            *notes = previous_notes;
+            return Err(rollback_failed_move(
+                rewrite_error,
+                &new_fs_path,
+                &current_fs_path,
+                full_notebook_path,
+            ));
+        }
+    };
+
     if let Err(metadata_error) = persist_metadata_if_changed(
         notebook_path,
         notes,
@@ -652,21 +872,14 @@ pub async fn move_note(
         &current_rel_path,
     ) {
         *notes = previous_notes;
-        if let Err(rollback_error) = rollback_rename(
+        let metadata_error =
+            rollback_rewritten_notes("move note rollback", metadata_error, &rewritten_links);
+        return Err(rollback_failed_move(
+            metadata_error,
             &new_fs_path,
             &current_fs_path,
             full_notebook_path,
-            FAIL_MOVE_ROLLBACK_MARKER,
-        ) {
-            return Err(NotebookError::recovery(
-                "move note rollback",
-                format!(
-                    "{} Rollback failed while restoring filesystem state: {}",
-                    metadata_error, rollback_error
-                ),
-            ));
-        }
-        return Err(metadata_error);
+        ));
     }
 
     cache_rename_search_index_entries(
@@ -674,11 +887,27 @@ pub async fn move_note(
         current_rel_path.as_str(),
         new_rel_path.as_str(),
     );
+    cache_rename_note_links(
+        notebook_path,
+        current_rel_path.as_str(),
+        new_rel_path.as_str(),
+    );
+    cache_rewritten_notes(notebook_path, &rewritten_links);
 
     #[cfg(debug_assertions)]
     eprintln!(
         "Move/Rename process completed. New path: {}",
         new_rel_path.as_str()
     );
-    Ok(new_rel_path.into_string())
+    Ok(MovedNote {
+        rel_path: new_rel_path.into_string(),
+        rewritten_notes: rewritten_links
+            .into_iter()
+            .map(|note| RewrittenLinksNote {
+                rel_path: note.rel_path,
+                previous_content: note.previous_content,
+                content: note.content,
+            })
+            .collect(),
+    })
 }
@@ -0,0 +1,416 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+use time::{Date, Month};
+
+use super::NotebookError;
+use super::labels::normalize_label;
+use super::search::tokenize;
+
+/// How an `updated:` filter compares a note's last update date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum DateComparison {
+    Before,
+    OnOrBefore,
+    On,
+    OnOrAfter,
+    After,
+}
+
+impl DateComparison {
+    pub(super) fn matches(self, date: Date, bound: Date) -> bool {
+        match self {
+            Self::Before => date < bound,
+            Self::OnOrBefore => date <= bound,
+            Self::On => date == bound,
+            Self::OnOrAfter => date >= bound,
+            Self::After => date > bound,
+        }
+    }
+}
+
+/// Parsed search query; text is already lowercased and split into index terms.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum QueryExpr {
+    /// A single term matched against note bodies, paths and labels.
+    Term(String),
+    /// Consecutive terms from a quoted phrase or a word like `well-known`.
+    Phrase(Vec<String>),
+    /// `label:` filter; matches a label or any label nested below it, ignoring case.
+    Label(String),
+    /// `path:` filter; matches notes whose path starts with the value, ignoring case.
+    Path(String),
+    /// `updated:` filter on the date part of the note's last update.
+    Updated(DateComparison, Date),
+    Not(Box<QueryExpr>),
+    And(Vec<QueryExpr>),
+    Or(Vec<QueryExpr>),
+}
+
+impl QueryExpr {
+    fn from_text(text: &str) -> Option<Self> {
+        let mut terms: Vec<String> = tokenize(text).collect();
+        match terms.len() {
+            0 => None,
+            1 => terms.pop().map(Self::Term),
+            _ => Some(Self::Phrase(terms)),
+        }
+    }
+
+    /// Calls `visit` for every term, phrase and filter, with whether it sits under a negation.
+    pub(super) fn visit_clauses<'a>(
+        &'a self,
+        negated: bool,
+        visit: &mut impl FnMut(&'a Self, bool),
+    ) {
+        match self {
+            Self::Not(inner) => inner.visit_clauses(!negated, visit),
+            Self::And(items) | Self::Or(items) => {
+                for item in items {
+                    item.visit_clauses(negated, visit);
+                }
+            }
+            _ => visit(self, negated),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum QueryToken {
+    OpenParen,
+    CloseParen,
+    And,
+    Or,
+    Not,
+    Clause(QueryExpr),
+}
+
+fn query_error(message: impl Into<String>) -> NotebookError {
+    NotebookError::validation("search query", message)
+}
+
+fn read_quoted(chars: &mut Peekable<Chars<'_>>) -> Result<String, NotebookError> {
+    let mut quoted = String::new();
+    for ch in chars.by_ref() {
+        if ch == '"' {
+            return Ok(quoted);
+        }
+        quoted.push(ch);
+    }
+    Err(query_error("Unterminated quote in search query."))
+}
+
+fn parse_date(value: &str) -> Option<Date> {
+    let mut parts = value.splitn(3, '-');
+    let year = parts.next()?.parse::<i32>().ok()?;
+    let month = Month::try_from(parts.next()?.parse::<u8>().ok()?).ok()?;
+    let day = parts.next()?.parse::<u8>().ok()?;
+    Date::from_calendar_date(year, month, day).ok()
+}
+
+fn parse_updated_filter(value: &str) -> Result<QueryExpr, NotebookError> {
+    let (comparison, date_text) = if let Some(rest) = value.strip_prefix(">=") {
+        (DateComparison::OnOrAfter, rest)
+    } else if let Some(rest) = value.strip_prefix("<=") {
+        (DateComparison::OnOrBefore, rest)
+    } else if let Some(rest) = value.strip_prefix('>') {
+        (DateComparison::After, rest)
+    } else if let Some(rest) = value.strip_prefix('<') {
+        (DateComparison::Before, rest)
+    } else {
+        (DateComparison::On, value.strip_prefix('=').unwrap_or(value))
+    };
+
+    let date = parse_date(date_text).ok_or_else(|| {
+        query_error(format!(
+            "Invalid date '{}' in 'updated:' filter. Use YYYY-MM-DD, optionally after >, >=, < or <=.",
+            date_text
+        ))
+    })?;
+    Ok(QueryExpr::Updated(comparison, date))
+}
+
+/// Turns one unquoted word into a token; words without any searchable text are dropped.
+fn classify_word(
+    word: &str,
+    quoted_value: Option<String>,
+) -> Result<Option<QueryToken>, NotebookError> {
+    if quoted_value.is_none() {
+        match word {
+            "AND" => return Ok(Some(QueryToken::And)),
+            "OR" => return Ok(Some(QueryToken::Or)),
+            "NOT" => return Ok(Some(QueryToken::Not)),
+            _ => {}
+        }
+    }
+
+    let field = word
+        .split_once(':')
+        .map(|(field, value)| (field.to_lowercase(), value));
+    let (field, inline_value) = match field {
+        Some((field, value)) if matches!(field.as_str(), "label" | "path" | "updated") => {
+            (field, value)
+        }
+        _ => {
+            let text = match &quoted_value {
+                Some(quoted) => format!("{word} {quoted}"),
+                None => word.to_string(),
+            };
+            return Ok(QueryExpr::from_text(&text).map(QueryToken::Clause));
+        }
+    };
+
+    let value = quoted_value.unwrap_or_else(|| inline_value.to_string());
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(query_error(format!(
+            "Search filter '{}:' needs a value.",
+            field
+        )));
+    }
+
+    let clause = match field.as_str() {
+        "label" => QueryExpr::Label(normalize_label(value).to_lowercase()),
+        "path" => QueryExpr::Path(value.to_lowercase()),
+        _ => parse_updated_filter(value)?,
+    };
+    Ok(Some(QueryToken::Clause(clause)))
+}
+
+fn lex_query(query: &str) -> Result<Vec<QueryToken>, NotebookError> {
+    let mut tokens = Vec::new();
+    let mut chars = query.chars().peekable();
+
+    while let Some(&ch) = chars.peek() {
+        match ch {
+            _ if ch.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(QueryToken::OpenParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(QueryToken::CloseParen);
+            }
+            '"' => {
+                chars.next();
+                let phrase = read_quoted(&mut chars)?;
+                if let Some(clause) = QueryExpr::from_text(&phrase) {
+                    tokens.push(QueryToken::Clause(clause));
+                }
+            }
+            '-' => {
+                chars.next();
+                if chars
+                    .peek()
+                    .is_some_and(|next| !next.is_whitespace() && *next != ')')
+                {
+                    tokens.push(QueryToken::Not);
+                }
+            }
+            _ => {
+                let mut word = String::new();
+                let mut quoted_value = None;
+                while let Some(&ch) = chars.peek() {
+                    if ch.is_whitespace() || ch == '(' || ch == ')' {
+                        break;
+                    }
+                    chars.next();
+                    if ch == '"' && word.ends_with(':') && quoted_value.is_none() {
+                        quoted_value = Some(read_quoted(&mut chars)?);
+                    } else {
+                        word.push(ch);
+                    }
+                }
+                if let Some(token) = classify_word(&word, quoted_value)? {
+                    tokens.push(token);
+                }
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct QueryParser {
+    tokens: Vec<QueryToken>,
+    position: usize,
+}
+
+impl QueryParser {
+    fn peek(&self) -> Option<&QueryToken> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<QueryToken> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<QueryExpr, NotebookError> {
+        let mut items = vec![self.parse_and()?];
+        while self.peek() == Some(&QueryToken::Or) {
+            self.next();
+            self.expect_operand("OR")?;
+            items.push(self.parse_and()?);
+        }
+        Ok(if items.len() == 1 {
+            items.remove(0)
+        } else {
+            QueryExpr::Or(items)
+        })
+    }
+
+    /// Fails unless another operand follows `operator`.
+    fn expect_operand(&self, operator: &str) -> Result<(), NotebookError> {
+        match self.peek() {
+            None | Some(QueryToken::CloseParen | QueryToken::And | QueryToken::Or) => Err(
+                query_error(format!("Expected a search term after {}.", operator)),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    fn parse_and(&mut self) -> Result<QueryExpr, NotebookError> {
+        let mut items = vec![self.parse_unary()?];
+        loop {
+            match self.peek() {
+                Some(QueryToken::And) => {
+                    self.next();
+                    self.expect_operand("AND")?;
+                    items.push(self.parse_unary()?);
+                }
+                None | Some(QueryToken::Or | QueryToken::CloseParen) => break,
+                Some(_) => items.push(self.parse_unary()?),
+            }
+        }
+        Ok(if items.len() == 1 {
+            items.remove(0)
+        } else {
+            QueryExpr::And(items)
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<QueryExpr, NotebookError> {
+        match self.next() {
+            Some(QueryToken::Not) => {
+                self.expect_operand("NOT")?;
+                Ok(QueryExpr::Not(Box::new(self.parse_unary()?)))
+            }
+            Some(QueryToken::OpenParen) => {
+                if self.peek() == Some(&QueryToken::CloseParen) {
+                    return Err(query_error("Empty parentheses in search query."));
+                }
+                let inner = self.parse_or()?;
+                if self.next() != Some(QueryToken::CloseParen) {
+                    return Err(query_error("Missing closing parenthesis in search query."));
+                }
+                Ok(inner)
+            }
+            Some(QueryToken::Clause(clause)) => Ok(clause),
+            Some(QueryToken::CloseParen) => Err(query_error("Unexpected ')' in search query.")),
+            Some(QueryToken::And) => Err(query_error("Expected a search term before AND.")),
+            Some(QueryToken::Or) => Err(query_error("Expected a search term before OR.")),
+            None => Err(query_error("Search query ended unexpectedly.")),
+        }
+    }
+}
+
+/// Parses the search box syntax: words, `"quoted phrases"`, `label:`, `path:` and `updated:`
+/// filters, `-` or `NOT` negation, `AND`/`OR` (words next to each other must all match) and
+/// parentheses.
+///
+/// Returns `None` for a query without anything to search for.
+pub(super) fn parse_search_query(query: &str) -> Result<Option<QueryExpr>, NotebookError> {
+    let tokens = lex_query(query)?;
+    if tokens.is_empty() {
+        return Ok(None);
+    }
+
+    let mut parser = QueryParser {
+        tokens,
+        position: 0,
+    };
+    let expr = parser.parse_or()?;
+    match parser.peek() {
+        None => Ok(Some(expr)),
+        Some(QueryToken::CloseParen) => Err(query_error("Unexpected ')' in search query.")),
+        Some(_) => Err(query_error("Could not parse search query.")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(text: &str) -> QueryExpr {
+        QueryExpr::Term(text.to_string())
+    }
+
+    fn date(year: i32, month: Month, day: u8) -> Date {
+        Date::from_calendar_date(year, month, day).expect("valid test date")
+    }
+
+    #[test]
+    fn parse_search_query_builds_filters_phrases_and_boolean_structure() {
+        let parsed = parse_search_query(
+            r#"label:Work -label:"on hold" path:projects/ updated:>=2026-01-02 "release notes" (alpha OR NOT beta)"#,
+        )
+        .expect("query should parse");
+
+        assert_eq!(
+            parsed,
+            Some(QueryExpr::And(vec![
+                QueryExpr::Label("work".to_string()),
+                QueryExpr::Not(Box::new(QueryExpr::Label("on hold".to_string()))),
+                QueryExpr::Path("projects/".to_string()),
+                QueryExpr::Updated(DateComparison::OnOrAfter, date(2026, Month::January, 2)),
+                QueryExpr::Phrase(vec!["release".to_string(), "notes".to_string()]),
+                QueryExpr::Or(vec![term("alpha"), QueryExpr::Not(Box::new(term("beta")))]),
+            ]))
+        );
+    }
+
+    #[test]
+    fn parse_search_query_binds_and_tighter_than_or() {
+        assert_eq!(
+            parse_search_query("a b OR c AND d").expect("query should parse"),
+            Some(QueryExpr::Or(vec![
+                QueryExpr::And(vec![term("a"), term("b")]),
+                QueryExpr::And(vec![term("c"), term("d")]),
+            ]))
+        );
+        assert_eq!(
+            parse_search_query("and or not").expect("lowercase words are terms"),
+            Some(QueryExpr::And(vec![term("and"), term("or"), term("not")]))
+        );
+        assert_eq!(
+            parse_search_query("  / ").expect("query should parse"),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_search_query_reports_malformed_queries() {
+        for query in [
+            "alpha AND",
+            "OR alpha",
+            "NOT",
+            "(alpha",
+            "alpha)",
+            "\"unterminated",
+            "label:",
+            "updated:>yesterday",
+            "updated:2026-02-30",
+        ] {
+            assert!(
+                parse_search_query(query).is_err(),
+                "Expected '{}' to be rejected",
+                query
+            );
+        }
+    }
+}
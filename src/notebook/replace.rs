@@ -0,0 +1,337 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::history::record_note_snapshot;
+use super::links::cache_upsert_note_links;
+use super::search::{cache_upsert_search_index_note_content, note_file_modified_time};
+use super::storage::{
+    current_timestamp_rfc3339, is_external_edit, save_metadata, write_text_file_atomically,
+};
+use super::watcher::remember_file_content;
+use super::{
+    NoteDiskState, NoteMetadata, NoteReplacePreview, NotebookError, NotebookRelativePath,
+    ReplaceHit, ReplaceLinePreview, ReplacePreview,
+};
+
+const NOTE_FILE_NAME: &str = "note.md";
+const REPLACE_CONTEXT: &str = "find and replace";
+
+/// Rebuilds `text` with every selected hit replaced by `replacement`.
+fn replace_selected_hits(text: &str, hits: &[ReplaceHit], replacement: &str) -> String {
+    let mut replaced = String::with_capacity(text.len());
+    let mut cursor = 0;
+    for hit in hits.iter().filter(|hit| hit.selected) {
+        replaced.push_str(&text[cursor..hit.start]);
+        replaced.push_str(replacement);
+        cursor = hit.end;
+    }
+    replaced.push_str(&text[cursor..]);
+    replaced
+}
+
+impl ReplaceLinePreview {
+    /// The line as it reads after the selected hits are replaced.
+    pub fn replaced_text(&self, replacement: &str) -> String {
+        replace_selected_hits(&self.text, &self.hits, replacement)
+    }
+}
+
+impl NoteReplacePreview {
+    /// The note content after the selected hits are replaced; line endings are kept as they are.
+    pub fn replaced_content(&self, replacement: &str) -> String {
+        let mut lines = self.lines.iter().peekable();
+        let mut replaced = String::with_capacity(self.original_content.len());
+        for (line_index, segment) in self.original_content.split_inclusive('\n').enumerate() {
+            match lines.next_if(|line| line.line_number == line_index + 1) {
+                Some(line) => {
+                    replaced.push_str(&replace_selected_hits(segment, &line.hits, replacement))
+                }
+                None => replaced.push_str(segment),
+            }
+        }
+        replaced
+    }
+
+    fn has_selected_hits(&self) -> bool {
+        self.lines
+            .iter()
+            .any(|line| line.hits.iter().any(|hit| hit.selected))
+    }
+}
+
+fn find_line_hits(content: &str, find: &str, next_hit_id: &mut usize) -> Vec<ReplaceLinePreview> {
+    let mut lines = Vec::new();
+    for (line_index, line) in content.lines().enumerate() {
+        let hits: Vec<ReplaceHit> = line
+            .match_indices(find)
+            .map(|(start, matched)| {
+                let id = *next_hit_id;
+                *next_hit_id += 1;
+                ReplaceHit {
+                    id,
+                    start,
+                    end: start + matched.len(),
+                    selected: true,
+                }
+            })
+            .collect();
+        if !hits.is_empty() {
+            lines.push(ReplaceLinePreview {
+                line_number: line_index + 1,
+                text: line.to_string(),
+                hits,
+            });
+        }
+    }
+    lines
+}
+
+/// Lists every occurrence of `find` in the notebook's notes, line by line, all selected.
+///
+/// Matching is literal and case-sensitive within a line. `open_note` holds the path, editor
+/// buffer and last known file state of the open note, which is previewed instead of its
+/// possibly older file.
+pub async fn preview_replace(
+    notebook_path: &str,
+    notes: &[NoteMetadata],
+    find: &str,
+    replacement: &str,
+    open_note: Option<(&str, &str, Option<&NoteDiskState>)>,
+) -> Result<ReplacePreview, NotebookError> {
+    if find.is_empty() {
+        return Err(NotebookError::validation(
+            "find and replace",
+            "Enter the text to find.",
+        ));
+    }
+    if find.contains(['\n', '\r']) || replacement.contains(['\n', '\r']) {
+        return Err(NotebookError::validation(
+            "find and replace",
+            "Find and replace text must fit on a single line.",
+        ));
+    }
+
+    let mut rel_paths: Vec<&str> = notes.iter().map(|note| note.rel_path.as_str()).collect();
+    rel_paths.sort_unstable();
+
+    let mut next_hit_id = 0;
+    let mut note_previews = Vec::new();
+    for rel_path in rel_paths {
+        let (content, is_open_note, disk_base) = match open_note {
+            Some((open_path, buffer, disk_base)) if open_path == rel_path => {
+                (buffer.to_string(), true, disk_base.cloned())
+            }
+            _ => {
+                let note_file_path = Path::new(notebook_path).join(rel_path).join(NOTE_FILE_NAME);
+                match fs::read_to_string(&note_file_path) {
+                    Ok(content) => (content, false, None),
+                    Err(error) if error.kind() == std::io::ErrorKind::NotFound => continue,
+                    Err(error) => {
+                        return Err(NotebookError::storage(
+                            "find and replace",
+                            format!("Failed to read note '{}': {}", rel_path, error),
+                        ));
+                    }
+                }
+            }
+        };
+
+        let lines = find_line_hits(&content, find, &mut next_hit_id);
+        if !lines.is_empty() {
+            note_previews.push(NoteReplacePreview {
+                rel_path: rel_path.to_string(),
+                original_content: content,
+                is_open_note,
+                disk_base,
+                lines,
+            });
+        }
+    }
+
+    Ok(ReplacePreview {
+        find: find.to_string(),
+        replacement: replacement.to_string(),
+        notes: note_previews,
+    })
+}
+
+/// Note whose `note.md` is rewritten as part of a batch.
+pub(super) struct RewrittenNote {
+    pub(super) rel_path: String,
+    pub(super) note_file_path: PathBuf,
+    /// File content before the rewrite, restored on rollback.
+    pub(super) previous_content: String,
+    pub(super) content: String,
+}
+
+fn restore_rewritten_notes(
+    context: &'static str,
+    rewritten: &[RewrittenNote],
+) -> Result<(), NotebookError> {
+    let mut failures = Vec::new();
+    for note in rewritten.iter().rev() {
+        match write_text_file_atomically(&note.note_file_path, &note.previous_content) {
+            Ok(()) => remember_file_content(&note.note_file_path, &note.previous_content),
+            Err(error) => failures.push(error.to_string()),
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(NotebookError::recovery(
+            context,
+            format!(
+                "Failed to restore note content after a failed rewrite: {}",
+                failures.join("; ")
+            ),
+        ))
+    }
+}
+
+/// Restores `rewritten` notes after `error` and reports both failures if that fails too.
+pub(super) fn rollback_rewritten_notes(
+    context: &'static str,
+    error: NotebookError,
+    rewritten: &[RewrittenNote],
+) -> NotebookError {
+    match restore_rewritten_notes(context, rewritten) {
+        Ok(()) => error,
+        Err(restore_error) => {
+            NotebookError::recovery(context, format!("{} {}", error, restore_error))
+        }
+    }
+}
+
+/// Writes each planned note after recording its previous content in the note history.
+///
+/// If a write fails, the notes already written are restored.
+pub(super) fn write_rewritten_notes(
+    context: &'static str,
+    planned: Vec<RewrittenNote>,
+) -> Result<Vec<RewrittenNote>, NotebookError> {
+    let mut rewritten: Vec<RewrittenNote> = Vec::with_capacity(planned.len());
+    for note in planned {
+        if !note.previous_content.is_empty()
+            && let Some(note_dir) = note.note_file_path.parent()
+            && let Err(_err) = record_note_snapshot(
+                note_dir,
+                &note.previous_content,
+                note_file_modified_time(&note.note_file_path),
+            )
+        {
+            #[cfg(debug_assertions)]
+            eprintln!(
+                "Warning: Failed to record history for '{}': {}",
+                note.rel_path, _err
+            );
+        }
+
+        if let Err(error) = write_text_file_atomically(&note.note_file_path, &note.content) {
+            return Err(rollback_rewritten_notes(context, error, &rewritten));
+        }
+        remember_file_content(&note.note_file_path, &note.content);
+        rewritten.push(note);
+    }
+    Ok(rewritten)
+}
+
+/// Updates the link and search caches for notes written by `write_rewritten_notes`.
+pub(super) fn cache_rewritten_notes(notebook_path: &str, rewritten: &[RewrittenNote]) {
+    for note in rewritten {
+        let modified_time = note_file_modified_time(&note.note_file_path);
+        cache_upsert_note_links(notebook_path, &note.rel_path, &note.content, modified_time);
+        cache_upsert_search_index_note_content(
+            notebook_path,
+            &note.rel_path,
+            &note.content,
+            modified_time,
+        );
+    }
+}
+
+/// Writes the selected hits of `preview` to disk as one batch and returns the changed note paths.
+///
+/// Nothing is written if a note changed on disk since the preview. The open note's preview came
+/// from the editor buffer, so its file is checked against the state the editor last loaded or
+/// saved instead, as a checked save would. If any write or the metadata update fails, notes
+/// already rewritten are restored.
+pub async fn apply_replace(
+    notebook_path: &str,
+    preview: &ReplacePreview,
+    notes: &mut Vec<NoteMetadata>,
+) -> Result<Vec<String>, NotebookError> {
+    let mut planned = Vec::new();
+    for note_preview in &preview.notes {
+        if !note_preview.has_selected_hits() {
+            continue;
+        }
+        let content = note_preview.replaced_content(&preview.replacement);
+        if content == note_preview.original_content {
+            continue;
+        }
+
+        let rel_path = NotebookRelativePath::parse("replace note path", &note_preview.rel_path)?;
+        let note_file_path = rel_path
+            .join_under(Path::new(notebook_path))
+            .join(NOTE_FILE_NAME);
+        let previous_content = fs::read_to_string(&note_file_path).map_err(|error| {
+            NotebookError::storage(
+                "find and replace",
+                format!("Failed to read note '{}': {}", note_preview.rel_path, error),
+            )
+        })?;
+        let changed_since_preview = if note_preview.is_open_note {
+            note_preview.disk_base.as_ref().is_some_and(|base| {
+                is_external_edit(
+                    &note_file_path,
+                    base,
+                    &previous_content,
+                    note_file_modified_time(&note_file_path),
+                    &content,
+                )
+            })
+        } else {
+            previous_content != note_preview.original_content
+        };
+        if changed_since_preview {
+            return Err(NotebookError::validation(
+                "find and replace",
+                format!(
+                    "'{}' changed since the preview. Preview the replacement again.",
+                    note_preview.rel_path
+                ),
+            ));
+        }
+        if previous_content == content {
+            continue;
+        }
+
+        planned.push(RewrittenNote {
+            rel_path: rel_path.as_str().to_string(),
+            note_file_path,
+            previous_content,
+            content,
+        });
+    }
+
+    let replaced = write_rewritten_notes(REPLACE_CONTEXT, planned)?;
+    if replaced.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let previous_notes = notes.clone();
+    let timestamp = current_timestamp_rfc3339();
+    for note in notes.iter_mut() {
+        if replaced.iter().any(|entry| entry.rel_path == note.rel_path) {
+            note.last_updated = Some(timestamp.clone());
+        }
+    }
+    if let Err(error) = save_metadata(notebook_path, notes) {
+        *notes = previous_notes;
+        return Err(rollback_rewritten_notes(REPLACE_CONTEXT, error, &replaced));
+    }
+    cache_rewritten_notes(notebook_path, &replaced);
+
+    Ok(replaced.into_iter().map(|note| note.rel_path).collect())
+}
@@ -0,0 +1,129 @@
+use super::search::{
+    SearchNote, is_in_search_scope, search_notes_with_snapshot, validate_search_query,
+};
+use super::storage::save_metadata_with;
+use super::{NoteMetadata, NotebookError, NotebookRelativePath, SavedSearch, SavedSearchMatches};
+
+/// Trims a folder scope and checks that it stays inside the notebook; an empty scope is the
+/// whole notebook.
+fn normalize_search_scope(scope: Option<&str>) -> Result<Option<String>, NotebookError> {
+    let Some(scope) = scope.map(|scope| scope.trim().trim_matches('/')) else {
+        return Ok(None);
+    };
+    if scope.is_empty() {
+        return Ok(None);
+    }
+    let scope = NotebookRelativePath::parse("saved search scope", scope)?;
+    Ok(Some(scope.as_str().to_string()))
+}
+
+/// Stores `search` in `metadata.json`, replacing a saved search with the same name.
+///
+/// The name and query are trimmed, and queries a search would reject are not saved.
+pub async fn save_search(
+    notebook_path: &str,
+    notes: &[NoteMetadata],
+    saved_searches: &mut Vec<SavedSearch>,
+    search: SavedSearch,
+) -> Result<(), NotebookError> {
+    let name = search.name.trim();
+    if name.is_empty() {
+        return Err(NotebookError::validation(
+            "save search",
+            "Enter a name for the saved search.",
+        ));
+    }
+    let query = search.query.trim();
+    if query.is_empty() {
+        return Err(NotebookError::validation(
+            "save search",
+            "Enter a search query to save.",
+        ));
+    }
+    validate_search_query(query, search.mode)?;
+
+    let saved = SavedSearch {
+        name: name.to_string(),
+        query: query.to_string(),
+        mode: search.mode,
+        scope: normalize_search_scope(search.scope.as_deref())?,
+    };
+
+    let previous_saved_searches = saved_searches.clone();
+    match saved_searches
+        .iter_mut()
+        .find(|existing| existing.name == saved.name)
+    {
+        Some(existing) => *existing = saved,
+        None => {
+            saved_searches.push(saved);
+            saved_searches.sort_by(|left, right| left.name.cmp(&right.name));
+        }
+    }
+
+    if let Err(error) = save_metadata_with(notebook_path, notes, |metadata| {
+        metadata.saved_searches = saved_searches.clone();
+    }) {
+        *saved_searches = previous_saved_searches;
+        return Err(error);
+    }
+    Ok(())
+}
+
+/// Removes the saved search called `name` from `metadata.json`.
+pub async fn delete_saved_search(
+    notebook_path: &str,
+    notes: &[NoteMetadata],
+    saved_searches: &mut Vec<SavedSearch>,
+    name: &str,
+) -> Result<(), NotebookError> {
+    let Some(index) = saved_searches.iter().position(|saved| saved.name == name) else {
+        return Err(NotebookError::validation(
+            "delete saved search",
+            format!("No saved search is called '{}'.", name),
+        ));
+    };
+
+    let removed = saved_searches.remove(index);
+    if let Err(error) = save_metadata_with(notebook_path, notes, |metadata| {
+        metadata.saved_searches = saved_searches.clone();
+    }) {
+        saved_searches.insert(index, removed);
+        return Err(error);
+    }
+    Ok(())
+}
+
+/// Runs every saved search against `notes` and lists the matching note paths of each.
+pub async fn evaluate_saved_searches(
+    notebook_path: String,
+    notes: Vec<SearchNote>,
+    saved_searches: Vec<SavedSearch>,
+) -> Vec<SavedSearchMatches> {
+    let mut evaluated = Vec::with_capacity(saved_searches.len());
+    for saved in saved_searches {
+        let scoped_notes: Vec<SearchNote> = notes
+            .iter()
+            .filter(|note| is_in_search_scope(&note.rel_path, saved.scope.as_deref()))
+            .cloned()
+            .collect();
+        let note_paths = search_notes_with_snapshot(
+            notebook_path.clone(),
+            scoped_notes,
+            saved.query,
+            saved.mode,
+        )
+        .await
+        .map(|results| {
+            let mut paths: Vec<String> =
+                results.into_iter().map(|result| result.rel_path).collect();
+            paths.sort();
+            paths
+        });
+        evaluated.push(SavedSearchMatches {
+            name: saved.name,
+            note_paths,
+        });
+    }
+    evaluated
+}
@@ -1,10 +1,22 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex, OnceLock};
-use std::time::{Duration, Instant, SystemTime};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use super::{NoteMetadata, NoteSearchResult};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use time::format_description::well_known::Rfc3339;
+use time::{Date, OffsetDateTime};
+
+use super::fuzzy::fuzzy_word_score;
+use super::history::content_hash;
+use super::labels::label_matches;
+use super::query::{QueryExpr, parse_search_query};
+use super::storage::write_text_file_atomically;
+use super::{
+    NoteMetadata, NoteSearchResult, NotebookError, SearchLineMatch, SearchMatchRange, SearchMode,
+};
 
 #[cfg(test)]
 const SEARCH_INDEX_EXTERNAL_REFRESH_INTERVAL: Duration = Duration::from_millis(150);
@@ -18,11 +30,34 @@ const SEARCH_INDEX_IDLE_EVICTION_INTERVAL: Duration = Duration::from_secs(15 * 6
 const SEARCH_INDEX_MAX_CACHED_NOTEBOOKS: usize = 4;
 #[cfg(not(test))]
 const SEARCH_INDEX_MAX_CACHED_NOTEBOOKS: usize = 24;
+const BM25_K1: f64 = 1.2;
+const BM25_B: f64 = 0.75;
+/// Path and label hits score this many times the term's IDF, more than any body hit can.
+const PATH_MATCH_BOOST: f64 = 3.0;
+const LABEL_MATCH_BOOST: f64 = 2.5;
+/// Query terms also match longer terms they are a prefix of, at this fraction of the score.
+const PREFIX_MATCH_WEIGHT: f64 = 0.5;
+/// Regex search gives up with an error once matching has taken this long.
+const REGEX_SEARCH_TIME_BUDGET: Duration = Duration::from_secs(1);
+/// Upper bound on the compiled size of a regex search pattern.
+const REGEX_SEARCH_SIZE_LIMIT: usize = 1 << 20;
+/// Fuzzy matches count fully in paths and slightly less in labels and headings.
+const FUZZY_LABEL_WEIGHT: f64 = 0.9;
+const FUZZY_HEADING_WEIGHT: f64 = 0.8;
+/// Index cache kept at the notebook root so the first search after launch needs no note reads.
+const SEARCH_INDEX_CACHE_FILE_NAME: &str = ".cognate_search_index.json";
+/// Cache files of any other version are discarded; bump it when tokenizing or the layout changes.
+const SEARCH_INDEX_CACHE_VERSION: u32 = 2;
+/// Matching lines are shown as excerpts of at most this many characters.
+const LINE_EXCERPT_MAX_CHARS: usize = 120;
+/// Characters kept before a match that would otherwise fall past the end of the excerpt.
+const LINE_EXCERPT_LEADING_CHARS: usize = 40;
 
 #[derive(Debug, Clone)]
 pub struct SearchNote {
     pub rel_path: String,
     pub labels: Vec<String>,
+    pub last_updated: Option<String>,
 }
 
 impl From<&NoteMetadata> for SearchNote {
@@ -30,34 +65,156 @@ impl From<&NoteMetadata> for SearchNote {
         Self {
             rel_path: note.rel_path.clone(),
             labels: note.labels.clone(),
+            last_updated: note.last_updated.clone(),
         }
     }
 }
 
+/// Splits text into lowercase alphanumeric terms.
+pub(super) fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|ch: char| !ch.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(str::to_lowercase)
+}
+
 #[derive(Debug, Clone, Default)]
 struct IndexedNoteContent {
-    content: Arc<str>,
-    content_lower: Arc<str>,
+    /// Note text; `None` for notes loaded from the cache file until a search needs it.
+    content: Option<Arc<str>>,
+    content_hash: u128,
     modified_time: Option<SystemTime>,
+    term_frequencies: HashMap<String, u32>,
+    token_count: u32,
+    /// Markdown heading texts, for fuzzy search.
+    headings: Vec<String>,
+}
+
+impl IndexedNoteContent {
+    fn new(content: String, modified_time: Option<SystemTime>) -> Self {
+        let mut term_frequencies: HashMap<String, u32> = HashMap::new();
+        let mut token_count = 0u32;
+        for term in tokenize(&content) {
+            *term_frequencies.entry(term).or_default() += 1;
+            token_count = token_count.saturating_add(1);
+        }
+
+        let headings = content
+            .lines()
+            .filter_map(|line| {
+                let heading = line.trim_start().trim_start_matches('#');
+                (heading.len() < line.trim_start().len() && heading.starts_with(' '))
+                    .then(|| heading.trim().to_string())
+            })
+            .filter(|heading| !heading.is_empty())
+            .collect();
+
+        Self {
+            content_hash: content_hash(&content),
+            content: Some(Arc::from(content)),
+            modified_time,
+            term_frequencies,
+            token_count,
+            headings,
+        }
+    }
+
+    fn text(&self) -> &str {
+        self.content.as_deref().unwrap_or_default()
+    }
 }
 
 #[derive(Debug)]
 struct NotebookSearchIndex {
     notes_by_path: HashMap<String, IndexedNoteContent>,
+    /// Notes containing each term; ordered so prefix matches are a range scan.
+    postings: BTreeMap<String, HashSet<Arc<str>>>,
+    total_token_count: u64,
     last_external_refresh: Option<Instant>,
     last_accessed_at: Instant,
+    /// Whether the index changed since it was last written to the cache file.
+    dirty: bool,
 }
 
 impl Default for NotebookSearchIndex {
     fn default() -> Self {
         Self {
             notes_by_path: HashMap::new(),
+            postings: BTreeMap::new(),
+            total_token_count: 0,
             last_external_refresh: None,
             last_accessed_at: Instant::now(),
+            dirty: false,
         }
     }
 }
 
+impl NotebookSearchIndex {
+    fn insert_note(&mut self, rel_path: String, note: IndexedNoteContent) {
+        self.remove_note(&rel_path);
+
+        let shared_path: Arc<str> = Arc::from(rel_path.as_str());
+        for term in note.term_frequencies.keys() {
+            self.postings
+                .entry(term.clone())
+                .or_default()
+                .insert(Arc::clone(&shared_path));
+        }
+        self.total_token_count += u64::from(note.token_count);
+        self.notes_by_path.insert(rel_path, note);
+        self.dirty = true;
+    }
+
+    fn remove_note(&mut self, rel_path: &str) -> Option<IndexedNoteContent> {
+        let note = self.notes_by_path.remove(rel_path)?;
+        for term in note.term_frequencies.keys() {
+            if let Some(paths) = self.postings.get_mut(term) {
+                paths.remove(rel_path);
+                if paths.is_empty() {
+                    self.postings.remove(term);
+                }
+            }
+        }
+        self.total_token_count = self
+            .total_token_count
+            .saturating_sub(u64::from(note.token_count));
+        self.dirty = true;
+        Some(note)
+    }
+
+    fn retain_notes(&mut self, keep: impl Fn(&str) -> bool) {
+        let removed_paths: Vec<String> = self
+            .notes_by_path
+            .keys()
+            .filter(|rel_path| !keep(rel_path))
+            .cloned()
+            .collect();
+        for rel_path in removed_paths {
+            self.remove_note(&rel_path);
+        }
+    }
+
+    /// Indexed terms matching `term` exactly or by prefix, with the weight of each match.
+    fn expand_term<'a>(&'a self, term: &'a str) -> impl Iterator<Item = (&'a str, f64)> + 'a {
+        self.postings
+            .range::<str, _>((std::ops::Bound::Included(term), std::ops::Bound::Unbounded))
+            .take_while(move |(indexed_term, _)| indexed_term.starts_with(term))
+            .map(move |(indexed_term, _)| {
+                let weight = if indexed_term == term {
+                    1.0
+                } else {
+                    PREFIX_MATCH_WEIGHT
+                };
+                (indexed_term.as_str(), weight)
+            })
+    }
+
+    fn inverse_document_frequency(&self, term: &str) -> f64 {
+        let document_count = self.notes_by_path.len() as f64;
+        let matching_count = self.postings.get(term).map_or(0, HashSet::len) as f64;
+        ((document_count - matching_count + 0.5) / (matching_count + 0.5) + 1.0).ln()
+    }
+}
+
 static SEARCH_INDEXES_BY_NOTEBOOK: OnceLock<Mutex<HashMap<String, NotebookSearchIndex>>> =
     OnceLock::new();
 
@@ -106,6 +263,116 @@ pub fn clear_search_index_for_notebook(notebook_path: &str) {
     });
 }
 
+#[derive(Serialize, Deserialize)]
+struct SearchIndexCacheFile {
+    version: u32,
+    notes: HashMap<String, CachedNoteEntry>,
+}
+
+/// Index data for one note; its text is not cached and is read again when a search needs it.
+#[derive(Serialize, Deserialize)]
+struct CachedNoteEntry {
+    /// Modification time as seconds and nanoseconds since the Unix epoch.
+    modified_time: Option<(u64, u32)>,
+    content_hash: u128,
+    term_frequencies: HashMap<String, u32>,
+    token_count: u32,
+    headings: Vec<String>,
+}
+
+fn search_index_cache_path(notebook_path: &str) -> PathBuf {
+    Path::new(notebook_path).join(SEARCH_INDEX_CACHE_FILE_NAME)
+}
+
+/// Reads the cached index of a notebook; a missing, corrupt or outdated cache file gives `None`.
+fn read_search_index_cache(notebook_path: &str) -> Option<NotebookSearchIndex> {
+    let contents = fs::read_to_string(search_index_cache_path(notebook_path)).ok()?;
+    let cache: SearchIndexCacheFile = serde_json::from_str(&contents).ok()?;
+    if cache.version != SEARCH_INDEX_CACHE_VERSION {
+        return None;
+    }
+
+    let mut index = NotebookSearchIndex::default();
+    for (rel_path, entry) in cache.notes {
+        let modified_time = entry
+            .modified_time
+            .and_then(|(secs, nanos)| UNIX_EPOCH.checked_add(Duration::new(secs, nanos)));
+        index.insert_note(
+            rel_path,
+            IndexedNoteContent {
+                content: None,
+                content_hash: entry.content_hash,
+                modified_time,
+                term_frequencies: entry.term_frequencies,
+                token_count: entry.token_count,
+                headings: entry.headings,
+            },
+        );
+    }
+    index.dirty = false;
+    Some(index)
+}
+
+fn serialize_search_index_cache(index: &NotebookSearchIndex) -> Option<String> {
+    let notes = index
+        .notes_by_path
+        .iter()
+        .map(|(rel_path, note)| {
+            let modified_time = note
+                .modified_time
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|since_epoch| (since_epoch.as_secs(), since_epoch.subsec_nanos()));
+            let entry = CachedNoteEntry {
+                modified_time,
+                content_hash: note.content_hash,
+                term_frequencies: note.term_frequencies.clone(),
+                token_count: note.token_count,
+                headings: note.headings.clone(),
+            };
+            (rel_path.clone(), entry)
+        })
+        .collect();
+
+    serde_json::to_string(&SearchIndexCacheFile {
+        version: SEARCH_INDEX_CACHE_VERSION,
+        notes,
+    })
+    .ok()
+}
+
+/// Writing the cache is best effort; without it the next launch reads every note again.
+fn write_search_index_cache(notebook_path: &str, contents: &str) {
+    if let Err(_err) = write_text_file_atomically(&search_index_cache_path(notebook_path), contents)
+    {
+        #[cfg(debug_assertions)]
+        eprintln!(
+            "Warning: Failed to write search index cache for '{}': {}",
+            notebook_path, _err
+        );
+    }
+}
+
+/// Loads the notebook's index from its cache file unless it is already in memory.
+fn ensure_search_index_loaded(notebook_path: &str) {
+    if with_search_indexes(|search_indexes| search_indexes.contains_key(notebook_path)) {
+        return;
+    }
+
+    let cached_index = read_search_index_cache(notebook_path);
+    with_search_indexes(|search_indexes| {
+        prune_search_indexes(search_indexes);
+        let index = search_indexes
+            .entry(notebook_path.to_string())
+            .or_insert_with(|| cached_index.unwrap_or_default());
+        touch_search_index(index);
+    });
+}
+
+/// Loads the notebook's cached search index in the background, so the first search is fast.
+pub async fn preload_search_index(notebook_path: String) {
+    ensure_search_index_loaded(&notebook_path);
+}
+
 fn truncate_search_snippet(input: &str, max_chars: usize) -> String {
     let char_count = input.chars().count();
     if char_count <= max_chars {
@@ -117,19 +384,105 @@ fn truncate_search_snippet(input: &str, max_chars: usize) -> String {
     }
 }
 
-fn find_matching_content_snippet(content: &str, normalized_query: &str) -> Option<String> {
-    for line in content.lines() {
-        let trimmed = line.trim();
-        if trimmed.is_empty() {
-            continue;
+/// Like [`tokenize`], with the byte range of each term in `text`.
+fn tokenize_with_ranges(text: &str) -> impl Iterator<Item = (SearchMatchRange, String)> + '_ {
+    let mut chars = text.char_indices().peekable();
+    std::iter::from_fn(move || {
+        let (start, _) = chars.find(|(_, ch)| ch.is_alphanumeric())?;
+        let mut end = text.len();
+        while let Some(&(offset, ch)) = chars.peek() {
+            if !ch.is_alphanumeric() {
+                end = offset;
+                break;
+            }
+            chars.next();
         }
+        Some((
+            SearchMatchRange { start, end },
+            text[start..end].to_lowercase(),
+        ))
+    })
+}
 
-        if trimmed.to_lowercase().contains(normalized_query) {
-            return Some(truncate_search_snippet(trimmed, 120));
-        }
+/// Byte offset `chars` characters after `start` in `text`, or the end of `text`.
+fn advance_chars(text: &str, start: usize, chars: usize) -> usize {
+    text[start..]
+        .char_indices()
+        .nth(chars)
+        .map_or(text.len(), |(offset, _)| start + offset)
+}
+
+/// Byte offset `chars` characters before `end` in `text`, or the start of `text`.
+fn retreat_chars(text: &str, end: usize, chars: usize) -> usize {
+    text[..end]
+        .char_indices()
+        .rev()
+        .nth(chars.saturating_sub(1))
+        .map_or(0, |(offset, _)| offset)
+}
+
+/// Builds the result entry for a matching line, with an excerpt that shows its first match.
+fn build_line_match(
+    line_index: usize,
+    line: &str,
+    columns: Vec<SearchMatchRange>,
+) -> SearchLineMatch {
+    let content_start = line.len() - line.trim_start().len();
+    let content_end = line.trim_end().len().max(content_start);
+
+    let mut excerpt_start = content_start;
+    if let Some(first) = columns.first()
+        && first.end > advance_chars(line, content_start, LINE_EXCERPT_MAX_CHARS)
+    {
+        excerpt_start =
+            retreat_chars(line, first.start, LINE_EXCERPT_LEADING_CHARS).max(content_start);
+    }
+    let excerpt_end = advance_chars(line, excerpt_start, LINE_EXCERPT_MAX_CHARS).min(content_end);
+
+    let mut text = String::new();
+    if excerpt_start > content_start {
+        text.push_str("...");
+    }
+    let offset = text.len();
+    text.push_str(&line[excerpt_start..excerpt_end]);
+    if excerpt_end < content_end {
+        text.push_str("...");
     }
 
-    None
+    let highlights = columns
+        .iter()
+        .filter(|range| range.start < excerpt_end && range.end > excerpt_start)
+        .map(|range| SearchMatchRange {
+            start: range.start.max(excerpt_start) - excerpt_start + offset,
+            end: range.end.min(excerpt_end) - excerpt_start + offset,
+        })
+        .filter(|range| range.start < range.end)
+        .collect();
+
+    SearchLineMatch {
+        line_number: line_index + 1,
+        text,
+        columns,
+        highlights,
+    }
+}
+
+/// Lists every line of `content` containing one of `matched_terms`, with each term occurrence.
+fn find_matching_content_lines(
+    content: &str,
+    matched_terms: &HashSet<&str>,
+) -> Vec<SearchLineMatch> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(line_index, line)| {
+            let columns: Vec<SearchMatchRange> = tokenize_with_ranges(line)
+                .filter(|(_, term)| matched_terms.contains(term.as_str()))
+                .map(|(range, _)| range)
+                .collect();
+            (!columns.is_empty()).then(|| build_line_match(line_index, line, columns))
+        })
+        .collect()
 }
 
 pub(super) fn note_file_modified_time(note_file_path: &Path) -> Option<SystemTime> {
@@ -138,16 +491,40 @@ pub(super) fn note_file_modified_time(note_file_path: &Path) -> Option<SystemTim
         .and_then(|metadata| metadata.modified().ok())
 }
 
-fn read_note_content_for_index(notebook_path: &str, rel_path: &str) -> IndexedNoteContent {
+/// Note file content read outside the index lock, ready to update the note's index entry.
+enum NoteFileUpdate {
+    /// The content hash matches the indexed one, so the terms are kept as they are.
+    Unchanged {
+        content: Arc<str>,
+        modified_time: Option<SystemTime>,
+    },
+    Changed(IndexedNoteContent),
+}
+
+fn read_note_file(notebook_path: &str, rel_path: &str) -> (String, Option<SystemTime>) {
     let note_file_path = Path::new(notebook_path).join(rel_path).join("note.md");
     let content = fs::read_to_string(&note_file_path).unwrap_or_default();
-    let content_lower = content.to_lowercase();
-    let modified_time = note_file_modified_time(&note_file_path);
+    (content, note_file_modified_time(&note_file_path))
+}
 
-    IndexedNoteContent {
-        content_lower: Arc::from(content_lower),
-        content: Arc::from(content),
-        modified_time,
+fn read_note_content_for_index(notebook_path: &str, rel_path: &str) -> IndexedNoteContent {
+    let (content, modified_time) = read_note_file(notebook_path, rel_path);
+    IndexedNoteContent::new(content, modified_time)
+}
+
+fn read_note_file_update(
+    notebook_path: &str,
+    rel_path: &str,
+    indexed_hash: u128,
+) -> NoteFileUpdate {
+    let (content, modified_time) = read_note_file(notebook_path, rel_path);
+    if content_hash(&content) == indexed_hash {
+        NoteFileUpdate::Unchanged {
+            content: Arc::from(content),
+            modified_time,
+        }
+    } else {
+        NoteFileUpdate::Changed(IndexedNoteContent::new(content, modified_time))
     }
 }
 
@@ -159,16 +536,15 @@ pub(super) fn cache_upsert_search_index_note_content(
 ) {
     with_search_indexes(|search_indexes| {
         prune_search_indexes(search_indexes);
-        let index = search_indexes.entry(notebook_path.to_string()).or_default();
+        // An index that is not loaded picks the change up from the file's modification time.
+        let Some(index) = search_indexes.get_mut(notebook_path) else {
+            return;
+        };
         touch_search_index(index);
 
-        index.notes_by_path.insert(
+        index.insert_note(
             rel_path.to_string(),
-            IndexedNoteContent {
-                content: Arc::from(content.to_string()),
-                content_lower: Arc::from(content.to_lowercase()),
-                modified_time,
-            },
+            IndexedNoteContent::new(content.to_string(), modified_time),
         );
     });
 }
@@ -182,9 +558,7 @@ pub(super) fn cache_remove_search_index_entries(notebook_path: &str, rel_path: &
             touch_search_index(index);
 
             let prefix = format!("{rel_path}/");
-            index
-                .notes_by_path
-                .retain(|path, _| path != rel_path && !path.starts_with(&prefix));
+            index.retain_notes(|path| path != rel_path && !path.starts_with(&prefix));
             remove_notebook_index = index.notes_by_path.is_empty();
         }
 
@@ -221,8 +595,8 @@ pub(super) fn cache_rename_search_index_entries(
         }
 
         for (old_path, new_path) in remapped_entries {
-            if let Some(entry) = index.notes_by_path.remove(&old_path) {
-                index.notes_by_path.insert(new_path, entry);
+            if let Some(entry) = index.remove_note(&old_path) {
+                index.insert_note(new_path, entry);
             }
         }
     });
@@ -235,35 +609,280 @@ fn should_refresh_search_index_from_filesystem(last_refresh: Option<Instant>) ->
     }
 }
 
-pub async fn search_notes_with_snapshot(
-    notebook_path: String,
-    notes: Vec<SearchNote>,
-    query: String,
-) -> Vec<NoteSearchResult> {
-    let normalized_query = query.trim().to_lowercase();
-    if normalized_query.is_empty() {
-        return Vec::new();
+/// A note that matched a search, before its snippet is built.
+struct ScoredNote {
+    rel_path: String,
+    score: f64,
+    content: Option<Arc<str>>,
+    body_terms: HashSet<String>,
+    label_match: Option<String>,
+    path_match: bool,
+    last_updated: Option<String>,
+}
+
+/// How well any of `candidates`' terms matches `term`: exactly, by prefix, or not at all.
+fn best_term_match_weight(candidates: impl Iterator<Item = String>, term: &str) -> f64 {
+    candidates.fold(0.0, |best, candidate| {
+        if candidate == term {
+            1.0
+        } else if candidate.starts_with(term) {
+            f64::max(best, PREFIX_MATCH_WEIGHT)
+        } else {
+            best
+        }
+    })
+}
+
+fn contains_phrase(terms: impl Iterator<Item = String>, phrase: &[String]) -> bool {
+    let terms: Vec<String> = terms.collect();
+    terms.windows(phrase.len()).any(|window| window == phrase)
+}
+
+fn note_updated_date(last_updated: Option<&str>) -> Option<Date> {
+    OffsetDateTime::parse(last_updated?, &Rfc3339)
+        .ok()
+        .map(OffsetDateTime::date)
+}
+
+/// Evaluates a parsed query against notes, with body matches looked up in the index.
+struct QueryMatcher<'a> {
+    index: &'a NotebookSearchIndex,
+    /// Notes whose body contains each query term, exactly or as a prefix.
+    body_matches: HashMap<&'a str, HashSet<&'a str>>,
+}
+
+impl<'a> QueryMatcher<'a> {
+    fn new(index: &'a NotebookSearchIndex, expr: &'a QueryExpr) -> Self {
+        let mut body_matches = HashMap::new();
+        expr.visit_clauses(false, &mut |clause, _| {
+            let QueryExpr::Term(term) = clause else {
+                return;
+            };
+            body_matches.entry(term.as_str()).or_insert_with(|| {
+                index
+                    .expand_term(term)
+                    .filter_map(|(indexed_term, _)| index.postings.get(indexed_term))
+                    .flatten()
+                    .map(AsRef::as_ref)
+                    .collect()
+            });
+        });
+
+        Self {
+            index,
+            body_matches,
+        }
     }
 
+    fn body_contains_phrase(&self, rel_path: &str, phrase: &[String]) -> bool {
+        let Some(indexed) = self.index.notes_by_path.get(rel_path) else {
+            return false;
+        };
+        phrase
+            .iter()
+            .all(|term| indexed.term_frequencies.contains_key(term))
+            && contains_phrase(tokenize(indexed.text()), phrase)
+    }
+
+    fn matches(&self, expr: &QueryExpr, note: &SearchNote) -> bool {
+        match expr {
+            QueryExpr::Term(term) => {
+                self.body_matches
+                    .get(term.as_str())
+                    .is_some_and(|paths| paths.contains(note.rel_path.as_str()))
+                    || best_term_match_weight(tokenize(&note.rel_path), term) > 0.0
+                    || note
+                        .labels
+                        .iter()
+                        .any(|label| best_term_match_weight(tokenize(label), term) > 0.0)
+            }
+            QueryExpr::Phrase(phrase) => {
+                contains_phrase(tokenize(&note.rel_path), phrase)
+                    || note
+                        .labels
+                        .iter()
+                        .any(|label| contains_phrase(tokenize(label), phrase))
+                    || self.body_contains_phrase(&note.rel_path, phrase)
+            }
+            QueryExpr::Label(expected) => note
+                .labels
+                .iter()
+                .any(|label| label_matches(label, expected)),
+            QueryExpr::Path(prefix) => note.rel_path.to_lowercase().starts_with(prefix.as_str()),
+            QueryExpr::Updated(comparison, bound) => {
+                note_updated_date(note.last_updated.as_deref())
+                    .is_some_and(|date| comparison.matches(date, *bound))
+            }
+            QueryExpr::Not(inner) => !self.matches(inner, note),
+            QueryExpr::And(items) => items.iter().all(|item| self.matches(item, note)),
+            QueryExpr::Or(items) => items.iter().any(|item| self.matches(item, note)),
+        }
+    }
+}
+
+/// Ranks `notes` for `query_terms` with BM25 over note bodies plus boosted path and label hits.
+///
+/// Every note is returned, so notes matched only by `filters` keep a score of zero; the filters
+/// only decide which label or path the snippet points at.
+fn score_notes(
+    index: &NotebookSearchIndex,
+    notes: &[&SearchNote],
+    query_terms: &[String],
+    filters: &[&QueryExpr],
+) -> Vec<ScoredNote> {
+    let document_count = index.notes_by_path.len();
+    let average_length = if document_count == 0 {
+        0.0
+    } else {
+        index.total_token_count as f64 / document_count as f64
+    };
+
+    // Best body score per note and query term.
+    let mut body_scores: HashMap<&str, HashMap<usize, f64>> = HashMap::new();
+    // Every indexed term each note matched, exactly or by prefix, for match locations.
+    let mut body_matched_terms: HashMap<&str, HashSet<&str>> = HashMap::new();
+    for (term_index, term) in query_terms.iter().enumerate() {
+        for (indexed_term, weight) in index.expand_term(term) {
+            let idf = index.inverse_document_frequency(indexed_term);
+            let Some(paths) = index.postings.get(indexed_term) else {
+                continue;
+            };
+
+            for rel_path in paths {
+                let Some(indexed) = index.notes_by_path.get(rel_path.as_ref()) else {
+                    continue;
+                };
+                let frequency = f64::from(
+                    indexed
+                        .term_frequencies
+                        .get(indexed_term)
+                        .copied()
+                        .unwrap_or_default(),
+                );
+                let length_ratio = if average_length > 0.0 {
+                    f64::from(indexed.token_count) / average_length
+                } else {
+                    1.0
+                };
+                let score = weight * idf * frequency * (BM25_K1 + 1.0)
+                    / (frequency + BM25_K1 * (1.0 - BM25_B + BM25_B * length_ratio));
+
+                body_matched_terms
+                    .entry(rel_path.as_ref())
+                    .or_default()
+                    .insert(indexed_term);
+                let best = body_scores
+                    .entry(rel_path.as_ref())
+                    .or_default()
+                    .entry(term_index)
+                    .or_insert(0.0);
+                *best = best.max(score);
+            }
+        }
+    }
+
+    let mut scored = Vec::new();
+    for note in notes {
+        let mut score = 0.0;
+        let mut path_match = false;
+        let mut label_match: Option<(f64, &String)> = None;
+
+        for term in query_terms {
+            let idf = index.inverse_document_frequency(term);
+            let path_weight = best_term_match_weight(tokenize(&note.rel_path), term);
+            score += PATH_MATCH_BOOST * idf * path_weight;
+            path_match |= path_weight > 0.0;
+
+            for label in &note.labels {
+                let weight = best_term_match_weight(tokenize(label), term);
+                if weight > 0.0 {
+                    score += LABEL_MATCH_BOOST * idf * weight;
+                    if label_match.is_none_or(|(best, _)| weight > best) {
+                        label_match = Some((weight, label));
+                    }
+                }
+            }
+        }
+
+        for filter in filters {
+            match filter {
+                QueryExpr::Label(expected) if label_match.is_none() => {
+                    label_match = note
+                        .labels
+                        .iter()
+                        .find(|label| label_matches(label, expected))
+                        .map(|label| (1.0, label));
+                }
+                QueryExpr::Path(prefix) => {
+                    path_match |= note.rel_path.to_lowercase().starts_with(prefix.as_str());
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(term_scores) = body_scores.get(note.rel_path.as_str()) {
+            score += term_scores.values().sum::<f64>();
+        }
+        let body_terms: HashSet<String> = body_matched_terms
+            .get(note.rel_path.as_str())
+            .map(|terms| terms.iter().map(|term| term.to_string()).collect())
+            .unwrap_or_default();
+
+        scored.push(ScoredNote {
+            rel_path: note.rel_path.clone(),
+            score,
+            content: (!body_terms.is_empty())
+                .then(|| index.notes_by_path.get(&note.rel_path))
+                .flatten()
+                .and_then(|indexed| indexed.content.clone()),
+            body_terms,
+            label_match: label_match.map(|(_, label)| label.clone()),
+            path_match,
+            last_updated: note.last_updated.clone(),
+        });
+    }
+
+    scored
+}
+
+/// Brings the notebook's index up to date with `notes` and runs `search` against it.
+///
+/// The index is loaded from the notebook's cache file if it is not in memory. Notes missing from
+/// the index are read, indexed notes whose file changed on disk are re-read at most once per
+/// refresh interval, and notes without loaded text are read when `needs_content` asks for it.
+/// Files are read outside the index lock, and a changed index is written back to the cache file.
+fn with_refreshed_search_index<R>(
+    notebook_path: &str,
+    notes: &[SearchNote],
+    needs_content: impl Fn(&NotebookSearchIndex, &str) -> bool,
+    search: impl FnOnce(&NotebookSearchIndex) -> R,
+) -> R {
+    ensure_search_index_loaded(notebook_path);
+
     let note_paths: HashSet<&str> = notes.iter().map(|note| note.rel_path.as_str()).collect();
     let (missing_paths, refresh_candidates, should_refresh) =
         with_search_indexes(|search_indexes| {
             prune_search_indexes(search_indexes);
-            let index = search_indexes.entry(notebook_path.clone()).or_default();
+            let index = search_indexes.entry(notebook_path.to_string()).or_default();
             touch_search_index(index);
-            index
-                .notes_by_path
-                .retain(|rel_path, _| note_paths.contains(rel_path.as_str()));
+            index.retain_notes(|rel_path| note_paths.contains(rel_path));
 
             let should_refresh =
                 should_refresh_search_index_from_filesystem(index.last_external_refresh);
             let mut missing_paths = Vec::new();
             let mut refresh_candidates = Vec::new();
 
-            for note in &notes {
+            for note in notes {
                 if let Some(indexed) = index.notes_by_path.get(&note.rel_path) {
-                    if should_refresh {
-                        refresh_candidates.push((note.rel_path.clone(), indexed.modified_time));
+                    let load_content =
+                        indexed.content.is_none() && needs_content(index, &note.rel_path);
+                    if should_refresh || load_content {
+                        refresh_candidates.push((
+                            note.rel_path.clone(),
+                            indexed.modified_time,
+                            indexed.content_hash,
+                            load_content,
+                        ));
                     }
                 } else {
                     missing_paths.push(note.rel_path.clone());
@@ -277,46 +896,59 @@ pub async fn search_notes_with_snapshot(
     for rel_path in missing_paths {
         missing_entries.push((
             rel_path.clone(),
-            read_note_content_for_index(&notebook_path, &rel_path),
+            read_note_content_for_index(notebook_path, &rel_path),
         ));
     }
 
     let mut refreshed_entries = Vec::new();
-    if should_refresh {
-        for (rel_path, previous_modified_time) in refresh_candidates {
-            let note_file_path = Path::new(&notebook_path).join(&rel_path).join("note.md");
-            let modified_time = note_file_modified_time(&note_file_path);
-
-            if previous_modified_time != modified_time {
-                refreshed_entries.push((
-                    rel_path.clone(),
-                    previous_modified_time,
-                    read_note_content_for_index(&notebook_path, &rel_path),
-                ));
-            }
+    for (rel_path, previous_modified_time, indexed_hash, load_content) in refresh_candidates {
+        let note_file_path = Path::new(notebook_path).join(&rel_path).join("note.md");
+        let modified_time = note_file_modified_time(&note_file_path);
+
+        if load_content || previous_modified_time != modified_time {
+            refreshed_entries.push((
+                rel_path.clone(),
+                previous_modified_time,
+                read_note_file_update(notebook_path, &rel_path, indexed_hash),
+            ));
         }
     }
 
-    let content_snapshot_by_path = with_search_indexes(|search_indexes| {
+    let (result, cache_contents) = with_search_indexes(|search_indexes| {
         prune_search_indexes(search_indexes);
-        let index = search_indexes.entry(notebook_path.clone()).or_default();
+        let index = search_indexes.entry(notebook_path.to_string()).or_default();
         touch_search_index(index);
-        index
-            .notes_by_path
-            .retain(|rel_path, _| note_paths.contains(rel_path.as_str()));
+        index.retain_notes(|rel_path| note_paths.contains(rel_path));
 
         for (rel_path, indexed_note) in missing_entries {
-            index.notes_by_path.entry(rel_path).or_insert(indexed_note);
+            if !index.notes_by_path.contains_key(&rel_path) {
+                index.insert_note(rel_path, indexed_note);
+            }
         }
 
-        for (rel_path, expected_previous_modified_time, indexed_note) in refreshed_entries {
+        for (rel_path, expected_previous_modified_time, update) in refreshed_entries {
             let should_apply = index
                 .notes_by_path
                 .get(&rel_path)
                 .is_none_or(|existing| existing.modified_time == expected_previous_modified_time);
+            if !should_apply {
+                continue;
+            }
 
-            if should_apply {
-                index.notes_by_path.insert(rel_path, indexed_note);
+            match update {
+                NoteFileUpdate::Changed(indexed_note) => index.insert_note(rel_path, indexed_note),
+                NoteFileUpdate::Unchanged {
+                    content,
+                    modified_time,
+                } => {
+                    if let Some(existing) = index.notes_by_path.get_mut(&rel_path) {
+                        existing.content = Some(content);
+                        if existing.modified_time != modified_time {
+                            existing.modified_time = modified_time;
+                            index.dirty = true;
+                        }
+                    }
+                }
             }
         }
 
@@ -324,55 +956,373 @@ pub async fn search_notes_with_snapshot(
             index.last_external_refresh = Some(Instant::now());
         }
 
-        let mut snapshot = HashMap::with_capacity(notes.len());
-        for note in &notes {
-            if let Some(indexed) = index.notes_by_path.get(&note.rel_path) {
-                snapshot.insert(note.rel_path.clone(), indexed.clone());
+        let cache_contents = if index.dirty {
+            index.dirty = false;
+            serialize_search_index_cache(index)
+        } else {
+            None
+        };
+        (search(index), cache_contents)
+    });
+
+    if let Some(cache_contents) = cache_contents {
+        write_search_index_cache(notebook_path, &cache_contents);
+    }
+    result
+}
+
+/// Searches notes with the query syntax described in [`parse_search_query`].
+///
+/// Matching notes are ranked by [`score_notes`] over the query's words and phrases that are not
+/// negated; a malformed query is reported as a validation error.
+fn search_text_notes(
+    notebook_path: &str,
+    notes: &[SearchNote],
+    query: &str,
+) -> Result<Vec<NoteSearchResult>, NotebookError> {
+    let Some(expr) = parse_search_query(query)? else {
+        return Ok(Vec::new());
+    };
+
+    let mut query_terms: Vec<String> = Vec::new();
+    // Negated phrases are matched against note text too, so their terms decide what is read.
+    let mut content_terms: Vec<&str> = Vec::new();
+    let mut filters: Vec<&QueryExpr> = Vec::new();
+    expr.visit_clauses(false, &mut |clause, negated| {
+        if let QueryExpr::Term(term) = clause {
+            content_terms.push(term);
+        } else if let QueryExpr::Phrase(terms) = clause {
+            content_terms.extend(terms.iter().map(String::as_str));
+        }
+        if negated {
+            return;
+        }
+        let terms = match clause {
+            QueryExpr::Term(term) => std::slice::from_ref(term),
+            QueryExpr::Phrase(terms) => terms.as_slice(),
+            _ => {
+                filters.push(clause);
+                return;
+            }
+        };
+        for term in terms {
+            if !query_terms.contains(term) {
+                query_terms.push(term.clone());
             }
         }
-        snapshot
     });
 
-    let mut results = Vec::new();
+    let needs_content = |index: &NotebookSearchIndex, rel_path: &str| {
+        content_terms.iter().any(|term| {
+            index.expand_term(term).any(|(indexed_term, _)| {
+                index
+                    .postings
+                    .get(indexed_term)
+                    .is_some_and(|paths| paths.contains(rel_path))
+            })
+        })
+    };
+    let mut scored_notes =
+        with_refreshed_search_index(notebook_path, notes, needs_content, |index| {
+            let matcher = QueryMatcher::new(index, &expr);
+            let matching_notes: Vec<&SearchNote> = notes
+                .iter()
+                .filter(|note| matcher.matches(&expr, note))
+                .collect();
+            score_notes(index, &matching_notes, &query_terms, &filters)
+        });
 
-    for note in &notes {
-        let rel_path_match = note.rel_path.to_lowercase().contains(&normalized_query);
-        let label_match = note
-            .labels
-            .iter()
-            .find(|label| label.to_lowercase().contains(&normalized_query))
-            .cloned();
-
-        let content_match = content_snapshot_by_path
-            .get(&note.rel_path)
-            .and_then(|indexed| {
-                if !indexed.content_lower.contains(&normalized_query) {
-                    return None;
-                }
-                find_matching_content_snippet(indexed.content.as_ref(), &normalized_query)
+    scored_notes.sort_by(|left, right| {
+        right
+            .score
+            .total_cmp(&left.score)
+            .then_with(|| left.rel_path.cmp(&right.rel_path))
+    });
+
+    Ok(scored_notes
+        .into_iter()
+        .map(|scored| {
+            let matched_terms: HashSet<&str> =
+                scored.body_terms.iter().map(String::as_str).collect();
+            let line_matches = scored.content.as_deref().map_or_else(Vec::new, |content| {
+                find_matching_content_lines(content, &matched_terms)
             });
 
-        if rel_path_match || label_match.is_some() || content_match.is_some() {
-            let snippet = if let Some(content_snippet) = content_match {
-                content_snippet
-            } else if let Some(matching_label) = label_match {
+            let snippet = if let Some(first_line) = line_matches.first() {
+                first_line.text.clone()
+            } else if let Some(matching_label) = scored.label_match {
                 format!(
                     "Label match: {}",
                     truncate_search_snippet(matching_label.as_str(), 100)
                 )
+            } else if let Some(last_updated) = scored.last_updated.filter(|_| !scored.path_match) {
+                format!("Last updated: {}", last_updated)
             } else {
                 "Path match".to_string()
             };
 
-            results.push(NoteSearchResult {
-                rel_path: note.rel_path.clone(),
+            NoteSearchResult {
+                rel_path: scored.rel_path,
                 snippet,
+                line_matches,
+            }
+        })
+        .collect())
+}
+
+/// Part of a note that fuzzy search matches query words against.
+#[derive(Clone, Copy)]
+enum FuzzyField {
+    Path,
+    Label,
+    Heading,
+}
+
+impl FuzzyField {
+    fn weight(self) -> f64 {
+        match self {
+            Self::Path => 1.0,
+            Self::Label => FUZZY_LABEL_WEIGHT,
+            Self::Heading => FUZZY_HEADING_WEIGHT,
+        }
+    }
+
+    fn snippet(self, text: &str) -> String {
+        match self {
+            Self::Path => "Path match".to_string(),
+            Self::Label => format!("Label match: {}", truncate_search_snippet(text, 100)),
+            Self::Heading => truncate_search_snippet(text, 120),
+        }
+    }
+}
+
+/// Ranks notes whose path, labels or headings fuzzily match every word of `query`.
+///
+/// Words may be abbreviations (`mtng` for `meetings`) or contain a typo or two; see
+/// [`fuzzy_word_score`]. Body text is not searched.
+fn search_fuzzy_notes(
+    notebook_path: &str,
+    notes: &[SearchNote],
+    query: &str,
+) -> Vec<NoteSearchResult> {
+    let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    if words.is_empty() {
+        return Vec::new();
+    }
+
+    let mut scored_notes = with_refreshed_search_index(
+        notebook_path,
+        notes,
+        |_, _| false,
+        |index| {
+            let mut scored_notes = Vec::new();
+            for note in notes {
+                let headings = index
+                    .notes_by_path
+                    .get(&note.rel_path)
+                    .map_or(&[][..], |indexed| indexed.headings.as_slice());
+                let candidates = std::iter::once((FuzzyField::Path, note.rel_path.as_str()))
+                    .chain(
+                        note.labels
+                            .iter()
+                            .map(|label| (FuzzyField::Label, label.as_str())),
+                    )
+                    .chain(
+                        headings
+                            .iter()
+                            .map(|heading| (FuzzyField::Heading, heading.as_str())),
+                    );
+
+                let mut score = 0.0;
+                // The candidate with the best single word match explains the result.
+                let mut best_match: Option<(f64, String)> = None;
+                let mut matched_all = true;
+                for word in &words {
+                    let best_for_word = candidates
+                        .clone()
+                        .filter_map(|(field, text)| {
+                            fuzzy_word_score(word, text)
+                                .map(|score| (field.weight() * score, field, text))
+                        })
+                        .max_by(|left, right| left.0.total_cmp(&right.0));
+                    let Some((word_score, field, text)) = best_for_word else {
+                        matched_all = false;
+                        break;
+                    };
+
+                    score += word_score;
+                    if best_match
+                        .as_ref()
+                        .is_none_or(|(best_score, _)| word_score > *best_score)
+                    {
+                        best_match = Some((word_score, field.snippet(text)));
+                    }
+                }
+
+                if let (true, Some((_, snippet))) = (matched_all, best_match) {
+                    scored_notes.push((
+                        score,
+                        NoteSearchResult {
+                            rel_path: note.rel_path.clone(),
+                            snippet,
+                            line_matches: Vec::new(),
+                        },
+                    ));
+                }
+            }
+            scored_notes
+        },
+    );
+
+    scored_notes.sort_by(|left, right| {
+        right
+            .0
+            .total_cmp(&left.0)
+            .then_with(|| left.1.rel_path.cmp(&right.1.rel_path))
+    });
+    scored_notes.into_iter().map(|(_, result)| result).collect()
+}
+
+fn compile_search_regex(pattern: &str) -> Result<Regex, NotebookError> {
+    RegexBuilder::new(pattern)
+        .size_limit(REGEX_SEARCH_SIZE_LIMIT)
+        .build()
+        .map_err(|error| {
+            // Syntax errors repeat the pattern with a caret marker; the last line says what is wrong.
+            let error = error.to_string();
+            let reason = error
+                .lines()
+                .rev()
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+                .unwrap_or_default()
+                .trim_start_matches("error: ");
+            NotebookError::validation(
+                "search pattern",
+                format!("Invalid regular expression: {}", reason),
+            )
+        })
+}
+
+/// Shares the loaded text of `notes` so it can be read after the index lock is released.
+fn snapshot_note_texts(
+    index: &NotebookSearchIndex,
+    notes: &[SearchNote],
+) -> Vec<(String, Arc<str>)> {
+    notes
+        .iter()
+        .filter_map(|note| {
+            let content = index.notes_by_path.get(&note.rel_path)?.content.clone()?;
+            Some((note.rel_path.clone(), content))
+        })
+        .collect()
+}
+
+/// Collects every matching line per note, failing once matching has run for `time_budget`.
+fn match_regex_lines(
+    note_texts: &[(String, Arc<str>)],
+    regex: &Regex,
+    time_budget: Duration,
+) -> Result<Vec<NoteSearchResult>, NotebookError> {
+    let started_at = Instant::now();
+    let mut results = Vec::new();
+    for (rel_path, text) in note_texts {
+        let mut line_matches = Vec::new();
+        for (line_index, line) in text.lines().enumerate() {
+            if started_at.elapsed() >= time_budget {
+                return Err(NotebookError::validation(
+                    "search pattern",
+                    format!(
+                        "Regular expression search took longer than {} ms. Use a more specific pattern.",
+                        time_budget.as_millis()
+                    ),
+                ));
+            }
+            if regex.is_match(line) {
+                let columns = regex
+                    .find_iter(line)
+                    .filter(|found| !found.is_empty())
+                    .map(|found| SearchMatchRange {
+                        start: found.start(),
+                        end: found.end(),
+                    })
+                    .collect();
+                line_matches.push(build_line_match(line_index, line, columns));
+            }
+        }
+
+        if let Some(first_match) = line_matches.first() {
+            results.push(NoteSearchResult {
+                rel_path: rel_path.clone(),
+                snippet: format!("{}: {}", first_match.line_number, first_match.text),
+                line_matches,
             });
         }
     }
+    Ok(results)
+}
+
+/// Lists every content line matching the regular expression `pattern`, note by note in path
+/// order.
+///
+/// Matching stops with a validation error once it runs past [`REGEX_SEARCH_TIME_BUDGET`], so a
+/// costly pattern over a large notebook cannot keep searching indefinitely.
+fn search_regex_notes(
+    notebook_path: &str,
+    notes: &[SearchNote],
+    pattern: &str,
+) -> Result<Vec<NoteSearchResult>, NotebookError> {
+    if pattern.is_empty() {
+        return Ok(Vec::new());
+    }
+    let regex = compile_search_regex(pattern)?;
+
+    // Matching can run for the whole time budget, so it works on shared copies of the note
+    // texts instead of holding the index lock that every search and save waits on.
+    let note_texts = with_refreshed_search_index(
+        notebook_path,
+        notes,
+        |_, _| true,
+        |index| snapshot_note_texts(index, notes),
+    );
+    let mut results = match_regex_lines(&note_texts, &regex, REGEX_SEARCH_TIME_BUDGET)?;
+    results.sort_by(|left, right| left.rel_path.cmp(&right.rel_path));
+    Ok(results)
+}
+
+/// Fails with the same validation error a search would for a malformed query or pattern.
+pub(super) fn validate_search_query(query: &str, mode: SearchMode) -> Result<(), NotebookError> {
+    match mode {
+        SearchMode::Text => parse_search_query(query).map(|_| ()),
+        SearchMode::Fuzzy => Ok(()),
+        SearchMode::Regex => compile_search_regex(query).map(|_| ()),
+    }
+}
 
-    results.sort_by(|a, b| a.rel_path.cmp(&b.rel_path));
-    results
+/// Whether the note at `rel_path` is `scope` or lies inside it; `None` is the whole notebook.
+///
+/// A note's folder can itself be a note, so the scope's own note is part of the scope.
+pub fn is_in_search_scope(rel_path: &str, scope: Option<&str>) -> bool {
+    scope.is_none_or(|folder| {
+        rel_path
+            .strip_prefix(folder)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    })
+}
+
+/// Searches `notes` in the given mode; malformed text queries and regex patterns are
+/// validation errors.
+pub async fn search_notes_with_snapshot(
+    notebook_path: String,
+    notes: Vec<SearchNote>,
+    query: String,
+    mode: SearchMode,
+) -> Result<Vec<NoteSearchResult>, NotebookError> {
+    match mode {
+        SearchMode::Text => search_text_notes(&notebook_path, &notes, &query),
+        SearchMode::Fuzzy => Ok(search_fuzzy_notes(&notebook_path, &notes, &query)),
+        SearchMode::Regex => search_regex_notes(&notebook_path, &notes, &query),
+    }
 }
 
 #[cfg(test)]
@@ -390,17 +1340,15 @@ mod search_index_eviction_tests {
         indexes.insert(
             "stale".to_string(),
             NotebookSearchIndex {
-                notes_by_path: HashMap::new(),
-                last_external_refresh: None,
                 last_accessed_at: stale_last_access,
+                ..NotebookSearchIndex::default()
             },
         );
         indexes.insert(
             "active".to_string(),
             NotebookSearchIndex {
-                notes_by_path: HashMap::new(),
-                last_external_refresh: None,
                 last_accessed_at: now,
+                ..NotebookSearchIndex::default()
             },
         );
 
@@ -428,9 +1376,8 @@ mod search_index_eviction_tests {
             indexes.insert(
                 format!("notebook_{i}"),
                 NotebookSearchIndex {
-                    notes_by_path: HashMap::new(),
-                    last_external_refresh: None,
                     last_accessed_at,
+                    ..NotebookSearchIndex::default()
                 },
             );
         }
@@ -452,3 +1399,113 @@ mod search_index_eviction_tests {
         );
     }
 }
+
+#[cfg(test)]
+mod inverted_index_tests {
+    use super::*;
+
+    fn indexed_paths(index: &NotebookSearchIndex, term: &str) -> Vec<String> {
+        let mut paths: Vec<String> = index
+            .postings
+            .get(term)
+            .map(|paths| paths.iter().map(|path| path.to_string()).collect())
+            .unwrap_or_default();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn postings_follow_note_updates_renames_and_removals() {
+        let mut index = NotebookSearchIndex::default();
+        index.insert_note(
+            "alpha".to_string(),
+            IndexedNoteContent::new("Shared words, alpha words".to_string(), None),
+        );
+        index.insert_note(
+            "beta".to_string(),
+            IndexedNoteContent::new("shared".to_string(), None),
+        );
+        assert_eq!(indexed_paths(&index, "shared"), vec!["alpha", "beta"]);
+        assert_eq!(index.total_token_count, 5);
+
+        index.insert_note(
+            "alpha".to_string(),
+            IndexedNoteContent::new("rewritten".to_string(), None),
+        );
+        assert_eq!(indexed_paths(&index, "shared"), vec!["beta"]);
+        assert!(!index.postings.contains_key("words"));
+        assert_eq!(index.total_token_count, 2);
+
+        let entry = index.remove_note("beta").expect("beta should be indexed");
+        index.insert_note("archive/beta".to_string(), entry);
+        assert_eq!(indexed_paths(&index, "shared"), vec!["archive/beta"]);
+
+        index.retain_notes(|path| path == "alpha");
+        assert!(!index.postings.contains_key("shared"));
+        assert_eq!(index.total_token_count, 1);
+
+        let expanded: Vec<(&str, f64)> = index.expand_term("re").collect();
+        assert_eq!(expanded, vec![("rewritten", PREFIX_MATCH_WEIGHT)]);
+    }
+}
+
+#[cfg(test)]
+mod line_excerpt_tests {
+    use super::*;
+
+    #[test]
+    fn build_line_match_keeps_a_late_match_visible_and_highlighted() {
+        let line = format!("  {}needle and more text", "é".repeat(200));
+        let start = line.find("needle").expect("needle is in the line");
+        let columns = vec![SearchMatchRange {
+            start,
+            end: start + "needle".len(),
+        }];
+
+        let line_match = build_line_match(4, &line, columns.clone());
+        assert_eq!(line_match.line_number, 5);
+        assert_eq!(line_match.columns, columns);
+        assert!(line_match.text.starts_with("..."));
+        assert!(line_match.text.ends_with("needle and more text"));
+        let highlight = line_match.highlights[0];
+        assert_eq!(&line_match.text[highlight.start..highlight.end], "needle");
+
+        let short = build_line_match(0, "\tshort line  ", Vec::new());
+        assert_eq!(short.text, "short line");
+        assert!(short.highlights.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod regex_search_tests {
+    use super::*;
+
+    #[test]
+    fn regex_search_reports_every_matching_line_within_the_time_budget() {
+        let mut index = NotebookSearchIndex::default();
+        index.insert_note(
+            "todo".to_string(),
+            IndexedNoteContent::new("- [ ] one\ndone\n- [ ] two".to_string(), None),
+        );
+        let notes = vec![SearchNote {
+            rel_path: "todo".to_string(),
+            labels: Vec::new(),
+            last_updated: None,
+        }];
+        let regex = compile_search_regex(r"^- \[ \]").expect("pattern should compile");
+
+        let note_texts = snapshot_note_texts(&index, &notes);
+        let results = match_regex_lines(&note_texts, &regex, REGEX_SEARCH_TIME_BUDGET)
+            .expect("search should finish");
+        let line_numbers: Vec<usize> = results[0]
+            .line_matches
+            .iter()
+            .map(|line_match| line_match.line_number)
+            .collect();
+        assert_eq!(line_numbers, [1, 3]);
+
+        let error = match_regex_lines(&note_texts, &regex, Duration::ZERO)
+            .expect_err("an exhausted budget should stop the search");
+        assert!(error.to_string().contains("took longer"), "{error}");
+    }
+}
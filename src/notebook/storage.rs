@@ -2,25 +2,50 @@ use std::fs;
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
 use std::process;
+use std::sync::OnceLock;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use time::OffsetDateTime;
+use serde_json::Value;
 use time::format_description::well_known::Rfc3339;
+use time::{OffsetDateTime, UtcOffset};
 
+use super::front_matter::sync_front_matter_from_disk;
+use super::fsck::find_untracked_notes;
+use super::history::record_note_snapshot;
+use super::links::cache_upsert_note_links;
 use super::search::{cache_upsert_search_index_note_content, note_file_modified_time};
+use super::watcher::{is_known_file_content, remember_file_content};
 use super::{
-    NoteMetadata, NotebookError, NotebookMetadata, NotebookRelativePath,
-    STAGED_DELETE_CLEANUP_GRACE_NANOS, STAGED_DELETE_PREFIX,
+    LabelRegistry, NoteDiskState, NoteMetadata, NoteSaveOutcome, NotebookError, NotebookMetadata,
+    NotebookRelativePath, NotebookSettings, STAGED_DELETE_CLEANUP_GRACE_NANOS,
+    STAGED_DELETE_PREFIX, SavedSearch,
 };
 
 const METADATA_FILE_NAME: &str = "metadata.json";
 const METADATA_BACKUP_FILE_NAME: &str = "metadata.json.bak";
+/// How many adopted note paths the load warning names before summarizing the rest.
+const ADOPTED_NOTE_WARNING_LIMIT: usize = 5;
+/// Schema version written by this build; older files are migrated on load.
+pub(crate) const METADATA_SCHEMA_VERSION: u32 = 5;
+/// Migration at index `n` upgrades a metadata document from version `n` to `n + 1`.
+const METADATA_MIGRATIONS: [fn(&mut Value); METADATA_SCHEMA_VERSION as usize] = [
+    migrate_metadata_v0_to_v1,
+    migrate_metadata_v1_to_v2,
+    migrate_metadata_v2_to_v3,
+    migrate_metadata_v3_to_v4,
+    migrate_metadata_v4_to_v5,
+];
 #[cfg(test)]
 const FAIL_ATOMIC_RENAME_MARKER: &str = ".cognate_fail_atomic_rename";
 
+static LOCAL_UTC_OFFSET: OnceLock<UtcOffset> = OnceLock::new();
+
 #[derive(Debug, Clone)]
 pub struct MetadataLoadResult {
     pub notes: Vec<NoteMetadata>,
+    pub saved_searches: Vec<SavedSearch>,
+    pub settings: NotebookSettings,
+    pub label_registry: LabelRegistry,
     pub warning: Option<String>,
 }
 
@@ -31,7 +56,32 @@ pub fn current_timestamp_rfc3339() -> String {
         .unwrap_or_else(|| "1970-01-01T00:00:00Z".to_string())
 }
 
-fn format_system_time_rfc3339(timestamp: SystemTime) -> Option<String> {
+fn local_utc_offset() -> UtcOffset {
+    *LOCAL_UTC_OFFSET.get_or_init(|| UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC))
+}
+
+/// Reads the local UTC offset once; `time` can only determine it while the process has a
+/// single thread, so call this at startup. Without a readable offset, local time is UTC.
+pub fn init_local_utc_offset() {
+    local_utc_offset();
+}
+
+/// Current wall-clock time in the local offset in effect now.
+///
+/// Where `time` cannot read the offset once other threads run (Linux among them), the offset
+/// read at startup is used instead, so a daylight-saving change while Cognate runs only shows
+/// up after a restart.
+pub(super) fn current_local_datetime() -> OffsetDateTime {
+    let now = OffsetDateTime::now_utc();
+    to_local_datetime(now, UtcOffset::local_offset_at(now).ok())
+}
+
+/// Puts `now` in `offset`, or in the offset read at startup when none could be read.
+pub(crate) fn to_local_datetime(now: OffsetDateTime, offset: Option<UtcOffset>) -> OffsetDateTime {
+    now.to_offset(offset.unwrap_or_else(local_utc_offset))
+}
+
+pub(super) fn format_system_time_rfc3339(timestamp: SystemTime) -> Option<String> {
     OffsetDateTime::from_unix_timestamp(OffsetDateTime::from(timestamp).unix_timestamp())
         .ok()
         .and_then(|dt| dt.format(&Rfc3339).ok())
@@ -244,16 +294,145 @@ pub(super) fn write_text_file_atomically(
     })
 }
 
+/// Sets `key` to `default` when the object lacks it or stores `null`, which serde's field
+/// defaults do not accept.
+fn fill_missing_field(object: &mut serde_json::Map<String, Value>, key: &str, default: Value) {
+    let value = object.entry(key).or_insert(Value::Null);
+    if value.is_null() {
+        *value = default;
+    }
+}
+
+/// Version 0 notes could omit their `labels` list or store it as `null`.
+fn migrate_metadata_v0_to_v1(metadata: &mut Value) {
+    let Some(notes) = metadata.get_mut("notes").and_then(Value::as_array_mut) else {
+        return;
+    };
+    for note in notes.iter_mut().filter_map(Value::as_object_mut) {
+        fill_missing_field(note, "labels", Value::Array(Vec::new()));
+    }
+}
+
+/// Version 2 adds the `saved_searches` list shown as smart folders.
+fn migrate_metadata_v1_to_v2(metadata: &mut Value) {
+    if let Some(metadata) = metadata.as_object_mut() {
+        fill_missing_field(metadata, "saved_searches", Value::Array(Vec::new()));
+    }
+}
+
+/// Version 3 adds the `settings` object holding the default template.
+fn migrate_metadata_v2_to_v3(metadata: &mut Value) {
+    if let Some(metadata) = metadata.as_object_mut() {
+        fill_missing_field(metadata, "settings", Value::Object(serde_json::Map::new()));
+    }
+}
+
+/// Version 4 notes carry the `title`, `aliases` and `properties` read from front matter.
+fn migrate_metadata_v3_to_v4(metadata: &mut Value) {
+    let Some(notes) = metadata.get_mut("notes").and_then(Value::as_array_mut) else {
+        return;
+    };
+    for note in notes.iter_mut().filter_map(Value::as_object_mut) {
+        fill_missing_field(note, "aliases", Value::Array(Vec::new()));
+        fill_missing_field(note, "properties", Value::Object(serde_json::Map::new()));
+    }
+}
+
+/// Version 5 adds the `label_registry` with label colors, descriptions and icons.
+fn migrate_metadata_v4_to_v5(metadata: &mut Value) {
+    if let Some(metadata) = metadata.as_object_mut() {
+        fill_missing_field(
+            metadata,
+            "label_registry",
+            Value::Object(serde_json::Map::new()),
+        );
+    }
+}
+
+enum MetadataParseError {
+    /// The document is not valid metadata; a backup copy may still be.
+    Invalid(String),
+    /// The document was written by a newer Cognate and must not be opened or overwritten.
+    Unsupported(NotebookError),
+}
+
+struct ParsedMetadata {
+    metadata: NotebookMetadata,
+    /// Schema version the document was stored with, before migration.
+    source_version: u32,
+}
+
+/// Parses a `metadata.json` document and migrates it to the current schema version.
+fn parse_metadata(
+    contents: &str,
+    metadata_path: &Path,
+) -> Result<ParsedMetadata, MetadataParseError> {
+    let mut document: Value = serde_json::from_str(contents)
+        .map_err(|error| MetadataParseError::Invalid(error.to_string()))?;
+
+    let source_version = match document.get("schema_version") {
+        None => 0,
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| {
+                MetadataParseError::Invalid(format!("invalid schema_version {}", version))
+            })?,
+    };
+
+    if source_version > METADATA_SCHEMA_VERSION {
+        return Err(MetadataParseError::Unsupported(NotebookError::validation(
+            "load metadata",
+            format!(
+                "'{}' uses metadata schema version {}, but this version of Cognate supports up to version {}. Update Cognate to open this notebook.",
+                metadata_path.display(),
+                source_version,
+                METADATA_SCHEMA_VERSION
+            ),
+        )));
+    }
+
+    for migration in &METADATA_MIGRATIONS[source_version as usize..] {
+        migration(&mut document);
+    }
+    if let Some(root) = document.as_object_mut() {
+        root.insert(
+            "schema_version".to_string(),
+            Value::from(METADATA_SCHEMA_VERSION),
+        );
+    }
+
+    let metadata = serde_json::from_value(document)
+        .map_err(|error| MetadataParseError::Invalid(error.to_string()))?;
+    Ok(ParsedMetadata {
+        metadata,
+        source_version,
+    })
+}
+
+/// Keeps the document a migration is about to replace, named after its schema version.
+fn write_pre_migration_backup(
+    notebook_path: &str,
+    source_version: u32,
+    contents: &str,
+) -> Result<PathBuf, NotebookError> {
+    let backup_path =
+        Path::new(notebook_path).join(format!("metadata.json.v{}.bak", source_version));
+    write_text_file_atomically(&backup_path, contents)?;
+    Ok(backup_path)
+}
+
 fn metadata_backup_path(notebook_path: &str) -> PathBuf {
     Path::new(notebook_path).join(METADATA_BACKUP_FILE_NAME)
 }
 
+/// Copies the current metadata to the recovery backup and returns it, if there is any.
 fn snapshot_known_good_metadata(
     metadata_path: &Path,
     backup_path: &Path,
-) -> Result<(), NotebookError> {
+) -> Result<Option<NotebookMetadata>, NotebookError> {
     if !metadata_path.exists() {
-        return Ok(());
+        return Ok(None);
     }
 
     let existing_metadata = fs::read_to_string(metadata_path).map_err(|error| {
@@ -267,16 +446,21 @@ fn snapshot_known_good_metadata(
         )
     })?;
 
-    serde_json::from_str::<NotebookMetadata>(&existing_metadata).map_err(|error| {
-        NotebookError::recovery(
-            "metadata snapshot",
-            format!(
-                "Refusing to overwrite invalid metadata at '{}': {}",
-                metadata_path.display(),
-                error
-            ),
-        )
-    })?;
+    let stored_metadata = match parse_metadata(&existing_metadata, metadata_path) {
+        Ok(parsed) => parsed.metadata,
+        Err(MetadataParseError::Invalid(error)) => {
+            return Err(NotebookError::recovery(
+                "metadata snapshot",
+                format!(
+                    "Refusing to overwrite invalid metadata at '{}': {}",
+                    metadata_path.display(),
+                    error
+                ),
+            ));
+        }
+        // Saving would drop whatever the newer schema added.
+        Err(MetadataParseError::Unsupported(error)) => return Err(error),
+    };
 
     write_text_file_atomically(backup_path, &existing_metadata).map_err(|error| {
         NotebookError::recovery(
@@ -287,7 +471,8 @@ fn snapshot_known_good_metadata(
                 error
             ),
         )
-    })
+    })?;
+    Ok(Some(stored_metadata))
 }
 
 fn append_warning(current: &mut Option<String>, warning: String) {
@@ -299,7 +484,53 @@ fn append_warning(current: &mut Option<String>, warning: String) {
     }
 }
 
+/// Adds notes that exist on disk but not in `notes`, reporting them through `warning`.
+///
+/// Returns whether any note was added.
+fn adopt_untracked_notes(
+    notebook_path: &str,
+    notes: &mut Vec<NoteMetadata>,
+    warning: &mut Option<String>,
+) -> bool {
+    let adopted = find_untracked_notes(Path::new(notebook_path), notes);
+    if adopted.is_empty() {
+        return false;
+    }
+
+    let listed: Vec<&str> = adopted
+        .iter()
+        .take(ADOPTED_NOTE_WARNING_LIMIT)
+        .map(|note| note.rel_path.as_str())
+        .collect();
+    let mut summary = format!("'{}'", listed.join("', '"));
+    if adopted.len() > listed.len() {
+        summary.push_str(&format!(" and {} more", adopted.len() - listed.len()));
+    }
+    append_warning(
+        warning,
+        format!(
+            "Added {} note(s) found on disk that were missing from metadata: {}.",
+            adopted.len(),
+            summary
+        ),
+    );
+
+    notes.extend(adopted);
+    true
+}
+
+/// Writes `notes` to `metadata.json`, keeping the saved searches, settings and label registry
+/// stored there.
 pub fn save_metadata(notebook_path: &str, notes: &[NoteMetadata]) -> Result<(), NotebookError> {
+    save_metadata_with(notebook_path, notes, |_| {})
+}
+
+/// Writes `notes` to `metadata.json` after `update` changed the rest of the stored metadata.
+pub(super) fn save_metadata_with(
+    notebook_path: &str,
+    notes: &[NoteMetadata],
+    update: impl FnOnce(&mut NotebookMetadata),
+) -> Result<(), NotebookError> {
     #[cfg(debug_assertions)]
     eprintln!(
         "Saving metadata to: {}",
@@ -327,11 +558,11 @@ pub fn save_metadata(notebook_path: &str, notes: &[NoteMetadata]) -> Result<(),
         ));
     }
 
-    let notebook_metadata = NotebookMetadata {
-        notes: notes.to_vec(),
-    };
-
-    snapshot_known_good_metadata(&metadata_path, &backup_path)?;
+    let mut notebook_metadata =
+        snapshot_known_good_metadata(&metadata_path, &backup_path)?.unwrap_or_default();
+    notebook_metadata.schema_version = METADATA_SCHEMA_VERSION;
+    notebook_metadata.notes = notes.to_vec();
+    update(&mut notebook_metadata);
 
     let json_string = serde_json::to_string_pretty(&notebook_metadata).map_err(|error| {
         NotebookError::storage(
@@ -341,6 +572,7 @@ pub fn save_metadata(notebook_path: &str, notes: &[NoteMetadata]) -> Result<(),
     })?;
 
     write_text_file_atomically(&metadata_path, &json_string)?;
+    remember_file_content(&metadata_path, &json_string);
 
     #[cfg(debug_assertions)]
     eprintln!("Metadata saved successfully.");
@@ -366,6 +598,7 @@ pub async fn load_notes_metadata(
                 "load_notes_metadata: Successfully read file: {}",
                 file_path.display()
             );
+            remember_file_content(&file_path, &c);
             c
         }
         Err(_err) => {
@@ -378,9 +611,25 @@ pub async fn load_notes_metadata(
             if _err.kind() == ErrorKind::NotFound {
                 #[cfg(debug_assertions)]
                 eprintln!("Metadata file not found, assuming new notebook.");
+                let mut notes = Vec::new();
+                let mut warning = None;
+                if adopt_untracked_notes(&notebook_path, &mut notes, &mut warning)
+                    && let Err(error) = save_metadata(&notebook_path, &notes)
+                {
+                    append_warning(
+                        &mut warning,
+                        format!(
+                            "Failed to create metadata for the notes found on disk: {}",
+                            error.ui_message()
+                        ),
+                    );
+                }
                 return Ok(MetadataLoadResult {
-                    notes: Vec::new(),
-                    warning: None,
+                    notes,
+                    saved_searches: Vec::new(),
+                    settings: NotebookSettings::default(),
+                    label_registry: LabelRegistry::new(),
+                    warning,
                 });
             }
             return Err(NotebookError::storage(
@@ -395,13 +644,14 @@ pub async fn load_notes_metadata(
     };
 
     let mut warning: Option<String> = None;
-    let metadata: NotebookMetadata = match serde_json::from_str(&contents) {
-        Ok(m) => {
+    let (parsed, parsed_contents) = match parse_metadata(&contents, &file_path) {
+        Ok(parsed) => {
             #[cfg(debug_assertions)]
             eprintln!("load_notes_metadata: Successfully parsed metadata.");
-            m
+            (parsed, contents)
         }
-        Err(_err) => {
+        Err(MetadataParseError::Unsupported(error)) => return Err(error),
+        Err(MetadataParseError::Invalid(_err)) => {
             #[cfg(debug_assertions)]
             eprintln!(
                 "load_notes_metadata: Error parsing metadata from {}: {}",
@@ -422,9 +672,11 @@ pub async fn load_notes_metadata(
                 )
             })?;
 
-            let backup_metadata = serde_json::from_str::<NotebookMetadata>(&backup_contents)
-                .map_err(|backup_parse_error| {
-                    NotebookError::recovery(
+            let backup_parsed = match parse_metadata(&backup_contents, &backup_path) {
+                Ok(parsed) => parsed,
+                Err(MetadataParseError::Unsupported(error)) => return Err(error),
+                Err(MetadataParseError::Invalid(backup_parse_error)) => {
+                    return Err(NotebookError::recovery(
                         "metadata recovery",
                         format!(
                             "Failed to parse metadata at '{}': {}. Backup '{}' is also invalid: {}",
@@ -433,8 +685,9 @@ pub async fn load_notes_metadata(
                             backup_path.display(),
                             backup_parse_error
                         ),
-                    )
-                })?;
+                    ));
+                }
+            };
 
             write_text_file_atomically(&file_path, &backup_contents).map_err(|restore_error| {
                 NotebookError::recovery(
@@ -455,12 +708,39 @@ pub async fn load_notes_metadata(
                 file_path.display()
             ));
 
-            backup_metadata
+            (backup_parsed, backup_contents)
         }
     };
 
-    let mut notes = metadata.notes;
     let mut metadata_changed = false;
+    if parsed.source_version < METADATA_SCHEMA_VERSION {
+        match write_pre_migration_backup(&notebook_path, parsed.source_version, &parsed_contents) {
+            // Saving below persists the migrated document.
+            Ok(_backup_path) => {
+                #[cfg(debug_assertions)]
+                eprintln!(
+                    "Migrating metadata from schema version {} to {}; kept '{}'.",
+                    parsed.source_version,
+                    METADATA_SCHEMA_VERSION,
+                    _backup_path.display()
+                );
+                metadata_changed = true;
+            }
+            Err(error) => append_warning(
+                &mut warning,
+                format!(
+                    "Loaded metadata in memory but did not upgrade it from schema version {} because the backup could not be written: {}",
+                    parsed.source_version,
+                    error.ui_message()
+                ),
+            ),
+        }
+    }
+
+    let saved_searches = parsed.metadata.saved_searches;
+    let settings = parsed.metadata.settings;
+    let label_registry = parsed.metadata.label_registry;
+    let mut notes = parsed.metadata.notes;
 
     for note in &mut notes {
         let note_file_path = Path::new(&notebook_path)
@@ -478,19 +758,59 @@ pub async fn load_notes_metadata(
         }
     }
 
+    if adopt_untracked_notes(&notebook_path, &mut notes, &mut warning) {
+        metadata_changed = true;
+    }
+    // Front matter edited outside Cognate wins over the stored copy.
+    if sync_front_matter_from_disk(&notebook_path, notes.iter_mut()) {
+        metadata_changed = true;
+    }
+
     if metadata_changed && let Err(_error) = save_metadata(&notebook_path, &notes) {
         append_warning(
             &mut warning,
             format!(
-                "Loaded metadata but failed to persist normalized timestamps: {}",
+                "Loaded metadata but failed to persist the upgraded or normalized metadata: {}",
                 _error.ui_message()
             ),
         );
     }
 
-    Ok(MetadataLoadResult { notes, warning })
+    Ok(MetadataLoadResult {
+        notes,
+        saved_searches,
+        settings,
+        label_registry,
+        warning,
+    })
+}
+
+/// Reads a note's Markdown and modification time for the editor.
+///
+/// The content is empty when the note cannot be read.
+pub fn load_note_content_sync(notebook_path: &str, rel_note_path: &str) -> NoteDiskState {
+    let full_note_path = Path::new(notebook_path).join(rel_note_path).join("note.md");
+    // Read the time first so a concurrent write makes the time look stale, never the content.
+    let modified_time = note_file_modified_time(&full_note_path);
+    let content = match fs::read_to_string(&full_note_path) {
+        Ok(content) => {
+            remember_file_content(&full_note_path, &content);
+            content
+        }
+        Err(_err) => {
+            #[cfg(debug_assertions)]
+            eprintln!("Failed to read note file for editor: {}", _err);
+            String::new()
+        }
+    };
+
+    NoteDiskState {
+        content,
+        modified_time,
+    }
 }
 
+#[cfg(test)]
 pub async fn save_note_content(
     notebook_path: String,
     rel_note_path: String,
@@ -499,11 +819,51 @@ pub async fn save_note_content(
     save_note_content_sync(&notebook_path, &rel_note_path, &content)
 }
 
+#[cfg(test)]
 pub fn save_note_content_sync(
     notebook_path: &str,
     rel_note_path: &str,
     content: &str,
 ) -> Result<(), NotebookError> {
+    save_note_content_checked_sync(notebook_path, rel_note_path, content, None).map(|_| ())
+}
+
+/// Saves note content unless `note.md` was changed by another program since `base` was loaded.
+pub async fn save_note_content_checked(
+    notebook_path: String,
+    rel_note_path: String,
+    content: String,
+    base: Option<NoteDiskState>,
+) -> Result<NoteSaveOutcome, NotebookError> {
+    save_note_content_checked_sync(&notebook_path, &rel_note_path, &content, base.as_ref())
+}
+
+/// Whether the file on disk holds an edit made outside Cognate since `base` was loaded.
+///
+/// Content Cognate wrote itself does not count, so overlapping saves of one editor buffer do not
+/// conflict with each other.
+pub(super) fn is_external_edit(
+    full_note_path: &Path,
+    base: &NoteDiskState,
+    disk_content: &str,
+    disk_modified_time: Option<SystemTime>,
+    content: &str,
+) -> bool {
+    if base.modified_time.is_some() && disk_modified_time == base.modified_time {
+        return false;
+    }
+
+    disk_content != base.content
+        && disk_content != content
+        && !is_known_file_content(full_note_path, disk_content)
+}
+
+pub fn save_note_content_checked_sync(
+    notebook_path: &str,
+    rel_note_path: &str,
+    content: &str,
+    base: Option<&NoteDiskState>,
+) -> Result<NoteSaveOutcome, NotebookError> {
     let rel_note_path = NotebookRelativePath::parse("note path", rel_note_path)?;
     let full_note_path = rel_note_path
         .join_under(Path::new(notebook_path))
@@ -520,6 +880,7 @@ pub fn save_note_content_sync(
         ));
     }
 
+    let existing_modified_time = note_file_modified_time(&full_note_path);
     let existing_content = match fs::read_to_string(&full_note_path) {
         Ok(existing) => Some(existing),
         Err(error) if error.kind() == ErrorKind::NotFound => None,
@@ -531,23 +892,180 @@ pub fn save_note_content_sync(
         }
     };
 
-    if existing_content.as_deref() == Some(content) {
-        cache_upsert_search_index_note_content(
-            notebook_path,
-            rel_note_path.as_str(),
+    if let (Some(base), Some(disk_content)) = (base, existing_content.as_deref())
+        && is_external_edit(
+            &full_note_path,
+            base,
+            disk_content,
+            existing_modified_time,
             content,
-            note_file_modified_time(&full_note_path),
-        );
-        return Ok(());
+        )
+    {
+        return Ok(NoteSaveOutcome::Conflict(NoteDiskState {
+            content: disk_content.to_string(),
+            modified_time: existing_modified_time,
+        }));
+    }
+
+    if existing_content.as_deref() != Some(content) {
+        if let Some(previous_content) = existing_content.as_deref().filter(|text| !text.is_empty())
+            && let Err(_err) = record_note_snapshot(
+                &rel_note_path.join_under(Path::new(notebook_path)),
+                previous_content,
+                existing_modified_time,
+            )
+        {
+            #[cfg(debug_assertions)]
+            eprintln!(
+                "Warning: Failed to record history for '{}': {}",
+                rel_note_path, _err
+            );
+        }
+
+        write_text_file_atomically(&full_note_path, content)?;
     }
 
-    write_text_file_atomically(&full_note_path, content)?;
+    remember_file_content(&full_note_path, content);
+    let modified_time = note_file_modified_time(&full_note_path);
+    cache_upsert_note_links(
+        notebook_path,
+        rel_note_path.as_str(),
+        content,
+        modified_time,
+    );
     cache_upsert_search_index_note_content(
         notebook_path,
         rel_note_path.as_str(),
         content,
-        note_file_modified_time(&full_note_path),
+        modified_time,
     );
 
-    Ok(())
+    Ok(NoteSaveOutcome::Saved(NoteDiskState {
+        content: content.to_string(),
+        modified_time,
+    }))
+}
+
+#[cfg(test)]
+mod metadata_migration_tests {
+    use super::*;
+    use crate::notebook::SearchMode;
+
+    /// A document stored with an older schema version and a check that its data survives the
+    /// upgrade to the current one.
+    struct StoredDocument {
+        contents: &'static str,
+        check_survived: fn(&NotebookMetadata),
+    }
+
+    /// Indexed by the schema version each document was stored with.
+    const STORED_DOCUMENTS: [StoredDocument; METADATA_MIGRATIONS.len()] = [
+        StoredDocument {
+            contents: r#"{ "notes": [{ "rel_path": "a" }, { "rel_path": "b", "labels": null }] }"#,
+            check_survived: |metadata| {
+                assert_eq!(metadata.notes.len(), 2);
+                assert!(metadata.notes.iter().all(|note| note.labels.is_empty()));
+            },
+        },
+        StoredDocument {
+            contents: r#"{
+                "schema_version": 1,
+                "notes": [],
+                "saved_searches": [
+                    {
+                        "name": "Alpha",
+                        "query": "release",
+                        "mode": "regex",
+                        "scope": "projects/alpha"
+                    }
+                ]
+            }"#,
+            check_survived: |metadata| {
+                assert_eq!(metadata.saved_searches.len(), 1);
+                let saved_search = &metadata.saved_searches[0];
+                assert_eq!(saved_search.mode, SearchMode::Regex);
+                assert_eq!(saved_search.scope.as_deref(), Some("projects/alpha"));
+            },
+        },
+        StoredDocument {
+            contents: r#"{
+                "schema_version": 2,
+                "notes": [],
+                "settings": { "default_template": "meeting" }
+            }"#,
+            check_survived: |metadata| {
+                assert_eq!(
+                    metadata.settings.default_template.as_deref(),
+                    Some("meeting")
+                );
+            },
+        },
+        StoredDocument {
+            contents: r#"{
+                "schema_version": 3,
+                "notes": [
+                    {
+                        "rel_path": "plans",
+                        "title": "Plans",
+                        "aliases": ["roadmap"],
+                        "properties": { "status": "draft" }
+                    },
+                    { "rel_path": "untitled", "aliases": null, "properties": null }
+                ]
+            }"#,
+            check_survived: |metadata| {
+                let plans = &metadata.notes[0];
+                assert_eq!(plans.title.as_deref(), Some("Plans"));
+                assert_eq!(plans.aliases, ["roadmap"]);
+                assert_eq!(
+                    plans.properties.get("status").map(String::as_str),
+                    Some("draft")
+                );
+                let untitled = &metadata.notes[1];
+                assert!(untitled.aliases.is_empty());
+                assert!(untitled.properties.is_empty());
+            },
+        },
+        StoredDocument {
+            contents: r##"{
+                "schema_version": 4,
+                "notes": [],
+                "label_registry": {
+                    "area": { "color": "#3366ff", "description": "Ongoing areas", "icon": "*" }
+                }
+            }"##,
+            check_survived: |metadata| {
+                let area = &metadata.label_registry["area"];
+                assert_eq!(area.color.as_deref(), Some("#3366ff"));
+                assert_eq!(area.description.as_deref(), Some("Ongoing areas"));
+                assert_eq!(area.icon.as_deref(), Some("*"));
+            },
+        },
+    ];
+
+    fn parse_or_panic(contents: &str) -> ParsedMetadata {
+        match parse_metadata(contents, Path::new(METADATA_FILE_NAME)) {
+            Ok(parsed) => parsed,
+            Err(MetadataParseError::Invalid(error)) => panic!("Invalid metadata: {}", error),
+            Err(MetadataParseError::Unsupported(error)) => {
+                panic!("Unsupported metadata: {}", error)
+            }
+        }
+    }
+
+    #[test]
+    fn every_older_schema_version_migrates_to_the_current_one() {
+        for (source_version, document) in STORED_DOCUMENTS.iter().enumerate() {
+            let parsed = parse_or_panic(document.contents);
+            assert_eq!(parsed.source_version as usize, source_version);
+            assert_eq!(parsed.metadata.schema_version, METADATA_SCHEMA_VERSION);
+            (document.check_survived)(&parsed.metadata);
+
+            let saved = serde_json::to_string(&parsed.metadata)
+                .expect("Failed to serialize migrated metadata");
+            let reloaded = parse_or_panic(&saved);
+            assert_eq!(reloaded.source_version, METADATA_SCHEMA_VERSION);
+            (document.check_survived)(&reloaded.metadata);
+        }
+    }
 }
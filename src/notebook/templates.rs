@@ -0,0 +1,271 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use time::Date;
+
+use super::front_matter::{front_matter_list, split_front_matter};
+use super::operations::{create_new_note, create_note_with_content};
+use super::storage::{current_local_datetime, save_metadata_with};
+use super::{CreatedNote, NoteMetadata, NotebookError, NotebookRelativePath};
+
+/// Notebook directory holding note templates, one Markdown file per template.
+const TEMPLATES_DIR_NAME: &str = ".templates";
+const TEMPLATE_EXTENSION: &str = "md";
+/// Marks where the cursor goes; it is removed from the note.
+const CURSOR_VARIABLE: &str = "{{cursor}}";
+
+fn template_file_path(notebook_path: &str, name: &str) -> Result<PathBuf, NotebookError> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        return Err(NotebookError::validation(
+            "note template",
+            format!("'{}' is not a valid template name.", name),
+        ));
+    }
+    Ok(Path::new(notebook_path)
+        .join(TEMPLATES_DIR_NAME)
+        .join(format!("{}.{}", name, TEMPLATE_EXTENSION)))
+}
+
+/// Lists the templates in the notebook's `.templates` directory by name, sorted.
+pub async fn list_templates(notebook_path: String) -> Result<Vec<String>, NotebookError> {
+    let templates_dir = Path::new(&notebook_path).join(TEMPLATES_DIR_NAME);
+    let entries = match fs::read_dir(&templates_dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => {
+            return Err(NotebookError::storage(
+                "list templates",
+                format!(
+                    "Failed to read templates directory '{}': {}",
+                    templates_dir.display(),
+                    error
+                ),
+            ));
+        }
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .is_some_and(|extension| extension == TEMPLATE_EXTENSION)
+        })
+        .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+        .filter(|name| !name.starts_with('.'))
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+/// Formats `date` as `YYYY-MM-DD`.
+pub(super) fn format_date(date: Date) -> String {
+    format!(
+        "{:04}-{:02}-{:02}",
+        date.year(),
+        u8::from(date.month()),
+        date.day()
+    )
+}
+
+/// Replaces each `{{name}}` in `text` whose name is in `variables`; other text is kept as is.
+fn substitute_variables(text: &str, variables: &[(&str, &str)]) -> String {
+    let mut substituted = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(length) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let name = rest[start + 2..start + 2 + length].trim();
+        substituted.push_str(&rest[..start]);
+        match variables.iter().find(|(variable, _)| *variable == name) {
+            Some((_, value)) => substituted.push_str(value),
+            None => substituted.push_str(&rest[start..start + 4 + length]),
+        }
+        rest = &rest[start + 4 + length..];
+    }
+    substituted.push_str(rest);
+    substituted
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct RenderedTemplate {
+    content: String,
+    /// Default labels from the template's front matter.
+    labels: Vec<String>,
+    /// 1-based line and byte column of the first `{{cursor}}`.
+    cursor: Option<(usize, usize)>,
+}
+
+/// Fills in a template; its front matter configures the template and is not copied.
+fn render_template(template: &str, variables: &[(&str, &str)]) -> RenderedTemplate {
+    let (front_matter, body) = split_front_matter(template);
+
+    let mut labels: Vec<String> = Vec::new();
+    for label in front_matter
+        .and_then(|front_matter| front_matter_list(front_matter, "labels"))
+        .unwrap_or_default()
+    {
+        if !labels.contains(&label) {
+            labels.push(label);
+        }
+    }
+
+    let mut parts = body.split(CURSOR_VARIABLE);
+    let mut content = substitute_variables(parts.next().unwrap_or_default(), variables);
+    let mut cursor = None;
+    for part in parts {
+        if cursor.is_none() {
+            let line_start = content.rfind('\n').map_or(0, |index| index + 1);
+            cursor = Some((
+                content.matches('\n').count() + 1,
+                content.len() - line_start,
+            ));
+        }
+        content.push_str(&substitute_variables(part, variables));
+    }
+
+    RenderedTemplate {
+        content,
+        labels,
+        cursor,
+    }
+}
+
+/// Creates a note, filled in from the named template when one is given.
+///
+/// Templates may use `{{date}}`, `{{time}}` (local, `YYYY-MM-DD` and `HH:MM`), `{{title}}`
+/// (the last path component), `{{path}}` and `{{cursor}}`, and list default labels under
+/// `labels` in their front matter.
+pub async fn create_note_from_template(
+    notebook_path: &str,
+    rel_path: &str,
+    template: Option<&str>,
+    notes: &mut Vec<NoteMetadata>,
+) -> Result<CreatedNote, NotebookError> {
+    let Some(template) = template else {
+        let metadata = create_new_note(notebook_path, rel_path, notes).await?;
+        return Ok(CreatedNote {
+            metadata,
+            cursor: None,
+        });
+    };
+
+    let today = current_local_datetime().date();
+    create_note_with_template(notebook_path, rel_path, template, today, Vec::new(), notes)
+}
+
+/// Creates a note from the named template with `date` as its `{{date}}`, adding `labels`
+/// before the template's own.
+pub(super) fn create_note_with_template(
+    notebook_path: &str,
+    rel_path: &str,
+    template: &str,
+    date: Date,
+    mut labels: Vec<String>,
+    notes: &mut Vec<NoteMetadata>,
+) -> Result<CreatedNote, NotebookError> {
+    let template_path = template_file_path(notebook_path, template)?;
+    let template_content = fs::read_to_string(&template_path).map_err(|error| {
+        if error.kind() == ErrorKind::NotFound {
+            NotebookError::validation(
+                "note template",
+                format!("Template '{}' does not exist.", template),
+            )
+        } else {
+            NotebookError::storage(
+                "note template",
+                format!("Failed to read template '{}': {}", template, error),
+            )
+        }
+    })?;
+
+    let rel_path = NotebookRelativePath::parse("relative path", rel_path)?;
+    let now = current_local_datetime();
+    let date = format_date(date);
+    let time = format!("{:02}:{:02}", now.hour(), now.minute());
+    let title = rel_path
+        .as_str()
+        .rsplit('/')
+        .next()
+        .unwrap_or(rel_path.as_str());
+    let rendered = render_template(
+        &template_content,
+        &[
+            ("date", &date),
+            ("time", &time),
+            ("title", title),
+            ("path", rel_path.as_str()),
+        ],
+    );
+
+    for label in rendered.labels {
+        if !labels.contains(&label) {
+            labels.push(label);
+        }
+    }
+    let metadata = create_note_with_content(
+        notebook_path,
+        rel_path.as_str(),
+        &rendered.content,
+        labels,
+        notes,
+    )?;
+    Ok(CreatedNote {
+        metadata,
+        cursor: rendered.cursor,
+    })
+}
+
+/// Stores the template the new-note dialog preselects; `None` starts from an empty note.
+pub async fn set_default_template(
+    notebook_path: &str,
+    notes: &[NoteMetadata],
+    template: Option<String>,
+) -> Result<(), NotebookError> {
+    if let Some(name) = &template
+        && !template_file_path(notebook_path, name)?.is_file()
+    {
+        return Err(NotebookError::validation(
+            "note template",
+            format!("Template '{}' does not exist.", name),
+        ));
+    }
+
+    save_metadata_with(notebook_path, notes, |metadata| {
+        metadata.settings.default_template = template;
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_template_fills_variables_and_places_the_cursor() {
+        let rendered = render_template(
+            "---\nlabels: [meeting, meeting, weekly]\n---\n# {{title}}\n\n{{ date }} {{unknown}}\n- {{cursor}}\n{{cursor}}",
+            &[("title", "Standup"), ("date", "2026-03-04")],
+        );
+
+        assert_eq!(
+            rendered,
+            RenderedTemplate {
+                content: "# Standup\n\n2026-03-04 {{unknown}}\n- \n".to_string(),
+                labels: vec!["meeting".to_string(), "weekly".to_string()],
+                cursor: Some((4, 2)),
+            }
+        );
+        assert_eq!(
+            render_template("{{cursor", &[]),
+            RenderedTemplate {
+                content: "{{cursor".to_string(),
+                labels: Vec::new(),
+                cursor: None,
+            }
+        );
+    }
+}
@@ -0,0 +1,350 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+use super::links::cache_upsert_note_links;
+use super::search::{cache_upsert_search_index_note_content, note_file_modified_time};
+use super::storage::{current_timestamp_rfc3339, save_metadata, write_text_file_atomically};
+use super::{NoteMetadata, NotebookError, NotebookRelativePath, TrashEntry};
+
+const TRASH_DIR_NAME: &str = ".cognate_trash";
+const TRASH_MANIFEST_FILE_NAME: &str = "entry.json";
+const TRASH_NOTE_DIR_NAME: &str = "note";
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Manifest stored next to each trashed note directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TrashManifest {
+    rel_path: String,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_updated: Option<String>,
+    deleted_at: String,
+}
+
+fn trash_root(notebook_path: &Path) -> PathBuf {
+    notebook_path.join(TRASH_DIR_NAME)
+}
+
+fn trash_entry_dir(notebook_path: &Path, entry_id: &str) -> Result<PathBuf, NotebookError> {
+    if entry_id.is_empty()
+        || entry_id.starts_with('.')
+        || entry_id.contains(['/', '\\'])
+        || entry_id.contains("..")
+    {
+        return Err(NotebookError::validation(
+            "trash entry",
+            format!("Invalid trash entry id '{}'.", entry_id),
+        ));
+    }
+
+    Ok(trash_root(notebook_path).join(entry_id))
+}
+
+/// Directory a trashed note is moved into once its deletion is committed.
+pub(super) fn trashed_note_dir(entry_dir: &Path) -> PathBuf {
+    entry_dir.join(TRASH_NOTE_DIR_NAME)
+}
+
+/// Creates a trash entry holding the manifest for a note that is about to be deleted.
+///
+/// The note directory itself is moved in by the caller after metadata is committed.
+pub(super) fn prepare_trash_entry(
+    notebook_path: &Path,
+    rel_path: &NotebookRelativePath,
+    note: Option<&NoteMetadata>,
+) -> Result<PathBuf, NotebookError> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+    let entry_dir = trash_root(notebook_path).join(format!(
+        "{}_{}",
+        timestamp,
+        rel_path.sanitized_for_temp_name()
+    ));
+
+    let manifest = TrashManifest {
+        rel_path: rel_path.as_str().to_string(),
+        labels: note.map(|note| note.labels.clone()).unwrap_or_default(),
+        last_updated: note.and_then(|note| note.last_updated.clone()),
+        deleted_at: current_timestamp_rfc3339(),
+    };
+    let manifest_json = serde_json::to_string_pretty(&manifest).map_err(|error| {
+        NotebookError::storage(
+            "trash note",
+            format!("Failed to serialize trash manifest: {}", error),
+        )
+    })?;
+
+    if let Err(error) =
+        write_text_file_atomically(&entry_dir.join(TRASH_MANIFEST_FILE_NAME), &manifest_json)
+    {
+        let _ = fs::remove_dir_all(&entry_dir);
+        return Err(error);
+    }
+
+    Ok(entry_dir)
+}
+
+pub(super) fn discard_trash_entry(entry_dir: &Path) {
+    if let Err(_e) = fs::remove_dir_all(entry_dir)
+        && _e.kind() != ErrorKind::NotFound
+    {
+        #[cfg(debug_assertions)]
+        eprintln!(
+            "Warning: Failed to discard trash entry '{}': {}",
+            entry_dir.display(),
+            _e
+        );
+    }
+}
+
+fn read_trash_manifest(entry_dir: &Path) -> Result<TrashManifest, NotebookError> {
+    let manifest_path = entry_dir.join(TRASH_MANIFEST_FILE_NAME);
+    let contents = fs::read_to_string(&manifest_path).map_err(|error| {
+        NotebookError::storage(
+            "trash entry",
+            format!(
+                "Failed to read trash manifest '{}': {}",
+                manifest_path.display(),
+                error
+            ),
+        )
+    })?;
+
+    serde_json::from_str(&contents).map_err(|error| {
+        NotebookError::storage(
+            "trash entry",
+            format!(
+                "Failed to parse trash manifest '{}': {}",
+                manifest_path.display(),
+                error
+            ),
+        )
+    })
+}
+
+fn is_expired(deleted_at: &str, retention_days: u32, now: OffsetDateTime) -> bool {
+    if retention_days == 0 {
+        return false;
+    }
+
+    OffsetDateTime::parse(deleted_at, &Rfc3339).is_ok_and(|deleted_at| {
+        (now - deleted_at).whole_seconds() >= i64::from(retention_days) * SECONDS_PER_DAY
+    })
+}
+
+/// Lists trashed notes, newest first, after purging entries older than `retention_days`.
+///
+/// A retention of `0` keeps trashed notes until they are purged manually.
+pub async fn load_trash(
+    notebook_path: String,
+    retention_days: u32,
+) -> Result<Vec<TrashEntry>, NotebookError> {
+    let root = trash_root(Path::new(&notebook_path));
+    let read_dir = match fs::read_dir(&root) {
+        Ok(read_dir) => read_dir,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => {
+            return Err(NotebookError::storage(
+                "load trash",
+                format!("Failed to read trash '{}': {}", root.display(), error),
+            ));
+        }
+    };
+
+    let now = OffsetDateTime::now_utc();
+    let mut entries = Vec::new();
+
+    for entry_result in read_dir {
+        let Ok(entry) = entry_result else {
+            continue;
+        };
+        let entry_dir = entry.path();
+        if !entry_dir.is_dir() {
+            continue;
+        }
+
+        let manifest = match read_trash_manifest(&entry_dir) {
+            Ok(manifest) => manifest,
+            Err(_err) => {
+                #[cfg(debug_assertions)]
+                eprintln!("Warning: Skipping unreadable trash entry: {}", _err);
+                continue;
+            }
+        };
+
+        if is_expired(&manifest.deleted_at, retention_days, now) {
+            discard_trash_entry(&entry_dir);
+            continue;
+        }
+
+        // Entries whose note directory never arrived belong to an interrupted delete.
+        if !trashed_note_dir(&entry_dir).exists() {
+            continue;
+        }
+
+        entries.push(TrashEntry {
+            id: entry.file_name().to_string_lossy().into_owned(),
+            rel_path: manifest.rel_path,
+            labels: manifest.labels,
+            last_updated: manifest.last_updated,
+            deleted_at: manifest.deleted_at,
+        });
+    }
+
+    entries.sort_by(|left, right| {
+        right
+            .deleted_at
+            .cmp(&left.deleted_at)
+            .then_with(|| right.id.cmp(&left.id))
+    });
+    Ok(entries)
+}
+
+/// Suggests a free note path for restoring `rel_path` when the original path is in use.
+pub fn available_restore_path(
+    notebook_path: &str,
+    rel_path: &str,
+    notes: &[NoteMetadata],
+) -> String {
+    let is_taken = |candidate: &str| {
+        notes.iter().any(|note| note.rel_path == candidate)
+            || Path::new(notebook_path).join(candidate).exists()
+    };
+
+    if !is_taken(rel_path) {
+        return rel_path.to_string();
+    }
+
+    let mut suffix = 1usize;
+    loop {
+        let candidate = if suffix == 1 {
+            format!("{} (restored)", rel_path)
+        } else {
+            format!("{} (restored {})", rel_path, suffix)
+        };
+        if !is_taken(&candidate) {
+            return candidate;
+        }
+        suffix += 1;
+    }
+}
+
+/// Moves a trashed note back into the notebook at `target_rel_path` and restores its labels.
+pub async fn restore_trashed_note(
+    notebook_path: &str,
+    entry_id: &str,
+    target_rel_path: &str,
+    notes: &mut Vec<NoteMetadata>,
+) -> Result<NoteMetadata, NotebookError> {
+    let target_rel_path = NotebookRelativePath::parse("restore path", target_rel_path)?;
+    let full_notebook_path = Path::new(notebook_path);
+    let entry_dir = trash_entry_dir(full_notebook_path, entry_id)?;
+    let manifest = read_trash_manifest(&entry_dir)?;
+    let trashed_dir = trashed_note_dir(&entry_dir);
+    let target_dir = target_rel_path.join_under(full_notebook_path);
+
+    if !trashed_dir.exists() {
+        return Err(NotebookError::validation(
+            "restore note",
+            format!("Trash entry '{}' no longer contains a note.", entry_id),
+        ));
+    }
+
+    if notes
+        .iter()
+        .any(|note| note.rel_path == target_rel_path.as_str())
+        || target_dir.exists()
+    {
+        return Err(NotebookError::validation(
+            "restore note",
+            format!(
+                "An item already exists at '{}'. Choose another path to restore '{}'.",
+                target_rel_path, manifest.rel_path
+            ),
+        ));
+    }
+
+    if let Some(parent) = target_dir.parent()
+        && let Err(error) = fs::create_dir_all(parent)
+    {
+        return Err(NotebookError::storage(
+            "restore note",
+            format!("Failed to create parent directories for restore: {}", error),
+        ));
+    }
+
+    if let Err(error) = fs::rename(&trashed_dir, &target_dir) {
+        return Err(NotebookError::storage(
+            "restore note",
+            format!(
+                "Failed to move '{}' out of the trash: {}",
+                manifest.rel_path, error
+            ),
+        ));
+    }
+
+    let restored_note = NoteMetadata {
+        rel_path: target_rel_path.as_str().to_string(),
+        labels: manifest.labels,
+        last_updated: manifest.last_updated,
+        ..NoteMetadata::default()
+    };
+    let previous_notes = notes.clone();
+    notes.push(restored_note.clone());
+
+    if let Err(metadata_error) = save_metadata(notebook_path, notes) {
+        *notes = previous_notes;
+        if let Err(rollback_error) = fs::rename(&target_dir, &trashed_dir) {
+            return Err(NotebookError::recovery(
+                "restore note rollback",
+                format!(
+                    "{} Rollback failed while returning the note to the trash: {}",
+                    metadata_error, rollback_error
+                ),
+            ));
+        }
+        return Err(metadata_error);
+    }
+
+    discard_trash_entry(&entry_dir);
+
+    let note_file_path = target_dir.join("note.md");
+    let content = fs::read_to_string(&note_file_path).unwrap_or_default();
+    let modified_time = note_file_modified_time(&note_file_path);
+    cache_upsert_note_links(
+        notebook_path,
+        target_rel_path.as_str(),
+        &content,
+        modified_time,
+    );
+    cache_upsert_search_index_note_content(
+        notebook_path,
+        target_rel_path.as_str(),
+        &content,
+        modified_time,
+    );
+
+    Ok(restored_note)
+}
+
+/// Permanently deletes a trashed note.
+pub async fn purge_trashed_note(notebook_path: &str, entry_id: &str) -> Result<(), NotebookError> {
+    let entry_dir = trash_entry_dir(Path::new(notebook_path), entry_id)?;
+    match fs::remove_dir_all(&entry_dir) {
+        Ok(()) => Ok(()),
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(()),
+        Err(error) => Err(NotebookError::storage(
+            "purge note",
+            format!("Failed to purge trash entry '{}': {}", entry_id, error),
+        )),
+    }
+}
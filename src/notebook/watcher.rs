@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use notify_debouncer_mini::notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{DebounceEventResult, Debouncer, new_debouncer};
+
+use super::history::content_hash;
+use super::links::{cache_remove_note_links, cache_upsert_note_links};
+use super::search::{
+    cache_remove_search_index_entries, cache_upsert_search_index_note_content,
+    note_file_modified_time,
+};
+use super::{NotebookChanges, NotebookError};
+
+const WATCH_DEBOUNCE_WINDOW: Duration = Duration::from_millis(300);
+const METADATA_FILE_NAME: &str = "metadata.json";
+const NOTE_FILE_NAME: &str = "note.md";
+
+/// Hashes of the file contents Cognate itself last wrote or read, keyed by full path.
+///
+/// Watcher events for files whose content still matches are echoes of our own saves.
+fn known_file_contents() -> &'static Mutex<HashMap<PathBuf, u128>> {
+    static KNOWN_FILE_CONTENTS: OnceLock<Mutex<HashMap<PathBuf, u128>>> = OnceLock::new();
+    KNOWN_FILE_CONTENTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+pub(super) fn remember_file_content(path: &Path, content: &str) {
+    let mut known = match known_file_contents().lock() {
+        Ok(known) => known,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    known.insert(path.to_path_buf(), content_hash(content));
+}
+
+pub(super) fn is_known_file_content(path: &Path, content: &str) -> bool {
+    let known = match known_file_contents().lock() {
+        Ok(known) => known,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    known.get(path) == Some(&content_hash(content))
+}
+
+fn is_known_file(path: &Path) -> bool {
+    let known = match known_file_contents().lock() {
+        Ok(known) => known,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    known.contains_key(path)
+}
+
+fn has_changed_externally(path: &Path) -> bool {
+    match fs::read_to_string(path) {
+        Ok(content) => !is_known_file_content(path, &content),
+        Err(_) => true,
+    }
+}
+
+/// Returns whether `content` is what Cognate last saved or loaded for the note.
+///
+/// The editor uses this to tell an untouched buffer from one with unsaved edits.
+pub fn is_note_content_persisted(notebook_path: &str, rel_path: &str, content: &str) -> bool {
+    let note_file_path = Path::new(notebook_path).join(rel_path).join(NOTE_FILE_NAME);
+    is_known_file_content(&note_file_path, content)
+}
+
+fn is_hidden_component(component: Component<'_>) -> bool {
+    match component {
+        Component::Normal(name) => name.to_string_lossy().starts_with('.'),
+        _ => true,
+    }
+}
+
+/// Maps changed filesystem paths to the notebook items they belong to.
+///
+/// Hidden entries (trash, history, temp files) are ignored, `note.md` changes map to their note
+/// directory, and extension-less paths are kept as possibly renamed or removed note folders.
+fn classify_changed_paths<'a>(
+    notebook_path: &Path,
+    paths: impl IntoIterator<Item = &'a Path>,
+) -> NotebookChanges {
+    let mut changes = NotebookChanges::default();
+
+    for path in paths {
+        let Ok(rel_path) = path.strip_prefix(notebook_path) else {
+            continue;
+        };
+        if rel_path.as_os_str().is_empty() || rel_path.components().any(is_hidden_component) {
+            continue;
+        }
+
+        let note_dir = if rel_path == Path::new(METADATA_FILE_NAME) {
+            changes.metadata_changed = true;
+            continue;
+        } else if rel_path
+            .file_name()
+            .is_some_and(|name| name == NOTE_FILE_NAME)
+        {
+            match rel_path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent,
+                _ => continue,
+            }
+        } else if rel_path.extension().is_none() {
+            rel_path
+        } else {
+            continue;
+        };
+
+        let note_dir = note_dir.to_string_lossy().replace('\\', "/");
+        if !changes.note_paths.contains(&note_dir) {
+            changes.note_paths.push(note_dir);
+        }
+    }
+
+    changes.note_paths.sort();
+    changes
+}
+
+/// Drops changes that only echo Cognate's own writes and refreshes the caches of the rest.
+fn collect_external_changes(notebook_path: &Path, mut changes: NotebookChanges) -> NotebookChanges {
+    let notebook_path_str = notebook_path.to_string_lossy();
+
+    if changes.metadata_changed {
+        changes.metadata_changed = has_changed_externally(&notebook_path.join(METADATA_FILE_NAME));
+    }
+
+    changes.note_paths.retain(|rel_path| {
+        let note_dir_path = notebook_path.join(rel_path);
+        let note_file_path = note_dir_path.join(NOTE_FILE_NAME);
+        if !note_dir_path.exists() {
+            cache_remove_note_links(&notebook_path_str, rel_path);
+            cache_remove_search_index_entries(&notebook_path_str, rel_path);
+            return true;
+        }
+        if !note_file_path.exists() {
+            // A folder without its own note only matters if it used to be a note.
+            return is_known_file(&note_file_path);
+        }
+
+        let Ok(content) = fs::read_to_string(&note_file_path) else {
+            return true;
+        };
+        if is_known_file_content(&note_file_path, &content) {
+            return false;
+        }
+
+        let modified_time = note_file_modified_time(&note_file_path);
+        cache_upsert_note_links(&notebook_path_str, rel_path, &content, modified_time);
+        cache_upsert_search_index_note_content(
+            &notebook_path_str,
+            rel_path,
+            &content,
+            modified_time,
+        );
+        true
+    });
+
+    changes
+}
+
+/// Keeps a recursive filesystem watch on a notebook alive until dropped.
+pub struct NotebookWatcher {
+    _debouncer: Debouncer<RecommendedWatcher>,
+}
+
+/// Watches a notebook for external changes to `note.md` files and `metadata.json`.
+///
+/// `on_change` runs on the watcher thread with debounced batches of changes that were not made
+/// by Cognate itself. Search and backlink caches are refreshed before it is called.
+pub fn watch_notebook(
+    notebook_path: &str,
+    mut on_change: impl FnMut(NotebookChanges) + Send + 'static,
+) -> Result<NotebookWatcher, NotebookError> {
+    let root = PathBuf::from(notebook_path);
+    // Events carry canonical paths on some platforms.
+    let canonical_root = fs::canonicalize(&root).unwrap_or_else(|_| root.clone());
+    let event_root = canonical_root.clone();
+    let notebook_root = root.clone();
+
+    let mut debouncer = new_debouncer(WATCH_DEBOUNCE_WINDOW, move |result: DebounceEventResult| {
+        let events = match result {
+            Ok(events) => events,
+            Err(_err) => {
+                #[cfg(debug_assertions)]
+                eprintln!("Warning: Notebook watcher error: {}", _err);
+                return;
+            }
+        };
+
+        let changes =
+            classify_changed_paths(&event_root, events.iter().map(|event| event.path.as_path()));
+        if changes.is_empty() {
+            return;
+        }
+
+        let changes = collect_external_changes(&notebook_root, changes);
+        if !changes.is_empty() {
+            on_change(changes);
+        }
+    })
+    .map_err(|error| {
+        NotebookError::storage(
+            "watch notebook",
+            format!("Failed to start notebook watcher: {}", error),
+        )
+    })?;
+
+    debouncer
+        .watcher()
+        .watch(&canonical_root, RecursiveMode::Recursive)
+        .map_err(|error| {
+            NotebookError::storage(
+                "watch notebook",
+                format!("Failed to watch '{}': {}", root.display(), error),
+            )
+        })?;
+
+    Ok(NotebookWatcher {
+        _debouncer: debouncer,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_changed_paths_maps_note_files_and_skips_hidden_entries() {
+        let root = Path::new("/notebook");
+        let paths = [
+            root.join("metadata.json"),
+            root.join("metadata.json.bak"),
+            root.join("projects/alpha/note.md"),
+            root.join("projects/alpha/.note.md.cognate_tmp_1"),
+            root.join("projects/alpha/images/pasted.png"),
+            root.join(".cognate_trash/1_alpha/note/note.md"),
+            root.join("projects/alpha/.cognate_history/versions.json"),
+            root.join("archive"),
+        ];
+
+        let changes = classify_changed_paths(root, paths.iter().map(PathBuf::as_path));
+
+        assert!(changes.metadata_changed);
+        assert_eq!(
+            changes.note_paths,
+            vec!["archive".to_string(), "projects/alpha".to_string()]
+        );
+    }
+
+    #[test]
+    fn known_file_contents_distinguish_own_writes_from_external_edits() {
+        let path = std::env::temp_dir().join(format!(
+            "cognate_watcher_known_{}/note.md",
+            std::process::id()
+        ));
+
+        remember_file_content(&path, "saved by cognate");
+
+        assert!(is_known_file_content(&path, "saved by cognate"));
+        assert!(!is_known_file_content(&path, "edited elsewhere"));
+    }
+}
@@ -6,7 +6,9 @@ mod tests {
     use crate::components::note_explorer;
     use crate::components::visualizer;
     use crate::configuration::Configuration;
-    use crate::notebook::{MetadataLoadResult, NoteMetadata, NotebookError};
+    use crate::notebook::{
+        self, MetadataLoadResult, MovedNote, NoteMetadata, NotebookError, NotebookSettings,
+    };
     use iced::widget::text_editor::Content;
 
     #[test]
@@ -17,6 +19,7 @@ mod tests {
             scale: 1.0,
             config_path: "config.json".to_string(),
             version: "0.1.0".to_string(),
+            ..Configuration::default()
         };
         let _ = Editor::create(cfg_known);
 
@@ -26,6 +29,7 @@ mod tests {
             scale: 1.0,
             config_path: "config.json".to_string(),
             version: "0.1.0".to_string(),
+            ..Configuration::default()
         };
         let _ = Editor::create(cfg_unknown);
     }
@@ -72,7 +76,13 @@ mod tests {
             EditorMessage::VisualizerMsg(visualizer::Message::FocusOnNote(None)),
             EditorMessage::InitiateFolderRename("folder".to_string()),
             EditorMessage::NoteCreated(Err(sample_error)),
-            EditorMessage::NoteMoved(Ok("new/path".to_string()), "old/path".to_string()),
+            EditorMessage::NoteMoved(
+                Ok(MovedNote {
+                    rel_path: "new/path".to_string(),
+                    rewritten_notes: Vec::new(),
+                }),
+                "old/path".to_string(),
+            ),
             EditorMessage::NoteDeleted(Ok(()), "to/delete".to_string()),
         ];
 
@@ -88,7 +98,11 @@ mod tests {
                         rel_path: "folder/note".to_string(),
                         labels: vec!["tag".to_string()],
                         last_updated: None,
+                        ..NoteMetadata::default()
                     }],
+                    saved_searches: Vec::new(),
+                    settings: NotebookSettings::default(),
+                    label_registry: Default::default(),
                     warning: None,
                 },
             ))),
@@ -111,7 +125,11 @@ mod tests {
                         rel_path: "folder/note".to_string(),
                         labels: vec![],
                         last_updated: None,
+                        ..NoteMetadata::default()
                     }],
+                    saved_searches: Vec::new(),
+                    settings: NotebookSettings::default(),
+                    label_registry: Default::default(),
                     warning: None,
                 },
             ))),
@@ -148,11 +166,13 @@ mod tests {
                 rel_path: "folder/note".to_string(),
                 labels: vec!["tag".to_string()],
                 last_updated: None,
+                ..NoteMetadata::default()
             },
             NoteMetadata {
                 rel_path: "single".to_string(),
                 labels: vec![],
                 last_updated: None,
+                ..NoteMetadata::default()
             },
         ];
         let visualizer = visualizer::Visualizer::new();
@@ -241,7 +261,12 @@ mod tests {
         state.hide_embedded_image_delete_dialog();
 
         let _ = dialogs::about_dialog("0.2.0");
-        let _ = dialogs::new_note_dialog("new/path");
+        state.show_new_note_dialog();
+        state.update_new_note_path("new/path".to_string());
+        state.set_new_note_templates(vec!["meeting".to_string()]);
+        state.select_new_note_template(Some("meeting".to_string()));
+        let _ = dialogs::new_note_dialog(&state);
+        state.hide_new_note_dialog();
         let _ = dialogs::move_note_dialog("folder/note", "other/note", false);
         let _ = dialogs::move_note_dialog("folder", "renamed", true);
         let _ = dialogs::confirm_embedded_image_delete_dialog(2);
@@ -250,7 +275,25 @@ mod tests {
             Some(&"folder/note".to_string()),
             &["tag".to_string()],
             "new",
+            &notebook::LabelRegistry::from([(
+                "tag".to_string(),
+                notebook::LabelInfo {
+                    color: Some("#3366ff".to_string()),
+                    description: Some("Tagged notes".to_string()),
+                    icon: Some("*".to_string()),
+                },
+            )]),
+            &["tags".to_string()],
+            0,
+        );
+        let _ = input_fields::create_labels_section(None, &[], "", &Default::default(), &[], 0);
+        let _ = input_fields::create_labels_section(
+            None,
+            &[],
+            "tag",
+            &Default::default(),
+            &["tag/sub".to_string()],
+            2,
         );
-        let _ = input_fields::create_labels_section(None, &[], "");
     }
 }
@@ -5,9 +5,13 @@ mod tests {
     use crate::components::note_explorer;
     use crate::configuration::Configuration;
     use crate::notebook::{
-        self, MetadataLoadResult, NoteMetadata, NoteSearchResult, NotebookError,
+        self, BulkLabelAction, FsckFinding, FsckFindingKind, LabelEditKind, LabelInfo,
+        LabelRegistry, MetadataLoadResult, NoteDiskState, NoteMetadata, NoteReplacePreview,
+        NoteSaveOutcome, NoteSearchResult, NoteVersion, NotebookChanges, NotebookError,
+        NotebookSettings, ReplaceHit, ReplaceLinePreview, ReplacePreview, SavedSearch,
+        SearchLineMatch, SearchMatchRange, SearchMode, TrashEntry,
     };
-    use iced::widget::text_editor::{Action, Edit};
+    use iced::widget::text_editor::{Action, Cursor, Edit, Motion, Position};
     use iced::window;
     use std::collections::HashMap;
     use std::fs;
@@ -60,6 +64,7 @@ mod tests {
             rel_path: rel_path.to_string(),
             labels: vec!["seed".to_string()],
             last_updated: Some("2024-01-01T00:00:00Z".to_string()),
+            ..NoteMetadata::default()
         }];
 
         notebook::save_metadata(notebook_dir.as_str(), &notes).expect("Failed to seed metadata");
@@ -73,11 +78,19 @@ mod tests {
             scale: 1.0,
             config_path: "config.json".to_string(),
             version: "test".to_string(),
+            ..Configuration::default()
         };
         let (editor, _initial_task) = Editor::create(cfg);
         editor
     }
 
+    fn disk_state(content: &str) -> NoteDiskState {
+        NoteDiskState {
+            content: content.to_string(),
+            modified_time: None,
+        }
+    }
+
     fn load_and_select_note(
         editor: &mut Editor,
         notes: Vec<NoteMetadata>,
@@ -89,6 +102,9 @@ mod tests {
             EditorMessage::NoteExplorerMsg(note_explorer::Message::NotesLoaded(Ok(
                 MetadataLoadResult {
                     notes,
+                    saved_searches: Vec::new(),
+                    settings: NotebookSettings::default(),
+                    label_registry: Default::default(),
                     warning: None,
                 },
             ))),
@@ -143,15 +159,17 @@ mod tests {
         let stale_result = NoteSearchResult {
             rel_path: "alpha/note".to_string(),
             snippet: "Path match".to_string(),
+            line_matches: Vec::new(),
         };
         let fresh_result = NoteSearchResult {
             rel_path: "beta/note".to_string(),
             snippet: "Path match".to_string(),
+            line_matches: Vec::new(),
         };
 
         let _ = Editor::update(
             &mut editor,
-            EditorMessage::SearchCompleted(alpha_generation, vec![stale_result]),
+            EditorMessage::SearchCompleted(alpha_generation, Ok(vec![stale_result])),
         );
         let (_, _, stale_applied_results) = editor.debug_search_state();
         assert!(
@@ -161,12 +179,339 @@ mod tests {
 
         let _ = Editor::update(
             &mut editor,
-            EditorMessage::SearchCompleted(beta_generation, vec![fresh_result.clone()]),
+            EditorMessage::SearchCompleted(beta_generation, Ok(vec![fresh_result.clone()])),
         );
         let (_, _, final_results) = editor.debug_search_state();
         assert_eq!(final_results, vec![fresh_result]);
     }
 
+    #[test]
+    fn search_query_errors_replace_results_until_the_next_search() {
+        let mut editor = Editor::default();
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::SearchQueryChanged("label:".to_string()),
+        );
+        let (generation, _, _) = editor.debug_search_state();
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::SearchCompleted(
+                generation,
+                Err(NotebookError::validation(
+                    "search query",
+                    "Search filter 'label:' needs a value.",
+                )),
+            ),
+        );
+        let error = editor
+            .debug_search_error()
+            .expect("parse errors should be shown next to the search box");
+        assert!(error.contains("needs a value"), "unexpected error: {error}");
+        assert!(editor.debug_search_state().2.is_empty());
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::SearchQueryChanged("label:work".to_string()),
+        );
+        let (generation, _, _) = editor.debug_search_state();
+        let result = NoteSearchResult {
+            rel_path: "work/todo".to_string(),
+            snippet: "Label match: work".to_string(),
+            line_matches: Vec::new(),
+        };
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::SearchCompleted(generation, Ok(vec![result.clone()])),
+        );
+        assert_eq!(editor.debug_search_error(), None);
+        assert_eq!(editor.debug_search_state().2, vec![result]);
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::SearchCompleted(
+                generation,
+                Err(NotebookError::validation("search query", "Unexpected ')'.")),
+            ),
+        );
+        let _ = Editor::update(&mut editor, EditorMessage::ClearSearch);
+        assert_eq!(editor.debug_search_error(), None);
+    }
+
+    #[test]
+    fn changing_search_mode_invalidates_results_from_the_previous_mode() {
+        let mut editor = Editor::default();
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::SearchQueryChanged("mtng".to_string()),
+        );
+        let (text_generation, _, _) = editor.debug_search_state();
+        assert_eq!(editor.debug_search_mode(), SearchMode::Text);
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::SearchModeChanged(SearchMode::Fuzzy),
+        );
+        let (fuzzy_generation, query, _) = editor.debug_search_state();
+        assert_eq!(editor.debug_search_mode(), SearchMode::Fuzzy);
+        assert_eq!(query, "mtng");
+        assert!(fuzzy_generation > text_generation);
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::SearchCompleted(text_generation, Ok(Vec::new())),
+        );
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::SearchCompleted(
+                fuzzy_generation,
+                Ok(vec![NoteSearchResult {
+                    rel_path: "meetings/notes".to_string(),
+                    snippet: "Path match".to_string(),
+                    line_matches: Vec::new(),
+                }]),
+            ),
+        );
+        assert_eq!(editor.debug_search_state().2.len(), 1);
+    }
+
+    #[test]
+    fn saved_searches_restore_their_query_mode_and_folder_scope() {
+        let notebook_dir = TestNotebookDir::new("saved_search_scope");
+        let mut editor = create_editor_with_notebook(notebook_dir.as_str());
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::NoteExplorerMsg(note_explorer::Message::NotesLoaded(Ok(
+                MetadataLoadResult {
+                    notes: Vec::new(),
+                    saved_searches: vec![SavedSearch {
+                        name: "Alpha plans".to_string(),
+                        query: "plan".to_string(),
+                        mode: SearchMode::Fuzzy,
+                        scope: Some("projects/alpha".to_string()),
+                    }],
+                    settings: NotebookSettings::default(),
+                    label_registry: Default::default(),
+                    warning: None,
+                },
+            ))),
+        );
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::SearchQueryChanged("release".to_string()),
+        );
+        let (query_generation, _, _) = editor.debug_search_state();
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::SearchInFolder("projects/beta".to_string()),
+        );
+        let (scoped_generation, _, _) = editor.debug_search_state();
+        assert_eq!(
+            editor.debug_search_scope().as_deref(),
+            Some("projects/beta")
+        );
+        assert!(
+            scoped_generation > query_generation,
+            "Changing the scope should restart the search"
+        );
+        let _ = Editor::update(&mut editor, EditorMessage::ClearSearchScope);
+        assert_eq!(editor.debug_search_scope(), None);
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::OpenSavedSearch("Alpha plans".to_string()),
+        );
+        let (_, query, _) = editor.debug_search_state();
+        assert_eq!(query, "plan");
+        assert_eq!(editor.debug_search_mode(), SearchMode::Fuzzy);
+        assert_eq!(
+            editor.debug_search_scope().as_deref(),
+            Some("projects/alpha")
+        );
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::SavedSearchesChanged(Err(NotebookError::validation(
+                "save search",
+                "Enter a name for the saved search.",
+            ))),
+        );
+        assert!(
+            editor
+                .debug_status_message()
+                .is_some_and(|message| message.contains("Enter a name for the saved search."))
+        );
+
+        let _ = Editor::update(&mut editor, EditorMessage::ClearSearch);
+        assert_eq!(editor.debug_search_scope(), None);
+    }
+
+    #[test]
+    fn new_note_dialog_preselects_the_default_template_while_it_exists() {
+        let notebook_dir = TestNotebookDir::new("new_note_default_template");
+        let mut editor = create_editor_with_notebook(notebook_dir.as_str());
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::NoteExplorerMsg(note_explorer::Message::NotesLoaded(Ok(
+                MetadataLoadResult {
+                    notes: Vec::new(),
+                    saved_searches: Vec::new(),
+                    settings: NotebookSettings {
+                        default_template: Some("meeting".to_string()),
+                    },
+                    label_registry: Default::default(),
+                    warning: None,
+                },
+            ))),
+        );
+
+        let _ = Editor::update(&mut editor, EditorMessage::NewNote);
+        assert_eq!(editor.debug_new_note_template().as_deref(), Some("meeting"));
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::NewNoteTemplatesLoaded(Ok(vec![
+                "daily".to_string(),
+                "meeting".to_string(),
+            ])),
+        );
+        assert_eq!(editor.debug_new_note_template().as_deref(), Some("meeting"));
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::NewNoteTemplateSelected(Some("daily".to_string())),
+        );
+        assert_eq!(editor.debug_new_note_template().as_deref(), Some("daily"));
+        let _ = Editor::update(&mut editor, EditorMessage::CancelNewNote);
+
+        // A default template deleted from disk falls back to an empty note.
+        let _ = Editor::update(&mut editor, EditorMessage::NewNote);
+        assert_eq!(editor.debug_new_note_template().as_deref(), Some("meeting"));
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::NewNoteTemplatesLoaded(Ok(vec!["daily".to_string()])),
+        );
+        assert_eq!(editor.debug_new_note_template(), None);
+    }
+
+    fn search_line_match(
+        line_number: usize,
+        text: &str,
+        columns: &[(usize, usize)],
+    ) -> SearchLineMatch {
+        let columns: Vec<SearchMatchRange> = columns
+            .iter()
+            .map(|&(start, end)| SearchMatchRange { start, end })
+            .collect();
+        SearchLineMatch {
+            line_number,
+            text: text.to_string(),
+            highlights: columns.clone(),
+            columns,
+        }
+    }
+
+    fn selected_range(line: usize, start: usize, end: usize) -> Cursor {
+        Cursor {
+            position: Position { line, column: end },
+            selection: Some(Position {
+                line,
+                column: start,
+            }),
+        }
+    }
+
+    #[test]
+    fn search_matches_open_in_the_editor_and_step_through_the_open_note() {
+        let notebook_dir = TestNotebookDir::new("search_match_navigation");
+        let mut notes = seed_note(&notebook_dir, "flow/note", "alpha beta\nbeta gamma beta");
+        let other_dir = Path::new(notebook_dir.as_str()).join("other/note");
+        fs::create_dir_all(&other_dir).expect("Failed to create note directory");
+        fs::write(other_dir.join("note.md"), "beta text").expect("Failed to seed note content");
+        notes.push(NoteMetadata {
+            rel_path: "other/note".to_string(),
+            labels: Vec::new(),
+            last_updated: None,
+            ..NoteMetadata::default()
+        });
+        let mut editor = create_editor_with_notebook(notebook_dir.as_str());
+        load_and_select_note(
+            &mut editor,
+            notes,
+            "flow/note",
+            "alpha beta\nbeta gamma beta",
+        );
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::SearchQueryChanged("beta".to_string()),
+        );
+        let (generation, _, _) = editor.debug_search_state();
+        let results = vec![
+            NoteSearchResult {
+                rel_path: "flow/note".to_string(),
+                snippet: "alpha beta".to_string(),
+                line_matches: vec![
+                    search_line_match(1, "alpha beta", &[(6, 10)]),
+                    search_line_match(2, "beta gamma beta", &[(0, 4), (11, 15)]),
+                ],
+            },
+            NoteSearchResult {
+                rel_path: "other/note".to_string(),
+                snippet: "beta text".to_string(),
+                line_matches: vec![search_line_match(1, "beta text", &[(0, 4)])],
+            },
+        ];
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::SearchCompleted(generation, Ok(results)),
+        );
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::OpenSearchMatch(
+                "flow/note".to_string(),
+                2,
+                SearchMatchRange { start: 11, end: 15 },
+            ),
+        );
+        assert_eq!(editor.debug_cursor(), selected_range(1, 11, 15));
+
+        let _ = Editor::update(&mut editor, EditorMessage::NextSearchMatch);
+        assert_eq!(editor.debug_cursor(), selected_range(0, 6, 10));
+        let _ = Editor::update(&mut editor, EditorMessage::PreviousSearchMatch);
+        assert_eq!(editor.debug_cursor(), selected_range(1, 11, 15));
+        let _ = Editor::update(&mut editor, EditorMessage::PreviousSearchMatch);
+        assert_eq!(editor.debug_cursor(), selected_range(1, 0, 4));
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::OpenSearchMatch(
+                "other/note".to_string(),
+                1,
+                SearchMatchRange { start: 0, end: 4 },
+            ),
+        );
+        assert_eq!(
+            editor.debug_selected_note_path().as_deref(),
+            Some("flow/note")
+        );
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::NoteSelected("other/note".to_string()),
+        );
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::LoadedNoteContent(
+                "other/note".to_string(),
+                "beta text".to_string(),
+                HashMap::new(),
+            ),
+        );
+        assert_eq!(editor.debug_cursor(), selected_range(0, 0, 4));
+    }
+
     #[test]
     fn clear_search_invalidates_in_flight_results() {
         let mut editor = Editor::default();
@@ -187,10 +532,11 @@ mod tests {
             &mut editor,
             EditorMessage::SearchCompleted(
                 generation,
-                vec![NoteSearchResult {
+                Ok(vec![NoteSearchResult {
                     rel_path: "alpha/note".to_string(),
                     snippet: "Path match".to_string(),
-                }],
+                    line_matches: Vec::new(),
+                }]),
             ),
         );
 
@@ -202,6 +548,61 @@ mod tests {
         );
     }
 
+    #[test]
+    fn label_changes_and_front_matter_edits_stay_in_sync() {
+        let notebook_dir = TestNotebookDir::new("front_matter_sync");
+        let notes = seed_note(&notebook_dir, "flow/note", "# Body\n");
+        let mut editor = create_editor_with_notebook(notebook_dir.as_str());
+        load_and_select_note(&mut editor, notes, "flow/note", "# Body\n");
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::NewLabelInputChanged("work".to_string()),
+        );
+        let _ = Editor::update(&mut editor, EditorMessage::AddLabel);
+        assert_eq!(
+            editor.debug_markdown_text(),
+            "---\nlabels: [seed, work]\n---\n# Body\n"
+        );
+        let _ = Editor::update(&mut editor, EditorMessage::RemoveLabel("seed".to_string()));
+        assert_eq!(
+            editor.debug_markdown_text(),
+            "---\nlabels: [work]\n---\n# Body\n"
+        );
+        // Labels the editor did not change leave the text alone.
+        let _ = Editor::update(&mut editor, EditorMessage::AddLabel);
+        assert_eq!(
+            editor.debug_markdown_text(),
+            "---\nlabels: [work]\n---\n# Body\n"
+        );
+
+        let edited = "---\nlabels: [work, plans]\ntitle: Roadmap\naliases: [plan]\nstatus: draft\n---\n# Body\n";
+        let stored = editor
+            .debug_note_metadata("flow/note")
+            .expect("Expected note metadata");
+        load_and_select_note(&mut editor, vec![stored], "flow/note", edited);
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::EditorAction(Action::Move(Motion::DocumentEnd)),
+        );
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::EditorAction(Action::Edit(Edit::Insert('!'))),
+        );
+
+        let note = editor
+            .debug_note_metadata("flow/note")
+            .expect("Expected note metadata");
+        assert_eq!(note.labels, vec!["work".to_string(), "plans".to_string()]);
+        assert_eq!(note.title.as_deref(), Some("Roadmap"));
+        assert_eq!(note.aliases, vec!["plan".to_string()]);
+        assert_eq!(
+            note.properties.get("status").map(String::as_str),
+            Some("draft")
+        );
+        assert_eq!(editor.debug_selected_note_labels(), note.labels);
+    }
+
     #[test]
     fn debounce_message_flow_reschedules_while_previous_save_is_in_flight() {
         let notebook_dir = TestNotebookDir::new("debounce_flow");
@@ -300,12 +701,13 @@ mod tests {
             "Close request should start shutdown flush flow"
         );
 
-        let (notebook_path, content_note_path, markdown_text, notes) =
+        let (notebook_path, content_note_path, markdown_text, content_note_base, notes) =
             editor.debug_shutdown_payload();
         let flush_result = note_coordinator::flush_for_shutdown(
             &notebook_path,
             content_note_path,
             &markdown_text,
+            content_note_base,
             &notes,
         );
         let _ = Editor::update(
@@ -346,4 +748,859 @@ mod tests {
         );
         assert!(!editor.debug_shutdown_in_progress());
     }
+
+    #[test]
+    fn backlinks_loaded_for_previous_selection_are_ignored() {
+        let notebook_dir = TestNotebookDir::new("backlinks_flow");
+        let notes = seed_note(&notebook_dir, "flow/note", "hello");
+        let mut editor = create_editor_with_notebook(notebook_dir.as_str());
+        load_and_select_note(&mut editor, notes, "flow/note", "hello");
+
+        let backlink = notebook::NoteBacklink {
+            rel_path: "other/note".to_string(),
+            context: "See [[flow/note]]".to_string(),
+        };
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::BacklinksLoaded("stale/note".to_string(), vec![backlink.clone()]),
+        );
+        assert!(editor.debug_selected_note_backlinks().is_empty());
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::BacklinksLoaded("flow/note".to_string(), vec![backlink.clone()]),
+        );
+        assert_eq!(editor.debug_selected_note_backlinks(), vec![backlink]);
+    }
+
+    #[test]
+    fn broken_preview_link_and_missing_anchor_surface_status_message() {
+        let notebook_dir = TestNotebookDir::new("broken_link_flow");
+        let notes = seed_note(&notebook_dir, "flow/note", "# Intro\n[[missing]]");
+        let mut editor = create_editor_with_notebook(notebook_dir.as_str());
+        load_and_select_note(&mut editor, notes, "flow/note", "# Intro\n[[missing]]");
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::MarkdownLinkClicked("cognate-note:missing".to_string()),
+        );
+        let status = editor
+            .debug_status_message()
+            .expect("Expected broken link to surface a status message");
+        assert!(status.contains("missing"), "Unexpected status: {}", status);
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::MarkdownLinkClicked("#intro".to_string()),
+        );
+        assert_eq!(editor.debug_status_message(), None);
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::MarkdownLinkClicked("#outro".to_string()),
+        );
+        assert!(editor.debug_status_message().is_some());
+
+        let _ = Editor::update(&mut editor, EditorMessage::DismissStatus);
+        assert_eq!(editor.debug_status_message(), None);
+    }
+
+    #[test]
+    fn restoring_trash_entry_over_existing_note_asks_for_new_path() {
+        let notebook_dir = TestNotebookDir::new("trash_restore_conflict");
+        let notes = seed_note(&notebook_dir, "flow/note", "body");
+        let mut editor = create_editor_with_notebook(notebook_dir.as_str());
+        load_and_select_note(&mut editor, notes, "flow/note", "body");
+        let trashed = TrashEntry {
+            id: "1_flow_note".to_string(),
+            rel_path: "flow/note".to_string(),
+            labels: vec!["seed".to_string()],
+            last_updated: None,
+            deleted_at: "2024-01-02T00:00:00Z".to_string(),
+        };
+
+        let _ = Editor::update(&mut editor, EditorMessage::ToggleTrash);
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::TrashLoaded(Ok(vec![trashed.clone()])),
+        );
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::RestoreTrashEntry(trashed.id.clone()),
+        );
+
+        let (show_trash, entries, restore_entry_id) = editor.debug_trash_state();
+        assert!(show_trash);
+        assert_eq!(entries, vec![trashed.clone()]);
+        assert_eq!(restore_entry_id.as_deref(), Some("1_flow_note"));
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::TrashEntryRestored(Err(NotebookError::validation(
+                "restore note",
+                "An item already exists at 'flow/note (restored)'.",
+            ))),
+        );
+        assert!(editor.debug_status_message().is_some());
+
+        let _ = Editor::update(&mut editor, EditorMessage::CancelTrashRestore);
+        let _ = Editor::update(&mut editor, EditorMessage::ToggleTrash);
+        let (show_trash, _, restore_entry_id) = editor.debug_trash_state();
+        assert!(!show_trash);
+        assert_eq!(restore_entry_id, None);
+    }
+
+    #[test]
+    fn notebook_check_lists_findings_and_blocks_editing_while_open() {
+        let notebook_dir = TestNotebookDir::new("fsck_view");
+        let notes = seed_note(&notebook_dir, "flow/note", "body");
+        let mut editor = create_editor_with_notebook(notebook_dir.as_str());
+        load_and_select_note(&mut editor, notes, "flow/note", "body");
+        let finding = FsckFinding {
+            kind: FsckFindingKind::UntrackedNote,
+            path: "stray".to_string(),
+            detail: "Contains a note.md but is not listed in metadata.json.".to_string(),
+        };
+
+        let _ = Editor::update(&mut editor, EditorMessage::ToggleFsck);
+        let (show_fsck, in_progress, findings) = editor.debug_fsck_state();
+        assert!(show_fsck);
+        assert!(in_progress);
+        assert!(findings.is_empty());
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::FsckCompleted(Ok(vec![finding.clone()])),
+        );
+        let (_, in_progress, findings) = editor.debug_fsck_state();
+        assert!(!in_progress);
+        assert_eq!(findings, vec![finding.clone()]);
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::EditorAction(Action::Edit(Edit::Insert('x'))),
+        );
+        assert_eq!(editor.debug_markdown_text(), "body");
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::ConfirmRepairFsckFinding(finding.clone(), false),
+        );
+        let (_, in_progress, findings) = editor.debug_fsck_state();
+        assert!(!in_progress);
+        assert_eq!(findings, vec![finding.clone()]);
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::FsckFindingRepaired(Err(NotebookError::validation(
+                "repair notebook",
+                "'stray' has changed since the notebook was checked.",
+            ))),
+        );
+        assert!(editor.debug_status_message().is_some());
+        assert_eq!(editor.debug_fsck_state().2, vec![finding]);
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::FsckFindingRepaired(Ok(Vec::new())),
+        );
+        let (show_fsck, _, findings) = editor.debug_fsck_state();
+        assert!(show_fsck);
+        assert!(findings.is_empty());
+        assert_eq!(editor.debug_status_message(), None);
+
+        let _ = Editor::update(&mut editor, EditorMessage::ToggleFsck);
+        assert!(!editor.debug_fsck_state().0);
+    }
+
+    fn open_note_replace_preview(original_content: &str) -> ReplacePreview {
+        let hit = |id, start| ReplaceHit {
+            id,
+            start,
+            end: start + 3,
+            selected: true,
+        };
+        ReplacePreview {
+            find: "old".to_string(),
+            replacement: "new".to_string(),
+            notes: vec![NoteReplacePreview {
+                rel_path: "flow/note".to_string(),
+                original_content: original_content.to_string(),
+                is_open_note: true,
+                disk_base: None,
+                lines: vec![ReplaceLinePreview {
+                    line_number: 1,
+                    text: "old text old".to_string(),
+                    hits: vec![hit(0, 0), hit(1, 9)],
+                }],
+            }],
+        }
+    }
+
+    #[test]
+    fn find_and_replace_updates_the_open_note_as_an_undoable_edit() {
+        let notebook_dir = TestNotebookDir::new("replace_open_note");
+        let notes = seed_note(&notebook_dir, "flow/note", "old text old");
+        let mut editor = create_editor_with_notebook(notebook_dir.as_str());
+        load_and_select_note(&mut editor, notes, "flow/note", "old text old");
+
+        let _ = Editor::update(&mut editor, EditorMessage::ToggleReplace);
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::ReplaceFindChanged("old".to_string()),
+        );
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::ReplaceWithChanged("new".to_string()),
+        );
+        let _ = Editor::update(&mut editor, EditorMessage::PreviewReplace);
+        assert!(editor.debug_replace_state().1);
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::ReplacePreviewLoaded(Ok(open_note_replace_preview("old text old"))),
+        );
+        let _ = Editor::update(&mut editor, EditorMessage::ToggleReplaceHit(1));
+        let (show_replace, in_progress, preview) = editor.debug_replace_state();
+        assert!(show_replace);
+        assert!(!in_progress);
+        let preview = preview.expect("preview should be loaded");
+        assert!(!preview.notes[0].lines[0].hits[1].selected);
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::EditorAction(Action::Edit(Edit::Insert('x'))),
+        );
+        assert_eq!(editor.debug_markdown_text(), "old text old");
+
+        let _ = Editor::update(&mut editor, EditorMessage::ApplyReplace);
+        assert!(editor.debug_replace_state().1);
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::ReplaceApplied(Ok(vec!["flow/note".to_string()])),
+        );
+        assert_eq!(editor.debug_markdown_text(), "new text old");
+        assert_eq!(
+            editor.debug_status_message().as_deref(),
+            Some("Replaced 1 occurrence(s) in 1 note(s).")
+        );
+        let (_, in_progress, preview) = editor.debug_replace_state();
+        assert!(!in_progress);
+        assert_eq!(preview, None);
+
+        let _ = Editor::update(&mut editor, EditorMessage::ToggleReplace);
+        assert!(!editor.debug_replace_state().0);
+        let _ = Editor::update(&mut editor, EditorMessage::Undo);
+        assert_eq!(editor.debug_markdown_text(), "old text old");
+    }
+
+    #[test]
+    fn find_and_replace_refuses_a_preview_older_than_the_open_note() {
+        let notebook_dir = TestNotebookDir::new("replace_stale_open_note");
+        let notes = seed_note(&notebook_dir, "flow/note", "old text old");
+        let mut editor = create_editor_with_notebook(notebook_dir.as_str());
+        load_and_select_note(&mut editor, notes, "flow/note", "old text old");
+
+        let _ = Editor::update(&mut editor, EditorMessage::ToggleReplace);
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::ReplaceFindChanged("old".to_string()),
+        );
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::ReplacePreviewLoaded(Ok(open_note_replace_preview("old text"))),
+        );
+        assert!(editor.debug_replace_state().2.is_some());
+
+        let _ = Editor::update(&mut editor, EditorMessage::ApplyReplace);
+        let (_, in_progress, preview) = editor.debug_replace_state();
+        assert!(!in_progress);
+        assert_eq!(preview, None);
+        assert!(editor.debug_status_message().is_some());
+        assert_eq!(editor.debug_markdown_text(), "old text old");
+    }
+
+    #[test]
+    fn notebook_label_edit_updates_the_open_note_labels_and_front_matter() {
+        let notebook_dir = TestNotebookDir::new("label_edit_open_note");
+        let content = "---\nlabels: [seed]\n---\nbody";
+        let notes = seed_note(&notebook_dir, "flow/note", content);
+        let mut editor = create_editor_with_notebook(notebook_dir.as_str());
+        load_and_select_note(&mut editor, notes, "flow/note", content);
+
+        let _ = Editor::update(&mut editor, EditorMessage::ToggleLabelEditor);
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::LabelEditLabelChanged("seed".to_string()),
+        );
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::PreviewLabelEdit(LabelEditKind::Rename),
+        );
+        assert_eq!(editor.debug_label_edit_state().2, None);
+        assert!(editor.debug_status_message().is_some());
+        let _ = Editor::update(&mut editor, EditorMessage::DismissStatus);
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::LabelEditTargetChanged("crop".to_string()),
+        );
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::PreviewLabelEdit(LabelEditKind::Rename),
+        );
+        let (show_label_editor, _, preview) = editor.debug_label_edit_state();
+        assert!(show_label_editor);
+        let preview = preview.expect("preview should be loaded");
+        assert_eq!(preview.notes[0].new_labels, ["crop"]);
+
+        let _ = Editor::update(&mut editor, EditorMessage::ApplyLabelEdit);
+        assert!(editor.debug_label_edit_state().1);
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::LabelEditApplied(Ok(vec!["flow/note".to_string()])),
+        );
+        let (_, in_progress, preview) = editor.debug_label_edit_state();
+        assert!(!in_progress);
+        assert_eq!(preview, None);
+        assert_eq!(editor.debug_selected_note_labels(), ["crop"]);
+        assert_eq!(
+            editor
+                .debug_note_metadata("flow/note")
+                .map(|note| note.labels),
+            Some(vec!["crop".to_string()])
+        );
+        assert_eq!(
+            editor.debug_markdown_text(),
+            "---\nlabels: [crop]\n---\nbody"
+        );
+        assert_eq!(
+            editor.debug_status_message().as_deref(),
+            Some("Renamed 'seed' to 'crop' in 1 note(s).")
+        );
+
+        let _ = Editor::update(&mut editor, EditorMessage::ToggleLabelEditor);
+        assert!(!editor.debug_label_edit_state().0);
+    }
+
+    /// Types into the open note, then saves it the way shutdown does and returns the outcome.
+    fn edit_and_flush_open_note(editor: &mut Editor) -> NoteSaveOutcome {
+        let _ = Editor::update(
+            editor,
+            EditorMessage::EditorAction(Action::Move(Motion::DocumentEnd)),
+        );
+        let _ = Editor::update(
+            editor,
+            EditorMessage::EditorAction(Action::Edit(Edit::Insert('!'))),
+        );
+        let (notebook_path, note_path, markdown, base, _) = editor.debug_shutdown_payload();
+        notebook::save_note_content_checked_sync(
+            &notebook_path,
+            &note_path.expect("a note should be open"),
+            &markdown,
+            base.as_ref(),
+        )
+        .expect("saving the open note should succeed")
+    }
+
+    #[test]
+    fn editing_the_open_note_after_a_label_rename_keeps_the_new_labels() {
+        let notebook_dir = TestNotebookDir::new("label_edit_then_type");
+        let content = "---\nlabels: [seed]\n---\nbody";
+        let notes = seed_note(&notebook_dir, "flow/note", content);
+        let mut editor = create_editor_with_notebook(notebook_dir.as_str());
+        load_and_select_note(&mut editor, notes.clone(), "flow/note", content);
+
+        let preview = notebook::preview_label_edit(&notes, LabelEditKind::Rename, "seed", "crop")
+            .expect("rename preview should succeed");
+        let mut saved_notes = notes;
+        let changed = iced::futures::executor::block_on(notebook::apply_label_edit(
+            notebook_dir.as_str(),
+            &preview,
+            &mut saved_notes,
+        ))
+        .expect("rename should succeed");
+        let _ = Editor::update(&mut editor, EditorMessage::ToggleLabelEditor);
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::LabelEditLabelChanged("seed".to_string()),
+        );
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::LabelEditTargetChanged("crop".to_string()),
+        );
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::PreviewLabelEdit(LabelEditKind::Rename),
+        );
+        let _ = Editor::update(&mut editor, EditorMessage::LabelEditApplied(Ok(changed)));
+        let _ = Editor::update(&mut editor, EditorMessage::ToggleLabelEditor);
+
+        let outcome = edit_and_flush_open_note(&mut editor);
+        assert!(matches!(outcome, NoteSaveOutcome::Saved(_)), "{outcome:?}");
+        assert_eq!(
+            fs::read_to_string(
+                Path::new(notebook_dir.as_str())
+                    .join("flow/note")
+                    .join("note.md")
+            )
+            .expect("note should be readable"),
+            "---\nlabels: [crop]\n---\nbody!"
+        );
+        assert_eq!(
+            editor
+                .debug_note_metadata("flow/note")
+                .map(|note| note.labels),
+            Some(vec!["crop".to_string()])
+        );
+    }
+
+    #[test]
+    fn label_rename_leaves_an_open_note_without_front_matter_labels_untouched() {
+        let notebook_dir = TestNotebookDir::new("label_edit_no_front_matter");
+        let content = "body";
+        let notes = seed_note(&notebook_dir, "flow/note", content);
+        let mut editor = create_editor_with_notebook(notebook_dir.as_str());
+        load_and_select_note(&mut editor, notes.clone(), "flow/note", content);
+
+        let preview = notebook::preview_label_edit(&notes, LabelEditKind::Rename, "seed", "crop")
+            .expect("rename preview should succeed");
+        let mut saved_notes = notes;
+        let changed = iced::futures::executor::block_on(notebook::apply_label_edit(
+            notebook_dir.as_str(),
+            &preview,
+            &mut saved_notes,
+        ))
+        .expect("rename should succeed");
+        let _ = Editor::update(&mut editor, EditorMessage::ToggleLabelEditor);
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::LabelEditLabelChanged("seed".to_string()),
+        );
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::LabelEditTargetChanged("crop".to_string()),
+        );
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::PreviewLabelEdit(LabelEditKind::Rename),
+        );
+        let _ = Editor::update(&mut editor, EditorMessage::LabelEditApplied(Ok(changed)));
+
+        assert_eq!(editor.debug_markdown_text(), "body");
+        assert_eq!(editor.debug_selected_note_labels(), ["crop"]);
+        assert_eq!(
+            fs::read_to_string(
+                Path::new(notebook_dir.as_str())
+                    .join("flow/note")
+                    .join("note.md")
+            )
+            .expect("note should be readable"),
+            "body"
+        );
+    }
+
+    #[test]
+    fn editing_the_open_note_after_a_move_keeps_the_rewritten_links() {
+        let notebook_dir = TestNotebookDir::new("move_then_type");
+        let content = "See [[docs/target]]";
+        let mut notes = seed_note(&notebook_dir, "flow/note", content);
+        let target_dir = Path::new(notebook_dir.as_str()).join("docs/target");
+        fs::create_dir_all(&target_dir).expect("Failed to create target directory");
+        fs::write(target_dir.join("note.md"), "target").expect("Failed to seed target note");
+        notes.push(NoteMetadata {
+            rel_path: "docs/target".to_string(),
+            ..NoteMetadata::default()
+        });
+        notebook::save_metadata(notebook_dir.as_str(), &notes).expect("Failed to seed metadata");
+        let mut editor = create_editor_with_notebook(notebook_dir.as_str());
+        load_and_select_note(&mut editor, notes.clone(), "flow/note", content);
+
+        let moved = iced::futures::executor::block_on(notebook::move_note(
+            notebook_dir.as_str(),
+            "docs/target",
+            "archive/target",
+            &mut notes,
+        ))
+        .expect("move should succeed");
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::NoteMoved(Ok(moved), "docs/target".to_string()),
+        );
+        assert_eq!(editor.debug_markdown_text(), "See [[archive/target]]");
+
+        let outcome = edit_and_flush_open_note(&mut editor);
+        assert!(matches!(outcome, NoteSaveOutcome::Saved(_)), "{outcome:?}");
+        assert_eq!(
+            fs::read_to_string(
+                Path::new(notebook_dir.as_str())
+                    .join("flow/note")
+                    .join("note.md")
+            )
+            .expect("note should be readable"),
+            "See [[archive/target]]!"
+        );
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    #[test]
+    fn editing_the_open_note_after_labelling_marked_notes_keeps_the_new_labels() {
+        let notebook_dir = TestNotebookDir::new("bulk_label_then_type");
+        let content = "---\nlabels: [seed]\n---\nbody";
+        let notes = seed_note(&notebook_dir, "flow/note", content);
+        let mut editor = create_editor_with_notebook(notebook_dir.as_str());
+        load_and_select_note(&mut editor, notes.clone(), "flow/note", content);
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::NoteExplorerMsg(note_explorer::Message::ToggleNoteMark(
+                "flow/note".to_string(),
+            )),
+        );
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::NewLabelInputChanged("crop".to_string()),
+        );
+
+        let changes = notebook::preview_bulk_label_edit(
+            &notes,
+            &["flow/note".to_string()],
+            BulkLabelAction::Add,
+            "crop",
+        )
+        .expect("add preview should succeed");
+        let mut saved_notes = notes;
+        let changed = iced::futures::executor::block_on(notebook::apply_bulk_label_edit(
+            notebook_dir.as_str(),
+            &changes,
+            &mut saved_notes,
+        ))
+        .expect("bulk add should succeed");
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::MarkedNotesLabelEdited(
+                BulkLabelAction::Add,
+                "crop".to_string(),
+                changes,
+                Ok(changed),
+            ),
+        );
+        assert_eq!(
+            editor.debug_markdown_text(),
+            "---\nlabels: [seed, crop]\n---\nbody"
+        );
+
+        let outcome = edit_and_flush_open_note(&mut editor);
+        assert!(matches!(outcome, NoteSaveOutcome::Saved(_)), "{outcome:?}");
+        assert_eq!(
+            fs::read_to_string(
+                Path::new(notebook_dir.as_str())
+                    .join("flow/note")
+                    .join("note.md")
+            )
+            .expect("note should be readable"),
+            "---\nlabels: [seed, crop]\n---\nbody!"
+        );
+        assert_eq!(
+            editor
+                .debug_note_metadata("flow/note")
+                .map(|note| note.labels),
+            Some(vec!["seed".to_string(), "crop".to_string()])
+        );
+    }
+
+    #[test]
+    fn label_styles_prefill_the_label_editor_and_follow_renames() {
+        let notebook_dir = TestNotebookDir::new("label_style_flow");
+        let notes = seed_note(&notebook_dir, "flow/note", "body");
+        let mut editor = create_editor_with_notebook(notebook_dir.as_str());
+        load_and_select_note(&mut editor, notes, "flow/note", "body");
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::NewLabelInputChanged("seed".to_string()),
+        );
+        let _ = Editor::update(&mut editor, EditorMessage::AddLabel);
+        let _ = Editor::update(&mut editor, EditorMessage::ToggleLabelEditor);
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::LabelEditLabelChanged("seed".to_string()),
+        );
+
+        let style = LabelInfo {
+            color: Some("#204060".to_string()),
+            description: Some("Sown ideas".to_string()),
+            icon: Some("~".to_string()),
+        };
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::LabelStyleSaved(Ok(LabelRegistry::from([(
+                "seed".to_string(),
+                style.clone(),
+            )]))),
+        );
+        assert_eq!(
+            editor.debug_status_message().as_deref(),
+            Some("Saved the style of 'seed'.")
+        );
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::LabelEditLabelChanged("other".to_string()),
+        );
+        assert_eq!(editor.debug_label_style().0, "");
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::LabelEditLabelChanged("Seed".to_string()),
+        );
+        let (color, description, icon, _) = editor.debug_label_style();
+        assert_eq!(
+            (color.as_str(), description.as_str(), icon.as_str()),
+            ("#204060", "Sown ideas", "~")
+        );
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::LabelEditTargetChanged("crop".to_string()),
+        );
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::PreviewLabelEdit(LabelEditKind::Rename),
+        );
+        assert!(editor.debug_label_edit_state().2.is_some());
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::LabelEditApplied(Ok(vec!["flow/note".to_string()])),
+        );
+        let (_, _, _, registry) = editor.debug_label_style();
+        assert_eq!(registry, LabelRegistry::from([("crop".to_string(), style)]));
+    }
+
+    #[test]
+    fn label_input_suggests_labels_and_edits_marked_notes() {
+        let notebook_dir = TestNotebookDir::new("bulk_label_flow");
+        let mut notes = seed_note(&notebook_dir, "flow/note", "body");
+        notes.push(NoteMetadata {
+            rel_path: "flow/other".to_string(),
+            labels: vec!["sequence".to_string()],
+            ..NoteMetadata::default()
+        });
+        let mut editor = create_editor_with_notebook(notebook_dir.as_str());
+        load_and_select_note(&mut editor, notes, "flow/note", "body");
+
+        // The open note's own labels are not suggested.
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::NewLabelInputChanged("se".to_string()),
+        );
+        assert_eq!(editor.debug_label_suggestions(), ["sequence"]);
+
+        for path in ["flow/note", "flow/other"] {
+            let _ = Editor::update(
+                &mut editor,
+                EditorMessage::NoteExplorerMsg(note_explorer::Message::ToggleNoteMark(
+                    path.to_string(),
+                )),
+            );
+        }
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::NewLabelInputChanged("se".to_string()),
+        );
+        assert_eq!(editor.debug_label_suggestions(), ["seed", "sequence"]);
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::NewLabelInputChanged("seed".to_string()),
+        );
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::EditMarkedNotesLabel(BulkLabelAction::Add),
+        );
+        let notes = vec![
+            editor.debug_note_metadata("flow/note").expect("note"),
+            editor.debug_note_metadata("flow/other").expect("note"),
+        ];
+        let changes = notebook::preview_bulk_label_edit(
+            &notes,
+            &["flow/note".to_string(), "flow/other".to_string()],
+            BulkLabelAction::Add,
+            "seed",
+        )
+        .expect("add preview should succeed");
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::MarkedNotesLabelEdited(
+                BulkLabelAction::Add,
+                "seed".to_string(),
+                changes,
+                Ok(vec!["flow/other".to_string()]),
+            ),
+        );
+        assert_eq!(
+            editor
+                .debug_note_metadata("flow/other")
+                .map(|note| note.labels),
+            Some(vec!["sequence".to_string(), "seed".to_string()])
+        );
+        assert_eq!(
+            editor.debug_status_message().as_deref(),
+            Some("Added 'seed' to 1 note(s).")
+        );
+        assert!(editor.debug_label_suggestions().is_empty());
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::NewLabelInputChanged("seed".to_string()),
+        );
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::EditMarkedNotesLabel(BulkLabelAction::Add),
+        );
+        assert_eq!(
+            editor.debug_status_message().as_deref(),
+            Some("Every marked note already has 'seed'.")
+        );
+    }
+
+    #[test]
+    fn restoring_history_version_is_an_undoable_edit() {
+        let notebook_dir = TestNotebookDir::new("history_restore");
+        let notes = seed_note(&notebook_dir, "flow/note", "current text");
+        let mut editor = create_editor_with_notebook(notebook_dir.as_str());
+        load_and_select_note(&mut editor, notes, "flow/note", "current text");
+        let version = NoteVersion {
+            id: "0123456789abcdef0123456789abcdef".to_string(),
+            saved_at: "2024-01-01T00:00:00Z".to_string(),
+        };
+
+        let _ = Editor::update(&mut editor, EditorMessage::ToggleHistory);
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::NoteHistoryLoaded("flow/note".to_string(), Ok(vec![version.clone()])),
+        );
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::HistoryVersionLoaded(
+                "flow/note".to_string(),
+                version.id.clone(),
+                Ok("older text".to_string()),
+            ),
+        );
+        let _ = Editor::update(&mut editor, EditorMessage::RestoreHistoryVersion);
+
+        assert_eq!(editor.debug_markdown_text(), "older text");
+
+        let _ = Editor::update(&mut editor, EditorMessage::Undo);
+        assert_eq!(editor.debug_markdown_text(), "current text");
+    }
+
+    #[test]
+    fn external_change_reloads_untouched_note_and_keeps_unsaved_edits() {
+        let notebook_dir = TestNotebookDir::new("external_change");
+        let notes = seed_note(&notebook_dir, "flow/note", "original");
+        let mut editor = create_editor_with_notebook(notebook_dir.as_str());
+        load_and_select_note(&mut editor, notes, "flow/note", "original");
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::OpenNoteReloadedFromDisk(
+                "flow/note".to_string(),
+                "original".to_string(),
+                disk_state("edited elsewhere"),
+            ),
+        );
+        assert_eq!(editor.debug_markdown_text(), "edited elsewhere");
+
+        // A reload read while the buffer was edited leaves both versions to the conflict view.
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::OpenNoteReloadedFromDisk(
+                "flow/note".to_string(),
+                "stale buffer".to_string(),
+                disk_state("newer disk text"),
+            ),
+        );
+        assert_eq!(editor.debug_markdown_text(), "edited elsewhere");
+        assert!(editor.debug_note_conflict().is_some());
+        let _ = Editor::update(&mut editor, EditorMessage::KeepMineInConflict);
+        assert!(editor.debug_note_conflict().is_none());
+        assert_eq!(editor.debug_markdown_text(), "edited elsewhere");
+
+        let _ = Editor::update(&mut editor, EditorMessage::Undo);
+        assert_eq!(editor.debug_markdown_text(), "original");
+
+        // The buffer was never saved through Cognate, so it counts as unsaved edits.
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::NotebookChangedOnDisk(NotebookChanges {
+                metadata_changed: false,
+                note_paths: vec!["flow/note".to_string()],
+            }),
+        );
+        assert_eq!(editor.debug_markdown_text(), "original");
+        assert!(editor.debug_status_message().is_some());
+    }
+
+    #[test]
+    fn save_conflict_offers_keep_theirs_and_three_way_merge() {
+        let notebook_dir = TestNotebookDir::new("save_conflict");
+        let notes = seed_note(&notebook_dir, "flow/note", "intro\nbody\nfooter");
+        let mut editor = create_editor_with_notebook(notebook_dir.as_str());
+        load_and_select_note(&mut editor, notes, "flow/note", "intro\nbody\nfooter");
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::NoteDiskStateLoaded(
+                "flow/note".to_string(),
+                disk_state("intro\nbody\nfooter"),
+            ),
+        );
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::EditorAction(Action::Edit(Edit::Insert('!'))),
+        );
+        assert_eq!(editor.debug_markdown_text(), "!intro\nbody\nfooter");
+
+        let theirs = "intro\nbody\nfooter, edited elsewhere";
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::NoteContentSaveFinished(
+                "flow/note".to_string(),
+                Ok(NoteSaveOutcome::Conflict(disk_state(theirs))),
+            ),
+        );
+        let conflict = editor
+            .debug_note_conflict()
+            .expect("A refused save should open the conflict view");
+        assert_eq!(conflict.merge.conflict_count, 0);
+
+        // Typing is blocked until the conflict is resolved.
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::EditorAction(Action::Edit(Edit::Insert('?'))),
+        );
+        assert_eq!(editor.debug_markdown_text(), "!intro\nbody\nfooter");
+
+        let _ = Editor::update(&mut editor, EditorMessage::MergeConflict);
+        assert!(editor.debug_note_conflict().is_none());
+        assert_eq!(
+            editor.debug_markdown_text(),
+            "!intro\nbody\nfooter, edited elsewhere"
+        );
+
+        let _ = Editor::update(
+            &mut editor,
+            EditorMessage::NoteContentSaveFinished(
+                "flow/note".to_string(),
+                Ok(NoteSaveOutcome::Conflict(disk_state("rewritten elsewhere"))),
+            ),
+        );
+        let _ = Editor::update(&mut editor, EditorMessage::KeepTheirsInConflict);
+        assert_eq!(editor.debug_markdown_text(), "rewritten elsewhere");
+
+        let _ = Editor::update(&mut editor, EditorMessage::Undo);
+        assert_eq!(
+            editor.debug_markdown_text(),
+            "!intro\nbody\nfooter, edited elsewhere"
+        );
+    }
 }
@@ -63,6 +63,39 @@ mod tests {
         assert!(!state.show_embedded_image_delete_confirmation());
     }
 
+    #[test]
+    fn every_view_and_dialog_blocks_note_editing() {
+        let mut state = EditorState::new();
+        state.set_notebook_path("notebook".to_string());
+        assert!(!state.blocks_note_editing());
+
+        let toggles: [fn(&mut EditorState); 7] = [
+            EditorState::toggle_visualizer,
+            EditorState::toggle_about_info,
+            EditorState::toggle_trash,
+            EditorState::toggle_history,
+            EditorState::toggle_fsck,
+            EditorState::toggle_replace,
+            EditorState::toggle_label_editor,
+        ];
+        for toggle in toggles {
+            toggle(&mut state);
+            assert!(state.blocks_note_editing());
+            toggle(&mut state);
+            assert!(!state.blocks_note_editing());
+        }
+
+        state.show_move_note_dialog("note".to_string());
+        assert!(state.blocks_note_editing());
+        state.hide_move_note_dialog();
+        assert!(!state.blocks_note_editing());
+
+        state.show_embedded_image_delete_dialog(1);
+        assert!(state.blocks_note_editing());
+        state.hide_embedded_image_delete_dialog();
+        assert!(!state.blocks_note_editing());
+    }
+
     #[test]
     fn note_and_label_accessors_work() {
         let mut state = EditorState::new();
@@ -74,6 +107,7 @@ mod tests {
         state.set_search_results(vec![crate::notebook::NoteSearchResult {
             rel_path: "x/y".to_string(),
             snippet: "Path match".to_string(),
+            line_matches: Vec::new(),
         }]);
         state.set_loading_note(true);
 
@@ -105,16 +139,19 @@ mod tests {
                 rel_path: "work/note1".to_string(),
                 labels: vec![],
                 last_updated: None,
+                ..NoteMetadata::default()
             },
             NoteMetadata {
                 rel_path: "work/sub/note2".to_string(),
                 labels: vec![],
                 last_updated: None,
+                ..NoteMetadata::default()
             },
             NoteMetadata {
                 rel_path: "top".to_string(),
                 labels: vec![],
                 last_updated: None,
+                ..NoteMetadata::default()
             },
         ];
 
@@ -17,6 +17,7 @@ mod tests {
             rel_path: "note/a".to_string(),
             labels: vec![],
             last_updated: None,
+            ..NoteMetadata::default()
         }];
 
         let visualizer = Visualizer::new();
@@ -58,10 +59,10 @@ mod tests {
 
     #[test]
     fn label_input_ignored_while_about_dialog_open() {
-        let (mut state, _explorer, _visualizer) = setup();
+        let (mut state, explorer, _visualizer) = setup();
         state.set_show_about_info(true);
 
-        label_actions::handle_label_input_changed(&mut state, "blocked".to_string());
+        label_actions::handle_label_input_changed(&mut state, &explorer, "blocked".to_string());
         assert_eq!(state.new_label_text(), "");
     }
 }
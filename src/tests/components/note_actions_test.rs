@@ -8,7 +8,9 @@ mod tests {
     use crate::components::note_explorer::NoteExplorer;
     use crate::components::visualizer;
     use crate::components::visualizer::Visualizer;
-    use crate::notebook::{MetadataLoadResult, NoteMetadata, NotebookError};
+    use crate::notebook::{
+        CreatedNote, MetadataLoadResult, MovedNote, NoteMetadata, NotebookError, NotebookSettings,
+    };
     use iced::widget::text_editor::{Content, Cursor, Position};
 
     fn note(path: &str, labels: &[&str]) -> NoteMetadata {
@@ -16,6 +18,7 @@ mod tests {
             rel_path: path.to_string(),
             labels: labels.iter().map(|s| s.to_string()).collect(),
             last_updated: None,
+            ..NoteMetadata::default()
         }
     }
 
@@ -23,6 +26,13 @@ mod tests {
         NotebookError::storage("test harness", "simulated failure")
     }
 
+    fn moved_note(rel_path: &str) -> MovedNote {
+        MovedNote {
+            rel_path: rel_path.to_string(),
+            rewritten_notes: Vec::new(),
+        }
+    }
+
     fn setup_state_with_notebook() -> EditorState {
         let mut state = EditorState::new();
         state.set_notebook_path("dummy_notebook".to_string());
@@ -66,10 +76,17 @@ mod tests {
     #[test]
     fn note_created_and_deleted_handlers_cover_success_and_error_paths() {
         let mut explorer = NoteExplorer::new("dummy".to_string());
-        let _ = note_actions::handle_note_created(Ok(note("created/path", &[])), &mut explorer);
-        let _ = note_actions::handle_note_created(Err(sample_error()), &mut explorer);
-
         let mut state = setup_state_with_notebook();
+        let _ = note_actions::handle_note_created(
+            Ok(CreatedNote {
+                metadata: note("created/path", &[]),
+                cursor: None,
+            }),
+            &mut state,
+            &mut explorer,
+        );
+        let _ = note_actions::handle_note_created(Err(sample_error()), &mut state, &mut explorer);
+
         let mut content = Content::with_text("hello");
         let mut markdown = "hello".to_string();
         let mut undo = UndoManager::new();
@@ -149,7 +166,7 @@ mod tests {
         undo.add_to_history("a", "v1".to_string(), cursor(0, 0));
         state.show_move_note_dialog("a".to_string());
         let _ = note_actions::handle_note_moved(
-            Ok("c".to_string()),
+            Ok(moved_note("c")),
             "a".to_string(),
             &mut state,
             &mut undo,
@@ -199,6 +216,9 @@ mod tests {
             &mut markdown,
             note_explorer::Message::NotesLoaded(Ok(MetadataLoadResult {
                 notes: loaded_notes,
+                saved_searches: Vec::new(),
+                settings: NotebookSettings::default(),
+                label_registry: Default::default(),
                 warning: None,
             })),
         );
@@ -227,10 +247,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn selected_journal_date_follows_the_configured_pattern() {
+        let mut state = setup_state_with_notebook();
+        assert_eq!(note_actions::selected_journal_date(&state), None);
+
+        state.set_selected_note_path(Some("journal/2026/03/04".to_string()));
+        assert_eq!(
+            note_actions::selected_journal_date(&state),
+            time::Date::from_calendar_date(2026, time::Month::March, 4).ok()
+        );
+
+        state.set_journal_settings("daily/{{date}}".to_string(), None);
+        assert_eq!(note_actions::selected_journal_date(&state), None);
+        state.set_selected_note_path(Some("daily/2026-03-05".to_string()));
+        assert_eq!(
+            note_actions::selected_journal_date(&state),
+            time::Date::from_calendar_date(2026, time::Month::March, 5).ok()
+        );
+    }
+
     #[test]
     fn editor_message_variants_for_note_actions_paths_are_constructible() {
-        let _ = EditorMessage::NoteCreated(Ok(note("n", &[])));
+        let _ = EditorMessage::NoteCreated(Ok(CreatedNote {
+            metadata: note("n", &[]),
+            cursor: Some((1, 0)),
+        }));
         let _ = EditorMessage::NoteDeleted(Ok(()), "n".to_string());
-        let _ = EditorMessage::NoteMoved(Ok("x".to_string()), "n".to_string());
+        let _ = EditorMessage::NoteMoved(Ok(moved_note("x")), "n".to_string());
     }
 }
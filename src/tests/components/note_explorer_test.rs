@@ -1,7 +1,10 @@
 #[cfg(test)]
 mod tests {
     use crate::components::note_explorer::{Message, NoteExplorer};
-    use crate::notebook::{MetadataLoadResult, NoteMetadata, NotebookError};
+    use crate::notebook::{
+        MetadataLoadResult, NoteMetadata, NotebookError, NotebookSettings, SavedSearch,
+        SavedSearchMatches, SearchMode,
+    };
 
     fn sample_notes() -> Vec<NoteMetadata> {
         vec![
@@ -9,16 +12,19 @@ mod tests {
                 rel_path: "b/note2".to_string(),
                 labels: vec![],
                 last_updated: None,
+                ..NoteMetadata::default()
             },
             NoteMetadata {
                 rel_path: "a/note1".to_string(),
                 labels: vec![],
                 last_updated: None,
+                ..NoteMetadata::default()
             },
             NoteMetadata {
                 rel_path: "a/sub/note3".to_string(),
                 labels: vec![],
                 last_updated: None,
+                ..NoteMetadata::default()
             },
         ]
     }
@@ -28,6 +34,9 @@ mod tests {
         let mut explorer = NoteExplorer::new("dummy".to_string());
         let _ = explorer.update(Message::NotesLoaded(Ok(MetadataLoadResult {
             notes: sample_notes(),
+            saved_searches: Vec::new(),
+            settings: NotebookSettings::default(),
+            label_registry: Default::default(),
             warning: None,
         })));
 
@@ -52,6 +61,9 @@ mod tests {
         let mut explorer = NoteExplorer::new("dummy".to_string());
         let _ = explorer.update(Message::NotesLoaded(Ok(MetadataLoadResult {
             notes: sample_notes(),
+            saved_searches: Vec::new(),
+            settings: NotebookSettings::default(),
+            label_registry: Default::default(),
             warning: None,
         })));
 
@@ -77,6 +89,9 @@ mod tests {
 
         let _ = explorer.update(Message::NotesLoaded(Ok(MetadataLoadResult {
             notes: sample_notes(),
+            saved_searches: Vec::new(),
+            settings: NotebookSettings::default(),
+            label_registry: Default::default(),
             warning: None,
         })));
         let _ = explorer.update(Message::CollapseAllAndExpandToNote(
@@ -93,6 +108,9 @@ mod tests {
         let mut explorer = NoteExplorer::new("dummy".to_string());
         let _ = explorer.update(Message::NotesLoaded(Ok(MetadataLoadResult {
             notes: sample_notes(),
+            saved_searches: Vec::new(),
+            settings: NotebookSettings::default(),
+            label_registry: Default::default(),
             warning: None,
         })));
         let before_error_paths: Vec<String> = explorer
@@ -116,4 +134,145 @@ mod tests {
             "Metadata load errors should not wipe previously loaded note state"
         );
     }
+
+    fn smart_folder_matches(note_paths: &[&str]) -> Vec<SavedSearchMatches> {
+        vec![SavedSearchMatches {
+            name: "Sub notes".to_string(),
+            note_paths: Ok(note_paths.iter().map(|path| path.to_string()).collect()),
+        }]
+    }
+
+    #[test]
+    fn smart_folders_follow_saved_searches_and_ignore_stale_evaluations() {
+        let mut explorer = NoteExplorer::new("dummy".to_string());
+        let _ = explorer.update(Message::NotesLoaded(Ok(MetadataLoadResult {
+            notes: sample_notes(),
+            saved_searches: vec![SavedSearch {
+                name: "Sub notes".to_string(),
+                query: "note3".to_string(),
+                mode: SearchMode::Fuzzy,
+                scope: Some("a".to_string()),
+            }],
+            settings: NotebookSettings::default(),
+            label_registry: Default::default(),
+            warning: None,
+        })));
+        assert_eq!(explorer.saved_searches.len(), 1);
+        assert!(explorer.smart_folders.is_empty());
+
+        // Notes changed again before the first evaluation finished.
+        let _ = explorer.update(Message::RefreshSmartFolders);
+        let _ = explorer.update(Message::SmartFoldersEvaluated(
+            1,
+            smart_folder_matches(&["a/note1"]),
+        ));
+        assert!(explorer.smart_folders.is_empty());
+        let _ = explorer.update(Message::SmartFoldersEvaluated(
+            2,
+            smart_folder_matches(&["a/sub/note3"]),
+        ));
+        assert_eq!(
+            explorer.smart_folders,
+            smart_folder_matches(&["a/sub/note3"])
+        );
+
+        let _ = explorer.update(Message::ToggleSmartFolder("Sub notes".to_string()));
+        let selected = "a/sub/note3".to_string();
+        {
+            let _smart_folder_view = explorer.view(Some(&selected));
+        }
+
+        let _ = explorer.update(Message::NotesLoaded(Ok(MetadataLoadResult {
+            notes: sample_notes(),
+            saved_searches: Vec::new(),
+            settings: NotebookSettings::default(),
+            label_registry: Default::default(),
+            warning: None,
+        })));
+        assert!(explorer.saved_searches.is_empty());
+        assert!(explorer.smart_folders.is_empty());
+    }
+
+    #[test]
+    fn label_filter_narrows_notes_to_a_label_and_its_descendants() {
+        let mut notes = sample_notes();
+        notes[0].labels = vec!["area/sub".to_string()];
+        notes[1].labels = vec!["Area".to_string()];
+        notes[2].labels = vec!["other".to_string()];
+        let mut explorer = NoteExplorer::new("dummy".to_string());
+        let _ = explorer.update(Message::NotesLoaded(Ok(MetadataLoadResult {
+            notes: notes.clone(),
+            saved_searches: Vec::new(),
+            settings: NotebookSettings::default(),
+            label_registry: Default::default(),
+            warning: None,
+        })));
+
+        let _ = explorer.update(Message::ToggleLabelTree);
+        let _ = explorer.update(Message::ToggleLabel("Area".to_string()));
+        let _ = explorer.update(Message::FilterByLabel(" area ".to_string()));
+        assert_eq!(explorer.label_filter.as_deref(), Some("area"));
+        {
+            let _filtered_view = explorer.view(None);
+        }
+
+        // Clicking the active label again clears the filter.
+        let _ = explorer.update(Message::FilterByLabel("AREA".to_string()));
+        assert_eq!(explorer.label_filter, None);
+
+        let _ = explorer.update(Message::FilterByLabel("other".to_string()));
+        notes[2].labels.clear();
+        let _ = explorer.update(Message::NotesLoaded(Ok(MetadataLoadResult {
+            notes,
+            saved_searches: Vec::new(),
+            settings: NotebookSettings::default(),
+            label_registry: Default::default(),
+            warning: None,
+        })));
+        assert_eq!(
+            explorer.label_filter, None,
+            "A filter whose label is no longer used should be dropped"
+        );
+    }
+
+    #[test]
+    fn note_marks_toggle_and_drop_notes_that_are_gone() {
+        let mut explorer = NoteExplorer::new("dummy".to_string());
+        let _ = explorer.update(Message::NotesLoaded(Ok(MetadataLoadResult {
+            notes: sample_notes(),
+            saved_searches: Vec::new(),
+            settings: NotebookSettings::default(),
+            label_registry: Default::default(),
+            warning: None,
+        })));
+
+        for path in ["a/note1", "b/note2", "a/note1", "a/sub/note3"] {
+            let _ = explorer.update(Message::ToggleNoteMark(path.to_string()));
+        }
+        assert_eq!(
+            explorer.marked_notes.iter().collect::<Vec<_>>(),
+            ["a/sub/note3", "b/note2"]
+        );
+        {
+            let _marked_view = explorer.view(None);
+        }
+
+        let _ = explorer.update(Message::NotesLoaded(Ok(MetadataLoadResult {
+            notes: sample_notes()
+                .into_iter()
+                .filter(|note| note.rel_path != "b/note2")
+                .collect(),
+            saved_searches: Vec::new(),
+            settings: NotebookSettings::default(),
+            label_registry: Default::default(),
+            warning: None,
+        })));
+        assert_eq!(
+            explorer.marked_notes.iter().collect::<Vec<_>>(),
+            ["a/sub/note3"]
+        );
+
+        let _ = explorer.update(Message::ClearNoteMarks);
+        assert!(explorer.marked_notes.is_empty());
+    }
 }
@@ -9,16 +9,19 @@ mod tests {
                 rel_path: "x/n1".to_string(),
                 labels: vec!["urgent".to_string(), "work".to_string()],
                 last_updated: None,
+                ..NoteMetadata::default()
             },
             NoteMetadata {
                 rel_path: "x/n2".to_string(),
                 labels: vec!["work".to_string()],
                 last_updated: None,
+                ..NoteMetadata::default()
             },
             NoteMetadata {
                 rel_path: "y/n3".to_string(),
                 labels: vec![],
                 last_updated: None,
+                ..NoteMetadata::default()
             },
         ]
     }
@@ -29,6 +32,7 @@ mod tests {
                 rel_path: "a/n1".to_string(),
                 labels: vec![" work ".to_string(), "work".to_string(), "".to_string()],
                 last_updated: None,
+                ..NoteMetadata::default()
             },
             NoteMetadata {
                 rel_path: "a/n2".to_string(),
@@ -38,11 +42,13 @@ mod tests {
                     " urgent ".to_string(),
                 ],
                 last_updated: None,
+                ..NoteMetadata::default()
             },
             NoteMetadata {
                 rel_path: "b/n3".to_string(),
                 labels: vec!["urgent".to_string()],
                 last_updated: None,
+                ..NoteMetadata::default()
             },
         ]
     }
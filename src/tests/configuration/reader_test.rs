@@ -124,6 +124,103 @@ mod tests {
             read_configuration(config_file.as_str()).expect("Expected valid configuration");
 
         assert!((config.scale - 1.0).abs() < f32::EPSILON);
+        assert_eq!(config.trash_retention_days, 30);
+    }
+
+    #[test]
+    fn read_configuration_reads_trash_retention_and_rejects_negative_values() {
+        let config_file = TestConfigFile::new(
+            "trash_retention",
+            r#"{
+                "theme": "Dark",
+                "notebook_path": "/tmp/my_notebook",
+                "trash_retention_days": 0
+            }"#,
+        );
+        let invalid_file = TestConfigFile::new(
+            "invalid_trash_retention",
+            r#"{
+                "theme": "Dark",
+                "notebook_path": "/tmp/my_notebook",
+                "trash_retention_days": -3
+            }"#,
+        );
+
+        let config =
+            read_configuration(config_file.as_str()).expect("Expected valid configuration");
+
+        assert_eq!(config.trash_retention_days, 0);
+        assert!(read_configuration(invalid_file.as_str()).is_err());
+    }
+
+    #[test]
+    fn read_configuration_reads_custom_link_opener_and_defaults_when_blank() {
+        let custom_file = TestConfigFile::new(
+            "custom_link_opener",
+            r#"{
+                "theme": "Dark",
+                "notebook_path": "/tmp/my_notebook",
+                "link_opener": "  firefox --new-tab  "
+            }"#,
+        );
+        let blank_file = TestConfigFile::new(
+            "blank_link_opener",
+            r#"{
+                "theme": "Dark",
+                "notebook_path": "/tmp/my_notebook",
+                "link_opener": "   "
+            }"#,
+        );
+
+        let custom =
+            read_configuration(custom_file.as_str()).expect("Expected valid configuration");
+        let blank = read_configuration(blank_file.as_str()).expect("Expected valid configuration");
+
+        assert_eq!(custom.link_opener, "firefox --new-tab");
+        assert!(!blank.link_opener.is_empty());
+    }
+
+    #[test]
+    fn read_configuration_reads_journal_settings_and_rejects_dateless_patterns() {
+        let custom_file = TestConfigFile::new(
+            "custom_journal",
+            r#"{
+                "theme": "Dark",
+                "notebook_path": "/tmp/my_notebook",
+                "journal_path_pattern": " daily/{{date}} ",
+                "journal_template": "day"
+            }"#,
+        );
+        let default_file = TestConfigFile::new(
+            "default_journal",
+            r#"{
+                "theme": "Dark",
+                "notebook_path": "/tmp/my_notebook",
+                "journal_template": "  "
+            }"#,
+        );
+        let invalid_file = TestConfigFile::new(
+            "invalid_journal",
+            r#"{
+                "theme": "Dark",
+                "notebook_path": "/tmp/my_notebook",
+                "journal_path_pattern": "journal/{{year}}/{{month}}"
+            }"#,
+        );
+
+        let custom =
+            read_configuration(custom_file.as_str()).expect("Expected valid configuration");
+        let default =
+            read_configuration(default_file.as_str()).expect("Expected valid configuration");
+
+        assert_eq!(custom.journal_path_pattern, "daily/{{date}}");
+        assert_eq!(custom.journal_template.as_deref(), Some("day"));
+        assert_eq!(
+            default.journal_path_pattern,
+            "journal/{{year}}/{{month}}/{{day}}"
+        );
+        assert_eq!(default.journal_template, None);
+        assert!(read_configuration(invalid_file.as_str()).is_err());
     }
 
     #[test]
@@ -1,13 +1,15 @@
 #[cfg(test)]
 mod tests {
-    use crate::notebook::{self, NoteMetadata, NotebookErrorKind};
+    use crate::notebook::{
+        self, FsckFindingKind, LineDiffKind, NoteMetadata, NoteSaveOutcome, NotebookErrorKind,
+    };
     use std::fs;
     use std::future::Future;
     use std::path::{Path, PathBuf};
     use std::pin::Pin;
     use std::sync::Arc;
     use std::task::{Context, Poll, Wake, Waker};
-    use std::time::{SystemTime, UNIX_EPOCH};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
     struct NoopWaker;
 
@@ -299,7 +301,7 @@ mod tests {
         ))
         .expect("move_note should succeed");
 
-        assert_eq!(moved_to, "new/path");
+        assert_eq!(moved_to.rel_path, "new/path");
         assert_note_md_not_exists(&notebook_dir, "old/path");
         assert_note_md_exists(&notebook_dir, "new/path");
 
@@ -394,6 +396,7 @@ mod tests {
             rel_path: "rollback/delete".to_string(),
             labels: Vec::new(),
             last_updated: None,
+            ..NoteMetadata::default()
         }];
 
         let note_dir = Path::new(notebook_dir.as_str()).join("rollback/delete");
@@ -421,6 +424,7 @@ mod tests {
             rel_path: "rollback/source".to_string(),
             labels: Vec::new(),
             last_updated: None,
+            ..NoteMetadata::default()
         }];
 
         let source_dir = Path::new(notebook_dir.as_str()).join("rollback/source");
@@ -619,6 +623,81 @@ mod tests {
         assert!(persisted.contains("last_updated"));
     }
 
+    #[test]
+    fn load_notes_metadata_migrates_unversioned_metadata_and_keeps_backup() {
+        let notebook_dir = TestNotebookDir::new("migrate_unversioned_metadata");
+        let note_dir = Path::new(notebook_dir.as_str()).join("legacy/note");
+        fs::create_dir_all(&note_dir).expect("Failed to create legacy note directory");
+        fs::write(note_dir.join("note.md"), "legacy").expect("Failed to create legacy note file");
+        let null_labels_dir = Path::new(notebook_dir.as_str()).join("legacy/null");
+        fs::create_dir_all(&null_labels_dir).expect("Failed to create legacy note directory");
+        fs::write(null_labels_dir.join("note.md"), "null")
+            .expect("Failed to create legacy note file");
+        let legacy_metadata = r#"{
+  "notes": [
+    {
+      "rel_path": "legacy/note",
+      "last_updated": "2024-01-01T00:00:00Z"
+    },
+    {
+      "rel_path": "legacy/null",
+      "labels": null,
+      "last_updated": "2024-01-01T00:00:00Z"
+    }
+  ]
+}"#;
+        fs::write(
+            Path::new(notebook_dir.as_str()).join("metadata.json"),
+            legacy_metadata,
+        )
+        .expect("Failed to write legacy metadata");
+
+        let loaded = load_notes_or_panic(&notebook_dir);
+        assert_eq!(loaded.len(), 2);
+        assert!(loaded.iter().all(|note| note.labels.is_empty()));
+
+        let backup =
+            fs::read_to_string(Path::new(notebook_dir.as_str()).join("metadata.json.v0.bak"))
+                .expect("Expected a pre-migration backup");
+        assert_eq!(backup, legacy_metadata);
+
+        let persisted = fs::read_to_string(Path::new(notebook_dir.as_str()).join("metadata.json"))
+            .expect("Failed to read migrated metadata");
+        assert!(persisted.contains(&format!(
+            "\"schema_version\": {}",
+            notebook::METADATA_SCHEMA_VERSION
+        )));
+        assert!(persisted.contains("\"labels\": []"));
+        assert!(!persisted.contains("\"labels\": null"));
+    }
+
+    #[test]
+    fn newer_metadata_schema_is_refused_on_load_and_save() {
+        let notebook_dir = TestNotebookDir::new("newer_metadata_schema");
+        let future_metadata = r#"{
+  "schema_version": 99,
+  "notes": [],
+  "future_field": true
+}"#;
+        let metadata_path = Path::new(notebook_dir.as_str()).join("metadata.json");
+        fs::write(&metadata_path, future_metadata).expect("Failed to write future metadata");
+
+        let error = block_on(notebook::load_notes_metadata(
+            notebook_dir.as_str().to_string(),
+        ))
+        .expect_err("Expected a newer schema to be refused");
+        assert_eq!(error.kind(), NotebookErrorKind::Validation);
+        assert!(error.to_string().contains("schema version 99"));
+
+        let save_error = notebook::save_metadata(notebook_dir.as_str(), &[])
+            .expect_err("Expected saving over a newer schema to be refused");
+        assert_eq!(save_error.kind(), NotebookErrorKind::Validation);
+        assert_eq!(
+            fs::read_to_string(&metadata_path).expect("Failed to read metadata"),
+            future_metadata
+        );
+    }
+
     #[test]
     fn load_notes_metadata_refreshes_stale_last_updated_from_note_file_mtime() {
         let notebook_dir = TestNotebookDir::new("refresh_stale_last_updated");
@@ -699,6 +778,157 @@ mod tests {
         );
     }
 
+    #[test]
+    fn load_notes_metadata_adopts_untracked_notes_and_skips_hidden_directories() {
+        let notebook_dir = TestNotebookDir::new("adopt_untracked");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(&notebook_dir, &mut notes, &[("known", "known body")]);
+        let root = Path::new(notebook_dir.as_str());
+        for rel_path in [
+            "pulled/note",
+            &format!(".cognate_txn_delete_staged_{}/nested", now_nanos()),
+            ".cognate_trash/1_old/note",
+            "known/.cognate_history/copy",
+        ] {
+            fs::create_dir_all(root.join(rel_path)).expect("Failed to create note directory");
+            fs::write(root.join(rel_path).join("note.md"), "body").expect("Failed to write note");
+        }
+
+        let loaded = block_on(notebook::load_notes_metadata(
+            notebook_dir.as_str().to_string(),
+        ))
+        .expect("Expected metadata load to succeed");
+
+        let paths: Vec<&str> = loaded
+            .notes
+            .iter()
+            .map(|note| note.rel_path.as_str())
+            .collect();
+        assert_eq!(paths, vec!["known", "pulled/note"]);
+        assert!(loaded.notes[1].labels.is_empty());
+        assert!(loaded.notes[1].last_updated.is_some());
+        let warning = loaded.warning.expect("Adopted notes should be reported");
+        assert!(warning.contains("'pulled/note'"), "{}", warning);
+
+        let reloaded = block_on(notebook::load_notes_metadata(
+            notebook_dir.as_str().to_string(),
+        ))
+        .expect("Expected metadata load to succeed");
+        assert_eq!(reloaded.notes.len(), 2);
+        assert_eq!(reloaded.warning, None);
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    #[test]
+    fn load_notes_metadata_adopts_notes_when_metadata_file_is_missing() {
+        let notebook_dir = TestNotebookDir::new("adopt_without_metadata");
+        let note_dir = Path::new(notebook_dir.as_str()).join("cloned");
+        fs::create_dir_all(&note_dir).expect("Failed to create note directory");
+        fs::write(note_dir.join("note.md"), "cloned body").expect("Failed to write note");
+
+        let loaded = block_on(notebook::load_notes_metadata(
+            notebook_dir.as_str().to_string(),
+        ))
+        .expect("Expected metadata load to succeed");
+
+        assert_eq!(loaded.notes.len(), 1);
+        assert_eq!(loaded.notes[0].rel_path, "cloned");
+        assert!(loaded.warning.is_some());
+        assert!(
+            Path::new(notebook_dir.as_str())
+                .join("metadata.json")
+                .exists()
+        );
+    }
+
+    #[test]
+    fn load_notes_metadata_reads_front_matter_edited_outside_the_app() {
+        let notebook_dir = TestNotebookDir::new("front_matter_load");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(
+            &notebook_dir,
+            &mut notes,
+            &[("plans", "body"), ("untagged", "body")],
+        );
+        for note in &mut notes {
+            note.labels = vec!["stored".to_string()];
+        }
+        notebook::save_metadata(notebook_dir.as_str(), &notes).expect("Failed to save metadata");
+
+        notebook::save_note_content_sync(
+            notebook_dir.as_str(),
+            "plans",
+            "---\ntags:\n  - work\n  - 'q: 3'\ntitle: \"Plans\"\naliases: [roadmap]\nstatus: draft\n---\nbody",
+        )
+        .expect("Failed to save note content");
+        notebook::save_note_content_sync(
+            notebook_dir.as_str(),
+            "untagged",
+            "---\ntitle: Untagged\n---\nbody",
+        )
+        .expect("Failed to save note content");
+
+        let loaded = load_notes_or_panic(&notebook_dir);
+        let plans = &loaded[0];
+        assert_eq!(plans.labels, vec!["work".to_string(), "q: 3".to_string()]);
+        assert_eq!(plans.title.as_deref(), Some("Plans"));
+        assert_eq!(plans.aliases, vec!["roadmap".to_string()]);
+        assert_eq!(
+            plans.properties.get("status").map(String::as_str),
+            Some("draft")
+        );
+        // Front matter without labels keeps the labels set in the app.
+        assert_eq!(loaded[1].labels, vec!["stored".to_string()]);
+        assert_eq!(loaded[1].title.as_deref(), Some("Untagged"));
+
+        let metadata = fs::read_to_string(Path::new(notebook_dir.as_str()).join("metadata.json"))
+            .expect("Expected metadata to be readable");
+        assert!(metadata.contains("roadmap"), "{}", metadata);
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    #[test]
+    fn load_notes_metadata_reads_front_matter_only_from_modified_notes() {
+        let notebook_dir = TestNotebookDir::new("front_matter_load_unchanged");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(
+            &notebook_dir,
+            &mut notes,
+            &[("plans", "---\ntitle: Plans\n---\nbody")],
+        );
+        let note_file = Path::new(notebook_dir.as_str()).join("plans/note.md");
+        assert_eq!(
+            load_notes_or_panic(&notebook_dir)[0].title.as_deref(),
+            Some("Plans")
+        );
+
+        // Same modification time: the file is not read again.
+        let modified_time = fs::metadata(&note_file)
+            .and_then(|metadata| metadata.modified())
+            .expect("Failed to read note modification time");
+        fs::write(&note_file, "---\ntitle: Renamed\n---\nbody").expect("Failed to edit note");
+        fs::File::options()
+            .write(true)
+            .open(&note_file)
+            .and_then(|file| file.set_modified(modified_time))
+            .expect("Failed to restore note modification time");
+        assert_eq!(
+            load_notes_or_panic(&notebook_dir)[0].title.as_deref(),
+            Some("Plans")
+        );
+
+        fs::File::options()
+            .write(true)
+            .open(&note_file)
+            .and_then(|file| file.set_modified(modified_time + Duration::from_secs(1)))
+            .expect("Failed to update note modification time");
+        assert_eq!(
+            load_notes_or_panic(&notebook_dir)[0].title.as_deref(),
+            Some("Renamed")
+        );
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
     #[test]
     fn search_notes_finds_matches_in_path_label_and_content() {
         let notebook_dir = TestNotebookDir::new("search_notes");
@@ -738,7 +968,9 @@ mod tests {
             notebook_dir.as_str().to_string(),
             search_snapshot.clone(),
             "work".to_string(),
-        ));
+            notebook::SearchMode::Text,
+        ))
+        .expect("search query should parse");
         assert_eq!(path_results.len(), 1);
         assert_eq!(path_results[0].rel_path, "work/todo");
         assert_eq!(path_results[0].snippet, "Path match");
@@ -747,7 +979,9 @@ mod tests {
             notebook_dir.as_str().to_string(),
             search_snapshot.clone(),
             "urgent".to_string(),
-        ));
+            notebook::SearchMode::Text,
+        ))
+        .expect("search query should parse");
         assert_eq!(label_results.len(), 1);
         assert_eq!(label_results[0].rel_path, "work/todo");
         assert!(
@@ -759,7 +993,9 @@ mod tests {
             notebook_dir.as_str().to_string(),
             search_snapshot,
             "indexing".to_string(),
-        ));
+            notebook::SearchMode::Text,
+        ))
+        .expect("search query should parse");
         assert_eq!(content_results.len(), 1);
         assert_eq!(content_results[0].rel_path, "ideas/brainstorm");
         assert!(
@@ -772,245 +1008,2658 @@ mod tests {
     }
 
     #[test]
-    fn move_folder_updates_nested_note_paths() {
-        let notebook_dir = TestNotebookDir::new("move_folder");
+    fn search_notes_ranks_path_and_label_hits_above_body_hits_with_bm25() {
+        let notebook_dir = TestNotebookDir::new("search_ranking");
         let mut notes: Vec<NoteMetadata> = Vec::new();
-
-        block_on(notebook::create_new_note(
-            notebook_dir.as_str(),
-            "folder/note_a",
-            &mut notes,
-        ))
-        .expect("Failed to create folder/note_a");
-        block_on(notebook::create_new_note(
-            notebook_dir.as_str(),
-            "folder/sub/note_b",
-            &mut notes,
-        ))
-        .expect("Failed to create folder/sub/note_b");
-
-        let moved_to = block_on(notebook::move_note(
-            notebook_dir.as_str(),
-            "folder",
-            "renamed",
+        create_notes_with_content(
+            &notebook_dir,
             &mut notes,
-        ))
-        .expect("move_note for folder should succeed");
-
-        assert_eq!(moved_to, "renamed");
-        assert_note_md_not_exists(&notebook_dir, "folder/note_a");
-        assert_note_md_not_exists(&notebook_dir, "folder/sub/note_b");
-        assert_note_md_exists(&notebook_dir, "renamed/note_a");
-        assert_note_md_exists(&notebook_dir, "renamed/sub/note_b");
-
-        let mut rel_paths: Vec<String> = notes.iter().map(|n| n.rel_path.clone()).collect();
-        rel_paths.sort();
+            &[
+                ("garden/plan", "Plant tomatoes in spring."),
+                ("journal/monday", "Read about the garden."),
+                (
+                    "journal/tuesday",
+                    "Garden work: garden beds, garden paths and more garden chores.",
+                ),
+                ("recipes/soup", "Tomatoes and basil."),
+                ("misc/tags", "Nothing relevant here."),
+            ],
+        );
+        notes
+            .iter_mut()
+            .find(|note| note.rel_path == "misc/tags")
+            .expect("misc/tags should exist")
+            .labels
+            .push("garden".to_string());
+        let search_snapshot = notes
+            .iter()
+            .map(notebook::SearchNote::from)
+            .collect::<Vec<notebook::SearchNote>>();
+        let search = |query: &str| {
+            block_on(notebook::search_notes_with_snapshot(
+                notebook_dir.as_str().to_string(),
+                search_snapshot.clone(),
+                query.to_string(),
+                notebook::SearchMode::Text,
+            ))
+            .expect("search query should parse")
+        };
+
+        let ranked: Vec<String> = search("garden")
+            .into_iter()
+            .map(|result| result.rel_path)
+            .collect();
         assert_eq!(
-            rel_paths,
+            ranked,
             vec![
-                "renamed/note_a".to_string(),
-                "renamed/sub/note_b".to_string()
+                "garden/plan",
+                "misc/tags",
+                "journal/tuesday",
+                "journal/monday"
             ]
         );
-    }
-
-    #[test]
-    fn load_notes_metadata_recovers_from_backup_when_primary_is_corrupted() {
-        let notebook_dir = TestNotebookDir::new("metadata_recovery_from_backup");
-
-        fs::write(
-            Path::new(notebook_dir.as_str()).join("metadata.json.bak"),
-            r#"{
-  "notes": [
-    {
-      "rel_path": "recovered/note",
-      "labels": ["restored"],
-      "last_updated": "2024-01-01T00:00:00Z"
-    }
-  ]
-}"#,
-        )
-        .expect("Failed to write metadata backup fixture");
-        fs::write(
-            Path::new(notebook_dir.as_str()).join("metadata.json"),
-            "{ corrupt_primary_json ",
-        )
-        .expect("Failed to write corrupted metadata.json fixture");
-
-        let load_result = block_on(notebook::load_notes_metadata(
-            notebook_dir.as_str().to_string(),
-        ))
-        .expect("Expected metadata load to recover from backup");
 
-        assert_eq!(load_result.notes.len(), 1);
-        assert_eq!(load_result.notes[0].rel_path, "recovered/note");
+        let multi_term = search("garden tomatoes");
+        assert_eq!(multi_term.len(), 1);
+        assert_eq!(multi_term[0].rel_path, "garden/plan");
+        assert_eq!(multi_term[0].snippet, "Plant tomatoes in spring.");
+        let either_term = search("garden OR tomatoes");
+        assert_eq!(either_term[0].rel_path, "garden/plan");
         assert!(
-            load_result.warning.is_some(),
-            "Recovery path should surface a warning"
+            either_term
+                .iter()
+                .any(|result| result.rel_path == "recipes/soup")
         );
 
-        let restored_primary =
-            fs::read_to_string(Path::new(notebook_dir.as_str()).join("metadata.json"))
-                .expect("Expected metadata.json to be restored from backup");
-        assert!(
-            restored_primary.contains("recovered/note"),
-            "Primary metadata should be restored from backup contents"
-        );
+        notebook::save_note_content_sync(notebook_dir.as_str(), "misc/tags", "garden notes")
+            .expect("Failed to save note content");
+        let updated = search("notes");
+        assert_eq!(updated.len(), 1);
+        assert_eq!(updated[0].rel_path, "misc/tags");
+        notebook::clear_search_index_for_notebook(notebook_dir.as_str());
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
     }
 
     #[test]
-    fn save_metadata_keeps_last_known_good_copy_and_preserves_primary_when_atomic_rename_fails() {
-        let notebook_dir = TestNotebookDir::new("metadata_backup_and_atomic_failure");
-        let initial_notes = vec![NoteMetadata {
-            rel_path: "stable/note".to_string(),
-            labels: vec!["v1".to_string()],
-            last_updated: Some("2024-01-01T00:00:00Z".to_string()),
-        }];
-        notebook::save_metadata(notebook_dir.as_str(), &initial_notes)
-            .expect("Failed to save initial metadata");
+    fn search_notes_evaluates_filters_phrases_and_boolean_operators() {
+        let notebook_dir = TestNotebookDir::new("search_query");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(
+            &notebook_dir,
+            &mut notes,
+            &[
+                ("projects/alpha", "Release notes for the alpha launch."),
+                ("projects/beta", "Notes about the release of beta."),
+                ("journal/today", "Nothing released today."),
+            ],
+        );
+        for (rel_path, labels, last_updated) in [
+            (
+                "projects/alpha",
+                vec!["Work", "Urgent"],
+                "2026-03-01T09:00:00Z",
+            ),
+            ("projects/beta", vec!["work"], "2025-12-31T23:00:00Z"),
+            ("journal/today", vec!["personal"], "2026-01-01T08:00:00Z"),
+        ] {
+            let note = notes
+                .iter_mut()
+                .find(|note| note.rel_path == rel_path)
+                .expect("note should exist");
+            note.labels = labels.into_iter().map(str::to_string).collect();
+            note.last_updated = Some(last_updated.to_string());
+        }
+        let search_snapshot = notes
+            .iter()
+            .map(notebook::SearchNote::from)
+            .collect::<Vec<notebook::SearchNote>>();
+        let search = |query: &str| {
+            block_on(notebook::search_notes_with_snapshot(
+                notebook_dir.as_str().to_string(),
+                search_snapshot.clone(),
+                query.to_string(),
+                notebook::SearchMode::Text,
+            ))
+        };
+        let paths = |query: &str| {
+            let mut paths: Vec<String> = search(query)
+                .expect("search query should parse")
+                .into_iter()
+                .map(|result| result.rel_path)
+                .collect();
+            paths.sort();
+            paths
+        };
+
+        assert_eq!(paths("label:work"), ["projects/alpha", "projects/beta"]);
+        assert_eq!(paths("label:work -label:urgent"), ["projects/beta"]);
+        assert_eq!(paths("label:wor"), Vec::<String>::new());
+        assert_eq!(paths("path:Projects/"), ["projects/alpha", "projects/beta"]);
+        assert_eq!(
+            paths("updated:>=2026-01-01"),
+            ["journal/today", "projects/alpha"]
+        );
+        assert_eq!(paths("updated:<2026-01-01"), ["projects/beta"]);
+        assert_eq!(paths("\"release notes\""), ["projects/alpha"]);
+        assert_eq!(paths("release notes"), ["projects/alpha", "projects/beta"]);
+        assert_eq!(
+            paths("alpha OR personal"),
+            ["journal/today", "projects/alpha"]
+        );
+        assert_eq!(
+            paths("path:projects/ AND NOT (alpha OR urgent)"),
+            ["projects/beta"]
+        );
 
-        fs::write(
-            Path::new(notebook_dir.as_str()).join(".cognate_fail_atomic_rename"),
-            "fail",
-        )
-        .expect("Failed to create atomic-rename failure marker");
+        let label_only = search("label:personal").expect("search query should parse");
+        assert_eq!(label_only[0].snippet, "Label match: personal");
+        let date_only = search("updated:2026-03-01").expect("search query should parse");
+        assert_eq!(date_only[0].snippet, "Last updated: 2026-03-01T09:00:00Z");
 
-        let updated_notes = vec![NoteMetadata {
-            rel_path: "stable/note".to_string(),
-            labels: vec!["v2".to_string()],
-            last_updated: Some("2024-01-02T00:00:00Z".to_string()),
-        }];
-        let save_result = notebook::save_metadata(notebook_dir.as_str(), &updated_notes);
+        for malformed in ["label:", "(release", "release OR", "updated:>2026-13-01"] {
+            let error = search(malformed).expect_err("malformed queries should be rejected");
+            assert_eq!(error.kind(), NotebookErrorKind::Validation, "{malformed}");
+        }
+        notebook::clear_search_index_for_notebook(notebook_dir.as_str());
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
 
-        assert!(
-            save_result.is_err(),
-            "Expected save to fail on simulated rename error"
+    #[test]
+    fn nested_labels_match_parent_queries_and_build_a_counted_tree() {
+        let notebook_dir = TestNotebookDir::new("nested_labels");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(
+            &notebook_dir,
+            &mut notes,
+            &[("alpha", "Alpha."), ("beta", "Beta."), ("gamma", "Gamma.")],
         );
-
-        let primary_after_failure =
-            fs::read_to_string(Path::new(notebook_dir.as_str()).join("metadata.json"))
-                .expect("Failed to read metadata.json after simulated rename failure");
-        assert!(
-            primary_after_failure.contains("\"v1\""),
-            "Atomic save should keep the previous metadata.json when rename fails"
+        for (rel_path, labels) in [
+            ("alpha", vec!["Area/Sub/Topic", "area/other"]),
+            ("beta", vec!["area/sub"]),
+            ("gamma", vec!["areas"]),
+        ] {
+            let note = notes
+                .iter_mut()
+                .find(|note| note.rel_path == rel_path)
+                .expect("note should exist");
+            note.labels = labels.into_iter().map(str::to_string).collect();
+        }
+        let search_snapshot = notes
+            .iter()
+            .map(notebook::SearchNote::from)
+            .collect::<Vec<notebook::SearchNote>>();
+        let paths = |query: &str| {
+            let mut paths: Vec<String> = block_on(notebook::search_notes_with_snapshot(
+                notebook_dir.as_str().to_string(),
+                search_snapshot.clone(),
+                query.to_string(),
+                notebook::SearchMode::Text,
+            ))
+            .expect("search query should parse")
+            .into_iter()
+            .map(|result| result.rel_path)
+            .collect();
+            paths.sort();
+            paths
+        };
+
+        assert_eq!(paths("label:area"), ["alpha", "beta"]);
+        assert_eq!(paths("label:area/sub"), ["alpha", "beta"]);
+        assert_eq!(paths("label:AREA/sub/topic"), ["alpha"]);
+        assert_eq!(paths("label:area -label:area/sub/topic"), ["beta"]);
+
+        let tree = notebook::build_label_tree(&notes);
+        let summary = |nodes: &[notebook::LabelTreeNode]| {
+            nodes
+                .iter()
+                .map(|node| (node.path.clone(), node.note_count))
+                .collect::<Vec<(String, usize)>>()
+        };
+        assert_eq!(
+            summary(&tree),
+            [("Area".to_string(), 2), ("areas".to_string(), 1)]
         );
-        assert!(
-            !primary_after_failure.contains("\"v2\""),
-            "Failed atomic save must not partially apply new metadata"
+        assert_eq!(
+            summary(&tree[0].children),
+            [("area/other".to_string(), 1), ("Area/Sub".to_string(), 2)]
         );
+        assert_eq!(tree[0].children[1].children[0].name, "Topic");
+        notebook::clear_search_index_for_notebook(notebook_dir.as_str());
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
 
-        let backup_after_failure =
-            fs::read_to_string(Path::new(notebook_dir.as_str()).join("metadata.json.bak"))
-                .expect("Failed to read metadata.json.bak after simulated rename failure");
+    #[test]
+    fn fuzzy_search_matches_abbreviated_paths_labels_and_headings() {
+        let notebook_dir = TestNotebookDir::new("search_fuzzy");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(
+            &notebook_dir,
+            &mut notes,
+            &[
+                ("meetings/notes-2026", "Agenda for the year."),
+                (
+                    "misc/todo",
+                    "# Quarterly Planning\nmeeting notes are elsewhere",
+                ),
+                ("recipes/soup", "Tomatoes and basil."),
+            ],
+        );
+        notes
+            .iter_mut()
+            .find(|note| note.rel_path == "recipes/soup")
+            .expect("recipes/soup should exist")
+            .labels
+            .push("Weeknight".to_string());
+        let search_snapshot = notes
+            .iter()
+            .map(notebook::SearchNote::from)
+            .collect::<Vec<notebook::SearchNote>>();
+        let search = |query: &str| {
+            block_on(notebook::search_notes_with_snapshot(
+                notebook_dir.as_str().to_string(),
+                search_snapshot.clone(),
+                query.to_string(),
+                notebook::SearchMode::Fuzzy,
+            ))
+            .expect("fuzzy search should not fail")
+        };
+
+        let abbreviated = search("mtng nts");
+        assert_eq!(abbreviated[0].rel_path, "meetings/notes-2026");
+        assert_eq!(abbreviated[0].snippet, "Path match");
+
+        let typo = search("meetnigs");
+        assert_eq!(typo[0].rel_path, "meetings/notes-2026");
+
+        let heading = search("quartrly planing");
+        assert_eq!(heading.len(), 1);
+        assert_eq!(heading[0].rel_path, "misc/todo");
+        assert_eq!(heading[0].snippet, "Quarterly Planning");
+
+        let label = search("wknight");
+        assert_eq!(label.len(), 1);
+        assert_eq!(label[0].snippet, "Label match: Weeknight");
+
+        assert!(search("zzz").is_empty());
         assert!(
-            backup_after_failure.contains("\"v1\""),
-            "Backup should preserve the last known-good metadata snapshot"
+            search("label:").is_empty(),
+            "fuzzy queries have no syntax to reject"
         );
+        notebook::clear_search_index_for_notebook(notebook_dir.as_str());
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
     }
 
     #[test]
-    fn delete_note_surfaces_failed_rollback_when_rollback_rename_fails() {
-        let notebook_dir = TestNotebookDir::new("delete_rollback_failure_surface");
-        let mut notes: Vec<NoteMetadata> = vec![NoteMetadata {
-            rel_path: "rollback/failure".to_string(),
-            labels: Vec::new(),
-            last_updated: None,
-        }];
-
-        let note_dir = Path::new(notebook_dir.as_str()).join("rollback/failure");
-        fs::create_dir_all(&note_dir).expect("Failed to create rollback target note directory");
-        fs::write(note_dir.join("note.md"), "rollback failure")
-            .expect("Failed to write rollback failure note");
-        fs::create_dir(Path::new(notebook_dir.as_str()).join("metadata.json"))
-            .expect("Failed to create metadata trap directory");
+    fn text_search_reports_every_match_location_in_matching_lines() {
+        let notebook_dir = TestNotebookDir::new("search_match_locations");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(
+            &notebook_dir,
+            &mut notes,
+            &[(
+                "garden",
+                "# Garden\n\n  Tomatoes need sun; tomato plants too.\nNothing else.\nTOMATOES!",
+            )],
+        );
+        let search_snapshot = notes
+            .iter()
+            .map(notebook::SearchNote::from)
+            .collect::<Vec<notebook::SearchNote>>();
+
+        let results = block_on(notebook::search_notes_with_snapshot(
+            notebook_dir.as_str().to_string(),
+            search_snapshot,
+            "tomato".to_string(),
+            notebook::SearchMode::Text,
+        ))
+        .expect("search should succeed");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].snippet, "Tomatoes need sun; tomato plants too.");
+
+        let locations: Vec<(usize, usize, usize)> = results[0]
+            .match_locations()
+            .map(|(line_number, range)| (line_number, range.start, range.end))
+            .collect();
+        assert_eq!(locations, vec![(3, 2, 10), (3, 21, 27), (5, 0, 8)]);
+        let first_line = &results[0].line_matches[0];
+        let highlighted: Vec<&str> = first_line
+            .highlights
+            .iter()
+            .map(|range| &first_line.text[range.start..range.end])
+            .collect();
+        assert_eq!(highlighted, vec!["Tomatoes", "tomato"]);
+        notebook::clear_search_index_for_notebook(notebook_dir.as_str());
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    fn search_paths(
+        notebook_dir: &TestNotebookDir,
+        notes: &[NoteMetadata],
+        query: &str,
+        mode: notebook::SearchMode,
+    ) -> Vec<String> {
+        let search_snapshot = notes
+            .iter()
+            .map(notebook::SearchNote::from)
+            .collect::<Vec<notebook::SearchNote>>();
+        let mut paths: Vec<String> = block_on(notebook::search_notes_with_snapshot(
+            notebook_dir.as_str().to_string(),
+            search_snapshot,
+            query.to_string(),
+            mode,
+        ))
+        .expect("search should succeed")
+        .into_iter()
+        .map(|result| result.rel_path)
+        .collect();
+        paths.sort();
+        paths
+    }
+
+    fn write_note_keeping_modified_time(
+        notebook_dir: &TestNotebookDir,
+        rel_path: &str,
+        content: &str,
+    ) {
+        let note_file_path = Path::new(notebook_dir.as_str())
+            .join(rel_path)
+            .join("note.md");
+        let modified_time = fs::metadata(&note_file_path)
+            .and_then(|metadata| metadata.modified())
+            .expect("Failed to read note modification time");
+        fs::write(&note_file_path, content).expect("Failed to rewrite note");
+        fs::File::options()
+            .write(true)
+            .open(&note_file_path)
+            .and_then(|file| file.set_modified(modified_time))
+            .expect("Failed to restore note modification time");
+    }
+
+    #[test]
+    fn search_index_cache_is_reused_after_restart_and_revalidated_by_modification_time() {
+        let notebook_dir = TestNotebookDir::new("search_index_cache");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(
+            &notebook_dir,
+            &mut notes,
+            &[("alpha", "# Orchard\napple pie"), ("beta", "banana bread")],
+        );
+        let cache_path = Path::new(notebook_dir.as_str()).join(".cognate_search_index.json");
+
+        assert_eq!(
+            search_paths(&notebook_dir, &notes, "apple", notebook::SearchMode::Text),
+            vec!["alpha"]
+        );
+        assert!(
+            cache_path.exists(),
+            "searching should write the index cache"
+        );
+
+        // A restart starts from the cache file. The cache trusts alpha's unchanged modification
+        // time, while beta's newer file is read again.
+        notebook::clear_search_index_for_notebook(notebook_dir.as_str());
+        write_note_keeping_modified_time(&notebook_dir, "alpha", "# Meadow\nnothing else");
+        let beta_file_path = Path::new(notebook_dir.as_str()).join("beta/note.md");
+        fs::write(&beta_file_path, "apple crumble").expect("Failed to rewrite note");
+        fs::File::options()
+            .write(true)
+            .open(&beta_file_path)
+            .and_then(|file| file.set_modified(SystemTime::now() + Duration::from_secs(5)))
+            .expect("Failed to bump note modification time");
+        block_on(notebook::preload_search_index(
+            notebook_dir.as_str().to_string(),
+        ));
+
+        assert_eq!(
+            search_paths(
+                &notebook_dir,
+                &notes,
+                "orchard",
+                notebook::SearchMode::Fuzzy
+            ),
+            vec!["alpha"]
+        );
+        assert_eq!(
+            search_paths(&notebook_dir, &notes, "crumble", notebook::SearchMode::Text),
+            vec!["beta"]
+        );
+        // Reading alpha's text for the match reveals the new content through its hash.
+        assert_eq!(
+            search_paths(&notebook_dir, &notes, "apple", notebook::SearchMode::Text),
+            vec!["beta"]
+        );
+        notebook::clear_search_index_for_notebook(notebook_dir.as_str());
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    #[test]
+    fn corrupt_or_outdated_search_index_cache_is_discarded() {
+        let notebook_dir = TestNotebookDir::new("search_index_cache_invalid");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(&notebook_dir, &mut notes, &[("alpha", "# Orchard")]);
+        let cache_path = Path::new(notebook_dir.as_str()).join(".cognate_search_index.json");
+        let modified_time = fs::metadata(Path::new(notebook_dir.as_str()).join("alpha/note.md"))
+            .and_then(|metadata| metadata.modified())
+            .expect("Failed to read note modification time")
+            .duration_since(UNIX_EPOCH)
+            .expect("System clock error");
+
+        let outdated = format!(
+            r#"{{"version":999,"notes":{{"alpha":{{"modified_time":[{},{}],"content_hash":0,"term_frequencies":{{"ghost":1}},"token_count":1,"headings":["Ghost"]}}}}}}"#,
+            modified_time.as_secs(),
+            modified_time.subsec_nanos()
+        );
+        for cache_contents in ["{not json", outdated.as_str()] {
+            fs::write(&cache_path, cache_contents).expect("Failed to write cache file");
+            notebook::clear_search_index_for_notebook(notebook_dir.as_str());
+
+            assert!(
+                search_paths(&notebook_dir, &notes, "ghost", notebook::SearchMode::Fuzzy)
+                    .is_empty()
+            );
+            assert_eq!(
+                search_paths(
+                    &notebook_dir,
+                    &notes,
+                    "orchard",
+                    notebook::SearchMode::Fuzzy
+                ),
+                vec!["alpha"]
+            );
+            let rewritten = fs::read_to_string(&cache_path).expect("Failed to read cache file");
+            assert!(rewritten.starts_with(r#"{"version":2,"#), "{rewritten}");
+        }
+        notebook::clear_search_index_for_notebook(notebook_dir.as_str());
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    #[test]
+    fn regex_search_lists_matching_lines_and_rejects_invalid_patterns() {
+        let notebook_dir = TestNotebookDir::new("search_regex");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(
+            &notebook_dir,
+            &mut notes,
+            &[
+                (
+                    "work/tickets",
+                    "Fixed BUG-12 today.\nNothing here.\nBUG-7 and BUG-9 remain.",
+                ),
+                ("home/list", "Buy milk."),
+            ],
+        );
+        let search_snapshot = notes
+            .iter()
+            .map(notebook::SearchNote::from)
+            .collect::<Vec<notebook::SearchNote>>();
+        let search = |pattern: &str| {
+            block_on(notebook::search_notes_with_snapshot(
+                notebook_dir.as_str().to_string(),
+                search_snapshot.clone(),
+                pattern.to_string(),
+                notebook::SearchMode::Regex,
+            ))
+        };
+
+        let results = search(r"BUG-\d+").expect("pattern should be valid");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].rel_path, "work/tickets");
+        assert_eq!(results[0].snippet, "1: Fixed BUG-12 today.");
+        let range = |start, end| notebook::SearchMatchRange { start, end };
+        assert_eq!(
+            results[0].line_matches,
+            vec![
+                notebook::SearchLineMatch {
+                    line_number: 1,
+                    text: "Fixed BUG-12 today.".to_string(),
+                    columns: vec![range(6, 12)],
+                    highlights: vec![range(6, 12)],
+                },
+                notebook::SearchLineMatch {
+                    line_number: 3,
+                    text: "BUG-7 and BUG-9 remain.".to_string(),
+                    columns: vec![range(0, 5), range(10, 15)],
+                    highlights: vec![range(0, 5), range(10, 15)],
+                },
+            ]
+        );
+
+        let error = search("(unclosed").expect_err("invalid patterns should be rejected");
+        assert_eq!(error.kind(), NotebookErrorKind::Validation);
+        assert!(
+            !error.to_string().contains('\n'),
+            "errors should fit next to the search box: {error}"
+        );
+        notebook::clear_search_index_for_notebook(notebook_dir.as_str());
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    fn saved_search(name: &str, query: &str, scope: Option<&str>) -> notebook::SavedSearch {
+        notebook::SavedSearch {
+            name: name.to_string(),
+            query: query.to_string(),
+            mode: notebook::SearchMode::Text,
+            scope: scope.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn saved_searches_are_stored_in_metadata_and_survive_note_changes() {
+        let notebook_dir = TestNotebookDir::new("saved_searches_metadata");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(&notebook_dir, &mut notes, &[("projects/alpha/plan", "")]);
+
+        let mut saved_searches = Vec::new();
+        block_on(notebook::save_search(
+            notebook_dir.as_str(),
+            &notes,
+            &mut saved_searches,
+            saved_search(" Alpha releases ", " release ", Some("/projects/alpha/")),
+        ))
+        .expect("Failed to save search");
+        assert_eq!(
+            saved_searches,
+            vec![saved_search(
+                "Alpha releases",
+                "release",
+                Some("projects/alpha")
+            )]
+        );
+
+        for (rejected, expected_kind) in [
+            (
+                saved_search("  ", "release", None),
+                NotebookErrorKind::Validation,
+            ),
+            (
+                saved_search("Empty", " ", None),
+                NotebookErrorKind::Validation,
+            ),
+            (
+                saved_search("Broken", "(release", None),
+                NotebookErrorKind::Validation,
+            ),
+            (
+                saved_search("Outside", "release", Some("../elsewhere")),
+                NotebookErrorKind::Validation,
+            ),
+        ] {
+            let error = block_on(notebook::save_search(
+                notebook_dir.as_str(),
+                &notes,
+                &mut saved_searches,
+                rejected,
+            ))
+            .expect_err("Invalid saved search should be rejected");
+            assert_eq!(error.kind(), expected_kind);
+        }
+        assert_eq!(saved_searches.len(), 1);
+
+        // Saving under an existing name replaces the query.
+        block_on(notebook::save_search(
+            notebook_dir.as_str(),
+            &notes,
+            &mut saved_searches,
+            saved_search("Alpha releases", "label:release", Some("projects/alpha")),
+        ))
+        .expect("Failed to replace saved search");
+        block_on(notebook::save_search(
+            notebook_dir.as_str(),
+            &notes,
+            &mut saved_searches,
+            saved_search("All todos", "todo", None),
+        ))
+        .expect("Failed to save second search");
+
+        // Note operations rewrite metadata.json without dropping the saved searches.
+        block_on(notebook::create_new_note(
+            notebook_dir.as_str(),
+            "inbox",
+            &mut notes,
+        ))
+        .expect("Failed to create note");
+        let loaded = block_on(notebook::load_notes_metadata(
+            notebook_dir.as_str().to_string(),
+        ))
+        .expect("Failed to load metadata");
+        assert_eq!(
+            loaded.saved_searches,
+            vec![
+                saved_search("All todos", "todo", None),
+                saved_search("Alpha releases", "label:release", Some("projects/alpha")),
+            ]
+        );
+
+        let mut saved_searches = loaded.saved_searches;
+        block_on(notebook::delete_saved_search(
+            notebook_dir.as_str(),
+            &notes,
+            &mut saved_searches,
+            "All todos",
+        ))
+        .expect("Failed to delete saved search");
+        let reloaded = block_on(notebook::load_notes_metadata(
+            notebook_dir.as_str().to_string(),
+        ))
+        .expect("Failed to load metadata");
+        assert_eq!(
+            reloaded.saved_searches,
+            vec![saved_search(
+                "Alpha releases",
+                "label:release",
+                Some("projects/alpha")
+            )]
+        );
+        notebook::clear_search_index_for_notebook(notebook_dir.as_str());
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    #[test]
+    fn saved_searches_evaluate_within_their_folder_and_follow_note_changes() {
+        let notebook_dir = TestNotebookDir::new("saved_searches_evaluate");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(
+            &notebook_dir,
+            &mut notes,
+            &[
+                ("projects/alpha/plan", "release checklist"),
+                ("projects/alpha/notes/retro", "after the release"),
+                ("projects/alphabet", "release of letters"),
+                ("projects/beta/plan", "nothing yet"),
+            ],
+        );
+        let evaluate = |notes: &[NoteMetadata]| {
+            block_on(notebook::evaluate_saved_searches(
+                notebook_dir.as_str().to_string(),
+                notes.iter().map(notebook::SearchNote::from).collect(),
+                vec![
+                    saved_search("Alpha releases", "release", Some("projects/alpha")),
+                    saved_search("Broken", "(release", None),
+                ],
+            ))
+        };
+
+        let matches = evaluate(&notes);
+        assert_eq!(matches[0].name, "Alpha releases");
+        assert_eq!(
+            matches[0].note_paths,
+            Ok(vec![
+                "projects/alpha/notes/retro".to_string(),
+                "projects/alpha/plan".to_string(),
+            ])
+        );
+        assert_eq!(
+            matches[1].note_paths.as_ref().map_err(|error| error.kind()),
+            Err(NotebookErrorKind::Validation)
+        );
+
+        notebook::save_note_content_sync(notebook_dir.as_str(), "projects/alpha/plan", "draft")
+            .expect("Failed to save note content");
+        block_on(notebook::move_note(
+            notebook_dir.as_str(),
+            "projects/beta/plan",
+            "projects/alpha/beta-plan",
+            &mut notes,
+        ))
+        .expect("Failed to move note");
+        notebook::save_note_content_sync(
+            notebook_dir.as_str(),
+            "projects/alpha/beta-plan",
+            "release soon",
+        )
+        .expect("Failed to save note content");
+
+        assert_eq!(
+            evaluate(&notes)[0].note_paths,
+            Ok(vec![
+                "projects/alpha/beta-plan".to_string(),
+                "projects/alpha/notes/retro".to_string(),
+            ])
+        );
+        assert!(notebook::is_in_search_scope(
+            "projects/alpha/plan",
+            Some("projects/alpha")
+        ));
+        assert!(notebook::is_in_search_scope(
+            "projects/alpha",
+            Some("projects/alpha")
+        ));
+        assert!(!notebook::is_in_search_scope(
+            "projects/alphabet",
+            Some("projects/alpha")
+        ));
+        assert!(notebook::is_in_search_scope("projects/alphabet", None));
+        notebook::clear_search_index_for_notebook(notebook_dir.as_str());
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    #[test]
+    fn saved_search_scope_includes_the_note_at_the_scope_path() {
+        let notebook_dir = TestNotebookDir::new("saved_search_scope_note");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(
+            &notebook_dir,
+            &mut notes,
+            &[
+                ("projects/alpha", "release overview"),
+                ("projects/alpha/plan", "release checklist"),
+                ("projects/alphabet", "release of letters"),
+            ],
+        );
+
+        let matches = block_on(notebook::evaluate_saved_searches(
+            notebook_dir.as_str().to_string(),
+            notes.iter().map(notebook::SearchNote::from).collect(),
+            vec![saved_search(
+                "Alpha releases",
+                "release",
+                Some("projects/alpha"),
+            )],
+        ));
+        assert_eq!(
+            matches[0].note_paths,
+            Ok(vec![
+                "projects/alpha".to_string(),
+                "projects/alpha/plan".to_string(),
+            ])
+        );
+        notebook::clear_search_index_for_notebook(notebook_dir.as_str());
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    #[test]
+    fn move_folder_updates_nested_note_paths() {
+        let notebook_dir = TestNotebookDir::new("move_folder");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+
+        block_on(notebook::create_new_note(
+            notebook_dir.as_str(),
+            "folder/note_a",
+            &mut notes,
+        ))
+        .expect("Failed to create folder/note_a");
+        block_on(notebook::create_new_note(
+            notebook_dir.as_str(),
+            "folder/sub/note_b",
+            &mut notes,
+        ))
+        .expect("Failed to create folder/sub/note_b");
+
+        let moved_to = block_on(notebook::move_note(
+            notebook_dir.as_str(),
+            "folder",
+            "renamed",
+            &mut notes,
+        ))
+        .expect("move_note for folder should succeed");
+
+        assert_eq!(moved_to.rel_path, "renamed");
+        assert_note_md_not_exists(&notebook_dir, "folder/note_a");
+        assert_note_md_not_exists(&notebook_dir, "folder/sub/note_b");
+        assert_note_md_exists(&notebook_dir, "renamed/note_a");
+        assert_note_md_exists(&notebook_dir, "renamed/sub/note_b");
+
+        let mut rel_paths: Vec<String> = notes.iter().map(|n| n.rel_path.clone()).collect();
+        rel_paths.sort();
+        assert_eq!(
+            rel_paths,
+            vec![
+                "renamed/note_a".to_string(),
+                "renamed/sub/note_b".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn load_notes_metadata_recovers_from_backup_when_primary_is_corrupted() {
+        let notebook_dir = TestNotebookDir::new("metadata_recovery_from_backup");
+
         fs::write(
-            Path::new(notebook_dir.as_str()).join(".cognate_fail_delete_rollback"),
+            Path::new(notebook_dir.as_str()).join("metadata.json.bak"),
+            r#"{
+  "notes": [
+    {
+      "rel_path": "recovered/note",
+      "labels": ["restored"],
+      "last_updated": "2024-01-01T00:00:00Z"
+    }
+  ]
+}"#,
+        )
+        .expect("Failed to write metadata backup fixture");
+        fs::write(
+            Path::new(notebook_dir.as_str()).join("metadata.json"),
+            "{ corrupt_primary_json ",
+        )
+        .expect("Failed to write corrupted metadata.json fixture");
+
+        let load_result = block_on(notebook::load_notes_metadata(
+            notebook_dir.as_str().to_string(),
+        ))
+        .expect("Expected metadata load to recover from backup");
+
+        assert_eq!(load_result.notes.len(), 1);
+        assert_eq!(load_result.notes[0].rel_path, "recovered/note");
+        assert!(
+            load_result.warning.is_some(),
+            "Recovery path should surface a warning"
+        );
+
+        let restored_primary =
+            fs::read_to_string(Path::new(notebook_dir.as_str()).join("metadata.json"))
+                .expect("Expected metadata.json to be restored from backup");
+        assert!(
+            restored_primary.contains("recovered/note"),
+            "Primary metadata should be restored from backup contents"
+        );
+    }
+
+    #[test]
+    fn save_metadata_keeps_last_known_good_copy_and_preserves_primary_when_atomic_rename_fails() {
+        let notebook_dir = TestNotebookDir::new("metadata_backup_and_atomic_failure");
+        let initial_notes = vec![NoteMetadata {
+            rel_path: "stable/note".to_string(),
+            labels: vec!["v1".to_string()],
+            last_updated: Some("2024-01-01T00:00:00Z".to_string()),
+            ..NoteMetadata::default()
+        }];
+        notebook::save_metadata(notebook_dir.as_str(), &initial_notes)
+            .expect("Failed to save initial metadata");
+
+        fs::write(
+            Path::new(notebook_dir.as_str()).join(".cognate_fail_atomic_rename"),
             "fail",
         )
-        .expect("Failed to create delete-rollback failure marker");
+        .expect("Failed to create atomic-rename failure marker");
+
+        let updated_notes = vec![NoteMetadata {
+            rel_path: "stable/note".to_string(),
+            labels: vec!["v2".to_string()],
+            last_updated: Some("2024-01-02T00:00:00Z".to_string()),
+            ..NoteMetadata::default()
+        }];
+        let save_result = notebook::save_metadata(notebook_dir.as_str(), &updated_notes);
+
+        assert!(
+            save_result.is_err(),
+            "Expected save to fail on simulated rename error"
+        );
+
+        let primary_after_failure =
+            fs::read_to_string(Path::new(notebook_dir.as_str()).join("metadata.json"))
+                .expect("Failed to read metadata.json after simulated rename failure");
+        assert!(
+            primary_after_failure.contains("\"v1\""),
+            "Atomic save should keep the previous metadata.json when rename fails"
+        );
+        assert!(
+            !primary_after_failure.contains("\"v2\""),
+            "Failed atomic save must not partially apply new metadata"
+        );
+
+        let backup_after_failure =
+            fs::read_to_string(Path::new(notebook_dir.as_str()).join("metadata.json.bak"))
+                .expect("Failed to read metadata.json.bak after simulated rename failure");
+        assert!(
+            backup_after_failure.contains("\"v1\""),
+            "Backup should preserve the last known-good metadata snapshot"
+        );
+    }
+
+    #[test]
+    fn delete_note_restores_the_note_when_moving_it_into_the_trash_fails() {
+        let notebook_dir = TestNotebookDir::new("delete_trash_move_failure");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(&notebook_dir, &mut notes, &[("keep/me", "still here")]);
+        fs::write(
+            Path::new(notebook_dir.as_str()).join(".cognate_fail_trash_move"),
+            "fail",
+        )
+        .expect("Failed to create trash-move failure marker");
+
+        let error = block_on(notebook::delete_note(
+            notebook_dir.as_str(),
+            "keep/me",
+            &mut notes,
+        ))
+        .expect_err("Expected delete to fail");
+
+        assert_eq!(error.kind(), NotebookErrorKind::Storage);
+        assert_eq!(read_note_md(&notebook_dir, "keep/me"), "still here");
+        assert_eq!(notes.len(), 1);
+        assert_eq!(
+            load_notes_or_panic(&notebook_dir)
+                .iter()
+                .map(|note| note.rel_path.as_str())
+                .collect::<Vec<_>>(),
+            vec!["keep/me"]
+        );
+        let staged_entries = fs::read_dir(notebook_dir.as_str())
+            .expect("Failed to scan notebook directory")
+            .filter_map(Result::ok)
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with(".cognate_txn_delete_")
+            })
+            .count();
+        assert_eq!(staged_entries, 0);
+        assert!(
+            block_on(notebook::load_trash(notebook_dir.as_str().to_string(), 30))
+                .expect("Failed to load trash")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn delete_note_surfaces_failed_rollback_when_rollback_rename_fails() {
+        let notebook_dir = TestNotebookDir::new("delete_rollback_failure_surface");
+        let mut notes: Vec<NoteMetadata> = vec![NoteMetadata {
+            rel_path: "rollback/failure".to_string(),
+            labels: Vec::new(),
+            last_updated: None,
+            ..NoteMetadata::default()
+        }];
+
+        let note_dir = Path::new(notebook_dir.as_str()).join("rollback/failure");
+        fs::create_dir_all(&note_dir).expect("Failed to create rollback target note directory");
+        fs::write(note_dir.join("note.md"), "rollback failure")
+            .expect("Failed to write rollback failure note");
+        fs::create_dir(Path::new(notebook_dir.as_str()).join("metadata.json"))
+            .expect("Failed to create metadata trap directory");
+        fs::write(
+            Path::new(notebook_dir.as_str()).join(".cognate_fail_delete_rollback"),
+            "fail",
+        )
+        .expect("Failed to create delete-rollback failure marker");
+
+        let delete_result = block_on(notebook::delete_note(
+            notebook_dir.as_str(),
+            "rollback/failure",
+            &mut notes,
+        ));
+
+        assert!(delete_result.is_err());
+        let error = delete_result.expect_err("Expected delete to fail");
+        assert_eq!(error.kind(), NotebookErrorKind::Recovery);
+        assert!(
+            error
+                .to_string()
+                .contains("Rollback failed while restoring filesystem state"),
+            "Expected explicit rollback failure message, got: {}",
+            error
+        );
+
+        let staged_entries: Vec<PathBuf> = fs::read_dir(notebook_dir.as_str())
+            .expect("Failed to scan notebook directory")
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .map(|name| name.starts_with(".cognate_txn_delete_"))
+                    .unwrap_or(false)
+            })
+            .collect();
+        assert!(
+            !staged_entries.is_empty(),
+            "Expected failed rollback to leave a staged delete entry for manual recovery"
+        );
+    }
+
+    #[test]
+    fn move_note_surfaces_failed_rollback_when_rollback_rename_fails() {
+        let notebook_dir = TestNotebookDir::new("move_rollback_failure_surface");
+        let mut notes: Vec<NoteMetadata> = vec![NoteMetadata {
+            rel_path: "rollback/source".to_string(),
+            labels: Vec::new(),
+            last_updated: None,
+            ..NoteMetadata::default()
+        }];
+
+        let source_dir = Path::new(notebook_dir.as_str()).join("rollback/source");
+        fs::create_dir_all(&source_dir).expect("Failed to create source note directory");
+        fs::write(source_dir.join("note.md"), "rollback failure")
+            .expect("Failed to write source note file");
+        fs::create_dir(Path::new(notebook_dir.as_str()).join("metadata.json"))
+            .expect("Failed to create metadata trap directory");
+        fs::write(
+            Path::new(notebook_dir.as_str()).join(".cognate_fail_move_rollback"),
+            "fail",
+        )
+        .expect("Failed to create move-rollback failure marker");
+
+        let move_result = block_on(notebook::move_note(
+            notebook_dir.as_str(),
+            "rollback/source",
+            "rollback/destination",
+            &mut notes,
+        ));
+
+        assert!(move_result.is_err());
+        let error = move_result.expect_err("Expected move to fail");
+        assert_eq!(error.kind(), NotebookErrorKind::Recovery);
+        assert!(
+            error
+                .to_string()
+                .contains("Rollback failed while restoring filesystem state"),
+            "Expected explicit rollback failure message, got: {}",
+            error
+        );
+
+        assert!(
+            Path::new(notebook_dir.as_str())
+                .join("rollback/source")
+                .join("note.md")
+                .exists()
+                || Path::new(notebook_dir.as_str())
+                    .join("rollback/destination")
+                    .join("note.md")
+                    .exists(),
+            "Failed move rollback should leave recoverable note data on disk"
+        );
+    }
+
+    #[test]
+    fn load_backlinks_reports_notes_linking_to_target() {
+        let notebook_dir = TestNotebookDir::new("backlinks");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+
+        for rel_path in ["work/todo", "ideas/brainstorm", "journal/today"] {
+            block_on(notebook::create_new_note(
+                notebook_dir.as_str(),
+                rel_path,
+                &mut notes,
+            ))
+            .expect("Failed to create note");
+        }
+
+        fs::write(
+            Path::new(notebook_dir.as_str())
+                .join("journal/today")
+                .join("note.md"),
+            "Plan from [[ideas/brainstorm|brainstorm]].",
+        )
+        .expect("Failed to write journal note content");
+        notebook::save_note_content_sync(
+            notebook_dir.as_str(),
+            "work/todo",
+            "Follow up on [[brainstorm]]\nand [[missing/note]]",
+        )
+        .expect("Failed to save todo note content");
+
+        let note_paths: Vec<String> = notes.iter().map(|note| note.rel_path.clone()).collect();
+        let backlinks = block_on(notebook::load_backlinks(
+            notebook_dir.as_str().to_string(),
+            note_paths.clone(),
+            "ideas/brainstorm".to_string(),
+        ));
+
+        assert_eq!(
+            backlinks
+                .iter()
+                .map(|backlink| backlink.rel_path.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["journal/today", "work/todo"]
+        );
+        assert_eq!(backlinks[1].context, "Follow up on [[brainstorm]]");
+
+        notebook::save_note_content_sync(notebook_dir.as_str(), "work/todo", "No links anymore")
+            .expect("Failed to update todo note content");
+        let updated_backlinks = block_on(notebook::load_backlinks(
+            notebook_dir.as_str().to_string(),
+            note_paths,
+            "ideas/brainstorm".to_string(),
+        ));
+        assert_eq!(updated_backlinks.len(), 1);
+        assert_eq!(updated_backlinks[0].rel_path, "journal/today");
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    #[test]
+    fn load_backlinks_follows_moved_source_notes() {
+        let notebook_dir = TestNotebookDir::new("backlinks_move");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+
+        for rel_path in ["target", "source/note"] {
+            block_on(notebook::create_new_note(
+                notebook_dir.as_str(),
+                rel_path,
+                &mut notes,
+            ))
+            .expect("Failed to create note");
+        }
+        notebook::save_note_content_sync(notebook_dir.as_str(), "source/note", "[[target]]")
+            .expect("Failed to save source note content");
+
+        block_on(notebook::move_note(
+            notebook_dir.as_str(),
+            "source",
+            "moved",
+            &mut notes,
+        ))
+        .expect("Failed to move source folder");
+
+        let backlinks = block_on(notebook::load_backlinks(
+            notebook_dir.as_str().to_string(),
+            notes.iter().map(|note| note.rel_path.clone()).collect(),
+            "target".to_string(),
+        ));
+        assert_eq!(backlinks.len(), 1);
+        assert_eq!(backlinks[0].rel_path, "moved/note");
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    fn read_note_md(notebook: &TestNotebookDir, rel_path: &str) -> String {
+        fs::read_to_string(Path::new(notebook.as_str()).join(rel_path).join("note.md"))
+            .expect("Expected note file to be readable")
+    }
+
+    fn create_notes_with_content(
+        notebook: &TestNotebookDir,
+        notes: &mut Vec<NoteMetadata>,
+        seeds: &[(&str, &str)],
+    ) {
+        for (rel_path, content) in seeds {
+            block_on(notebook::create_new_note(
+                notebook.as_str(),
+                rel_path,
+                notes,
+            ))
+            .expect("Failed to create note");
+            notebook::save_note_content_sync(notebook.as_str(), rel_path, content)
+                .expect("Failed to save note content");
+        }
+    }
+
+    #[test]
+    fn move_note_rewrites_inbound_and_outbound_links() {
+        let notebook_dir = TestNotebookDir::new("move_rewrites_links");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(
+            &notebook_dir,
+            &mut notes,
+            &[
+                ("docs/target", "Back to [index](../../index/note.md)"),
+                (
+                    "index",
+                    "See [[docs/target#Intro|the target]] and [doc](../docs/target/note.md).\n\
+                     ![diagram](../docs/target/images/chart.png)\n\
+                     `[[docs/target]]` stays literal.",
+                ),
+            ],
+        );
+
+        let moved = block_on(notebook::move_note(
+            notebook_dir.as_str(),
+            "docs/target",
+            "archive/2024/target",
+            &mut notes,
+        ))
+        .expect("move_note should succeed");
+
+        let mut rewritten_paths: Vec<&str> = moved
+            .rewritten_notes
+            .iter()
+            .map(|note| note.rel_path.as_str())
+            .collect();
+        rewritten_paths.sort_unstable();
+        assert_eq!(rewritten_paths, vec!["archive/2024/target", "index"]);
+        for rewritten in &moved.rewritten_notes {
+            assert_eq!(
+                read_note_md(&notebook_dir, &rewritten.rel_path),
+                rewritten.content
+            );
+            // The watcher takes the rewrite for Cognate's own write.
+            assert!(notebook::is_note_content_persisted(
+                notebook_dir.as_str(),
+                &rewritten.rel_path,
+                &rewritten.content
+            ));
+        }
+        assert_eq!(
+            read_note_md(&notebook_dir, "index"),
+            "See [[archive/2024/target#Intro|the target]] and \
+             [doc](../archive/2024/target/note.md).\n\
+             ![diagram](../archive/2024/target/images/chart.png)\n\
+             `[[docs/target]]` stays literal."
+        );
+        assert_eq!(
+            read_note_md(&notebook_dir, "archive/2024/target"),
+            "Back to [index](../../../index/note.md)"
+        );
+
+        let backlinks = block_on(notebook::load_backlinks(
+            notebook_dir.as_str().to_string(),
+            notes.iter().map(|note| note.rel_path.clone()).collect(),
+            "archive/2024/target".to_string(),
+        ));
+        assert_eq!(backlinks.len(), 1);
+        assert_eq!(backlinks[0].rel_path, "index");
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    #[test]
+    fn move_folder_rewrites_links_but_keeps_links_inside_the_folder() {
+        let notebook_dir = TestNotebookDir::new("move_folder_rewrites_links");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(
+            &notebook_dir,
+            &mut notes,
+            &[
+                ("projects/alpha", "Sibling: [beta](../beta) and [[beta]]"),
+                ("projects/beta", "plain"),
+                ("home", "[Alpha](/projects/alpha) and [[projects/beta]]"),
+            ],
+        );
+
+        block_on(notebook::move_note(
+            notebook_dir.as_str(),
+            "projects",
+            "work projects",
+            &mut notes,
+        ))
+        .expect("move_note for folder should succeed");
+
+        assert_eq!(
+            read_note_md(&notebook_dir, "work projects/alpha"),
+            "Sibling: [beta](../beta) and [[beta]]"
+        );
+        assert_eq!(
+            read_note_md(&notebook_dir, "home"),
+            "[Alpha](/work%20projects/alpha) and [[work projects/beta]]"
+        );
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    #[test]
+    fn move_note_rolls_back_move_and_rewrites_when_link_rewrite_fails() {
+        let notebook_dir = TestNotebookDir::new("move_rewrite_rollback");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(
+            &notebook_dir,
+            &mut notes,
+            &[
+                ("a_first", "[t](/target)"),
+                ("target", "plain"),
+                ("z_last", "[t](/target)"),
+            ],
+        );
+        fs::write(
+            Path::new(notebook_dir.as_str())
+                .join("z_last")
+                .join(".cognate_fail_atomic_rename"),
+            "simulate rename failure",
+        )
+        .expect("Failed to create rename-failure marker");
+
+        let result = block_on(notebook::move_note(
+            notebook_dir.as_str(),
+            "target",
+            "moved/target",
+            &mut notes,
+        ));
+
+        assert!(result.is_err());
+        assert!(notes.iter().any(|note| note.rel_path == "target"));
+        assert_note_md_exists(&notebook_dir, "target");
+        assert_note_md_not_exists(&notebook_dir, "moved/target");
+        assert_eq!(read_note_md(&notebook_dir, "a_first"), "[t](/target)");
+        assert_eq!(read_note_md(&notebook_dir, "z_last"), "[t](/target)");
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    fn trash_entry_dir(notebook: &TestNotebookDir, entry_id: &str) -> PathBuf {
+        Path::new(notebook.as_str())
+            .join(".cognate_trash")
+            .join(entry_id)
+    }
+
+    #[test]
+    fn delete_note_moves_note_and_labels_into_trash() {
+        let notebook_dir = TestNotebookDir::new("delete_moves_to_trash");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(
+            &notebook_dir,
+            &mut notes,
+            &[("projects/alpha", "alpha body")],
+        );
+        notes[0].labels = vec!["work".to_string()];
+        notebook::save_metadata(notebook_dir.as_str(), &notes).expect("Failed to save labels");
+
+        block_on(notebook::delete_note(
+            notebook_dir.as_str(),
+            "projects/alpha",
+            &mut notes,
+        ))
+        .expect("delete_note should succeed");
+
+        assert!(notes.is_empty());
+        assert_note_md_not_exists(&notebook_dir, "projects/alpha");
+
+        let entries = block_on(notebook::load_trash(notebook_dir.as_str().to_string(), 30))
+            .expect("Expected trash to load");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].rel_path, "projects/alpha");
+        assert_eq!(entries[0].labels, vec!["work".to_string()]);
+        let trashed_note = trash_entry_dir(&notebook_dir, &entries[0].id).join("note/note.md");
+        assert_eq!(
+            fs::read_to_string(trashed_note).expect("Expected trashed note file"),
+            "alpha body"
+        );
+    }
+
+    #[test]
+    fn restore_trashed_note_returns_note_to_original_path() {
+        let notebook_dir = TestNotebookDir::new("restore_from_trash");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(&notebook_dir, &mut notes, &[("alpha", "alpha body")]);
+        notes[0].labels = vec!["keep".to_string()];
+        notebook::save_metadata(notebook_dir.as_str(), &notes).expect("Failed to save labels");
+        block_on(notebook::delete_note(
+            notebook_dir.as_str(),
+            "alpha",
+            &mut notes,
+        ))
+        .expect("delete_note should succeed");
+        let entries = block_on(notebook::load_trash(notebook_dir.as_str().to_string(), 30))
+            .expect("Expected trash to load");
+
+        let restored = block_on(notebook::restore_trashed_note(
+            notebook_dir.as_str(),
+            &entries[0].id,
+            "alpha",
+            &mut notes,
+        ))
+        .expect("restore should succeed");
+
+        assert_eq!(restored.rel_path, "alpha");
+        assert_eq!(restored.labels, vec!["keep".to_string()]);
+        assert_eq!(read_note_md(&notebook_dir, "alpha"), "alpha body");
+        let loaded = load_notes_or_panic(&notebook_dir);
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].labels, vec!["keep".to_string()]);
+        assert!(!trash_entry_dir(&notebook_dir, &entries[0].id).exists());
+        assert!(
+            block_on(notebook::load_trash(notebook_dir.as_str().to_string(), 30))
+                .expect("Expected trash to load")
+                .is_empty()
+        );
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    #[test]
+    fn restore_trashed_note_rejects_occupied_path_and_suggests_alternative() {
+        let notebook_dir = TestNotebookDir::new("restore_conflict");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(&notebook_dir, &mut notes, &[("alpha", "old body")]);
+        block_on(notebook::delete_note(
+            notebook_dir.as_str(),
+            "alpha",
+            &mut notes,
+        ))
+        .expect("delete_note should succeed");
+        create_notes_with_content(&notebook_dir, &mut notes, &[("alpha", "new body")]);
+        let entries = block_on(notebook::load_trash(notebook_dir.as_str().to_string(), 30))
+            .expect("Expected trash to load");
+
+        let conflict = block_on(notebook::restore_trashed_note(
+            notebook_dir.as_str(),
+            &entries[0].id,
+            "alpha",
+            &mut notes,
+        ))
+        .expect_err("restore over an existing note should fail");
+        assert_eq!(conflict.kind(), NotebookErrorKind::Validation);
+
+        let suggested = notebook::available_restore_path(notebook_dir.as_str(), "alpha", &notes);
+        assert_eq!(suggested, "alpha (restored)");
+        block_on(notebook::restore_trashed_note(
+            notebook_dir.as_str(),
+            &entries[0].id,
+            &suggested,
+            &mut notes,
+        ))
+        .expect("restore under the suggested path should succeed");
+
+        assert_eq!(read_note_md(&notebook_dir, "alpha"), "new body");
+        assert_eq!(read_note_md(&notebook_dir, "alpha (restored)"), "old body");
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    #[test]
+    fn purge_trashed_note_and_retention_remove_entries_permanently() {
+        let notebook_dir = TestNotebookDir::new("purge_trash");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(
+            &notebook_dir,
+            &mut notes,
+            &[("alpha", "alpha body"), ("beta", "beta body")],
+        );
+        block_on(notebook::delete_note(
+            notebook_dir.as_str(),
+            "alpha",
+            &mut notes,
+        ))
+        .expect("delete alpha should succeed");
+        block_on(notebook::delete_note(
+            notebook_dir.as_str(),
+            "beta",
+            &mut notes,
+        ))
+        .expect("delete beta should succeed");
+        let entries = block_on(notebook::load_trash(notebook_dir.as_str().to_string(), 30))
+            .expect("Expected trash to load");
+        assert_eq!(entries.len(), 2);
+        let alpha_id = entries
+            .iter()
+            .find(|entry| entry.rel_path == "alpha")
+            .map(|entry| entry.id.clone())
+            .expect("Expected alpha in trash");
+        let beta_id = entries
+            .iter()
+            .find(|entry| entry.rel_path == "beta")
+            .map(|entry| entry.id.clone())
+            .expect("Expected beta in trash");
+
+        block_on(notebook::purge_trashed_note(
+            notebook_dir.as_str(),
+            &alpha_id,
+        ))
+        .expect("purge should succeed");
+        assert!(!trash_entry_dir(&notebook_dir, &alpha_id).exists());
+
+        let manifest_path = trash_entry_dir(&notebook_dir, &beta_id).join("entry.json");
+        let manifest = fs::read_to_string(&manifest_path).expect("Expected trash manifest");
+        let mut manifest: serde_json::Value =
+            serde_json::from_str(&manifest).expect("Expected manifest JSON");
+        manifest["deleted_at"] = serde_json::Value::from("2000-01-01T00:00:00Z");
+        fs::write(&manifest_path, manifest.to_string()).expect("Failed to age trash entry");
+
+        let kept = block_on(notebook::load_trash(notebook_dir.as_str().to_string(), 0))
+            .expect("Expected trash to load");
+        assert_eq!(kept.len(), 1, "A retention of 0 keeps entries forever");
+
+        let remaining = block_on(notebook::load_trash(notebook_dir.as_str().to_string(), 30))
+            .expect("Expected trash to load");
+        assert!(remaining.is_empty());
+        assert!(!trash_entry_dir(&notebook_dir, &beta_id).exists());
+        assert!(
+            block_on(notebook::purge_trashed_note(
+                notebook_dir.as_str(),
+                "../escape"
+            ))
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn save_note_content_records_replaced_content_once_per_snapshot_interval() {
+        let notebook_dir = TestNotebookDir::new("history_snapshots");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(&notebook_dir, &mut notes, &[("alpha", "first draft")]);
+
+        notebook::save_note_content_sync(notebook_dir.as_str(), "alpha", "second draft")
+            .expect("Failed to save second draft");
+        notebook::save_note_content_sync(notebook_dir.as_str(), "alpha", "third draft")
+            .expect("Failed to save third draft");
+
+        let versions = block_on(notebook::load_note_history(
+            notebook_dir.as_str().to_string(),
+            "alpha".to_string(),
+        ))
+        .expect("Expected history to load");
+        assert_eq!(versions.len(), 1, "Snapshots should be throttled");
+        let content = block_on(notebook::load_note_version(
+            notebook_dir.as_str().to_string(),
+            "alpha".to_string(),
+            versions[0].id.clone(),
+        ))
+        .expect("Expected version content");
+        assert_eq!(content, "first draft");
+        assert_eq!(read_note_md(&notebook_dir, "alpha"), "third draft");
+
+        block_on(notebook::move_note(
+            notebook_dir.as_str(),
+            "alpha",
+            "archive/alpha",
+            &mut notes,
+        ))
+        .expect("move should succeed");
+        let moved_versions = block_on(notebook::load_note_history(
+            notebook_dir.as_str().to_string(),
+            "archive/alpha".to_string(),
+        ))
+        .expect("Expected history to follow the note");
+        assert_eq!(moved_versions, versions);
+
+        assert!(
+            block_on(notebook::load_note_version(
+                notebook_dir.as_str().to_string(),
+                "archive/alpha".to_string(),
+                "../../metadata.json".to_string(),
+            ))
+            .is_err()
+        );
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    #[test]
+    fn diff_lines_marks_added_removed_and_unchanged_lines() {
+        let diff = notebook::diff_lines("a\nb\nc\nd", "a\nc\nx\nd");
+        let rendered: Vec<(LineDiffKind, &str)> = diff
+            .iter()
+            .map(|line| (line.kind, line.text.as_str()))
+            .collect();
+
+        assert_eq!(
+            rendered,
+            vec![
+                (LineDiffKind::Unchanged, "a"),
+                (LineDiffKind::Removed, "b"),
+                (LineDiffKind::Unchanged, "c"),
+                (LineDiffKind::Added, "x"),
+                (LineDiffKind::Unchanged, "d"),
+            ]
+        );
+        assert!(
+            notebook::diff_lines("same", "same")
+                .iter()
+                .all(|line| line.kind == LineDiffKind::Unchanged)
+        );
+    }
+
+    #[test]
+    fn is_note_content_persisted_tracks_saved_and_loaded_content() {
+        let notebook_dir = TestNotebookDir::new("persisted_content");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(&notebook_dir, &mut notes, &[("alpha", "saved text")]);
+
+        assert!(notebook::is_note_content_persisted(
+            notebook_dir.as_str(),
+            "alpha",
+            "saved text"
+        ));
+        assert!(!notebook::is_note_content_persisted(
+            notebook_dir.as_str(),
+            "alpha",
+            "unsaved text"
+        ));
+
+        fs::write(
+            Path::new(notebook_dir.as_str()).join("alpha/note.md"),
+            "external text",
+        )
+        .expect("Failed to simulate external edit");
+        assert_eq!(
+            notebook::load_note_content_sync(notebook_dir.as_str(), "alpha").content,
+            "external text"
+        );
+        assert!(notebook::is_note_content_persisted(
+            notebook_dir.as_str(),
+            "alpha",
+            "external text"
+        ));
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    #[test]
+    fn checked_save_refuses_to_overwrite_external_edits() {
+        let notebook_dir = TestNotebookDir::new("checked_save_conflict");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(&notebook_dir, &mut notes, &[("alpha", "loaded text")]);
+        let base = notebook::load_note_content_sync(notebook_dir.as_str(), "alpha");
+
+        // Overlapping saves of one buffer share a base and must not conflict with each other.
+        let first = notebook::save_note_content_checked_sync(
+            notebook_dir.as_str(),
+            "alpha",
+            "first edit",
+            Some(&base),
+        )
+        .expect("First save should succeed");
+        assert!(matches!(first, NoteSaveOutcome::Saved(_)));
+        let second = notebook::save_note_content_checked_sync(
+            notebook_dir.as_str(),
+            "alpha",
+            "second edit",
+            Some(&base),
+        )
+        .expect("Second save should succeed");
+        let NoteSaveOutcome::Saved(saved) = second else {
+            panic!("Expected overlapping save to succeed, got {:?}", second);
+        };
+        assert_eq!(saved.content, "second edit");
+
+        let note_file_path = Path::new(notebook_dir.as_str()).join("alpha/note.md");
+        fs::write(&note_file_path, "external text").expect("Failed to simulate external edit");
+        // Coarse filesystem clocks can give both writes the same modification time.
+        fs::File::options()
+            .write(true)
+            .open(&note_file_path)
+            .and_then(|file| file.set_modified(SystemTime::now() + Duration::from_secs(5)))
+            .expect("Failed to bump note modification time");
+
+        let outcome = notebook::save_note_content_checked_sync(
+            notebook_dir.as_str(),
+            "alpha",
+            "third edit",
+            Some(&saved),
+        )
+        .expect("Conflicting save should not fail");
+        let NoteSaveOutcome::Conflict(theirs) = outcome else {
+            panic!("Expected a conflict, got {:?}", outcome);
+        };
+        assert_eq!(theirs.content, "external text");
+        assert_eq!(read_note_md(&notebook_dir, "alpha"), "external text");
+
+        let resolved = notebook::save_note_content_checked_sync(
+            notebook_dir.as_str(),
+            "alpha",
+            "resolved text",
+            Some(&theirs),
+        )
+        .expect("Save against the new disk state should succeed");
+        assert!(matches!(resolved, NoteSaveOutcome::Saved(_)));
+        assert_eq!(read_note_md(&notebook_dir, "alpha"), "resolved text");
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    #[test]
+    fn merge_three_way_combines_separate_edits_and_marks_overlaps() {
+        let base = "title\nintro\nbody\nfooter\n";
+
+        let merged = notebook::merge_three_way(
+            base,
+            "title\nintro, edited here\nbody\nfooter\n",
+            "title\nintro\nbody\nfooter, edited elsewhere\n",
+        );
+        assert_eq!(merged.conflict_count, 0);
+        assert_eq!(
+            merged.text,
+            "title\nintro, edited here\nbody\nfooter, edited elsewhere\n"
+        );
+
+        let conflicted = notebook::merge_three_way(
+            base,
+            "title\nmine\nbody\nfooter\n",
+            "title\ntheirs\nbody\nfooter\n",
+        );
+        assert_eq!(conflicted.conflict_count, 1);
+        assert_eq!(
+            conflicted.text,
+            "title\n<<<<<<< mine\nmine\n=======\ntheirs\n>>>>>>> theirs\nbody\nfooter\n"
+        );
+    }
+
+    fn seed_fsck_problems(notebook_dir: &TestNotebookDir) -> Vec<NoteMetadata> {
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(
+            notebook_dir,
+            &mut notes,
+            &[
+                ("alpha", "![kept](images/kept.png)"),
+                ("gone", "soon missing"),
+            ],
+        );
+        let root = Path::new(notebook_dir.as_str());
+        fs::remove_dir_all(root.join("gone")).expect("Failed to remove note directory");
+        fs::create_dir_all(root.join("stray")).expect("Failed to create untracked note");
+        fs::write(root.join("stray/note.md"), "stray body").expect("Failed to write note");
+        fs::create_dir_all(root.join("alpha/images")).expect("Failed to create images");
+        fs::write(root.join("alpha/images/kept.png"), "png").expect("Failed to write image");
+        fs::write(root.join("alpha/images/unused.png"), "png").expect("Failed to write image");
+        fs::write(root.join("alpha/.note.md.cognate_tmp_1_0"), "old").expect("Failed to write");
+        fs::write(
+            root.join(format!("alpha/.note.md.cognate_tmp_1_{}", now_nanos())),
+            "in progress",
+        )
+        .expect("Failed to write temp file");
+        fs::create_dir_all(root.join(".cognate_txn_delete_beta_0"))
+            .expect("Failed to create staged delete");
+        notes
+    }
+
+    #[test]
+    fn check_notebook_reports_metadata_mismatches_temp_files_and_unused_images() {
+        let notebook_dir = TestNotebookDir::new("fsck_report");
+        let notes = seed_fsck_problems(&notebook_dir);
+
+        let findings = block_on(notebook::check_notebook(notebook_dir.as_str(), &notes))
+            .expect("check should succeed");
+        let summary: Vec<(FsckFindingKind, &str)> = findings
+            .iter()
+            .map(|finding| (finding.kind, finding.path.as_str()))
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                (FsckFindingKind::DanglingEntry, "gone"),
+                (FsckFindingKind::UntrackedNote, "stray"),
+                (FsckFindingKind::StaleTempFile, ".cognate_txn_delete_beta_0"),
+                (
+                    FsckFindingKind::StaleTempFile,
+                    "alpha/.note.md.cognate_tmp_1_0"
+                ),
+                (
+                    FsckFindingKind::UnreferencedImage,
+                    "alpha/images/unused.png"
+                ),
+            ]
+        );
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    #[test]
+    fn check_notebook_matches_decoded_markdown_image_destinations() {
+        let notebook_dir = TestNotebookDir::new("fsck_image_destinations");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(
+            &notebook_dir,
+            &mut notes,
+            &[(
+                "photos",
+                "![spaced](<images/a b.png>)\n![encoded](images/c%20d.png)\n![logo][logo]\n\n\
+                 [logo]: images/logo.png\n\nimages/mentioned.png is only text.\n\
+                 `![code](images/code.png)`\n",
+            )],
+        );
+        let image_dir = Path::new(notebook_dir.as_str()).join("photos/images");
+        fs::create_dir_all(&image_dir).expect("Failed to create images");
+        for file_name in [
+            "a b.png",
+            "c d.png",
+            "logo.png",
+            "mentioned.png",
+            "code.png",
+        ] {
+            fs::write(image_dir.join(file_name), "png").expect("Failed to write image");
+        }
+
+        let findings = block_on(notebook::check_notebook(notebook_dir.as_str(), &notes))
+            .expect("check should succeed");
+        let mut unreferenced: Vec<&str> = findings
+            .iter()
+            .filter(|finding| finding.kind == FsckFindingKind::UnreferencedImage)
+            .map(|finding| finding.path.as_str())
+            .collect();
+        unreferenced.sort();
+
+        assert_eq!(
+            unreferenced,
+            vec!["photos/images/code.png", "photos/images/mentioned.png"]
+        );
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    #[test]
+    fn repair_fsck_finding_resolves_each_kind_of_finding() {
+        let notebook_dir = TestNotebookDir::new("fsck_repair");
+        let mut notes = seed_fsck_problems(&notebook_dir);
+        let findings = block_on(notebook::check_notebook(notebook_dir.as_str(), &notes))
+            .expect("check should succeed");
+
+        for finding in &findings {
+            block_on(notebook::repair_fsck_finding(
+                notebook_dir.as_str(),
+                finding,
+                &mut notes,
+            ))
+            .expect("repair should succeed");
+        }
+
+        assert!(
+            block_on(notebook::check_notebook(notebook_dir.as_str(), &notes))
+                .expect("check should succeed")
+                .is_empty()
+        );
+        let loaded = load_notes_or_panic(&notebook_dir);
+        let mut paths: Vec<&str> = loaded.iter().map(|note| note.rel_path.as_str()).collect();
+        paths.sort();
+        assert_eq!(paths, vec!["alpha", "stray"]);
+        let root = Path::new(notebook_dir.as_str());
+        assert!(root.join("alpha/images/kept.png").exists());
+        assert!(!root.join("alpha/images/unused.png").exists());
+        assert!(!root.join(".cognate_txn_delete_beta_0").exists());
+
+        fs::write(root.join("alpha/images/later.png"), "png").expect("Failed to write image");
+        let findings = block_on(notebook::check_notebook(notebook_dir.as_str(), &notes))
+            .expect("check should succeed");
+        notebook::save_note_content_sync(notebook_dir.as_str(), "alpha", "![](images/later.png)")
+            .expect("Failed to save note content");
+        let stale = block_on(notebook::repair_fsck_finding(
+            notebook_dir.as_str(),
+            &findings[0],
+            &mut notes,
+        ))
+        .expect_err("an image referenced after the check must not be deleted");
+        assert_eq!(stale.kind(), NotebookErrorKind::Validation);
+        assert!(root.join("alpha/images/later.png").exists());
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    #[test]
+    fn apply_replace_changes_only_selected_hits_and_refreshes_search() {
+        let notebook_dir = TestNotebookDir::new("replace_apply");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(
+            &notebook_dir,
+            &mut notes,
+            &[
+                ("work/plan", "Ship apple pie.\r\nMore apple, apple!\r\n"),
+                ("home/list", "Buy apples."),
+                ("home/other", "Nothing to see."),
+            ],
+        );
+
+        let mut preview = block_on(notebook::preview_replace(
+            notebook_dir.as_str(),
+            &notes,
+            "apple",
+            "pear",
+            Some(("home/list", "Buy apples and apple juice.", None)),
+        ))
+        .expect("preview should succeed");
+        let previewed: Vec<(&str, bool, usize)> = preview
+            .notes
+            .iter()
+            .map(|note| {
+                let hits = note.lines.iter().map(|line| line.hits.len()).sum();
+                (note.rel_path.as_str(), note.is_open_note, hits)
+            })
+            .collect();
+        assert_eq!(
+            previewed,
+            vec![("home/list", true, 2), ("work/plan", false, 3)]
+        );
+        let plan_lines = &preview.notes[1].lines;
+        assert_eq!(plan_lines[1].line_number, 2);
+        assert_eq!(plan_lines[1].text, "More apple, apple!");
+
+        // Keep the second "apple" on the plan's second line.
+        preview.toggle_hit(plan_lines[1].hits[1].id);
+        assert_eq!(
+            preview.notes[1].lines[1].replaced_text("pear"),
+            "More pear, apple!"
+        );
+
+        let changed = block_on(notebook::apply_replace(
+            notebook_dir.as_str(),
+            &preview,
+            &mut notes,
+        ))
+        .expect("replace should succeed");
+        assert_eq!(changed, vec!["home/list", "work/plan"]);
+        assert_eq!(
+            read_note_md(&notebook_dir, "work/plan"),
+            "Ship pear pie.\r\nMore pear, apple!\r\n"
+        );
+        assert_eq!(
+            read_note_md(&notebook_dir, "home/list"),
+            "Buy pears and pear juice."
+        );
+        assert_eq!(read_note_md(&notebook_dir, "home/other"), "Nothing to see.");
+        let loaded = load_notes_or_panic(&notebook_dir);
+        assert!(
+            loaded
+                .iter()
+                .filter(|note| note.rel_path != "home/other")
+                .all(|note| note.last_updated.is_some())
+        );
+
+        let search_snapshot = notes
+            .iter()
+            .map(notebook::SearchNote::from)
+            .collect::<Vec<notebook::SearchNote>>();
+        let results = block_on(notebook::search_notes_with_snapshot(
+            notebook_dir.as_str().to_string(),
+            search_snapshot,
+            "pear".to_string(),
+            notebook::SearchMode::Text,
+        ))
+        .expect("search should succeed");
+        let mut found: Vec<&str> = results
+            .iter()
+            .map(|result| result.rel_path.as_str())
+            .collect();
+        found.sort();
+        assert_eq!(found, vec!["home/list", "work/plan"]);
+        notebook::clear_search_index_for_notebook(notebook_dir.as_str());
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    #[test]
+    fn apply_replace_rejects_previews_of_notes_changed_on_disk() {
+        let notebook_dir = TestNotebookDir::new("replace_stale");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(
+            &notebook_dir,
+            &mut notes,
+            &[("alpha", "old word"), ("beta", "old word again")],
+        );
+
+        let preview = block_on(notebook::preview_replace(
+            notebook_dir.as_str(),
+            &notes,
+            "old",
+            "new",
+            None,
+        ))
+        .expect("preview should succeed");
+        notebook::save_note_content_sync(notebook_dir.as_str(), "beta", "old word edited")
+            .expect("Failed to save note content");
 
-        let delete_result = block_on(notebook::delete_note(
+        let error = block_on(notebook::apply_replace(
             notebook_dir.as_str(),
-            "rollback/failure",
+            &preview,
             &mut notes,
-        ));
+        ))
+        .expect_err("a stale preview must not be applied");
+        assert_eq!(error.kind(), NotebookErrorKind::Validation);
+        assert_eq!(read_note_md(&notebook_dir, "alpha"), "old word");
+        assert_eq!(read_note_md(&notebook_dir, "beta"), "old word edited");
+
+        for (find, replacement) in [("", "x"), ("a\nb", "x"), ("old", "two\nlines")] {
+            let error = block_on(notebook::preview_replace(
+                notebook_dir.as_str(),
+                &notes,
+                find,
+                replacement,
+                None,
+            ))
+            .expect_err("invalid find and replace text should be rejected");
+            assert_eq!(error.kind(), NotebookErrorKind::Validation);
+        }
+        notebook::clear_search_index_for_notebook(notebook_dir.as_str());
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
 
-        assert!(delete_result.is_err());
-        let error = delete_result.expect_err("Expected delete to fail");
-        assert_eq!(error.kind(), NotebookErrorKind::Recovery);
+    #[test]
+    fn apply_replace_rejects_an_open_note_edited_outside_the_editor() {
+        let notebook_dir = TestNotebookDir::new("replace_open_note_stale");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(&notebook_dir, &mut notes, &[("alpha", "old word")]);
+        let note_file = Path::new(notebook_dir.as_str()).join("alpha/note.md");
+        let loaded_state = notebook::NoteDiskState {
+            content: "old word".to_string(),
+            modified_time: None,
+        };
+
+        let preview = block_on(notebook::preview_replace(
+            notebook_dir.as_str(),
+            &notes,
+            "old",
+            "new",
+            Some(("alpha", "old word typed", Some(&loaded_state))),
+        ))
+        .expect("preview should succeed");
+        fs::write(&note_file, "old word from elsewhere").expect("Failed to edit note");
+
+        let error = block_on(notebook::apply_replace(
+            notebook_dir.as_str(),
+            &preview,
+            &mut notes,
+        ))
+        .expect_err("an open note edited outside the editor must not be overwritten");
+        assert_eq!(error.kind(), NotebookErrorKind::Validation);
+        assert_eq!(
+            read_note_md(&notebook_dir, "alpha"),
+            "old word from elsewhere"
+        );
+
+        let reloaded_state = notebook::NoteDiskState {
+            content: "old word from elsewhere".to_string(),
+            modified_time: None,
+        };
+        let preview = block_on(notebook::preview_replace(
+            notebook_dir.as_str(),
+            &notes,
+            "old",
+            "new",
+            Some(("alpha", "old word typed", Some(&reloaded_state))),
+        ))
+        .expect("preview should succeed");
+        block_on(notebook::apply_replace(
+            notebook_dir.as_str(),
+            &preview,
+            &mut notes,
+        ))
+        .expect("replace should succeed once the editor has seen the file");
+        assert_eq!(read_note_md(&notebook_dir, "alpha"), "new word typed");
+        notebook::clear_search_index_for_notebook(notebook_dir.as_str());
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    #[test]
+    fn label_edits_move_nested_labels_and_rewrite_front_matter_in_one_save() {
+        let notebook_dir = TestNotebookDir::new("label_edit_apply");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(
+            &notebook_dir,
+            &mut notes,
+            &[
+                (
+                    "alpha",
+                    "---\ntitle: Alpha\nlabels: [Area/Sub, misc]\n---\nBody",
+                ),
+                ("beta", "Plain body."),
+                ("gamma", "Other body."),
+            ],
+        );
+        for (rel_path, labels) in [
+            ("alpha", vec!["Area/Sub", "misc"]),
+            ("beta", vec!["area", "topic"]),
+            ("gamma", vec!["areas"]),
+        ] {
+            let note = notes
+                .iter_mut()
+                .find(|note| note.rel_path == rel_path)
+                .expect("note should exist");
+            note.labels = labels.into_iter().map(str::to_string).collect();
+        }
+
+        let preview =
+            notebook::preview_label_edit(&notes, notebook::LabelEditKind::Rename, "area", "Zone")
+                .expect("rename preview should succeed");
+        let previewed: Vec<(&str, Vec<String>)> = preview
+            .notes
+            .iter()
+            .map(|note| (note.rel_path.as_str(), note.new_labels.clone()))
+            .collect();
+        assert_eq!(
+            previewed,
+            vec![
+                ("alpha", vec!["Zone/Sub".to_string(), "misc".to_string()]),
+                ("beta", vec!["Zone".to_string(), "topic".to_string()]),
+            ]
+        );
+
+        let changed = block_on(notebook::apply_label_edit(
+            notebook_dir.as_str(),
+            &preview,
+            &mut notes,
+        ))
+        .expect("rename should succeed");
+        assert_eq!(changed, vec!["alpha", "beta"]);
+        assert_eq!(
+            read_note_md(&notebook_dir, "alpha"),
+            "---\ntitle: Alpha\nlabels: [Zone/Sub, misc]\n---\nBody"
+        );
+        assert_eq!(read_note_md(&notebook_dir, "beta"), "Plain body.");
+        let loaded = load_notes_or_panic(&notebook_dir);
+        let labels_of = |rel_path: &str| {
+            loaded
+                .iter()
+                .find(|note| note.rel_path == rel_path)
+                .map(|note| note.labels.clone())
+                .expect("note should be saved")
+        };
+        assert_eq!(labels_of("alpha"), ["Zone/Sub", "misc"]);
+        assert_eq!(labels_of("beta"), ["Zone", "topic"]);
+        assert_eq!(labels_of("gamma"), ["areas"]);
+
+        let merge =
+            notebook::preview_label_edit(&notes, notebook::LabelEditKind::Merge, "topic", "zone")
+                .expect("merge preview should succeed");
+        assert_eq!(merge.notes.len(), 1);
+        assert_eq!(merge.notes[0].new_labels, ["Zone"]);
+
+        let delete =
+            notebook::preview_label_edit(&notes, notebook::LabelEditKind::Delete, "zone", "")
+                .expect("delete preview should succeed");
+        assert_eq!(
+            delete
+                .notes
+                .iter()
+                .map(|note| note.new_labels.clone())
+                .collect::<Vec<Vec<String>>>(),
+            vec![vec!["misc".to_string()], vec!["topic".to_string()]]
+        );
+        notebook::clear_search_index_for_notebook(notebook_dir.as_str());
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    #[test]
+    fn label_edits_reject_invalid_targets_and_stale_previews() {
+        let notes = vec![
+            NoteMetadata {
+                rel_path: "alpha".to_string(),
+                labels: vec!["work".to_string(), "home".to_string()],
+                ..NoteMetadata::default()
+            },
+            NoteMetadata {
+                rel_path: "beta".to_string(),
+                labels: vec!["home/garden".to_string()],
+                ..NoteMetadata::default()
+            },
+        ];
+        for (kind, label, target) in [
+            (notebook::LabelEditKind::Rename, " ", "x"),
+            (notebook::LabelEditKind::Rename, "work", ""),
+            (notebook::LabelEditKind::Rename, "work", "home"),
+            (notebook::LabelEditKind::Rename, "home", "home/old"),
+            (notebook::LabelEditKind::Merge, "work", "WORK"),
+            (notebook::LabelEditKind::Delete, "missing", ""),
+        ] {
+            let error = notebook::preview_label_edit(&notes, kind, label, target)
+                .expect_err("invalid label edits should be rejected");
+            assert_eq!(
+                error.kind(),
+                NotebookErrorKind::Validation,
+                "{label} {target}"
+            );
+        }
+
+        let case_only =
+            notebook::preview_label_edit(&notes, notebook::LabelEditKind::Rename, "work", "Work")
+                .expect("changing the case of a label is a rename");
+        assert_eq!(case_only.notes[0].new_labels, ["Work", "home"]);
+
+        let notebook_dir = TestNotebookDir::new("label_edit_stale");
+        let mut changed_notes = notes.clone();
+        changed_notes[0].labels.push("later".to_string());
+        let error = block_on(notebook::apply_label_edit(
+            notebook_dir.as_str(),
+            &case_only,
+            &mut changed_notes,
+        ))
+        .expect_err("a stale preview must not be applied");
+        assert_eq!(error.kind(), NotebookErrorKind::Validation);
         assert!(
-            error
-                .to_string()
-                .contains("Rollback failed while restoring filesystem state"),
-            "Expected explicit rollback failure message, got: {}",
-            error
+            !Path::new(notebook_dir.as_str())
+                .join("metadata.json")
+                .exists()
         );
+    }
 
-        let staged_entries: Vec<PathBuf> = fs::read_dir(notebook_dir.as_str())
-            .expect("Failed to scan notebook directory")
-            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
-            .filter(|path| {
-                path.file_name()
-                    .and_then(|name| name.to_str())
-                    .map(|name| name.starts_with(".cognate_txn_delete_"))
-                    .unwrap_or(false)
+    fn label_info(color: &str, description: &str, icon: &str) -> notebook::LabelInfo {
+        let field = |value: &str| Some(value.to_string());
+        notebook::LabelInfo {
+            color: field(color),
+            description: field(description),
+            icon: field(icon),
+        }
+    }
+
+    #[test]
+    fn label_registry_is_stored_in_metadata_and_follows_label_edits() {
+        let notebook_dir = TestNotebookDir::new("label_registry");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(&notebook_dir, &mut notes, &[("alpha", "Body")]);
+        notes[0].labels = vec!["area/sub".to_string(), "misc".to_string()];
+
+        let mut registry = notebook::LabelRegistry::new();
+        block_on(notebook::save_label_info(
+            notebook_dir.as_str(),
+            &notes,
+            &mut registry,
+            " Area ",
+            label_info(" #36F ", " Ongoing areas ", "*"),
+        ))
+        .expect("Failed to save label style");
+        block_on(notebook::save_label_info(
+            notebook_dir.as_str(),
+            &notes,
+            &mut registry,
+            "area/sub",
+            label_info("", "", "+"),
+        ))
+        .expect("Failed to save nested label style");
+        assert_eq!(
+            registry.get("area"),
+            Some(&notebook::LabelInfo {
+                color: Some("#3366ff".to_string()),
+                description: Some("Ongoing areas".to_string()),
+                icon: Some("*".to_string()),
             })
-            .collect();
+        );
+        assert_eq!(
+            notebook::label_color(&registry, "Area/Sub"),
+            Some([0x33, 0x66, 0xff])
+        );
+
+        for rejected in [
+            label_info("blue", "", ""),
+            label_info("#12345", "", ""),
+            label_info("", "", "too long"),
+        ] {
+            let error = block_on(notebook::save_label_info(
+                notebook_dir.as_str(),
+                &notes,
+                &mut registry,
+                "misc",
+                rejected,
+            ))
+            .expect_err("Invalid label style should be rejected");
+            assert_eq!(error.kind(), NotebookErrorKind::Validation);
+        }
+        assert_eq!(registry.len(), 2);
+
+        // Note saves keep the registry, and renaming the label keeps its style.
+        notebook::save_metadata(notebook_dir.as_str(), &notes).expect("Failed to save metadata");
+        let preview =
+            notebook::preview_label_edit(&notes, notebook::LabelEditKind::Rename, "area", "Zone")
+                .expect("rename preview should succeed");
+        block_on(notebook::apply_label_edit(
+            notebook_dir.as_str(),
+            &preview,
+            &mut notes,
+        ))
+        .expect("rename should succeed");
+        let loaded = block_on(notebook::load_notes_metadata(
+            notebook_dir.as_str().to_string(),
+        ))
+        .expect("Failed to load metadata");
+        assert_eq!(
+            loaded.label_registry.keys().collect::<Vec<_>>(),
+            ["zone", "zone/sub"]
+        );
+        assert_eq!(
+            notebook::label_color(&loaded.label_registry, "Zone/Sub"),
+            Some([0x33, 0x66, 0xff])
+        );
+
+        // Clearing every field removes the entry.
+        let mut registry = loaded.label_registry;
+        block_on(notebook::save_label_info(
+            notebook_dir.as_str(),
+            &notes,
+            &mut registry,
+            "zone/sub",
+            label_info(" ", "", ""),
+        ))
+        .expect("Failed to clear label style");
+        assert_eq!(registry.keys().collect::<Vec<_>>(), ["zone"]);
+        notebook::clear_search_index_for_notebook(notebook_dir.as_str());
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    #[test]
+    fn label_suggestions_rank_prefixes_then_fuzzy_matches_by_frequency() {
+        let notes: Vec<NoteMetadata> = [
+            vec!["meetings", "work/meta"],
+            vec!["Meetings"],
+            vec!["meeting"],
+            vec!["work/standup", "mentoring"],
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(index, labels)| NoteMetadata {
+            rel_path: format!("note{}", index),
+            labels: labels.into_iter().map(str::to_string).collect(),
+            ..NoteMetadata::default()
+        })
+        .collect();
+
+        assert_eq!(
+            notebook::suggest_labels(&notes, "me", &[], 10),
+            ["meetings", "meeting", "mentoring", "work/meta"]
+        );
+        assert_eq!(
+            notebook::suggest_labels(&notes, " Meeting ", &[], 10),
+            ["meetings"]
+        );
+        assert_eq!(
+            notebook::suggest_labels(&notes, "mtng", &["meeting".to_string()], 10),
+            ["meetings", "mentoring"]
+        );
+        assert_eq!(
+            notebook::suggest_labels(&notes, "meetnigs", &[], 10),
+            ["meetings", "meeting"]
+        );
+        assert_eq!(notebook::suggest_labels(&notes, "wo", &[], 2).len(), 2);
+        assert!(notebook::suggest_labels(&notes, " ", &[], 10).is_empty());
+    }
+
+    #[test]
+    fn bulk_label_edits_add_and_remove_a_label_on_marked_notes() {
+        let notebook_dir = TestNotebookDir::new("bulk_label_edit");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(
+            &notebook_dir,
+            &mut notes,
+            &[
+                (
+                    "alpha",
+                    "---
+labels: [draft]
+---
+Body",
+                ),
+                ("beta", "Plain body."),
+                ("gamma", "Unmarked."),
+            ],
+        );
+        notes
+            .iter_mut()
+            .find(|note| note.rel_path == "alpha")
+            .expect("note should exist")
+            .labels = vec!["draft".to_string()];
+        let marked = vec!["alpha".to_string(), "beta".to_string()];
+
+        for (rel_paths, label) in [(&marked[..], " "), (&[][..], "review")] {
+            let error = notebook::preview_bulk_label_edit(
+                &notes,
+                rel_paths,
+                notebook::BulkLabelAction::Add,
+                label,
+            )
+            .expect_err("incomplete bulk edits should be rejected");
+            assert_eq!(error.kind(), NotebookErrorKind::Validation);
+        }
+
+        let changes = notebook::preview_bulk_label_edit(
+            &notes,
+            &marked,
+            notebook::BulkLabelAction::Add,
+            "Draft",
+        )
+        .expect("add preview should succeed");
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].rel_path, "beta");
+        assert_eq!(changes[0].new_labels, ["Draft"]);
+
+        let changed = block_on(notebook::apply_bulk_label_edit(
+            notebook_dir.as_str(),
+            &changes,
+            &mut notes,
+        ))
+        .expect("bulk add should succeed");
+        assert_eq!(changed, vec!["beta"]);
+
+        let changes = notebook::preview_bulk_label_edit(
+            &notes,
+            &marked,
+            notebook::BulkLabelAction::Remove,
+            "DRAFT",
+        )
+        .expect("remove preview should succeed");
+        let changed = block_on(notebook::apply_bulk_label_edit(
+            notebook_dir.as_str(),
+            &changes,
+            &mut notes,
+        ))
+        .expect("bulk remove should succeed");
+        assert_eq!(changed, vec!["alpha", "beta"]);
+        assert_eq!(read_note_md(&notebook_dir, "alpha"), "Body");
+        assert_eq!(read_note_md(&notebook_dir, "beta"), "Plain body.");
         assert!(
-            !staged_entries.is_empty(),
-            "Expected failed rollback to leave a staged delete entry for manual recovery"
+            load_notes_or_panic(&notebook_dir)
+                .iter()
+                .all(|note| note.labels.is_empty())
         );
+        notebook::clear_search_index_for_notebook(notebook_dir.as_str());
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    fn write_template(notebook: &TestNotebookDir, name: &str, content: &str) {
+        let templates_dir = Path::new(notebook.as_str()).join(".templates");
+        fs::create_dir_all(&templates_dir).expect("Failed to create templates directory");
+        fs::write(templates_dir.join(format!("{}.md", name)), content)
+            .expect("Failed to write template");
     }
 
     #[test]
-    fn move_note_surfaces_failed_rollback_when_rollback_rename_fails() {
-        let notebook_dir = TestNotebookDir::new("move_rollback_failure_surface");
-        let mut notes: Vec<NoteMetadata> = vec![NoteMetadata {
-            rel_path: "rollback/source".to_string(),
-            labels: Vec::new(),
-            last_updated: None,
-        }];
+    fn create_note_from_template_fills_variables_labels_and_cursor() {
+        let notebook_dir = TestNotebookDir::new("note_templates_create");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        assert_eq!(
+            block_on(notebook::list_templates(notebook_dir.as_str().to_string()))
+                .expect("Missing templates directory should list nothing"),
+            Vec::<String>::new()
+        );
 
-        let source_dir = Path::new(notebook_dir.as_str()).join("rollback/source");
-        fs::create_dir_all(&source_dir).expect("Failed to create source note directory");
-        fs::write(source_dir.join("note.md"), "rollback failure")
-            .expect("Failed to write source note file");
-        fs::create_dir(Path::new(notebook_dir.as_str()).join("metadata.json"))
-            .expect("Failed to create metadata trap directory");
+        write_template(
+            &notebook_dir,
+            "meeting",
+            "---\nlabels: [meeting]\n---\n# {{title}}\n\nWhen: {{date}}\nPath: {{path}}\n\n{{cursor}}\n",
+        );
+        write_template(&notebook_dir, "daily", "{{date}} {{time}}");
         fs::write(
-            Path::new(notebook_dir.as_str()).join(".cognate_fail_move_rollback"),
-            "fail",
+            Path::new(notebook_dir.as_str())
+                .join(".templates")
+                .join("notes.txt"),
+            "not a template",
         )
-        .expect("Failed to create move-rollback failure marker");
+        .expect("Failed to write non-template file");
+        assert_eq!(
+            block_on(notebook::list_templates(notebook_dir.as_str().to_string()))
+                .expect("Failed to list templates"),
+            vec!["daily".to_string(), "meeting".to_string()]
+        );
 
-        let move_result = block_on(notebook::move_note(
+        let created = block_on(notebook::create_note_from_template(
             notebook_dir.as_str(),
-            "rollback/source",
-            "rollback/destination",
+            "work/Standup",
+            Some("meeting"),
             &mut notes,
-        ));
-
-        assert!(move_result.is_err());
-        let error = move_result.expect_err("Expected move to fail");
-        assert_eq!(error.kind(), NotebookErrorKind::Recovery);
+        ))
+        .expect("Failed to create note from template");
+        assert_eq!(created.metadata.rel_path, "work/Standup");
+        assert_eq!(created.metadata.labels, vec!["meeting".to_string()]);
+        assert_eq!(created.cursor, Some((6, 0)));
+
+        let content = read_note_md(&notebook_dir, "work/Standup");
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines[0], "# Standup");
+        let date = lines[2].strip_prefix("When: ").expect("Expected date line");
+        assert_eq!(date.len(), "YYYY-MM-DD".len());
         assert!(
-            error
-                .to_string()
-                .contains("Rollback failed while restoring filesystem state"),
-            "Expected explicit rollback failure message, got: {}",
-            error
+            date.chars()
+                .enumerate()
+                .all(|(index, ch)| if index == 4 || index == 7 {
+                    ch == '-'
+                } else {
+                    ch.is_ascii_digit()
+                })
         );
-
+        assert_eq!(lines[3], "Path: work/Standup");
+        assert!(!content.contains("{{"));
         assert!(
+            notes
+                .iter()
+                .any(|note| note.rel_path == "work/Standup" && note.labels == ["meeting"])
+        );
+
+        let empty = block_on(notebook::create_note_from_template(
+            notebook_dir.as_str(),
+            "scratch",
+            None,
+            &mut notes,
+        ))
+        .expect("Failed to create empty note");
+        assert_eq!(empty.cursor, None);
+        assert_eq!(read_note_md(&notebook_dir, "scratch"), "");
+
+        for template in ["missing", "../escape"] {
+            let error = block_on(notebook::create_note_from_template(
+                notebook_dir.as_str(),
+                "other",
+                Some(template),
+                &mut notes,
+            ))
+            .expect_err("Unknown template should be rejected");
+            assert_eq!(error.kind(), NotebookErrorKind::Validation);
+        }
+        assert!(!notes.iter().any(|note| note.rel_path == "other"));
+        assert!(!Path::new(notebook_dir.as_str()).join("other").exists());
+        notebook::clear_search_index_for_notebook(notebook_dir.as_str());
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    #[test]
+    fn default_template_is_stored_in_metadata_settings() {
+        let notebook_dir = TestNotebookDir::new("note_templates_default");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        create_notes_with_content(&notebook_dir, &mut notes, &[("inbox", "")]);
+        write_template(&notebook_dir, "meeting", "# {{title}}\n");
+
+        let error = block_on(notebook::set_default_template(
+            notebook_dir.as_str(),
+            &notes,
+            Some("missing".to_string()),
+        ))
+        .expect_err("Unknown default template should be rejected");
+        assert_eq!(error.kind(), NotebookErrorKind::Validation);
+
+        block_on(notebook::set_default_template(
+            notebook_dir.as_str(),
+            &notes,
+            Some("meeting".to_string()),
+        ))
+        .expect("Failed to set default template");
+
+        // Note operations rewrite metadata.json without dropping the setting.
+        block_on(notebook::create_new_note(
+            notebook_dir.as_str(),
+            "later",
+            &mut notes,
+        ))
+        .expect("Failed to create note");
+        let loaded = block_on(notebook::load_notes_metadata(
+            notebook_dir.as_str().to_string(),
+        ))
+        .expect("Failed to load metadata");
+        assert_eq!(loaded.settings.default_template.as_deref(), Some("meeting"));
+        assert_eq!(loaded.notes.len(), 2);
+
+        block_on(notebook::set_default_template(
+            notebook_dir.as_str(),
+            &notes,
+            None,
+        ))
+        .expect("Failed to clear default template");
+        let reloaded = block_on(notebook::load_notes_metadata(
+            notebook_dir.as_str().to_string(),
+        ))
+        .expect("Failed to load metadata");
+        assert_eq!(reloaded.settings.default_template, None);
+        let metadata_json =
+            fs::read_to_string(Path::new(notebook_dir.as_str()).join("metadata.json"))
+                .expect("Failed to read metadata.json");
+        assert!(!metadata_json.contains("settings"));
+        notebook::clear_search_index_for_notebook(notebook_dir.as_str());
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
+    }
+
+    #[test]
+    fn local_dates_follow_the_offset_in_effect_and_fall_back_to_the_startup_offset() {
+        // 2024-03-30T23:30:00Z, the night Central European clocks move to summer time.
+        let now = time::OffsetDateTime::from_unix_timestamp(1_711_841_400)
+            .expect("Expected a valid timestamp");
+        let winter = time::UtcOffset::from_hms(1, 0, 0).expect("Expected a valid offset");
+        let summer = time::UtcOffset::from_hms(2, 0, 0).expect("Expected a valid offset");
+
+        let before_change = notebook::to_local_datetime(now, Some(winter));
+        assert_eq!(before_change.date().day(), 31);
+        assert_eq!(before_change.hour(), 0);
+        let after_change = notebook::to_local_datetime(now, Some(summer));
+        assert_eq!(after_change.hour(), 1);
+        assert_eq!(after_change.offset(), summer);
+
+        // Without a readable offset the offset read at startup is used for every moment.
+        let fallback = notebook::to_local_datetime(now, None);
+        assert_eq!(fallback, now);
+        let later = notebook::to_local_datetime(now + time::Duration::days(180), None);
+        assert_eq!(later.offset(), fallback.offset());
+    }
+
+    #[test]
+    fn open_journal_note_creates_a_labeled_daily_note_once() {
+        let notebook_dir = TestNotebookDir::new("journal_notes");
+        let mut notes: Vec<NoteMetadata> = Vec::new();
+        let march_fourth = time::Date::from_calendar_date(2026, time::Month::March, 4)
+            .expect("Expected a valid date");
+
+        let created = block_on(notebook::open_journal_note(
+            notebook_dir.as_str(),
+            notebook::DEFAULT_JOURNAL_PATH_PATTERN,
+            None,
+            march_fourth,
+            &mut notes,
+        ))
+        .expect("Failed to open journal note");
+        assert_eq!(created.metadata.rel_path, "journal/2026/03/04");
+        assert_eq!(created.metadata.labels, vec!["journal".to_string()]);
+        assert_eq!(read_note_md(&notebook_dir, "journal/2026/03/04"), "");
+
+        fs::write(
             Path::new(notebook_dir.as_str())
-                .join("rollback/source")
-                .join("note.md")
-                .exists()
-                || Path::new(notebook_dir.as_str())
-                    .join("rollback/destination")
-                    .join("note.md")
-                    .exists(),
-            "Failed move rollback should leave recoverable note data on disk"
+                .join("journal/2026/03/04")
+                .join("note.md"),
+            "Already written",
+        )
+        .expect("Failed to write journal note");
+        let reopened = block_on(notebook::open_journal_note(
+            notebook_dir.as_str(),
+            notebook::DEFAULT_JOURNAL_PATH_PATTERN,
+            None,
+            march_fourth,
+            &mut notes,
+        ))
+        .expect("Failed to reopen journal note");
+        assert_eq!(reopened.metadata.rel_path, "journal/2026/03/04");
+        assert_eq!(notes.len(), 1);
+        assert_eq!(
+            read_note_md(&notebook_dir, "journal/2026/03/04"),
+            "Already written"
+        );
+
+        // Templates fill `{{date}}` with the journal date rather than today.
+        write_template(
+            &notebook_dir,
+            "day",
+            "---\nlabels: [daily, journal]\n---\n# {{date}}\n{{cursor}}",
+        );
+        let previous_day = march_fourth
+            .previous_day()
+            .expect("Expected a previous day");
+        let templated = block_on(notebook::open_journal_note(
+            notebook_dir.as_str(),
+            "days/{{date}}",
+            Some("day"),
+            previous_day,
+            &mut notes,
+        ))
+        .expect("Failed to open templated journal note");
+        assert_eq!(templated.metadata.rel_path, "days/2026-03-03");
+        assert_eq!(
+            templated.metadata.labels,
+            vec!["journal".to_string(), "daily".to_string()]
+        );
+        assert_eq!(templated.cursor, Some((2, 0)));
+        assert_eq!(
+            read_note_md(&notebook_dir, "days/2026-03-03"),
+            "# 2026-03-03\n"
+        );
+        assert_eq!(
+            notebook::journal_note_date("days/{{date}}", &templated.metadata.rel_path),
+            Some(previous_day)
+        );
+
+        let error = block_on(notebook::open_journal_note(
+            notebook_dir.as_str(),
+            "journal/{{month}}",
+            None,
+            march_fourth,
+            &mut notes,
+        ))
+        .expect_err("Pattern without a full date should be rejected");
+        assert_eq!(error.kind(), NotebookErrorKind::Validation);
+        assert_eq!(notes.len(), 2);
+
+        let loaded = block_on(notebook::load_notes_metadata(
+            notebook_dir.as_str().to_string(),
+        ))
+        .expect("Failed to load metadata");
+        assert!(
+            loaded
+                .notes
+                .iter()
+                .all(|note| note.labels.contains(&"journal".to_string()))
         );
+        notebook::clear_search_index_for_notebook(notebook_dir.as_str());
+        notebook::clear_link_index_for_notebook(notebook_dir.as_str());
     }
 }